@@ -0,0 +1,11 @@
+// Stamps the build time into the binary for the About screen; SOURCE_DATE_EPOCH overrides it
+// for reproducible builds
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+    println!("cargo:rustc-env=BUILD_UNIX_TIME={}", secs);
+}
@@ -0,0 +1,116 @@
+//! Replays as text animations: an [asciinema](https://asciinema.org) v2 cast that draws the
+//! grid with characters, for sharing runs in terminals, gists and chat without a video.
+use serde_json::json;
+
+use crate::logging;
+use crate::map::{Cell, Direction};
+use crate::powerups::PowerUp;
+use crate::replay::Replay;
+use crate::save::write_atomic;
+use crate::sim::{self, SnakeSim};
+
+pub const CAST_EXTENSION: &str = ".cast";
+
+// Colours by SGR code, so the cast reads like the game's matrix theme
+const GREEN: &str = "32";
+const BRIGHT_GREEN: &str = "92";
+const DIM_GREEN: &str = "2;32";
+const WHITE: &str = "97";
+const YELLOW: &str = "93";
+const RED: &str = "91";
+
+/// Two characters per cell, so the board comes out roughly square in a terminal
+fn cell_glyph(sim: &SnakeSim, cell: Cell) -> (&'static str, &'static str) {
+    // The snake first, as it can be phasing through a wall
+    if sim.head() == cell { return ("@@", BRIGHT_GREEN); }
+    if sim.occupied.contains(&cell) { return ("[]", GREEN); }
+    if sim.map.is_wall(cell) { return ("##", DIM_GREEN); }
+    let belt = sim.map.conveyor_at(cell).map(|dir| match dir {
+        Direction::Up => "^^",
+        Direction::Down => "vv",
+        Direction::Left => "<<",
+        Direction::Right => ">>",
+    });
+    if sim.waves.as_ref().is_some_and(|w| w.patrollers.iter().any(|p| p.at == cell)) { return ("XX", RED); }
+    if sim.food == Some(cell) { return ("()", WHITE); }
+    if sim.premium.as_ref().and_then(|p| p.at) == Some(cell) { return ("$$", YELLOW); }
+    if sim.glitch.as_ref().and_then(|g| g.at) == Some(cell) { return ("()", RED); }
+    if let Some(kind) = sim.chain.as_ref().and_then(|c| c.kind_at(cell)) { return ("**", [GREEN, WHITE, YELLOW][kind]); }
+    if sim.map.plate_at(cell).is_some() { return ("oo", YELLOW); }
+    if sim.map.stair_exit(cell).is_some() { return ("HH", WHITE); }
+    match sim.powerups.as_ref().and_then(|p| p.at) {
+        Some((PowerUp::Reverse, at)) if at == cell => ("&&", YELLOW),
+        Some((PowerUp::Bomb, at)) if at == cell => ("%%", YELLOW),
+        _ => belt.map_or(("  ", ""), |glyph| (glyph, DIM_GREEN)),
+    }
+}
+
+/// One line per grid row plus the status line, each a complete line of escape codes and text
+fn frame(sim: &SnakeSim, tick: u32) -> Vec<String> {
+    let grid = sim.map.grid;
+    let mut lines: Vec<String> = (0..grid.height)
+        .map(|y| {
+            let mut line = String::new();
+            let mut color = "";
+            for x in 0..grid.width {
+                let (glyph, c) = cell_glyph(sim, Cell { x, y });
+                if c != color && glyph != "  " {
+                    line.push_str(&format!("\x1b[0;{}m", c));
+                    color = c;
+                }
+                line.push_str(glyph);
+            }
+            line.push_str("\x1b[0m");
+            line
+        })
+        .collect();
+    let state = if sim.won { "  CLEARED" } else if sim.alive { "" } else { "  GAME OVER" };
+    lines.push(format!("\x1b[0;{}mScore: {}  Tick: {}{}\x1b[0m\x1b[K", BRIGHT_GREEN, sim.score, tick, state));
+    lines
+}
+
+/// The replay as an asciinema v2 cast: a JSON header line, then one output event per tick
+/// that redraws only the rows that changed, timed at the run's own speed
+pub fn to_cast(replay: &Replay) -> String {
+    let grid = replay.grid;
+    let header = json!({
+        "version": 2,
+        "width": grid.width * 2,
+        "height": grid.height + 1,
+        "title": format!("Snake - seed {}, score {}", replay.seed, replay.score),
+        "env": { "TERM": "xterm-256color" },
+    });
+    let mut out = format!("{}\n", header);
+    let mut event = |time: f64, data: String| {
+        out.push_str(&json!([(time * 1000.0).round() / 1000.0, "o", data]).to_string());
+        out.push('\n');
+    };
+
+    let mut sim = replay.headless_start();
+    let mut shown = frame(&sim, 0);
+    event(0.0, format!("\x1b[?25l\x1b[2J\x1b[H{}", shown.join("\r\n")));
+    let mut time = 0.0;
+    for tick in 0..replay.ticks {
+        if !sim.alive { break; }
+        time += (replay.move_interval * sim.waves.as_ref().map_or(1.0, |w| w.speed)) as f64;
+        sim = sim::advance(sim, replay.turn_at(tick)).0;
+        let next = frame(&sim, tick + 1);
+        let mut data = String::new();
+        for (row, line) in next.iter().enumerate() {
+            if shown.get(row) != Some(line) { data.push_str(&format!("\x1b[{};1H{}", row + 1, line)); }
+        }
+        event(time, data);
+        shown = next;
+    }
+    // Hold the last frame for a moment, then leave the cursor below the board
+    event(time + 2.0, format!("\x1b[{};1H\x1b[?25h\r\n", shown.len()));
+    out
+}
+
+/// Writes `<seed>_<score>.cast` to the working directory and returns its path
+pub fn export_cast(replay: &Replay) -> Result<String, String> {
+    let path = format!("{}_{}{}", replay.seed, replay.score, CAST_EXTENSION);
+    write_atomic(&path, &to_cast(replay)).map_err(|e| format!("Export failed: {}", e))?;
+    logging::info(format!("Replay exported as a cast: {}", path));
+    Ok(path)
+}
@@ -0,0 +1,46 @@
+//! The game logic of snake_macroquad, without the window: the deterministic rules, map
+//! generation, replays and the save models. The graphical game, the terminal frontend and
+//! the benchmark are all built on it, and anything else that wants to run, check or analyse
+//! snake games can be too.
+//!
+//! - [`sim::SnakeSim`] is the whole state of a run. [`sim::advance`] plays one tick with an
+//!   optional turn and returns the new state with the [`sim::SimEvent`]s it raised; the same
+//!   state and inputs always give the same result, which replays and netplay rely on.
+//! - [`mapgen::generate`] builds a [`map::Map`] in a named style from a seed, grid size and
+//!   wall density; [`mapgen::register`] adds styles of your own.
+//! - [`rules`] switches the sim's optional rules on for a game mode, modifier or
+//!   set of extras.
+//! - [`replay::Replay`] records a run as its setup and turns. It plays back headless, checks
+//!   a claimed score and reads and writes the shareable `.snakereplay` text format.
+//!   [`cast`] turns one into an asciinema recording drawn with characters.
+//! - [`save`] has the high score table and progress kept across runs, as stored in
+//!   `snake_save.json`.
+//!
+//! ```
+//! use snake_core::map::{Direction, GridConfig, DEFAULT_STYLE};
+//! use snake_core::{mapgen, sim};
+//!
+//! let map = mapgen::generate(DEFAULT_STYLE, GridConfig::DEFAULT, 42, 0.1);
+//! let mut state = sim::SnakeSim::new(map);
+//! for turn in [None, Some(Direction::Up), None] {
+//!     let (next, events) = sim::advance(state, turn);
+//!     assert!(!events.is_empty());
+//!     state = next;
+//! }
+//! assert!(state.alive);
+//! ```
+//!
+//! Files the game writes (the log, replays) go to the working directory.
+
+pub mod cast;
+pub mod logging;
+pub mod map;
+pub mod mapgen;
+pub mod missions;
+pub mod powerups;
+pub mod replay;
+pub mod rng;
+pub mod rules;
+pub mod save;
+pub mod sim;
+pub mod waves;
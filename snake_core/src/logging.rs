@@ -0,0 +1,88 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const LOG_PATH: &str = "snake.log";
+const MAX_LOG_BYTES: u64 = 256 * 1024; // rotate past this size
+const KEEP_ROTATED: usize = 3; // snake.log.1 .. snake.log.3
+const RECENT_LINES: usize = 12; // kept in memory for the debug overlay
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn label(self) -> &'static str {
+        match self {
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        }
+    }
+}
+
+struct Logger {
+    file: Option<File>,
+    written: u64,
+    recent: VecDeque<String>,
+}
+
+impl Logger {
+    fn open() -> Self {
+        let mut logger = Self { file: None, written: 0, recent: VecDeque::new() };
+        logger.reopen();
+        logger
+    }
+
+    fn reopen(&mut self) {
+        self.file = OpenOptions::new().create(true).append(true).open(LOG_PATH).ok();
+        self.written = fs::metadata(LOG_PATH).map(|m| m.len()).unwrap_or(0);
+    }
+
+    // snake.log -> snake.log.1 -> snake.log.2 ..., dropping the oldest
+    fn rotate(&mut self) {
+        self.file = None;
+        for i in (1..KEEP_ROTATED).rev() {
+            let _ = fs::rename(format!("{}.{}", LOG_PATH, i), format!("{}.{}", LOG_PATH, i + 1));
+        }
+        let _ = fs::rename(LOG_PATH, format!("{}.1", LOG_PATH));
+        self.reopen();
+    }
+
+    fn write(&mut self, level: Level, msg: &str) {
+        let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0);
+        let line = format!("{:.3} {:<5} {}", stamp, level.label(), msg);
+        if self.written > MAX_LOG_BYTES { self.rotate(); }
+        if let Some(file) = self.file.as_mut() && writeln!(file, "{}", line).is_ok() {
+            self.written += line.len() as u64 + 1;
+        }
+        if level != Level::Info { eprintln!("{}", line); }
+        self.recent.push_back(line);
+        if self.recent.len() > RECENT_LINES { self.recent.pop_front(); }
+    }
+}
+
+fn logger() -> &'static Mutex<Logger> {
+    static LOGGER: OnceLock<Mutex<Logger>> = OnceLock::new();
+    LOGGER.get_or_init(|| Mutex::new(Logger::open()))
+}
+
+pub fn log(level: Level, msg: impl AsRef<str>) {
+    if let Ok(mut l) = logger().lock() { l.write(level, msg.as_ref()); }
+}
+
+pub fn info(msg: impl AsRef<str>) { log(Level::Info, msg); }
+
+pub fn warn(msg: impl AsRef<str>) { log(Level::Warn, msg); }
+
+pub fn error(msg: impl AsRef<str>) { log(Level::Error, msg); }
+
+/// Most recent log lines, oldest first, for the debug overlay
+pub fn recent() -> Vec<String> {
+    logger().lock().map(|l| l.recent.iter().cloned().collect()).unwrap_or_default()
+}
@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::rng::Rng;
+
+/// Size of the board in tiles. Each map carries its own, so maps of different sizes
+/// (configured, loaded or edited) can coexist; tiles are scaled to the screen when drawn.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct GridConfig {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl GridConfig {
+    pub const DEFAULT: GridConfig = GridConfig { width: 32, height: 24 };
+    pub const MIN: GridConfig = GridConfig { width: 12, height: 10 };
+    pub const MAX: GridConfig = GridConfig { width: 200, height: 150 };
+
+    pub fn contains(self, c: Cell) -> bool {
+        c.x >= 0 && c.y >= 0 && c.x < self.width && c.y < self.height
+    }
+
+    /// The outermost ring, which every map walls in
+    pub fn on_border(self, c: Cell) -> bool {
+        c.x == 0 || c.y == 0 || c.x == self.width - 1 || c.y == self.height - 1
+    }
+
+    pub fn center(self) -> Cell { Cell { x: self.width / 2, y: self.height / 2 } }
+
+    pub fn cell_count(self) -> usize { (self.width * self.height) as usize }
+
+    /// Kept free of walls by every generator so the starting snake has room to move
+    pub fn in_spawn_area(self, c: Cell) -> bool {
+        let spawn = self.center();
+        (c.x - spawn.x).abs() <= 2 && (c.y - spawn.y).abs() <= 2
+    }
+
+    pub fn is_valid(self) -> bool {
+        (Self::MIN.width..=Self::MAX.width).contains(&self.width)
+            && (Self::MIN.height..=Self::MAX.height).contains(&self.height)
+    }
+}
+
+impl Default for GridConfig {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn clockwise(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct Cell {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Cell {
+    /// The neighbouring cell one step in `dir`
+    pub fn step(self, dir: Direction) -> Cell {
+        match dir {
+            Direction::Up => Cell { x: self.x, y: self.y - 1 },
+            Direction::Down => Cell { x: self.x, y: self.y + 1 },
+            Direction::Left => Cell { x: self.x - 1, y: self.y },
+            Direction::Right => Cell { x: self.x + 1, y: self.y },
+        }
+    }
+
+    /// The direction that steps from here onto `to`, if it's a neighbour
+    pub fn direction_to(self, to: Cell) -> Option<Direction> {
+        [Direction::Up, Direction::Down, Direction::Left, Direction::Right].into_iter().find(|d| self.step(*d) == to)
+    }
+}
+
+/// Name of the original generator, assumed for maps saved before styles existed
+pub const DEFAULT_STYLE: &str = "Scattered";
+
+fn default_style() -> String { DEFAULT_STYLE.to_string() }
+
+/// A stretch of wall that opens and closes on a cycle, counted in ticks. It never shuts on
+/// the snake: one due to close stays open until its cells are clear.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Gate {
+    pub cells: Vec<Cell>,
+    pub open_ticks: u32,
+    pub closed_ticks: u32,
+    pub open: bool,
+    pub ticks_left: u32, // until it next opens or closes
+}
+
+impl Gate {
+    /// How far through its current state the gate is, 0 just after a change to 1 at the next
+    pub fn progress(&self) -> f32 {
+        let length = if self.open { self.open_ticks } else { self.closed_ticks };
+        1.0 - self.ticks_left.min(length) as f32 / length.max(1) as f32
+    }
+}
+
+/// One storey of a multi-floor map: a band of rows across the grid, walled off from the
+/// others and reached only by stairs. Floors share the grid, so cells keep plain x and y and
+/// every floor is on screen at once.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Floor {
+    pub top: i32,
+    pub bottom: i32, // inclusive
+}
+
+/// How many switch groups a map can have, one per switch colour
+pub const SWITCH_GROUPS: usize = 4;
+
+/// Pressure plates and the walls they work: the head crossing any of the plates flips every
+/// wall in the group between solid and open. A wall turning solid under the body lets it slide
+/// out, as the head never goes back into cells the body holds.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Switch {
+    pub plates: Vec<Cell>,
+    pub walls: Vec<Cell>,
+    pub solid: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Map {
+    pub walls: HashSet<Cell>,
+    pub seed: u64,
+    pub wall_density: f32,
+    #[serde(default)]
+    pub grid: GridConfig,
+    #[serde(default = "default_style")]
+    pub style: String, // generator that built the walls, so the map can be rebuilt
+    #[serde(default)]
+    pub gates: Vec<Gate>, // timed walls on top of `walls`; closed ones count as wall
+    #[serde(default)]
+    pub conveyors: Vec<(Cell, Direction)>, // belts that push the head a cell further
+    #[serde(default)]
+    pub switches: Vec<Switch>, // at most SWITCH_GROUPS, the group being the index
+    #[serde(default)]
+    pub floors: Vec<Floor>, // empty on single-floor maps
+    #[serde(default)]
+    pub stairs: Vec<(Cell, Cell)>, // pairs linking two floors; stepping on either end comes out at the other
+    #[serde(default)]
+    pub shed: Vec<(Cell, u32)>, // tail segments shed over a length cap: walls for this many more ticks
+    /// `is_wall` for each grid cell, kept by whatever opens or closes one; empty until `reindex`
+    #[serde(skip)]
+    pub solid: Vec<bool>,
+}
+
+/// Cells at least this dangerous count as risky
+pub const RISKY: u8 = 3;
+
+impl Map {
+    pub fn is_wall(&self, c: Cell) -> bool {
+        match self.slot(c).and_then(|slot| self.solid.get(slot)) {
+            Some(solid) => *solid,
+            None => self.scan_wall(c),
+        }
+    }
+
+    // Works `is_wall` out from every wall, gate, switch and shed segment; the index is filled from this
+    fn scan_wall(&self, c: Cell) -> bool {
+        self.walls.contains(&c)
+            || self.gates.iter().any(|g| !g.open && g.cells.contains(&c))
+            || self.switches.iter().any(|s| s.solid && s.walls.contains(&c))
+            || self.shed.iter().any(|(at, _)| *at == c)
+    }
+
+    fn slot(&self, c: Cell) -> Option<usize> {
+        self.grid.contains(c).then(|| (c.y * self.grid.width + c.x) as usize)
+    }
+
+    /// Rebuilds the wall index from scratch, after a map is generated, loaded or edited in bulk
+    pub fn reindex(&mut self) {
+        let grid = self.grid;
+        self.solid = (0..grid.height).flat_map(|y| (0..grid.width).map(move |x| Cell { x, y })).map(|c| self.scan_wall(c)).collect();
+    }
+
+    /// Brings the wall index up to date for `c` after something there opened or closed
+    pub fn refresh(&mut self, c: Cell) {
+        if let Some(slot) = self.slot(c) && slot < self.solid.len() { self.solid[slot] = self.scan_wall(c); }
+    }
+
+    /// Part of a gate or a switched wall, open or closed, or a stair: never a place for food,
+    /// pickups or hazards
+    pub fn is_fixture(&self, c: Cell) -> bool {
+        self.gates.iter().any(|g| g.cells.contains(&c))
+            || self.switches.iter().any(|s| s.walls.contains(&c))
+            || self.stair_exit(c).is_some()
+    }
+
+    /// Where the head comes out stepping onto a stair at `c`: the stair it's paired with
+    pub fn stair_exit(&self, c: Cell) -> Option<Cell> {
+        self.stairs.iter().find_map(|(a, b)| if *a == c { Some(*b) } else if *b == c { Some(*a) } else { None })
+    }
+
+    /// The floor `c` is on; None on single-floor maps and on the walls between floors
+    pub fn floor_of(&self, c: Cell) -> Option<usize> {
+        self.floors.iter().position(|f| (f.top..=f.bottom).contains(&c.y))
+    }
+
+    /// The switch group with a plate at `c`
+    pub fn plate_at(&self, c: Cell) -> Option<usize> {
+        self.switches.iter().position(|s| s.plates.contains(&c))
+    }
+
+    pub fn conveyor_at(&self, c: Cell) -> Option<Direction> {
+        self.conveyors.iter().find(|(at, _)| *at == c).map(|(_, dir)| *dir)
+    }
+
+    /// One tick of every gate's cycle; a gate due to close while any of its cells is in
+    /// `occupied` waits for them to clear
+    pub fn advance_gates(&mut self, occupied: &HashSet<Cell>) {
+        let mut changed = Vec::new();
+        for gate in &mut self.gates {
+            gate.ticks_left = gate.ticks_left.saturating_sub(1);
+            if gate.ticks_left > 0 { continue; }
+            if gate.open && gate.cells.iter().any(|c| occupied.contains(c)) { continue; }
+            gate.open = !gate.open;
+            gate.ticks_left = if gate.open { gate.open_ticks } else { gate.closed_ticks };
+            changed.extend(gate.cells.iter().copied());
+        }
+        for c in changed { self.refresh(c); }
+    }
+
+    /// How boxed in each open cell is, row by row (walls score 0): one point per wall next to
+    /// it, plus one more when walls sit on both sides of it as in a corridor. A dead end
+    /// scores 3, a one-wide corridor 3, a corner 2.
+    pub fn danger_scores(&self) -> Vec<u8> {
+        let grid = self.grid;
+        let mut scores = vec![0; grid.cell_count()];
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                let c = Cell { x, y };
+                if self.is_wall(c) { continue; }
+                let [up, down, left, right] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right].map(|d| self.is_wall(c.step(d)));
+                let walls = [up, down, left, right].iter().filter(|w| **w).count() as u8;
+                let corridor = (up && down) || (left && right);
+                scores[(y * grid.width + x) as usize] = walls + corridor as u8;
+            }
+        }
+        scores
+    }
+
+    /// The outer ring every map is enclosed by
+    pub fn border_walls(grid: GridConfig) -> HashSet<Cell> {
+        let mut walls = HashSet::new();
+        for x in 0..grid.width {
+            walls.insert(Cell { x, y: 0 });
+            walls.insert(Cell { x, y: grid.height - 1 });
+        }
+        for y in 0..grid.height {
+            walls.insert(Cell { x: 0, y });
+            walls.insert(Cell { x: grid.width - 1, y });
+        }
+        walls
+    }
+
+    /// The "Scattered" style: walls sprinkled uniformly at `wall_density`
+    pub fn generate(grid: GridConfig, seed: u64, wall_density: f32) -> Self {
+        // Own RNG seeded for reproducibility
+        let mut rng = Rng::new(seed);
+
+        let mut walls = Self::border_walls(grid);
+
+        // Random interior walls
+        for y in 1..(grid.height - 1) {
+            for x in 1..(grid.width - 1) {
+                let c = Cell { x, y };
+                if grid.in_spawn_area(c) { continue; }
+                let r: f32 = rng.next_f32();
+                if r < wall_density { walls.insert(c); }
+            }
+        }
+
+        Self { walls, seed, wall_density, grid, style: default_style(), gates: Vec::new(), conveyors: Vec::new(), switches: Vec::new(), floors: Vec::new(), stairs: Vec::new(), shed: Vec::new(), solid: Vec::new() }
+    }
+}
@@ -0,0 +1,370 @@
+//! Map styles: each generator turns a seed and the lobby parameters into walls. The builtin
+//! ones are always registered, and anything embedding the rules can add its own with `register`
+//! (the game does this for script mods).
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::rc::Rc;
+
+use crate::logging;
+use crate::map::{Cell, Direction, Floor, Gate, GridConfig, Map, Switch, DEFAULT_STYLE};
+use crate::rng::Rng;
+use crate::sim::CONVEYOR_STREAM;
+
+#[derive(Copy, Clone)]
+pub struct MapParams {
+    pub grid: GridConfig,
+    pub wall_density: f32,
+}
+
+pub trait MapGenerator {
+    // Shown in the lobby and stored with saves and replays, so keep it stable
+    fn name(&self) -> &str;
+    // Must be deterministic in `seed`: replays and netplay rebuild maps from it
+    fn generate(&self, seed: u64, params: &MapParams) -> Map;
+}
+
+struct Scattered;
+
+impl MapGenerator for Scattered {
+    fn name(&self) -> &str { DEFAULT_STYLE }
+
+    fn generate(&self, seed: u64, params: &MapParams) -> Map {
+        Map::generate(params.grid, seed, params.wall_density)
+    }
+}
+
+// Cellular-automaton caves: random fill smoothed into blobs, then every pocket the snake
+// can't reach from the spawn is filled in so food never lands out of reach
+struct Caves;
+
+const CAVE_SMOOTHING_PASSES: usize = 4;
+
+impl MapGenerator for Caves {
+    fn name(&self) -> &str { "Caves" }
+
+    fn generate(&self, seed: u64, params: &MapParams) -> Map {
+        let grid = params.grid;
+        let (w, h) = (grid.width, grid.height);
+        let border = |x: i32, y: i32| x == 0 || y == 0 || x == w - 1 || y == h - 1;
+        let mut rng = Rng::new(seed);
+        // Lobby density 0-35% maps to 40-54% initial fill: denser means narrower tunnels
+        let fill = 0.40 + params.wall_density * 0.4;
+
+        let mut solid = vec![false; grid.cell_count()];
+        for y in 0..h {
+            for x in 0..w {
+                let c = Cell { x, y };
+                solid[(y * w + x) as usize] = border(x, y) || (!grid.in_spawn_area(c) && rng.next_f32() < fill);
+            }
+        }
+
+        // A cell becomes wall when most of its 3x3 neighbourhood is; off-grid counts as wall
+        for _ in 0..CAVE_SMOOTHING_PASSES {
+            let prev = solid.clone();
+            for y in 0..h {
+                for x in 0..w {
+                    let mut count = 0;
+                    for dy in -1..=1 {
+                        for dx in -1..=1 {
+                            let c = Cell { x: x + dx, y: y + dy };
+                            if !grid.contains(c) || prev[(c.y * w + c.x) as usize] { count += 1; }
+                        }
+                    }
+                    let c = Cell { x, y };
+                    solid[(y * w + x) as usize] = border(x, y) || (!grid.in_spawn_area(c) && count >= 5);
+                }
+            }
+        }
+
+        // Flood fill from the spawn; whatever stays unreached becomes wall
+        let mut reached = HashSet::from([grid.center()]);
+        let mut queue = VecDeque::from([grid.center()]);
+        while let Some(c) = queue.pop_front() {
+            for n in [Cell { x: c.x + 1, y: c.y }, Cell { x: c.x - 1, y: c.y }, Cell { x: c.x, y: c.y + 1 }, Cell { x: c.x, y: c.y - 1 }] {
+                if grid.contains(n) && !solid[(n.y * w + n.x) as usize] && reached.insert(n) { queue.push_back(n); }
+            }
+        }
+
+        let mut walls = HashSet::new();
+        for y in 0..h {
+            for x in 0..w {
+                let c = Cell { x, y };
+                if !reached.contains(&c) { walls.insert(c); }
+            }
+        }
+        Map { walls, seed, wall_density: params.wall_density, grid, style: self.name().to_string(), gates: Vec::new(), conveyors: Vec::new(), switches: Vec::new(), floors: Vec::new(), stairs: Vec::new(), shed: Vec::new(), solid: Vec::new() }
+    }
+}
+
+// Barriers down the map, each with a way round at both ends and a gate partway along that
+// opens and closes on its own cycle: waiting for it is the shortcut. Density adds barriers.
+struct Gates;
+
+const GATE_LENGTH: i32 = 3;
+
+impl MapGenerator for Gates {
+    fn name(&self) -> &str { "Gates" }
+
+    fn generate(&self, seed: u64, params: &MapParams) -> Map {
+        let grid = params.grid;
+        let mut rng = Rng::new(seed);
+        let mut walls = Map::border_walls(grid);
+        let mut gates = Vec::new();
+        // Lobby density 0-35% gives 2-5 barriers, at least three columns apart
+        let count = (2 + (params.wall_density * 10.0).round() as i32).min(grid.width / 3 - 1);
+        let spacing = grid.width / (count + 1);
+        let spawn = grid.center();
+        for i in 1..=count {
+            let x = i * spacing;
+            if (x - spawn.x).abs() <= 2 { continue; }
+            // Rows 1 and height-2 stay open, so every barrier can be gone round
+            let (top, bottom) = (2, grid.height - 3);
+            let gate_top = rng.range_i32(top, bottom - GATE_LENGTH + 2);
+            let cells: Vec<Cell> = (gate_top..gate_top + GATE_LENGTH).map(|y| Cell { x, y }).collect();
+            for y in top..=bottom {
+                if !(gate_top..gate_top + GATE_LENGTH).contains(&y) { walls.insert(Cell { x, y }); }
+            }
+            let open_ticks = rng.range_i32(15, 31) as u32;
+            let closed_ticks = rng.range_i32(20, 41) as u32;
+            // Started partway through, so the gates don't all move together
+            let open = rng.next_f32() < 0.5;
+            let ticks_left = rng.range_i32(1, if open { open_ticks } else { closed_ticks } as i32 + 1) as u32;
+            gates.push(Gate { cells, open_ticks, closed_ticks, open, ticks_left });
+        }
+        Map { walls, seed, wall_density: params.wall_density, grid, style: self.name().to_string(), gates, conveyors: Vec::new(), switches: Vec::new(), floors: Vec::new(), stairs: Vec::new(), shed: Vec::new(), solid: Vec::new() }
+    }
+}
+
+// Three rooms stacked top to bottom, split by walls that each have a door of two colours, one
+// shut and one open to start with. Every room has a plate of each colour, so whichever doors are
+// shut the snake can always work its way out. Density scatters walls in the rooms; a grid too
+// short for a room above or below the spawn gets fewer rooms.
+struct Switches;
+
+const DOOR_WIDTH: i32 = 3;
+
+impl MapGenerator for Switches {
+    fn name(&self) -> &str { "Switches" }
+
+    fn generate(&self, seed: u64, params: &MapParams) -> Map {
+        let grid = params.grid;
+        let (w, h) = (grid.width, grid.height);
+        let mut rng = Rng::new(seed);
+        let mut walls = Map::border_walls(grid);
+        let mut switches = vec![Switch { solid: true, ..Switch::default() }, Switch::default()];
+        let spawn = grid.center();
+        let splits: Vec<i32> = [(h / 4).min(spawn.y - 3), (h - 1 - h / 4).max(spawn.y + 3)]
+            .into_iter()
+            .filter(|y| (2..=h - 3).contains(y))
+            .collect();
+
+        // A door in each half of every split, the colours either way round
+        let mut doorsteps = HashSet::new();
+        for &y in &splits {
+            let left = rng.range_i32(1, w / 2 - DOOR_WIDTH + 1);
+            let right = rng.range_i32(w / 2, w - 1 - DOOR_WIDTH + 1);
+            let swap = rng.next_f32() < 0.5;
+            for x in 1..w - 1 {
+                let group = if (left..left + DOOR_WIDTH).contains(&x) {
+                    Some(swap as usize)
+                } else if (right..right + DOOR_WIDTH).contains(&x) {
+                    Some(!swap as usize)
+                } else {
+                    None
+                };
+                match group {
+                    Some(g) => {
+                        switches[g].walls.push(Cell { x, y });
+                        doorsteps.extend([Cell { x, y: y - 1 }, Cell { x, y: y + 1 }]);
+                    }
+                    None => { walls.insert(Cell { x, y }); }
+                }
+            }
+        }
+
+        for y in 1..h - 1 {
+            if splits.contains(&y) { continue; }
+            for x in 1..w - 1 {
+                let c = Cell { x, y };
+                if grid.in_spawn_area(c) || doorsteps.contains(&c) { continue; }
+                if rng.next_f32() < params.wall_density { walls.insert(c); }
+            }
+        }
+
+        // One plate of each colour per room, on open floor
+        let mut bounds = vec![0];
+        bounds.extend(&splits);
+        bounds.push(h - 1);
+        let mut plates = HashSet::new();
+        for room in bounds.windows(2) {
+            for switch in &mut switches {
+                let spot = (0..64)
+                    .map(|_| Cell { x: rng.range_i32(1, w - 1), y: rng.range_i32(room[0] + 1, room[1]) })
+                    .find(|c| !walls.contains(c) && !grid.in_spawn_area(*c) && !doorsteps.contains(c) && !plates.contains(c));
+                if let Some(c) = spot {
+                    plates.insert(c);
+                    switch.plates.push(c);
+                }
+            }
+        }
+        Map { walls, seed, wall_density: params.wall_density, grid, style: self.name().to_string(), gates: Vec::new(), conveyors: Vec::new(), switches, floors: Vec::new(), stairs: Vec::new(), shed: Vec::new(), solid: Vec::new() }
+    }
+}
+
+// Two or three floors stacked down the grid, walled apart and joined by pairs of stairs: two
+// between each neighbouring pair of floors. The spawn is on the first floor, or the middle one
+// of three. Density scatters walls on every floor; a grid too short for a second floor comes
+// out as one.
+struct Floors;
+
+const STAIRS_PER_LANDING: usize = 2;
+
+impl MapGenerator for Floors {
+    fn name(&self) -> &str { "Floors" }
+
+    fn generate(&self, seed: u64, params: &MapParams) -> Map {
+        let grid = params.grid;
+        let (w, h) = (grid.width, grid.height);
+        let mut rng = Rng::new(seed);
+        let mut walls = Map::border_walls(grid);
+        let spawn = grid.center();
+        // The dividing walls keep clear of the spawn area's rows, with a floor at least two rows deep beyond
+        let mut dividers: Vec<i32> = if rng.next_f32() < 0.5 { vec![spawn.y + 3] } else { vec![spawn.y - 3, spawn.y + 3] };
+        dividers.retain(|y| (3..=h - 4).contains(y));
+        let mut floors = Vec::new();
+        let mut top = 1;
+        for &y in &dividers {
+            floors.push(Floor { top, bottom: y - 1 });
+            top = y + 1;
+            for x in 1..w - 1 { walls.insert(Cell { x, y }); }
+        }
+        floors.push(Floor { top, bottom: h - 2 });
+
+        // Stairs with their neighbours kept free of scattered walls, so the head has a way on
+        let mut clear = HashSet::new();
+        let mut stairs = Vec::new();
+        let spot = |floor: Floor, rng: &mut Rng, clear: &mut HashSet<Cell>| {
+            let found = (0..64)
+                .map(|_| Cell { x: rng.range_i32(2, w - 2), y: rng.range_i32(floor.top + 1, floor.bottom) })
+                .find(|c| !grid.in_spawn_area(*c) && !clear.contains(c));
+            if let Some(c) = found {
+                clear.insert(c);
+                clear.extend([Direction::Up, Direction::Down, Direction::Left, Direction::Right].map(|d| c.step(d)));
+            }
+            found
+        };
+        for pair in floors.windows(2) {
+            for _ in 0..STAIRS_PER_LANDING {
+                if let (Some(a), Some(b)) = (spot(pair[0], &mut rng, &mut clear), spot(pair[1], &mut rng, &mut clear)) {
+                    stairs.push((a, b));
+                }
+            }
+        }
+
+        for floor in &floors {
+            for y in floor.top..=floor.bottom {
+                for x in 1..w - 1 {
+                    let c = Cell { x, y };
+                    if grid.in_spawn_area(c) || clear.contains(&c) { continue; }
+                    if rng.next_f32() < params.wall_density { walls.insert(c); }
+                }
+            }
+        }
+        if floors.len() < 2 { floors.clear(); }
+        Map { walls, seed, wall_density: params.wall_density, grid, style: self.name().to_string(), gates: Vec::new(), conveyors: Vec::new(), switches: Vec::new(), floors, stairs, shed: Vec::new(), solid: Vec::new() }
+    }
+}
+
+// Conveyors: short belts laid along corridors, on straight stretches of floor that run beside
+// a wall, each belt running one way along it. They have their own RNG stream, so a seed's walls
+// stay as they were.
+const BELT_LENGTH: usize = 3;
+const CELLS_PER_BELT: usize = 24; // corridor cells for each belt laid
+const MAX_BELTS: usize = 8;
+
+fn place_conveyors(map: &mut Map) {
+    let grid = map.grid;
+    let mut rng = Rng::with_stream(map.seed, CONVEYOR_STREAM);
+    // Some(true) for floor with a wall above or below and none to either side, so a corridor
+    // running left-right; Some(false) the same turned on its side
+    let corridor = |c: Cell| -> Option<bool> {
+        if map.is_wall(c) || map.is_fixture(c) || map.plate_at(c).is_some() || grid.in_spawn_area(c) { return None; }
+        let [up, down, left, right] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right].map(|d| map.is_wall(c.step(d)));
+        match (up || down, left || right) {
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            _ => None,
+        }
+    };
+    // Every straight stretch of two or more such cells, along rows and then columns
+    let mut runs: Vec<(Vec<Cell>, bool)> = Vec::new();
+    for across in [true, false] {
+        let (lines, length) = if across { (grid.height, grid.width) } else { (grid.width, grid.height) };
+        for a in 1..lines - 1 {
+            let mut run = Vec::new();
+            for b in 1..length {
+                let c = if across { Cell { x: b, y: a } } else { Cell { x: a, y: b } };
+                if corridor(c) == Some(across) {
+                    run.push(c);
+                } else if run.len() >= 2 {
+                    runs.push((std::mem::take(&mut run), across));
+                } else {
+                    run.clear();
+                }
+            }
+        }
+    }
+    let belts = runs.iter().map(|(run, _)| run.len()).sum::<usize>() / CELLS_PER_BELT;
+    for _ in 0..belts.min(MAX_BELTS).min(runs.len()) {
+        let (run, across) = runs.swap_remove(rng.range_usize(0, runs.len()));
+        let length = run.len().min(BELT_LENGTH);
+        let start = rng.range_usize(0, run.len() - length + 1);
+        let dir = match (across, rng.next_f32() < 0.5) {
+            (true, true) => Direction::Right,
+            (true, false) => Direction::Left,
+            (false, true) => Direction::Down,
+            (false, false) => Direction::Up,
+        };
+        map.conveyors.extend(run[start..start + length].iter().map(|c| (*c, dir)));
+    }
+}
+
+thread_local! {
+    // Builtins first, then mod generators in load order; the lobby cycles in this order
+    static REGISTRY: RefCell<Vec<Rc<dyn MapGenerator>>> = RefCell::new(vec![Rc::new(Scattered), Rc::new(Caves), Rc::new(Gates), Rc::new(Switches), Rc::new(Floors)]);
+}
+
+/// Adds a generator to the lobby's style list; names must be unique
+pub fn register(generator: Rc<dyn MapGenerator>) -> Result<(), String> {
+    REGISTRY.with(|r| {
+        let mut r = r.borrow_mut();
+        if r.iter().any(|g| g.name() == generator.name()) {
+            return Err(format!("map style '{}' already exists", generator.name()));
+        }
+        r.push(generator);
+        Ok(())
+    })
+}
+
+pub fn names() -> Vec<String> {
+    REGISTRY.with(|r| r.borrow().iter().map(|g| g.name().to_string()).collect())
+}
+
+pub fn is_registered(style: &str) -> bool {
+    REGISTRY.with(|r| r.borrow().iter().any(|g| g.name() == style))
+}
+
+/// Builds a map in the named style, with conveyors laid along its corridors; unknown styles
+/// (a mod that was removed) fall back to the default one
+pub fn generate(style: &str, grid: GridConfig, seed: u64, wall_density: f32) -> Map {
+    let found = REGISTRY.with(|r| r.borrow().iter().find(|g| g.name() == style).cloned());
+    let generator: Rc<dyn MapGenerator> = found.unwrap_or_else(|| {
+        logging::warn(format!("Unknown map style '{}', using {}", style, DEFAULT_STYLE));
+        Rc::new(Scattered)
+    });
+    let mut map = generator.generate(seed, &MapParams { grid, wall_density });
+    map.style = generator.name().to_string();
+    map.conveyors.clear();
+    place_conveyors(&mut map);
+    map
+}
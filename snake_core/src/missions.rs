@@ -0,0 +1,118 @@
+//! In-run objectives: one mission at a time, drawn from a rotation and tracked tick by tick
+//! from what the sim did. A finished mission pays its bonus into the score; a failed one is
+//! simply replaced. Missions get a little harder with every one completed.
+use serde::{Deserialize, Serialize};
+
+use crate::map::{Cell, GridConfig};
+use crate::rng::Rng;
+
+const MAX_STEP: u32 = 5; // completions after which missions stop getting harder
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Objective {
+    /// Eat `food` within `seconds` of the mission starting
+    EatWithin { food: u32, seconds: u32 },
+    /// Grow to `length` without the head touching a cell next to the border
+    ReachLength { length: u32 },
+    /// Eat `food` in a row, each within `moves` of the last
+    Streak { food: u32, moves: u32 },
+}
+
+impl Objective {
+    pub fn bonus(self) -> u32 {
+        match self {
+            Objective::EatWithin { food, .. } => food,
+            Objective::ReachLength { .. } => 5,
+            Objective::Streak { food, .. } => food + 1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Outcome {
+    Completed { bonus: u32 },
+    Failed,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Missions {
+    pub objective: Objective,
+    pub progress: u32, // food eaten so far; unused by ReachLength
+    pub elapsed: u32, // ticks since the mission started
+    pub since_food: u32, // ticks since the last meal, for Streak
+    pub off_edge: bool, // the head has been away from the border since the mission started
+    pub completed: u32,
+    pub bonus: u32, // points paid out over the run
+    pub tick_seconds: f32, // the run's move interval, to turn seconds into ticks
+    pub rng: Rng,
+}
+
+impl Missions {
+    pub fn new(mut rng: Rng, tick_seconds: f32, grid: GridConfig, length: usize) -> Self {
+        let objective = Self::pick(&mut rng, 0, grid, length);
+        Self { objective, progress: 0, elapsed: 0, since_food: 0, off_edge: false, completed: 0, bonus: 0, tick_seconds, rng }
+    }
+
+    fn pick(rng: &mut Rng, completed: u32, grid: GridConfig, length: usize) -> Objective {
+        let step = completed.min(MAX_STEP);
+        match rng.range_usize(0, 3) {
+            0 => Objective::EatWithin { food: 3 + step / 2, seconds: 30 - step * 2 },
+            1 => Objective::ReachLength { length: length as u32 + 6 + step * 2 },
+            _ => Objective::Streak { food: 3 + step / 2, moves: ((grid.width + grid.height) as u32).saturating_sub(step * 2) },
+        }
+    }
+
+    /// Seconds left for EatWithin, counted from the ticks played
+    pub fn seconds_left(&self) -> Option<f32> {
+        let Objective::EatWithin { seconds, .. } = self.objective else { return None; };
+        Some((seconds as f32 - self.elapsed as f32 * self.tick_seconds).max(0.0))
+    }
+
+    /// Call once per tick the snake moved; returns how the mission ended, if it did
+    pub fn on_tick(&mut self, ate: bool, head: Cell, length: usize, grid: GridConfig) -> Option<Outcome> {
+        self.elapsed += 1;
+        self.since_food += 1;
+        if ate {
+            self.progress += 1;
+            self.since_food = 0;
+        }
+        let outcome = match self.objective {
+            Objective::EatWithin { food, .. } => {
+                if self.progress >= food {
+                    Some(Outcome::Completed { bonus: self.objective.bonus() })
+                } else if self.seconds_left() == Some(0.0) {
+                    Some(Outcome::Failed)
+                } else {
+                    None
+                }
+            }
+            Objective::ReachLength { length: goal } => {
+                // Handed out while already on the edge, it only counts once the head has left it
+                let edge = head.x <= 1 || head.y <= 1 || head.x >= grid.width - 2 || head.y >= grid.height - 2;
+                if edge && self.off_edge {
+                    Some(Outcome::Failed)
+                } else if length as u32 >= goal {
+                    Some(Outcome::Completed { bonus: self.objective.bonus() })
+                } else {
+                    self.off_edge |= !edge;
+                    None
+                }
+            }
+            Objective::Streak { food, moves } => {
+                // Too slow: the streak starts over from the next meal
+                if self.since_food > moves { self.progress = 0; }
+                (self.progress >= food).then(|| Outcome::Completed { bonus: self.objective.bonus() })
+            }
+        }?;
+        if let Outcome::Completed { bonus } = outcome {
+            self.completed += 1;
+            self.bonus += bonus;
+        }
+        self.objective = Self::pick(&mut self.rng, self.completed, grid, length);
+        self.progress = 0;
+        self.elapsed = 0;
+        self.since_food = 0;
+        self.off_edge = false;
+        Some(outcome)
+    }
+}
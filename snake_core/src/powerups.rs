@@ -0,0 +1,81 @@
+//! Power-up pickups: now and then one turns up on a free cell for a while, and running over it
+//! triggers its effect. Only one is on the board at a time. This keeps the spawn schedule; the
+//! sim applies the effects.
+use serde::{Deserialize, Serialize};
+
+use crate::map::Cell;
+use crate::rng::Rng;
+
+pub const POWERUP_EVERY: u32 = 6; // meals between chances of a power-up
+pub const POWERUP_CHANCE: f32 = 0.4;
+pub const POWERUP_TICKS: u32 = 120; // how long one stays before it's gone
+pub const BOMB_RADIUS: i32 = 3; // interior walls this close to a bomb are destroyed
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum PowerUp {
+    Reverse, // head and tail swap places
+    Bomb, // clears the walls around it for the rest of the run
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 2] = [PowerUp::Reverse, PowerUp::Bomb];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerUp::Reverse => "REVERSE",
+            PowerUp::Bomb => "BOOM",
+        }
+    }
+
+    // Relative odds when one is placed; bombs are the rare one
+    fn weight(self) -> usize {
+        match self {
+            PowerUp::Reverse => 3,
+            PowerUp::Bomb => 1,
+        }
+    }
+
+    /// Not one of the Matrix glyphs, so it can't be mistaken for food
+    pub fn glyph(self) -> char {
+        match self {
+            PowerUp::Reverse => '&',
+            PowerUp::Bomb => '%',
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PowerUps {
+    pub kinds: Vec<PowerUp>, // what may spawn in this run
+    pub at: Option<(PowerUp, Cell)>,
+    pub ticks_left: u32,
+    pub meals: u32, // counting toward the next chance
+    pub used: u32,
+    pub rng: Rng,
+}
+
+impl PowerUps {
+    pub fn new(rng: Rng, kinds: &[PowerUp]) -> Self {
+        Self { kinds: kinds.to_vec(), at: None, ticks_left: 0, meals: 0, used: 0, rng }
+    }
+
+    pub fn at_cell(&self, cell: Cell) -> Option<PowerUp> {
+        self.at.filter(|(_, c)| *c == cell).map(|(kind, _)| kind)
+    }
+
+    /// Counts a meal; true if a power-up should be placed now
+    pub fn on_food(&mut self) -> bool {
+        self.meals += 1;
+        self.at.is_none() && !self.kinds.is_empty() && self.meals.is_multiple_of(POWERUP_EVERY) && self.rng.next_f32() < POWERUP_CHANCE
+    }
+
+    pub fn pick(&mut self) -> PowerUp {
+        let total = self.kinds.iter().map(|k| k.weight()).sum();
+        let mut roll = self.rng.range_usize(0, total);
+        for kind in &self.kinds {
+            if roll < kind.weight() { return *kind; }
+            roll -= kind.weight();
+        }
+        self.kinds[0]
+    }
+}
@@ -0,0 +1,474 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::logging;
+use crate::map::{Cell, Direction, GridConfig, Map, DEFAULT_STYLE};
+use crate::mapgen;
+use crate::powerups::PowerUp;
+use crate::rng::fnv1a;
+use crate::rules::{self, Extras, GameMode, Modifier};
+use crate::save::write_atomic;
+use crate::sim::{self, SnakeSim};
+
+pub const REPLAY_VERSION: u32 = 7;
+// Runs recorded from these versions on can have bombs among their power-ups, and have
+// conveyors on the map
+const BOMB_VERSION: u32 = 4;
+const CONVEYOR_VERSION: u32 = 6;
+pub const REPLAY_DIR: &str = "replays";
+const MAX_REPLAYS: usize = 50; // oldest files are pruned past this
+pub const SHARE_EXTENSION: &str = ".snakereplay";
+const SHARE_MAGIC: &str = "snakereplay";
+
+fn default_map_style() -> String { DEFAULT_STYLE.to_string() }
+
+/// Everything needed to re-run a game deterministically: the map parameters plus the tick
+/// at which each turn took effect. Food placement follows from the seed via the sim's own RNG.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub wall_density: f32,
+    pub move_interval: f32,
+    #[serde(default)]
+    pub grid: GridConfig,
+    #[serde(default)]
+    pub mode: GameMode,
+    #[serde(default = "default_map_style")]
+    pub map_style: String,
+    #[serde(default)]
+    pub missions: bool,
+    #[serde(default)]
+    pub modifier: Option<Modifier>, // weekly challenge or settings rule
+    #[serde(default)]
+    pub corpses: Vec<Cell>, // Graveyard walls the run started with, in the order they were placed
+    #[serde(default)]
+    pub extras: Extras, // optional pickups; older replays had none
+    pub score: u32,
+    pub ticks: u32, // total ticks in the run
+    pub turns: Vec<(u32, Direction)>,
+}
+
+impl Replay {
+    pub fn new(map: &Map, move_interval: f32) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            seed: map.seed,
+            wall_density: map.wall_density,
+            move_interval,
+            grid: map.grid,
+            mode: GameMode::Classic,
+            map_style: map.style.clone(),
+            missions: false,
+            modifier: None,
+            corpses: Vec::new(),
+            extras: Extras::default(),
+            score: 0,
+            ticks: 0,
+            turns: Vec::new(),
+        }
+    }
+
+    /// Everything but the seed that shapes a run, for telling comparable runs apart
+    pub fn settings_key(&self) -> String {
+        let missions = if self.missions { "|missions" } else { "" };
+        let modifier = self.modifier.map_or(String::new(), |m| format!("|{}", m.label()));
+        let extras = if self.extras == Extras::default() { String::new() } else { format!("|{}", self.extras.key()) };
+        format!("{}|{}|{}x{}|{}|{}{}{}{}", self.wall_density, self.move_interval, self.grid.width, self.grid.height, self.mode.label(), self.map_style, missions, modifier, extras)
+    }
+
+    /// The run's settings as a factor on its score; see [`rules::score_multiplier`]
+    pub fn score_multiplier(&self) -> f32 {
+        rules::score_multiplier(self.wall_density, self.move_interval, self.grid, self.mode, self.modifier)
+    }
+
+    /// The score with the multiplier applied, as the High Scores table ranks it
+    pub fn rated_score(&self) -> u32 {
+        (self.score as f32 * self.score_multiplier()).round() as u32
+    }
+
+    /// Whether the map had conveyors in the version this was recorded with
+    pub fn conveyors(&self) -> bool {
+        self.version >= CONVEYOR_VERSION
+    }
+
+    /// Power-ups that could spawn in the version this was recorded with
+    fn powerups(&self) -> &'static [PowerUp] {
+        if !self.extras.powerups {
+            &[]
+        } else if self.version >= BOMB_VERSION {
+            &PowerUp::ALL
+        } else {
+            &[PowerUp::Reverse]
+        }
+    }
+
+    /// Switches on the run's extras as its version had them
+    pub fn apply_extras(&self, sim: &mut SnakeSim) {
+        Extras { powerups: false, ..self.extras }.apply(sim);
+        if !self.powerups().is_empty() { sim.enable_powerups(self.powerups()); }
+    }
+
+    /// Direction that takes effect on `tick`, if the player turned then
+    pub fn turn_at(&self, tick: u32) -> Option<Direction> {
+        let idx = self.turns.partition_point(|(t, _)| *t < tick);
+        self.turns.get(idx).filter(|(t, _)| *t == tick).map(|(_, d)| *d)
+    }
+
+    // The sim on the recorded map with the recorded rules, before the first tick
+    pub(crate) fn headless_start(&self) -> SnakeSim {
+        let mut map = mapgen::generate(&self.map_style, self.grid, self.seed, self.wall_density);
+        if !self.conveyors() { map.conveyors.clear(); }
+        let mut sim = SnakeSim::new(map);
+        self.apply_extras(&mut sim);
+        self.mode.apply(&mut sim);
+        if self.missions { sim.enable_missions(self.move_interval); }
+        if let Some(modifier) = self.modifier { modifier.apply(&mut sim, self.move_interval); }
+        for c in &self.corpses { sim.add_wall(*c); }
+        sim
+    }
+
+    /// Re-runs the turns without a window and returns the sim as the run left it
+    pub fn play_headless(&self) -> SnakeSim {
+        let mut sim = self.headless_start();
+        for tick in 0..self.ticks {
+            if !sim.alive { break; }
+            // Recorded turns were accepted by `turn` when played, so it takes them again
+            sim = sim::advance(sim, self.turn_at(tick)).0;
+        }
+        sim
+    }
+
+    /// Re-runs the turns headless and checks they really produce the recorded score
+    pub fn verify(&self) -> Result<(), String> {
+        let sim = self.play_headless();
+        if sim.score != self.score {
+            return Err(format!("Replay claims score {} but plays out to {}", self.score, sim.score));
+        }
+        Ok(())
+    }
+
+    /// Shareable text form: one "key value" per line, turns as tick deltas ("12U4L"), and a
+    /// checksum over everything above it so edited files are rejected
+    pub fn to_share_text(&self) -> String {
+        let mut turns = String::new();
+        let mut last = 0;
+        for (tick, dir) in &self.turns {
+            let letter = match dir {
+                Direction::Up => 'U',
+                Direction::Down => 'D',
+                Direction::Left => 'L',
+                Direction::Right => 'R',
+            };
+            turns.push_str(&format!("{}{}", tick - last, letter));
+            last = *tick;
+        }
+        let corpses: Vec<String> = self.corpses.iter().map(|c| format!("{},{}", c.x, c.y)).collect();
+        let body = format!(
+            "{} {}\nseed {}\ndensity {}\ninterval {}\ngrid {}x{}\nmode {}\nstyle {}\nmissions {}\nmodifier {}\nextras {}\ncorpses {}\nscore {}\nticks {}\nturns {}\n",
+            SHARE_MAGIC, self.version, self.seed, self.wall_density, self.move_interval, self.grid.width, self.grid.height,
+            self.mode.label(), self.map_style, self.missions as u8, self.modifier.map_or("none", |m| m.label()), self.extras.key(), corpses.join(" "),
+            self.score, self.ticks, turns
+        );
+        format!("{}checksum {:016x}\n", body, fnv1a(body.as_bytes()))
+    }
+
+    pub fn from_share_text(text: &str) -> Result<Self, String> {
+        let text = text.replace("\r\n", "\n");
+        let (body, checksum) = text.rsplit_once("checksum ").ok_or("Not a snake replay (no checksum)")?;
+        if format!("{:016x}", fnv1a(body.as_bytes())) != checksum.trim() {
+            return Err("Replay checksum doesn't match - the file was changed or damaged".to_string());
+        }
+        let mut fields = std::collections::HashMap::new();
+        for line in body.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            fields.insert(key, value);
+        }
+        let field = |key: &str| fields.get(key).copied().ok_or_else(|| format!("Replay is missing '{}'", key));
+        let number = |key: &str| -> Result<f64, String> { field(key)?.parse().map_err(|_| format!("Replay has a bad '{}'", key)) };
+
+        let version = number(SHARE_MAGIC)? as u32;
+        if version > REPLAY_VERSION {
+            return Err(format!("Replay version {} is newer than supported ({})", version, REPLAY_VERSION));
+        }
+        let (w, h) = field("grid")?.split_once('x').ok_or("Replay has a bad 'grid'")?;
+        let grid = GridConfig { width: w.parse().map_err(|_| "Replay has a bad 'grid'")?, height: h.parse().map_err(|_| "Replay has a bad 'grid'")? };
+        if !grid.is_valid() { return Err(format!("Replay grid {}x{} is out of range", grid.width, grid.height)); }
+        let mode_name = field("mode")?;
+        let mode = *GameMode::ALL.iter().find(|m| m.label() == mode_name).ok_or_else(|| format!("Unknown mode '{}'", mode_name))?;
+
+        let mut turns = Vec::new();
+        let mut tick = 0u32;
+        let mut digits = String::new();
+        for c in field("turns")?.chars() {
+            let dir = match c {
+                '0'..='9' => { digits.push(c); continue; }
+                'U' => Direction::Up,
+                'D' => Direction::Down,
+                'L' => Direction::Left,
+                'R' => Direction::Right,
+                _ => return Err(format!("Replay has a bad turn '{}'", c)),
+            };
+            tick += digits.parse::<u32>().map_err(|_| "Replay has a bad turn list")?;
+            digits.clear();
+            turns.push((tick, dir));
+        }
+
+        // Files shared before the graveyard existed have no 'corpses' line
+        let mut corpses = Vec::new();
+        for pair in fields.get("corpses").copied().unwrap_or("").split_whitespace() {
+            let cell = pair.split_once(',').and_then(|(x, y)| Some(Cell { x: x.parse().ok()?, y: y.parse().ok()? }));
+            corpses.push(cell.ok_or("Replay has a bad 'corpses'")?);
+        }
+
+        // The seed is a full u64, too big to go through f64
+        let seed = field("seed")?.parse().map_err(|_| "Replay has a bad 'seed'")?;
+        Ok(Self {
+            version,
+            seed,
+            wall_density: number("density")? as f32,
+            move_interval: number("interval")? as f32,
+            grid,
+            mode,
+            // Files shared before map styles existed have no 'style' line
+            map_style: fields.get("style").map_or_else(default_map_style, |s| s.to_string()),
+            missions: fields.get("missions") == Some(&"1"),
+            modifier: Modifier::ALL.iter().copied().find(|m| fields.get("modifier") == Some(&m.label())),
+            corpses,
+            // Files shared before extras were recorded have no 'extras' line
+            extras: fields.get("extras").map_or_else(Extras::default, |text| Extras::from_key(text)),
+            score: number("score")? as u32,
+            ticks: number("ticks")? as u32,
+            turns,
+        })
+    }
+}
+
+/// Writes a finished run to `replays/` and returns its file name
+pub fn save_replay(replay: &Replay) -> Option<String> {
+    if let Err(e) = fs::create_dir_all(REPLAY_DIR) {
+        logging::error(format!("Could not create {}: {}", REPLAY_DIR, e));
+        return None;
+    }
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let name = format!("{}_{}_{}.json", stamp, replay.seed, replay.score);
+    let text = serde_json::to_string(replay).ok()?;
+    if let Err(e) = write_atomic(&format!("{}/{}", REPLAY_DIR, name), &text) {
+        logging::error(format!("Saving replay {} failed: {}", name, e));
+        return None;
+    }
+    logging::info(format!("Replay saved: {} ({} ticks)", name, replay.ticks));
+    prune_replays();
+    Some(name)
+}
+
+/// Replay file names, newest first
+pub fn list_replays() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(REPLAY_DIR) else { return Vec::new(); };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|n| n.ends_with(".json"))
+        .collect();
+    names.sort_by(|a, b| b.cmp(a));
+    names
+}
+
+pub fn load_replay(name: &str) -> Result<Replay, String> {
+    let text = fs::read_to_string(format!("{}/{}", REPLAY_DIR, name)).map_err(|e| e.to_string())?;
+    let replay: Replay = serde_json::from_str(&text).map_err(|e| format!("Invalid replay: {}", e))?;
+    if replay.version > REPLAY_VERSION {
+        return Err(format!("Replay version {} is newer than supported ({})", replay.version, REPLAY_VERSION));
+    }
+    Ok(replay)
+}
+
+/// Writes `<seed>_<score>.snakereplay` to the working directory and returns its path
+pub fn export_replay(replay: &Replay) -> Result<String, String> {
+    let path = format!("{}_{}{}", replay.seed, replay.score, SHARE_EXTENSION);
+    write_atomic(&path, &replay.to_share_text()).map_err(|e| format!("Export failed: {}", e))?;
+    logging::info(format!("Replay exported: {}", path));
+    Ok(path)
+}
+
+/// Loads a shared replay, checking version and checksum and that it replays to its score
+pub fn import_replay(path: &str) -> Result<Replay, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+    let replay = Replay::from_share_text(&text)?;
+    replay.verify()?;
+    logging::info(format!("Replay imported: {} (seed {}, score {})", path, replay.seed, replay.score));
+    Ok(replay)
+}
+
+/// Shared replay files in the working directory, newest first
+pub fn list_shared_replays() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(".") else { return Vec::new(); };
+    let mut files: Vec<(std::time::SystemTime, String)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Some((e.metadata().and_then(|m| m.modified()).ok()?, e.file_name().into_string().ok()?)))
+        .filter(|(_, n)| n.ends_with(SHARE_EXTENSION))
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.0));
+    files.into_iter().map(|(_, n)| n).collect()
+}
+
+fn prune_replays() {
+    for name in list_replays().iter().skip(MAX_REPLAYS) {
+        let _ = fs::remove_file(format!("{}/{}", REPLAY_DIR, name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+    const GOLDEN_HEADER: &str = "\
+# Final SnakeSim::state_hash of each replay in this folder, played headless.
+# Only a change meant to alter how runs play out may change these; after one, regenerate
+# with `UPDATE_GOLDEN=1 cargo test -p snake_core golden` and check the new values in with it.
+";
+
+    fn hashes_path() -> String { format!("{}/hashes.txt", GOLDEN_DIR) }
+
+    fn recorded(turns: Vec<(u32, Direction)>, ticks: u32) -> Replay {
+        let mut replay = Replay::new(&Map::generate(GridConfig::DEFAULT, 42, 0.05), 0.12);
+        replay.turns = turns;
+        replay.ticks = ticks;
+        replay
+    }
+
+    // Plays the turns out the way `verify` does and records the score they reach
+    fn scored(mut replay: Replay) -> Replay {
+        let mut sim = SnakeSim::new(Map::generate(replay.grid, replay.seed, replay.wall_density));
+        for tick in 0..replay.ticks {
+            if !sim.alive { break; }
+            if let Some(dir) = replay.turn_at(tick) { sim.next_direction = dir; }
+            sim.tick();
+        }
+        replay.score = sim.score;
+        replay
+    }
+
+    #[test]
+    fn saved_json_reads_back_the_same() {
+        let mut replay = recorded(vec![(3, Direction::Up), (9, Direction::Left)], 40);
+        replay.score = 7;
+        let back: Replay = serde_json::from_str(&serde_json::to_string(&replay).unwrap()).unwrap();
+        assert_eq!((back.version, back.seed, back.wall_density, back.move_interval), (replay.version, replay.seed, replay.wall_density, replay.move_interval));
+        assert_eq!(back.grid, replay.grid);
+        assert!(back.mode == replay.mode);
+        assert_eq!((back.score, back.ticks), (replay.score, replay.ticks));
+        assert_eq!(back.turns, replay.turns);
+    }
+
+    #[test]
+    fn turn_at_finds_only_recorded_ticks() {
+        let replay = recorded(vec![(3, Direction::Up), (8, Direction::Left)], 10);
+        assert_eq!(replay.turn_at(3), Some(Direction::Up));
+        assert_eq!(replay.turn_at(8), Some(Direction::Left));
+        assert_eq!(replay.turn_at(0), None);
+        assert_eq!(replay.turn_at(5), None);
+        assert_eq!(replay.turn_at(9), None);
+    }
+
+    #[test]
+    fn share_text_round_trips() {
+        let mut replay = recorded(vec![(3, Direction::Up), (9, Direction::Left), (15, Direction::Down)], 40);
+        replay.seed = u64::MAX - 1; // past what an f64 holds exactly
+        replay.mode = GameMode::TwitchPlays;
+        replay.extras = Extras { premium: true, phase: true, glitch: true, ..Extras::default() };
+        replay.score = 3;
+
+        let back = Replay::from_share_text(&replay.to_share_text()).expect("own text parses");
+        assert_eq!((back.version, back.seed, back.wall_density, back.move_interval), (replay.version, replay.seed, replay.wall_density, replay.move_interval));
+        assert_eq!(back.grid, replay.grid);
+        assert!(back.mode == replay.mode);
+        assert_eq!(back.extras, replay.extras);
+        assert_eq!((back.score, back.ticks), (replay.score, replay.ticks));
+        assert_eq!(back.turns, replay.turns);
+        assert_eq!(back.to_share_text(), replay.to_share_text());
+    }
+
+    #[test]
+    fn verify_rejects_a_claimed_score_the_turns_dont_reach() {
+        let mut replay = scored(recorded(vec![(2, Direction::Down), (6, Direction::Left), (12, Direction::Up)], 30));
+        let back = Replay::from_share_text(&replay.to_share_text()).unwrap();
+        assert!(back.verify().is_ok());
+        replay.score += 1;
+        assert!(replay.verify().is_err());
+    }
+
+    #[test]
+    fn power_ups_follow_the_recorded_choice_and_version() {
+        let mut replay = recorded(Vec::new(), 10);
+        assert!(replay.powerups().is_empty());
+        replay.extras.powerups = true;
+        assert_eq!(replay.powerups(), &PowerUp::ALL);
+        // Bombs came a version after the reverse
+        replay.version = BOMB_VERSION - 1;
+        assert_eq!(replay.powerups(), &[PowerUp::Reverse]);
+    }
+
+    #[test]
+    fn edited_share_text_is_rejected() {
+        let text = recorded(vec![(5, Direction::Up)], 20).to_share_text();
+        let edited = text.replacen("ticks 20", "ticks 21", 1);
+        assert_ne!(edited, text);
+        assert!(Replay::from_share_text(&edited).err().expect("rejected").contains("checksum"));
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let mut replay = recorded(Vec::new(), 10);
+        replay.version = REPLAY_VERSION + 1;
+        assert!(Replay::from_share_text(&replay.to_share_text()).err().expect("rejected").contains("newer"));
+    }
+
+    // Every checked-in replay must still reach its recorded score and the exact final state it
+    // reached when it was recorded. Anything that breaks this breaks old replays and netplay.
+    #[test]
+    fn golden_replays_play_out_the_same() {
+        let mut names: Vec<String> = fs::read_dir(GOLDEN_DIR)
+            .expect("golden replay folder")
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .filter(|n| n.ends_with(SHARE_EXTENSION))
+            .collect();
+        names.sort();
+        assert!(!names.is_empty(), "no golden replays in {}", GOLDEN_DIR);
+
+        let mut played = BTreeMap::new();
+        for name in &names {
+            let text = fs::read_to_string(format!("{}/{}", GOLDEN_DIR, name)).expect("readable replay");
+            let replay = Replay::from_share_text(&text).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            let sim = replay.play_headless();
+            assert_eq!(sim.score, replay.score, "{} no longer reaches its recorded score", name);
+            played.insert(name.trim_end_matches(SHARE_EXTENSION).to_string(), format!("{:016x}", sim.state_hash()));
+        }
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            let lines: String = played.iter().map(|(name, hash)| format!("{} {}\n", name, hash)).collect();
+            fs::write(hashes_path(), format!("{}{}", GOLDEN_HEADER, lines)).expect("writable hashes file");
+            return;
+        }
+        let text = fs::read_to_string(hashes_path()).expect("golden hashes file");
+        let expected: BTreeMap<String, String> = text
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .filter_map(|l| l.split_once(' ').map(|(name, hash)| (name.to_string(), hash.trim().to_string())))
+            .collect();
+        let failures: Vec<String> = played
+            .iter()
+            .filter_map(|(name, hash)| match expected.get(name) {
+                Some(want) if want == hash => None,
+                Some(want) => Some(format!("{}: expected {}, got {}", name, want, hash)),
+                None => Some(format!("{}: no golden hash (got {})", name, hash)),
+            })
+            .collect();
+        assert!(failures.is_empty(), "Replays no longer play out as recorded:\n{}", failures.join("\n"));
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// Small owned PCG32 generator. Each consumer (map layout, food, cosmetics, rain) keeps its
+/// own instance so drawing from one never shifts the sequence of another.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+    inc: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self { Self::with_stream(seed, 0) }
+
+    /// Same seed on a different stream gives an unrelated sequence
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self { state: 0, inc: (stream << 1) | 1 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    /// Uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Uniform in [lo, hi); returns `lo` for an empty range
+    pub fn range_i32(&mut self, lo: i32, hi: i32) -> i32 {
+        if hi <= lo { return lo; }
+        let span = (hi - lo) as u64;
+        lo + ((self.next_u32() as u64 * span) >> 32) as i32
+    }
+
+    pub fn range_usize(&mut self, lo: usize, hi: usize) -> usize {
+        if hi <= lo { return lo; }
+        let span = (hi - lo) as u64;
+        lo + ((self.next_u32() as u64 * span) >> 32) as usize
+    }
+
+    pub fn range_f32(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.next_f32()
+    }
+}
+
+/// FNV-1a, for short stable checksums: shared replay files, scores sent to the leaderboard
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, b| (h ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
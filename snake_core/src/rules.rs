@@ -0,0 +1,158 @@
+//! Rule sets a run can be played under: a game mode, plus at most one modifier on top, and
+//! the optional pickups switched on for it.
+use serde::{Deserialize, Serialize};
+
+use crate::map::GridConfig;
+use crate::powerups::PowerUp;
+use crate::sim::SnakeSim;
+
+/// The setup that scores ×1: the frontends' default wall density and speed on the default grid
+const BASE_DENSITY: f32 = 0.10;
+const BASE_INTERVAL: f32 = 0.12;
+
+#[derive(Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    /// Direction chosen each tick by a vote in a Twitch channel's chat
+    TwitchPlays,
+    /// Eat often enough or the tail starves away
+    Hunger,
+    /// Every few meals a new wave adds a hazard: more walls, a patroller or faster ticks
+    Waves,
+    /// Wall density and speed follow a difficulty rating that rises and falls with the player
+    Adaptive,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 5] = [GameMode::Classic, GameMode::TwitchPlays, GameMode::Hunger, GameMode::Waves, GameMode::Adaptive];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::TwitchPlays => "Twitch",
+            GameMode::Hunger => "Hunger",
+            GameMode::Waves => "Waves",
+            GameMode::Adaptive => "Adaptive",
+        }
+    }
+
+    /// Switches on the sim rules this mode plays by
+    pub fn apply(self, sim: &mut SnakeSim) {
+        match self {
+            GameMode::Hunger => sim.enable_hunger(),
+            GameMode::Waves => sim.enable_waves(),
+            GameMode::Classic | GameMode::TwitchPlays | GameMode::Adaptive => {}
+        }
+    }
+}
+
+/// A twist on the rules for the whole run, set by the weekly challenge or picked in settings
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Modifier {
+    Mirror, // up/down and left/right keys swapped
+    Fog, // only the cells near the head are drawn
+    Hunger, // Hunger mode's move budget on top of the run
+    Decay, // the score drops while the snake goes without food
+    Graveyard, // bodies of earlier deaths on the map stay as walls
+    DayNight, // at night only the cells around the head and the food show, and food scores more
+    LengthCap, // past a length, meals shed the tail as a wall that crumbles after a while
+    FoodChain, // three kinds of food at once, to be eaten in order for a bonus
+}
+
+impl Modifier {
+    pub const ALL: [Modifier; 8] = [Modifier::Mirror, Modifier::Fog, Modifier::Hunger, Modifier::Decay, Modifier::Graveyard, Modifier::DayNight, Modifier::LengthCap, Modifier::FoodChain];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Modifier::Mirror => "Mirror controls",
+            Modifier::Fog => "Fog",
+            Modifier::Hunger => "Hunger",
+            Modifier::Decay => "Score decay",
+            Modifier::Graveyard => "Graveyard",
+            Modifier::DayNight => "Day and night",
+            Modifier::LengthCap => "Length cap",
+            Modifier::FoodChain => "Food chain",
+        }
+    }
+
+    /// How much the modifier adds to the score multiplier
+    pub fn score_factor(self) -> f32 {
+        match self {
+            Modifier::Mirror => 1.25,
+            Modifier::Fog => 1.3,
+            Modifier::Hunger => 1.2,
+            Modifier::Decay => 1.1,
+            Modifier::Graveyard => 1.15,
+            Modifier::DayNight => 1.1,
+            Modifier::LengthCap => 1.15,
+            Modifier::FoodChain => 1.1,
+        }
+    }
+
+    /// Switches on the sim rules; Mirror and Fog only change input and drawing, and Graveyard's
+    /// corpses come from the caller
+    pub fn apply(self, sim: &mut SnakeSim, tick_seconds: f32) {
+        match self {
+            Modifier::Hunger => sim.enable_hunger(),
+            Modifier::Decay => sim.enable_decay(tick_seconds),
+            Modifier::DayNight => sim.enable_daylight(tick_seconds),
+            Modifier::LengthCap => sim.enable_length_cap(tick_seconds),
+            Modifier::FoodChain => sim.enable_chain(),
+            Modifier::Mirror | Modifier::Fog | Modifier::Graveyard => {}
+        }
+    }
+}
+
+/// Optional pickups, each switched on in settings; a plain run has none of them
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Extras {
+    pub premium: bool, // premium food worth more in risky spots
+    pub powerups: bool, // the reverse and bomb pickups
+    pub phase: bool, // wall-phasing charges earned with the score
+    pub glitch: bool, // food that costs points and inverts the controls
+}
+
+impl Extras {
+    /// Names as written in shared replays, in this order
+    const NAMES: [&'static str; 4] = ["premium", "powerups", "phase", "glitch"];
+
+    fn flags(self) -> [bool; 4] {
+        [self.premium, self.powerups, self.phase, self.glitch]
+    }
+
+    /// "premium+powerups", or "none"
+    pub fn key(self) -> String {
+        let on: Vec<&str> = Self::NAMES.iter().zip(self.flags()).filter(|(_, on)| *on).map(|(name, _)| *name).collect();
+        if on.is_empty() { "none".to_string() } else { on.join("+") }
+    }
+
+    /// Reads `key`'s form back; unknown names are ignored
+    pub fn from_key(text: &str) -> Self {
+        let has = |name: &str| text.split('+').any(|n| n == name);
+        Self { premium: has("premium"), powerups: has("powerups"), phase: has("phase"), glitch: has("glitch") }
+    }
+
+    /// Switches the chosen pickups on in the sim
+    pub fn apply(self, sim: &mut SnakeSim) {
+        if self.premium { sim.enable_premium(); }
+        if self.powerups { sim.enable_powerups(&PowerUp::ALL); }
+        if self.phase { sim.enable_phase(); }
+        if self.glitch { sim.enable_glitch(); }
+    }
+}
+
+/// How much harder a setup is than the default one, as a factor on its score, so runs on
+/// different settings can share one table. Denser walls, faster ticks, a smaller grid, Hunger
+/// mode and a modifier raise it; emptier, slower and roomier setups lower it. Rounded to
+/// hundredths, the way it is shown.
+pub fn score_multiplier(wall_density: f32, move_interval: f32, grid: GridConfig, mode: GameMode, modifier: Option<Modifier>) -> f32 {
+    let walls = (1.0 + 2.0 * wall_density.clamp(0.0, 1.0)) / (1.0 + 2.0 * BASE_DENSITY);
+    let speed = (BASE_INTERVAL / move_interval.max(0.01)).sqrt();
+    let default_area = (GridConfig::DEFAULT.width * GridConfig::DEFAULT.height) as f32;
+    let size = (default_area / (grid.width * grid.height).max(1) as f32).powf(0.25);
+    let mode = if mode == GameMode::Hunger { 1.2 } else { 1.0 };
+    let modifier = modifier.map_or(1.0, Modifier::score_factor);
+    (walls * speed * size * mode * modifier * 100.0).round() / 100.0
+}
@@ -0,0 +1,134 @@
+//! Save models shared by every frontend: the high score table and the progress kept across
+//! runs, plus the atomic file write all save data goes through.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::map::{GridConfig, DEFAULT_STYLE};
+use crate::replay::Replay;
+use crate::rules::GameMode;
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Write to a temp file and rename over the target so a crash never leaves a half-written save
+pub fn write_atomic(path: &str, text: &str) -> std::io::Result<()> {
+    let tmp = format!("{}.tmp", path);
+    fs::write(&tmp, text)?;
+    fs::rename(&tmp, path)
+}
+
+/// Runs kept on the local High Scores table
+pub const HIGH_SCORE_COUNT: usize = 10;
+/// Longest name that can go with a high score
+pub const NAME_MAX_LEN: usize = 12;
+
+/// One run on the High Scores table, with what it takes to play the same map again
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScoreEntry {
+    pub score: u32,
+    pub seed: u64,
+    pub wall_density: f32,
+    pub move_interval: f32,
+    pub grid: GridConfig,
+    pub mode: GameMode,
+    #[serde(default = "default_map_style")]
+    pub map_style: String,
+    pub date: u64, // unix seconds
+    #[serde(default)]
+    pub name: String, // empty until the player names the run
+    /// The settings' score multiplier when the run was recorded; entries from before it count as ×1
+    #[serde(default = "no_multiplier")]
+    pub multiplier: f32,
+}
+
+fn default_map_style() -> String { DEFAULT_STYLE.to_string() }
+
+fn no_multiplier() -> f32 { 1.0 }
+
+impl ScoreEntry {
+    pub fn from_replay(replay: &Replay) -> Self {
+        Self {
+            score: replay.score,
+            seed: replay.seed,
+            wall_density: replay.wall_density,
+            move_interval: replay.move_interval,
+            grid: replay.grid,
+            mode: replay.mode,
+            map_style: replay.map_style.clone(),
+            date: unix_now(),
+            name: String::new(),
+            multiplier: replay.score_multiplier(),
+        }
+    }
+
+    /// The score with the multiplier applied, which the table is ranked by
+    pub fn rated(&self) -> u32 {
+        (self.score as f32 * self.multiplier).round() as u32
+    }
+
+    /// Identifies a run across saves (imports, renaming after the fact)
+    pub fn same_run(&self, other: &ScoreEntry) -> bool {
+        self.date == other.date && self.seed == other.seed && self.score == other.score
+    }
+}
+
+/// Achievements of the player across runs
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Progress {
+    pub best_score: u32,
+    pub high_scores: Vec<ScoreEntry>, // highest rated first
+    pub achievements: BTreeMap<String, u64>, // achievement id -> unix time it was unlocked
+    pub best_splits: BTreeMap<String, Vec<u32>>, // speedrun::run_key -> splits of the best pace
+    pub weekly_best: BTreeMap<String, u32>, // weekly challenge key ("2026-W42") -> best score
+    pub coop_best: u32, // best shared score of a co-op run
+}
+
+impl Progress {
+    /// Adds a finished run if it makes the table; returns its place (0 = top). Ties keep the
+    /// older run ahead.
+    pub fn record_score(&mut self, entry: ScoreEntry) -> Option<usize> {
+        if entry.score == 0 { return None; }
+        let rank = self.high_scores.partition_point(|e| e.rated() >= entry.rated());
+        if rank >= HIGH_SCORE_COUNT { return None; }
+        self.high_scores.insert(rank, entry);
+        self.high_scores.truncate(HIGH_SCORE_COUNT);
+        Some(rank)
+    }
+
+    /// Keeps the splits if they beat the best pace for their seed and settings; true if they did
+    pub fn record_splits(&mut self, key: String, splits: &[u32]) -> bool {
+        if splits.is_empty() || self.best_splits.get(&key).is_some_and(|best| !is_better(splits, best)) { return false; }
+        self.best_splits.insert(key, splits.to_vec());
+        true
+    }
+
+    /// Keeps the week's best score; true if `score` beat it
+    pub fn record_weekly(&mut self, key: String, score: u32) -> bool {
+        if score == 0 || self.weekly_best.get(&key).is_some_and(|best| *best >= score) { return false; }
+        self.weekly_best.insert(key, score);
+        true
+    }
+
+    /// Keeps the best co-op score; true if `score` beat it
+    pub fn record_coop(&mut self, score: u32) -> bool {
+        if score <= self.coop_best { return false; }
+        self.coop_best = score;
+        true
+    }
+
+    /// Names a run already on the table; false once it has dropped off
+    pub fn name_score(&mut self, entry: &ScoreEntry, name: &str) -> bool {
+        let Some(e) = self.high_scores.iter_mut().find(|e| e.same_run(entry)) else { return false; };
+        e.name = name.to_string();
+        true
+    }
+}
+
+/// A run beats the best pace by reaching more splits, or the same number sooner
+pub fn is_better(splits: &[u32], best: &[u32]) -> bool {
+    splits.len() > best.len() || (splits.len() == best.len() && splits.last() < best.last())
+}
@@ -0,0 +1,1488 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+use crate::map::{Cell, Direction, GridConfig, Map, Switch, RISKY, SWITCH_GROUPS};
+use crate::missions::{Missions, Outcome};
+use crate::powerups::{PowerUp, PowerUps, BOMB_RADIUS, POWERUP_TICKS};
+use crate::rng::Rng;
+use crate::waves::{Hazard, Patroller, Waves, PATROL_EVERY, SAFE_RADIUS, WALLS_PER_WAVE};
+
+// RNG streams derived from the map seed
+pub const FOOD_STREAM: u64 = 1;
+pub const GLYPH_STREAM: u64 = 2;
+pub const MOD_STREAM: u64 = 3;
+pub const WAVE_STREAM: u64 = 4;
+pub const MISSION_STREAM: u64 = 5;
+pub const PREMIUM_STREAM: u64 = 6;
+pub const POWERUP_STREAM: u64 = 7;
+pub const CONVEYOR_STREAM: u64 = 8;
+pub const CHAIN_STREAM: u64 = 9;
+pub const GLITCH_STREAM: u64 = 10;
+
+pub const PREMIUM_POINTS: u32 = 3;
+const PREMIUM_EVERY: u32 = 4; // meals between chances of a premium food
+const PREMIUM_CHANCE: f32 = 0.5;
+const PREMIUM_TICKS: u32 = 80; // how long one stays before it's gone
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DeathCause {
+    Border,
+    Wall,
+    SelfCollision,
+    Starved,
+    Patroller, // Waves mode
+}
+
+impl DeathCause {
+    pub fn label(self) -> &'static str {
+        match self {
+            DeathCause::Border => "Border",
+            DeathCause::Wall => "Wall",
+            DeathCause::SelfCollision => "Own tail",
+            DeathCause::Starved => "Starved",
+            DeathCause::Patroller => "Patroller",
+        }
+    }
+}
+
+/// Everything that happened during one tick, in order
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SimEvent {
+    Moved { head: Cell },
+    FoodEaten { at: Cell, score: u32, points: u32 },
+    /// Hunger mode: a starving snake lost its tail segment
+    Shrank { tail: Cell },
+    Died(DeathCause),
+    /// The snake fills every cell it could be in: the board is full
+    Won,
+    /// The head crossed a plate of this switch group, whose walls are now `solid` or not
+    Switched { group: usize, solid: bool },
+    /// Waves mode: a new wave began with this hazard
+    WaveStarted { wave: u32, hazard: Hazard },
+    /// Missions: the current mission ended and a new one was handed out
+    MissionEnded(Outcome),
+    PremiumSpawned { at: Cell },
+    PremiumEaten { at: Cell, score: u32 },
+    /// The head ate glitch food: the score is down to `score` and the controls are inverted
+    GlitchEaten { at: Cell, score: u32 },
+    /// Score decay: a point was lost to going without food
+    ScoreDecayed { score: u32 },
+    PowerUpSpawned { kind: PowerUp, at: Cell },
+    /// The head ran over a power-up, whose effect has already been applied
+    PowerUpTaken { kind: PowerUp, at: Cell },
+    /// The score earned a phase charge; `charges` is how many are held now
+    PhaseEarned { charges: u32 },
+    /// The head went into an interior wall on a phase charge instead of dying
+    Phased { at: Cell, charges: u32 },
+    /// Food chain: a food of kind `kind` was eaten, `in_order` or not; `points` includes the
+    /// bonus when it cleared a set in order
+    ChainEaten { at: Cell, kind: usize, score: u32, points: u32, in_order: bool },
+    /// Day/night: night fell (`true`) or day broke (`false`)
+    Nightfall { night: bool },
+}
+
+/// A starving snake loses a tail segment this often (ticks)
+pub const STARVE_EVERY: u32 = 4;
+
+/// Hunger mode: the snake must eat within `limit` moves or it starts losing its tail
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Hunger {
+    pub moves_left: u32,
+    pub limit: u32, // moves allowed after the last meal
+    pub starved_for: u32, // ticks since moves_left ran out
+}
+
+impl Hunger {
+    /// Twice the grid's width plus height, minus the snake's length, but never less than
+    /// width plus height: longer snakes get less slack, yet any food stays reachable in time
+    pub fn full(grid: GridConfig, length: usize) -> Self {
+        let reach = (grid.width + grid.height) as u32;
+        let limit = (2 * reach).saturating_sub(length as u32).max(reach);
+        Self { moves_left: limit, limit, starved_for: 0 }
+    }
+}
+
+/// Score decay modifier: every DECAY_SECONDS without a meal the score drops by one
+pub const DECAY_SECONDS: f32 = 10.0;
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Decay {
+    pub every: u32, // DECAY_SECONDS in ticks at the run's move interval
+    pub since_food: u32,
+    pub lost: u32, // points taken over the run
+    pub peak: u32, // highest score reached
+}
+
+impl Decay {
+    /// Seconds until the next point goes
+    pub fn seconds_left(&self, tick_seconds: f32) -> f32 {
+        self.every.saturating_sub(self.since_food) as f32 * tick_seconds
+    }
+}
+
+/// Length cap modifier: the snake stops growing at LENGTH_CAP, and each meal past it sheds the
+/// tail as a wall that crumbles after SHED_SECONDS
+pub const LENGTH_CAP: usize = 30;
+pub const SHED_SECONDS: f32 = 10.0;
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct LengthCap {
+    pub cap: usize,
+    pub lifetime: u32, // SHED_SECONDS in ticks at the run's move interval
+    pub shed: u32, // segments shed over the run
+}
+
+/// Food chain modifier: CHAIN_LENGTH foods of different kinds lie on the board at once, to be
+/// eaten in order; a set cleared in order pays CHAIN_BONUS on top
+pub const CHAIN_LENGTH: usize = 3;
+pub const CHAIN_BONUS: u32 = 3;
+
+/// The current set of chain foods, indexed by kind; the next set is laid once all are eaten
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Chain {
+    pub foods: [Option<Cell>; CHAIN_LENGTH], // None once eaten
+    pub broken: bool, // one of this set was eaten out of order
+    pub completed: u32, // sets cleared in order
+    pub bonus: u32, // points those sets paid on top
+    pub rng: Rng,
+}
+
+impl Chain {
+    /// The kind to eat next; None while no set is out
+    pub fn next(&self) -> Option<usize> {
+        self.foods.iter().position(Option::is_some)
+    }
+
+    pub fn kind_at(&self, c: Cell) -> Option<usize> {
+        self.foods.iter().position(|f| *f == Some(c))
+    }
+}
+
+/// Day/night modifier: a full day and night take DAY_SECONDS, half each, and dusk and dawn
+/// fade over DUSK_SECONDS either side of the change
+pub const DAY_SECONDS: f32 = 60.0;
+pub const DUSK_SECONDS: f32 = 4.0;
+/// What food eaten at night is worth on top of its point
+pub const NIGHT_BONUS: u32 = 1;
+
+/// The time of day, counted in ticks from the start of a day
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Daylight {
+    pub length: u32, // DAY_SECONDS in ticks at the run's move interval
+    pub fade: u32, // DUSK_SECONDS in ticks
+    pub tick: u32,
+    pub bonus: u32, // points night meals earned on top
+}
+
+impl Daylight {
+    /// 0.0 by day, 1.0 at night, in between during dusk and dawn: dusk ends the day half and
+    /// dawn the night half
+    pub fn darkness(&self) -> f32 {
+        // How much of a fade is left before `end`
+        let left = |end: u32| ((end - self.tick) as f32 / self.fade as f32).min(1.0);
+        let half = self.length / 2;
+        if self.tick < half { 1.0 - left(half) } else { left(self.length) }
+    }
+
+    /// Whether it's night, which falls and breaks halfway through dusk and dawn
+    pub fn is_night(&self) -> bool {
+        (self.length / 2 - self.fade / 2..self.length - self.fade / 2).contains(&self.tick)
+    }
+}
+
+/// A phase charge comes with every PHASE_EVERY points, up to MAX_PHASE_CHARGES held at once
+pub const PHASE_EVERY: u32 = 10;
+pub const MAX_PHASE_CHARGES: u32 = 3;
+
+/// Charges that let the head pass through interior walls. One is spent going into a wall and
+/// lasts until the head comes out the other side; the border still kills.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Phase {
+    pub charges: u32,
+    pub next_at: u32, // score that earns the next charge
+    pub used: u32,
+}
+
+/// Occasional food worth PREMIUM_POINTS that only turns up in risky cells (dead ends and
+/// one-wide corridors) and goes away again if it isn't eaten in time
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Premium {
+    pub at: Option<Cell>,
+    pub ticks_left: u32,
+    pub meals: u32, // regular meals, counting toward the next chance
+    pub eaten: u32,
+    pub rng: Rng,
+}
+
+/// Glitch food: after each regular meal there's a GLITCH_CHANCE of a corrupted food that looks
+/// much like the real one. Eating it costs GLITCH_PENALTY points and inverts the controls for
+/// INVERT_TICKS; left alone it's gone after GLITCH_TICKS.
+pub const GLITCH_PENALTY: u32 = 3;
+pub const INVERT_TICKS: u32 = 25;
+const GLITCH_CHANCE: f32 = 0.15;
+const GLITCH_TICKS: u32 = 60;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Glitch {
+    pub at: Option<Cell>,
+    pub ticks_left: u32,
+    pub inverted: u32, // ticks the controls stay inverted
+    pub eaten: u32,
+    pub lost: u32, // points taken over the run
+    pub rng: Rng,
+}
+
+/// Cells that are neither wall, gate nor snake, kept in a vector with a per-cell index so
+/// insert, remove and uniform sampling are all O(1)
+#[derive(Clone, Default)]
+pub struct FreeCells {
+    cells: Vec<Cell>,
+    index: Vec<usize>, // position in `cells` for each grid cell, or NOT_FREE
+    grid: GridConfig,
+}
+
+const NOT_FREE: usize = usize::MAX;
+
+impl FreeCells {
+    pub fn new(map: &Map, occupied: &HashSet<Cell>) -> Self {
+        let grid = map.grid;
+        let mut free = Self { cells: Vec::new(), index: vec![NOT_FREE; grid.cell_count()], grid };
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                let cell = Cell { x, y };
+                if !map.is_wall(cell) && !map.is_fixture(cell) && !occupied.contains(&cell) { free.insert(cell); }
+            }
+        }
+        free
+    }
+
+    fn slot(&self, cell: Cell) -> Option<usize> {
+        self.grid.contains(cell).then(|| (cell.y * self.grid.width + cell.x) as usize)
+    }
+
+    pub fn insert(&mut self, cell: Cell) {
+        let Some(slot) = self.slot(cell) else { return; };
+        if self.index[slot] != NOT_FREE { return; }
+        self.index[slot] = self.cells.len();
+        self.cells.push(cell);
+    }
+
+    pub fn remove(&mut self, cell: Cell) {
+        let Some(slot) = self.slot(cell) else { return; };
+        let pos = self.index[slot];
+        if pos == NOT_FREE { return; }
+        self.index[slot] = NOT_FREE;
+        self.cells.swap_remove(pos);
+        if let Some(moved) = self.cells.get(pos).copied() && let Some(moved_slot) = self.slot(moved) {
+            self.index[moved_slot] = pos;
+        }
+    }
+
+    pub fn contains(&self, cell: Cell) -> bool {
+        self.slot(cell).is_some_and(|slot| self.index[slot] != NOT_FREE)
+    }
+
+    pub fn sample(&self, rng: &mut Rng) -> Option<Cell> {
+        if self.cells.is_empty() { return None; }
+        Some(self.cells[rng.range_usize(0, self.cells.len())])
+    }
+}
+
+/// Pure snake rules: no window, input, timing or audio, so tests and bots can drive it directly
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SnakeSim {
+    pub snake: VecDeque<Cell>, // head at the front
+    /// Same cells as `snake`, for O(1) collision and spawn checks; rebuilt after loading
+    #[serde(skip)]
+    pub occupied: HashSet<Cell>,
+    #[serde(skip)]
+    pub free: FreeCells,
+    pub direction: Direction,
+    pub next_direction: Direction,
+    pub food: Option<Cell>, // None once the board is full
+    #[serde(default)]
+    pub food_owed: bool, // the food was eaten while pickups held every free cell
+    #[serde(default)]
+    pub won: bool,
+    pub grow: bool,
+    pub score: u32,
+    pub alive: bool,
+    pub map: Map,
+    pub rng: Rng, // food placement, seeded from the map so a seed always replays the same food
+    #[serde(default)]
+    pub hunger: Option<Hunger>, // Some in Hunger mode
+    #[serde(default)]
+    pub death: Option<DeathCause>, // why the run ended, once it has
+    #[serde(default)]
+    pub waves: Option<Waves>, // Some in Waves mode
+    #[serde(default)]
+    pub missions: Option<Missions>, // Some when the run plays with missions
+    #[serde(default)]
+    pub premium: Option<Premium>, // Some in runs recorded since premium food was added
+    #[serde(default)]
+    pub powerups: Option<PowerUps>, // Some in runs recorded since power-ups were added
+    #[serde(default)]
+    pub decay: Option<Decay>, // Some with the score decay modifier
+    #[serde(default)]
+    pub phase: Option<Phase>, // Some in runs recorded since phase charges were added
+    #[serde(default)]
+    pub daylight: Option<Daylight>, // Some with the day/night modifier
+    #[serde(default)]
+    pub length_cap: Option<LengthCap>, // Some with the length cap modifier
+    #[serde(default)]
+    pub chain: Option<Chain>, // Some with the food chain modifier
+    #[serde(default)]
+    pub glitch: Option<Glitch>, // Some in runs recorded since glitch food was added
+}
+
+impl SnakeSim {
+    pub fn new(mut map: Map) -> Self {
+        map.reindex();
+        let snake = Self::initial_snake(map.grid);
+        let occupied: HashSet<Cell> = snake.iter().copied().collect();
+        let free = FreeCells::new(&map, &occupied);
+        let mut rng = Rng::with_stream(map.seed, FOOD_STREAM);
+        let food = free.sample(&mut rng);
+        Self {
+            snake,
+            occupied,
+            free,
+            direction: Direction::Right,
+            next_direction: Direction::Right,
+            food,
+            food_owed: false,
+            won: false,
+            grow: false,
+            score: 0,
+            alive: true,
+            map,
+            rng,
+            hunger: None,
+            death: None,
+            waves: None,
+            missions: None,
+            premium: None,
+            powerups: None,
+            decay: None,
+            phase: None,
+            daylight: None,
+            length_cap: None,
+            chain: None,
+            glitch: None,
+        }
+    }
+
+    /// Restore the occupancy set, free-cell list and wall index, which aren't serialized
+    pub fn rebuild_occupancy(&mut self) {
+        self.map.reindex();
+        self.occupied = self.snake.iter().copied().collect();
+        self.free = FreeCells::new(&self.map, &self.occupied);
+        for p in self.waves.iter().flat_map(|w| &w.patrollers) { self.free.remove(p.at); }
+        if let Some(at) = self.premium.as_ref().and_then(|p| p.at) { self.free.remove(at); }
+        if let Some((_, at)) = self.powerups.as_ref().and_then(|p| p.at) { self.free.remove(at); }
+        for at in self.chain.iter().flat_map(|c| c.foods).flatten() { self.free.remove(at); }
+        if let Some(at) = self.glitch.as_ref().and_then(|g| g.at) { self.free.remove(at); }
+    }
+
+    fn initial_snake(grid: GridConfig) -> VecDeque<Cell> {
+        let start = grid.center();
+        VecDeque::from([start, Cell { x: start.x - 1, y: start.y }, Cell { x: start.x - 2, y: start.y }])
+    }
+
+    pub fn head(&self) -> Cell { self.snake[0] }
+
+    pub fn enable_hunger(&mut self) {
+        self.hunger = Some(Hunger::full(self.map.grid, self.snake.len()));
+    }
+
+    pub fn enable_waves(&mut self) {
+        self.waves = Some(Waves::new(Rng::with_stream(self.map.seed, WAVE_STREAM)));
+    }
+
+    pub fn enable_missions(&mut self, tick_seconds: f32) {
+        let rng = Rng::with_stream(self.map.seed, MISSION_STREAM);
+        self.missions = Some(Missions::new(rng, tick_seconds, self.map.grid, self.snake.len()));
+    }
+
+    pub fn enable_premium(&mut self) {
+        let rng = Rng::with_stream(self.map.seed, PREMIUM_STREAM);
+        self.premium = Some(Premium { at: None, ticks_left: 0, meals: 0, eaten: 0, rng });
+    }
+
+    pub fn enable_decay(&mut self, tick_seconds: f32) {
+        let every = (DECAY_SECONDS / tick_seconds).round().max(1.0) as u32;
+        self.decay = Some(Decay { every, since_food: 0, lost: 0, peak: self.score });
+    }
+
+    pub fn enable_daylight(&mut self, tick_seconds: f32) {
+        let ticks = |seconds: f32| (seconds / tick_seconds).round().max(1.0) as u32;
+        let length = ticks(DAY_SECONDS).max(4);
+        let fade = ticks(DUSK_SECONDS).min(length / 2);
+        self.daylight = Some(Daylight { length, fade, tick: 0, bonus: 0 });
+    }
+
+    pub fn enable_length_cap(&mut self, tick_seconds: f32) {
+        let lifetime = (SHED_SECONDS / tick_seconds).round().max(1.0) as u32;
+        self.length_cap = Some(LengthCap { cap: LENGTH_CAP, lifetime, shed: 0 });
+    }
+
+    pub fn enable_chain(&mut self) {
+        let rng = Rng::with_stream(self.map.seed, CHAIN_STREAM);
+        self.chain = Some(Chain { foods: [None; CHAIN_LENGTH], broken: false, completed: 0, bonus: 0, rng });
+        self.lay_chain();
+    }
+
+    pub fn enable_glitch(&mut self) {
+        let rng = Rng::with_stream(self.map.seed, GLITCH_STREAM);
+        self.glitch = Some(Glitch { at: None, ticks_left: 0, inverted: 0, eaten: 0, lost: 0, rng });
+    }
+
+    /// Whether glitch food has the controls inverted; callers flip the player's turns while so
+    pub fn inverted(&self) -> bool {
+        self.glitch.as_ref().is_some_and(|g| g.inverted > 0)
+    }
+
+    pub fn enable_phase(&mut self) {
+        self.phase = Some(Phase { charges: 0, next_at: PHASE_EVERY, used: 0 });
+    }
+
+    pub fn enable_powerups(&mut self, kinds: &[PowerUp]) {
+        self.powerups = Some(PowerUps::new(Rng::with_stream(self.map.seed, POWERUP_STREAM), kinds));
+    }
+
+    /// FNV-1a over everything that affects future ticks; two sims fed the same inputs
+    /// must agree on this, which is how netplay detects a desync
+    pub fn state_hash(&self) -> u64 {
+        let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut mix = |v: i64| {
+            for b in v.to_le_bytes() { h = (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3); }
+        };
+        for c in &self.snake {
+            mix(c.x as i64);
+            mix(c.y as i64);
+        }
+        if let Some(f) = self.food {
+            mix(f.x as i64);
+            mix(f.y as i64);
+        }
+        mix(self.direction as i64);
+        mix(self.next_direction as i64);
+        mix(self.score as i64);
+        mix(self.alive as i64);
+        if let Some(hunger) = self.hunger { mix(hunger.moves_left as i64); }
+        if let Some(decay) = self.decay { mix(decay.since_food as i64); }
+        if let Some(phase) = self.phase { mix(phase.charges as i64); }
+        if let Some(daylight) = self.daylight { mix(daylight.tick as i64); }
+        if let Some(chain) = &self.chain {
+            for at in chain.foods.iter().flatten() {
+                mix(at.x as i64);
+                mix(at.y as i64);
+            }
+            mix(chain.broken as i64);
+        }
+        for gate in &self.map.gates {
+            mix(gate.open as i64);
+            mix(gate.ticks_left as i64);
+        }
+        for switch in &self.map.switches { mix(switch.solid as i64); }
+        for (at, ticks_left) in &self.map.shed {
+            mix(at.x as i64);
+            mix(at.y as i64);
+            mix(*ticks_left as i64);
+        }
+        if let Some(at) = self.premium.as_ref().and_then(|p| p.at) {
+            mix(at.x as i64);
+            mix(at.y as i64);
+        }
+        if let Some(glitch) = &self.glitch {
+            if let Some(at) = glitch.at {
+                mix(at.x as i64);
+                mix(at.y as i64);
+            }
+            mix(glitch.inverted as i64);
+        }
+        if let Some((kind, at)) = self.powerups.as_ref().and_then(|p| p.at) {
+            mix(kind as i64);
+            mix(at.x as i64);
+            mix(at.y as i64);
+        }
+        if let Some(missions) = &self.missions {
+            mix(missions.completed as i64);
+            mix(missions.progress as i64);
+        }
+        if let Some(waves) = &self.waves {
+            mix(waves.wave as i64);
+            for p in &waves.patrollers {
+                mix(p.at.x as i64);
+                mix(p.at.y as i64);
+            }
+        }
+        // Mods, wave hazards and the graveyard change the walls mid-run; sorted, since a set
+        // iterates in no fixed order
+        let mut walls: Vec<Cell> = self.map.walls.iter().copied().collect();
+        walls.sort_unstable_by_key(|c| (c.y, c.x));
+        for c in walls {
+            mix(c.x as i64);
+            mix(c.y as i64);
+        }
+        h
+    }
+
+    /// Queue a turn for the next tick; reversing onto the neck, or turning a dead snake, is ignored
+    pub fn turn(&mut self, dir: Direction) {
+        if self.alive && dir != self.direction.opposite() { self.next_direction = dir; }
+    }
+
+    /// Advance the snake by one cell and report what happened
+    pub fn tick(&mut self) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        if !self.alive { return events; }
+
+        self.direction = self.next_direction;
+        let tentative = self.head().step(self.direction);
+        // A stair takes the head straight to the other end, on another floor; the body follows
+        let tentative = self.map.stair_exit(tentative).unwrap_or(tentative);
+
+        // Bounds and wall collision (no wrap)
+        if !self.map.grid.contains(tentative) {
+            return self.die(events, DeathCause::Border);
+        }
+        if self.map.is_wall(tentative) {
+            match self.phase_into(tentative) {
+                Some(event) => events.extend(event),
+                None => return self.die(events, DeathCause::Wall),
+            }
+        }
+        if self.waves.as_ref().is_some_and(|w| w.patroller_at(tentative)) {
+            return self.die(events, DeathCause::Patroller);
+        }
+        let new_head = tentative;
+
+        // Self collision
+        if self.occupied.contains(&new_head) {
+            return self.die(events, DeathCause::SelfCollision);
+        }
+
+        let mut ate = self.enter(new_head, &mut events);
+        // A conveyor under the head carries it one cell further, if that cell is clear
+        if self.alive && let Some(to) = self.conveyed(new_head) { ate |= self.enter(to, &mut events); }
+        if self.alive && self.chain.as_ref().is_some_and(|c| c.next().is_none()) { self.lay_chain(); }
+        if self.alive && self.premium.is_some() { events = self.premium_tick(ate, events); }
+        if self.alive && self.glitch.is_some() { events = self.glitch_tick(ate, events); }
+        if self.alive && self.powerups.is_some() { events = self.powerups_tick(ate, events); }
+        if self.alive && self.hunger.is_some() { events = self.hunger_tick(ate, events); }
+        if self.alive && self.waves.is_some() { events = self.waves_tick(ate, events); }
+        if self.alive && let Some(missions) = &mut self.missions {
+            let head = self.snake[0];
+            if let Some(outcome) = missions.on_tick(ate, head, self.snake.len(), self.map.grid) {
+                if let Outcome::Completed { bonus } = outcome { self.score += bonus; }
+                events.push(SimEvent::MissionEnded(outcome));
+            }
+        }
+        if self.alive && self.decay.is_some() { events = self.decay_tick(ate, events); }
+        if self.alive && let Some(phase) = &mut self.phase && self.score >= phase.next_at {
+            // Counted from the score rather than from meals, so bonuses earn them too; a full
+            // hand still moves the mark on, and decayed points have to be made up again
+            phase.next_at = (self.score / PHASE_EVERY + 1) * PHASE_EVERY;
+            if phase.charges < MAX_PHASE_CHARGES {
+                phase.charges += 1;
+                events.push(SimEvent::PhaseEarned { charges: phase.charges });
+            }
+        }
+        if self.alive && self.food_owed { self.next_food(&mut events); }
+        // Last, so the gates and the time of day on screen between ticks are the ones the next
+        // move meets
+        if self.alive && !self.map.gates.is_empty() { self.map.advance_gates(&self.occupied); }
+        if self.alive && !self.map.shed.is_empty() { self.crumble(); }
+        if self.alive && let Some(daylight) = &mut self.daylight {
+            let night = daylight.is_night();
+            daylight.tick = (daylight.tick + 1) % daylight.length;
+            if daylight.is_night() != night { events.push(SimEvent::Nightfall { night: !night }); }
+        }
+        events
+    }
+
+    // Moves the head onto `new_head`, which must be clear, eating whatever is there; says
+    // whether that was the regular food
+    fn enter(&mut self, new_head: Cell, events: &mut Vec<SimEvent>) -> bool {
+        self.snake.push_front(new_head);
+        self.occupied.insert(new_head);
+        self.free.remove(new_head);
+        events.push(SimEvent::Moved { head: new_head });
+        if let Some(group) = self.map.plate_at(new_head) {
+            let switch = &mut self.map.switches[group];
+            switch.solid = !switch.solid;
+            events.push(SimEvent::Switched { group, solid: switch.solid });
+            for c in self.map.switches[group].walls.clone() { self.map.refresh(c); }
+        }
+
+        // Food collision
+        let ate = self.food == Some(new_head);
+        if ate {
+            let mut points = 1;
+            if let Some(daylight) = &mut self.daylight && daylight.is_night() {
+                daylight.bonus += NIGHT_BONUS;
+                points += NIGHT_BONUS;
+            }
+            self.grow = true;
+            self.score += points;
+            events.push(SimEvent::FoodEaten { at: new_head, score: self.score, points });
+        }
+        if let Some(chain) = &mut self.chain && let Some(kind) = chain.kind_at(new_head) {
+            let in_order = chain.next() == Some(kind);
+            chain.foods[kind] = None;
+            chain.broken |= !in_order;
+            let mut points = 1;
+            if chain.next().is_none() && !chain.broken {
+                chain.completed += 1;
+                chain.bonus += CHAIN_BONUS;
+                points += CHAIN_BONUS;
+            }
+            self.grow = true;
+            self.score += points;
+            events.push(SimEvent::ChainEaten { at: new_head, kind, score: self.score, points, in_order });
+        }
+        if let Some(glitch) = &mut self.glitch && glitch.at == Some(new_head) {
+            // Costs points rather than growing the snake
+            let lost = self.score.min(GLITCH_PENALTY);
+            glitch.at = None;
+            glitch.eaten += 1;
+            glitch.lost += lost;
+            glitch.inverted = INVERT_TICKS;
+            self.score -= lost;
+            events.push(SimEvent::GlitchEaten { at: new_head, score: self.score });
+        }
+        if let Some(premium) = &mut self.premium && premium.at == Some(new_head) {
+            premium.at = None;
+            premium.eaten += 1;
+            self.grow = true;
+            self.score += PREMIUM_POINTS;
+            events.push(SimEvent::PremiumEaten { at: new_head, score: self.score });
+        }
+
+        // Growing past the length cap sheds the tail instead
+        let shed = self.grow && self.length_cap.is_some_and(|l| self.snake.len() > l.cap);
+        if (!self.grow || shed) && let Some(tail) = self.snake.pop_back() {
+            if shed { self.shed(tail); } else { self.vacate(tail); }
+        }
+        self.grow = false;
+
+        if ate { self.next_food(events); }
+        ate
+    }
+
+    // Sampled from the free list, so this never retries. An empty list is a win only once the
+    // snake fills the board; pickups on the last cells hold the food back until one clears.
+    fn next_food(&mut self, events: &mut Vec<SimEvent>) {
+        self.food = self.free.sample(&mut self.rng);
+        self.food_owed = self.food.is_none();
+        if self.food_owed && self.board_full() {
+            self.alive = false;
+            self.won = true;
+            events.push(SimEvent::Won);
+        }
+    }
+
+    // Every cell the snake could be in holds it; walls it phased into count as its own
+    fn board_full(&self) -> bool {
+        let grid = self.map.grid;
+        (0..grid.height).flat_map(|y| (0..grid.width).map(move |x| Cell { x, y }))
+            .all(|c| self.occupied.contains(&c) || self.map.is_wall(c) || self.map.is_fixture(c))
+    }
+
+    // Where a conveyor at `at` pushes the head: the next cell in its direction, unless a wall,
+    // the border, the body or a patroller is there, in which case the belt does nothing
+    fn conveyed(&self, at: Cell) -> Option<Cell> {
+        let to = at.step(self.map.conveyor_at(at)?);
+        let blocked = !self.map.grid.contains(to)
+            || self.map.is_wall(to)
+            || self.occupied.contains(&to)
+            || self.waves.as_ref().is_some_and(|w| w.patroller_at(to));
+        (!blocked).then_some(to)
+    }
+
+    // Whether the head may go into the wall at `at`: free while it's already inside one, for a
+    // charge otherwise. Border walls can't be passed. None means it crashes; Some carries the
+    // event for a charge spent.
+    fn phase_into(&mut self, at: Cell) -> Option<Option<SimEvent>> {
+        let head = self.head();
+        let border = self.map.grid.on_border(at);
+        let phase = self.phase.as_mut().filter(|_| !border)?;
+        if self.map.is_wall(head) { return Some(None); }
+        if phase.charges == 0 { return None; }
+        phase.charges -= 1;
+        phase.used += 1;
+        Some(Some(SimEvent::Phased { at, charges: phase.charges }))
+    }
+
+    // A cell the snake left; wall cells it phased through and fixtures stay off the free list
+    fn vacate(&mut self, cell: Cell) {
+        self.occupied.remove(&cell);
+        if !self.map.is_wall(cell) && !self.map.is_fixture(cell) { self.free.insert(cell); }
+    }
+
+    // Lays a new set of chain foods on free cells, one of each kind, as many as fit
+    fn lay_chain(&mut self) {
+        let Some(mut chain) = self.chain.take() else { return; };
+        chain.broken = false;
+        for kind in 0..CHAIN_LENGTH {
+            let spots: Vec<Cell> = self.free.cells.iter()
+                .copied()
+                .filter(|c| self.food != Some(*c) && self.map.conveyor_at(*c).is_none())
+                .collect();
+            if spots.is_empty() { break; }
+            let at = spots[chain.rng.range_usize(0, spots.len())];
+            // Off the free list, so nothing else lands under it
+            self.free.remove(at);
+            chain.foods[kind] = Some(at);
+        }
+        self.chain = Some(chain);
+    }
+
+    // Leaves the tail segment behind as a wall until the cap's lifetime runs out
+    fn shed(&mut self, tail: Cell) {
+        let Some(cap) = &mut self.length_cap else { return; };
+        cap.shed += 1;
+        self.occupied.remove(&tail);
+        self.map.shed.push((tail, cap.lifetime));
+        self.map.refresh(tail);
+    }
+
+    // Counts down the shed walls and frees the cells of those that crumble; one the head is
+    // phasing through frees when the snake leaves it
+    fn crumble(&mut self) {
+        for (_, ticks_left) in &mut self.map.shed { *ticks_left = ticks_left.saturating_sub(1); }
+        let (gone, kept): (Vec<_>, Vec<_>) = self.map.shed.drain(..).partition(|(_, ticks_left)| *ticks_left == 0);
+        self.map.shed = kept;
+        for (c, _) in gone {
+            self.map.refresh(c);
+            if !self.map.is_wall(c) && !self.map.is_fixture(c) && !self.occupied.contains(&c) { self.free.insert(c); }
+        }
+    }
+
+    // Counts the ticks since the last meal and takes a point each time they reach `every`;
+    // the peak is taken first, so points scored this tick count toward it
+    fn decay_tick(&mut self, ate: bool, mut events: Vec<SimEvent>) -> Vec<SimEvent> {
+        let Some(mut decay) = self.decay else { return events; };
+        decay.peak = decay.peak.max(self.score);
+        if ate {
+            decay.since_food = 0;
+        } else {
+            decay.since_food += 1;
+            if decay.since_food >= decay.every {
+                decay.since_food = 0;
+                if self.score > 0 {
+                    self.score -= 1;
+                    decay.lost += 1;
+                    events.push(SimEvent::ScoreDecayed { score: self.score });
+                }
+            }
+        }
+        self.decay = Some(decay);
+        events
+    }
+
+    // Runs down an uneaten premium food, and every few meals may place a new one
+    fn premium_tick(&mut self, ate: bool, mut events: Vec<SimEvent>) -> Vec<SimEvent> {
+        let Some(mut premium) = self.premium.take() else { return events; };
+        if let Some(at) = premium.at {
+            premium.ticks_left = premium.ticks_left.saturating_sub(1);
+            if premium.ticks_left == 0 {
+                premium.at = None;
+                self.free.insert(at);
+            }
+        }
+        if ate {
+            premium.meals += 1;
+            if premium.at.is_none() && premium.meals % PREMIUM_EVERY == 0 && premium.rng.next_f32() < PREMIUM_CHANCE {
+                // Analysed on the spot, since mods and waves can move walls mid-run
+                let scores = self.map.danger_scores();
+                let width = self.map.grid.width;
+                let risky: Vec<Cell> = self.free.cells.iter()
+                    .copied()
+                    .filter(|c| scores[(c.y * width + c.x) as usize] >= RISKY && self.food != Some(*c) && self.map.conveyor_at(*c).is_none())
+                    .collect();
+                if !risky.is_empty() {
+                    let at = risky[premium.rng.range_usize(0, risky.len())];
+                    // Off the free list, so regular food never lands under it
+                    self.free.remove(at);
+                    premium.at = Some(at);
+                    premium.ticks_left = PREMIUM_TICKS;
+                    events.push(SimEvent::PremiumSpawned { at });
+                }
+            }
+        }
+        self.premium = Some(premium);
+        events
+    }
+
+    // Runs down the inversion and a glitch food left lying, and after a meal may place a new one
+    // anywhere free
+    fn glitch_tick(&mut self, ate: bool, events: Vec<SimEvent>) -> Vec<SimEvent> {
+        let Some(mut glitch) = self.glitch.take() else { return events; };
+        // Counted from the tick after the one it was eaten on
+        if !events.iter().any(|e| matches!(e, SimEvent::GlitchEaten { .. })) { glitch.inverted = glitch.inverted.saturating_sub(1); }
+        if let Some(at) = glitch.at {
+            glitch.ticks_left = glitch.ticks_left.saturating_sub(1);
+            if glitch.ticks_left == 0 {
+                glitch.at = None;
+                self.free.insert(at);
+            }
+        }
+        if ate && glitch.at.is_none() && glitch.rng.next_f32() < GLITCH_CHANCE {
+            let spots: Vec<Cell> = self.free.cells.iter()
+                .copied()
+                .filter(|c| self.food != Some(*c) && self.map.conveyor_at(*c).is_none())
+                .collect();
+            if !spots.is_empty() {
+                let at = spots[glitch.rng.range_usize(0, spots.len())];
+                self.free.remove(at);
+                glitch.at = Some(at);
+                glitch.ticks_left = GLITCH_TICKS;
+            }
+        }
+        self.glitch = Some(glitch);
+        events
+    }
+
+    // Takes a power-up the head landed on, runs down one left lying, and may place a new one
+    fn powerups_tick(&mut self, ate: bool, mut events: Vec<SimEvent>) -> Vec<SimEvent> {
+        let Some(mut powerups) = self.powerups.take() else { return events; };
+        if let Some((kind, at)) = powerups.at {
+            if at == self.head() {
+                // Already off the free list and now under the snake, so nothing to give back
+                powerups.at = None;
+                powerups.used += 1;
+                match kind {
+                    PowerUp::Reverse => self.reverse(),
+                    PowerUp::Bomb => self.blast(at),
+                }
+                events.push(SimEvent::PowerUpTaken { kind, at });
+            } else {
+                powerups.ticks_left = powerups.ticks_left.saturating_sub(1);
+                if powerups.ticks_left == 0 {
+                    powerups.at = None;
+                    self.free.insert(at);
+                }
+            }
+        }
+        if ate && powerups.on_food() {
+            let kind = powerups.pick();
+            // Not on a belt, which would carry the head straight past it
+            if let Some(at) = self.free.sample(&mut powerups.rng).filter(|c| self.food != Some(*c) && self.map.conveyor_at(*c).is_none()) {
+                self.free.remove(at);
+                powerups.at = Some((kind, at));
+                powerups.ticks_left = POWERUP_TICKS;
+                events.push(SimEvent::PowerUpSpawned { kind, at });
+            }
+        }
+        self.powerups = Some(powerups);
+        events
+    }
+
+    // Swaps head and tail: the snake now heads away from where its tail pointed. The new
+    // direction comes from the new first two segments, so the next move can't fold back onto
+    // the neck; a lone head simply turns around.
+    fn reverse(&mut self) {
+        self.snake.make_contiguous().reverse();
+        let dir = match (self.snake.front(), self.snake.get(1)) {
+            (Some(head), Some(neck)) => neck.direction_to(*head).unwrap_or(self.direction.opposite()),
+            _ => self.direction.opposite(),
+        };
+        self.direction = dir;
+        self.next_direction = dir;
+    }
+
+    // Destroys the interior walls within BOMB_RADIUS of `at`; the border always stays
+    fn blast(&mut self, at: Cell) {
+        let r = BOMB_RADIUS;
+        for y in at.y - r..=at.y + r {
+            for x in at.x - r..=at.x + r {
+                let c = Cell { x, y };
+                if (x - at.x).pow(2) + (y - at.y).pow(2) <= r * r && self.map.is_wall(c) { self.remove_wall(c); }
+            }
+        }
+    }
+
+    // A meal refills the hunger; without one the counter runs down and then the tail starves
+    fn hunger_tick(&mut self, ate: bool, mut events: Vec<SimEvent>) -> Vec<SimEvent> {
+        let Some(mut hunger) = self.hunger else { return events; };
+        if ate {
+            hunger = Hunger::full(self.map.grid, self.snake.len());
+        } else if hunger.moves_left > 0 {
+            hunger.moves_left -= 1;
+        } else {
+            hunger.starved_for += 1;
+            if hunger.starved_for % STARVE_EVERY == 0 {
+                if self.snake.len() <= 1 { return self.die(events, DeathCause::Starved); }
+                if let Some(tail) = self.snake.pop_back() {
+                    self.vacate(tail);
+                    events.push(SimEvent::Shrank { tail });
+                }
+            }
+        }
+        self.hunger = Some(hunger);
+        events
+    }
+
+    // A meal counts toward the wave and may open the next one; then the patrollers move
+    fn waves_tick(&mut self, ate: bool, mut events: Vec<SimEvent>) -> Vec<SimEvent> {
+        let Some(mut waves) = self.waves.take() else { return events; };
+        if ate && let Some(hazard) = waves.on_food() {
+            self.open_hazard(&mut waves, hazard);
+            events.push(SimEvent::WaveStarted { wave: waves.wave, hazard });
+        }
+        waves.ticks += 1;
+        if waves.ticks % PATROL_EVERY == 0 {
+            for i in 0..waves.patrollers.len() {
+                let mut p = waves.patrollers[i];
+                // Anything solid turns it around; the head is fair game
+                let blocked = |c: Cell| {
+                    !self.map.grid.contains(c)
+                        || self.map.is_wall(c)
+                        || self.map.is_fixture(c)
+                        || self.food == Some(c)
+                        || self.premium.as_ref().is_some_and(|p| p.at == Some(c))
+                        || self.powerups.as_ref().is_some_and(|p| p.at_cell(c).is_some())
+                        || waves.patroller_at(c)
+                        || (self.occupied.contains(&c) && c != self.head())
+                };
+                if blocked(p.at.step(p.dir)) { p.dir = p.dir.opposite(); }
+                let next = p.at.step(p.dir);
+                if !blocked(next) {
+                    self.free.insert(p.at);
+                    self.free.remove(next);
+                    p.at = next;
+                }
+                waves.patrollers[i] = p;
+            }
+        }
+        let caught = waves.patroller_at(self.head());
+        self.waves = Some(waves);
+        if caught { return self.die(events, DeathCause::Patroller); }
+        events
+    }
+
+    fn open_hazard(&mut self, waves: &mut Waves, hazard: Hazard) {
+        match hazard {
+            Hazard::Walls => {
+                for _ in 0..WALLS_PER_WAVE * Waves::round(waves.wave) as usize {
+                    if let Some(c) = self.hazard_cell(&mut waves.rng) { self.add_wall(c); }
+                }
+            }
+            Hazard::Patroller => {
+                if let Some(at) = self.hazard_cell(&mut waves.rng) {
+                    let dirs = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+                    let dir = dirs[waves.rng.range_usize(0, dirs.len())];
+                    // Off the free list, so food never lands under it
+                    self.free.remove(at);
+                    waves.patrollers.push(Patroller { at, dir });
+                }
+            }
+            Hazard::Faster => {} // the slower tick comes from `Waves::speed`
+        }
+    }
+
+    // A free cell away from the head for a new hazard; None if a few tries all land too close
+    fn hazard_cell(&self, rng: &mut Rng) -> Option<Cell> {
+        let head = self.head();
+        (0..32)
+            .filter_map(|_| self.free.sample(rng))
+            .find(|c| (c.x - head.x).abs() + (c.y - head.y).abs() >= SAFE_RADIUS && self.food != Some(*c))
+    }
+
+    fn die(&mut self, mut events: Vec<SimEvent>, cause: DeathCause) -> Vec<SimEvent> {
+        self.alive = false;
+        self.death = Some(cause);
+        events.push(SimEvent::Died(cause));
+        events
+    }
+}
+
+/// One tick as a function: the state after it and what happened, given the state before and the
+/// turn asked for since the last tick, if any. Nothing else goes in (no clock, audio or shared
+/// RNG), so the same state and input always give the same result.
+pub fn advance(mut state: SnakeSim, input: Option<Direction>) -> (SnakeSim, Vec<SimEvent>) {
+    if let Some(dir) = input { state.turn(dir); }
+    let events = state.tick();
+    (state, events)
+}
+
+/// Rule changes made from outside the tick (mods and wave hazards). Each refuses cells it
+/// can't take and keeps the occupancy and free-cell bookkeeping in step; the return value says
+/// if it applied.
+impl SnakeSim {
+    pub fn place_food(&mut self, c: Cell) -> bool {
+        if !self.free.contains(c) { return false; }
+        self.food = Some(c);
+        self.food_owed = false;
+        true
+    }
+
+    /// The board goes without food until some is placed again
+    pub fn remove_food(&mut self) -> bool {
+        self.food_owed = false;
+        self.food.take().is_some()
+    }
+
+    pub fn add_wall(&mut self, c: Cell) -> bool {
+        if !self.free.contains(c) || self.food == Some(c) { return false; }
+        self.map.walls.insert(c);
+        self.map.refresh(c);
+        self.free.remove(c);
+        true
+    }
+
+    /// Makes `c` a wall of switch `group`, solid or open with the rest of it, or takes it out of
+    /// the group. Only free cells join one.
+    pub fn toggle_switch_wall(&mut self, group: usize, c: Cell) -> bool {
+        if group >= SWITCH_GROUPS { return false; }
+        if self.map.switches.len() <= group { self.map.switches.resize_with(group + 1, Switch::default); }
+        if let Some(i) = self.map.switches[group].walls.iter().position(|w| *w == c) {
+            self.map.switches[group].walls.swap_remove(i);
+            self.map.refresh(c);
+            if !self.map.is_wall(c) && !self.map.is_fixture(c) && !self.occupied.contains(&c) { self.free.insert(c); }
+            return true;
+        }
+        if !self.free.contains(c) || self.food == Some(c) || self.map.plate_at(c).is_some() { return false; }
+        self.map.switches[group].walls.push(c);
+        self.map.refresh(c);
+        self.free.remove(c);
+        true
+    }
+
+    /// Puts a plate of switch `group` on `c`, or takes away the one there. Plates go on floor
+    /// that isn't already a plate.
+    pub fn toggle_plate(&mut self, group: usize, c: Cell) -> bool {
+        if group >= SWITCH_GROUPS { return false; }
+        if self.map.switches.len() <= group { self.map.switches.resize_with(group + 1, Switch::default); }
+        if let Some(i) = self.map.switches[group].plates.iter().position(|p| *p == c) {
+            self.map.switches[group].plates.swap_remove(i);
+            self.map.refresh(c);
+            return true;
+        }
+        if !self.map.grid.contains(c) || self.map.is_wall(c) || self.map.is_fixture(c) || self.map.plate_at(c).is_some() { return false; }
+        self.map.switches[group].plates.push(c);
+        self.map.refresh(c);
+        true
+    }
+
+    /// Border walls stay, so the snake can never leave the grid
+    pub fn remove_wall(&mut self, c: Cell) -> bool {
+        if self.map.grid.on_border(c) || !self.map.walls.remove(&c) { return false; }
+        self.map.refresh(c);
+        // A phasing snake may be in it
+        if !self.occupied.contains(&c) { self.free.insert(c); }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::{Gate, DEFAULT_STYLE};
+
+    fn open_map(width: i32, height: i32) -> Map {
+        Map { walls: HashSet::new(), seed: 7, wall_density: 0.0, grid: GridConfig { width, height }, style: DEFAULT_STYLE.to_string(), gates: Vec::new(), conveyors: Vec::new(), switches: Vec::new(), floors: Vec::new(), stairs: Vec::new(), shed: Vec::new(), solid: Vec::new() }
+    }
+
+    // A sim on an open board with no food, so nothing but the moves under test happens
+    fn bare(width: i32, height: i32) -> SnakeSim {
+        let mut sim = SnakeSim::new(open_map(width, height));
+        sim.remove_food();
+        sim
+    }
+
+    fn cell(x: i32, y: i32) -> Cell { Cell { x, y } }
+
+    #[test]
+    fn moves_one_cell_per_tick() {
+        let sim = bare(10, 10);
+        let head = sim.head();
+        let (sim, events) = advance(sim, None);
+        assert_eq!(sim.head(), cell(head.x + 1, head.y));
+        assert_eq!(sim.snake.len(), 3);
+        assert_eq!(events, vec![SimEvent::Moved { head: sim.head() }]);
+    }
+
+    #[test]
+    fn same_state_and_input_give_the_same_result() {
+        let sim = SnakeSim::new(Map::generate(GridConfig::DEFAULT, 42, 0.1));
+        let (a, a_events) = advance(sim.clone(), Some(Direction::Up));
+        let (b, b_events) = advance(sim, Some(Direction::Up));
+        assert_eq!(a.state_hash(), b.state_hash());
+        assert_eq!(a_events, b_events);
+    }
+
+    #[test]
+    fn different_walls_hash_differently() {
+        let a = bare(10, 10);
+        let mut b = a.clone();
+        assert!(b.add_wall(cell(8, 8)));
+        assert_ne!(a.state_hash(), b.state_hash());
+
+        // Same walls added in another order still hash the same
+        let (mut c, mut d) = (a.clone(), a.clone());
+        assert!(c.add_wall(cell(1, 1)) && c.add_wall(cell(8, 1)));
+        assert!(d.add_wall(cell(8, 1)) && d.add_wall(cell(1, 1)));
+        assert_eq!(c.state_hash(), d.state_hash());
+    }
+
+    #[test]
+    fn a_queued_turn_changes_the_hash() {
+        let a = bare(10, 10);
+        let mut b = a.clone();
+        b.turn(Direction::Up);
+        assert_ne!(a.state_hash(), b.state_hash());
+    }
+
+    #[test]
+    fn reversing_onto_the_neck_is_ignored() {
+        let sim = bare(10, 10);
+        let head = sim.head();
+        let (sim, events) = advance(sim, Some(Direction::Left));
+        assert!(sim.alive);
+        assert_eq!(sim.direction, Direction::Right);
+        assert_eq!(sim.head(), cell(head.x + 1, head.y));
+        assert!(!events.iter().any(|e| matches!(e, SimEvent::Died(_))));
+    }
+
+    #[test]
+    fn two_quick_turns_cant_reverse_within_a_tick() {
+        let mut sim = bare(10, 10);
+        let head = sim.head();
+        sim.turn(Direction::Up);
+        let (sim, _) = advance(sim, Some(Direction::Left));
+        assert!(sim.alive);
+        assert_eq!(sim.direction, Direction::Up);
+        assert_eq!(sim.head(), cell(head.x, head.y - 1));
+    }
+
+    #[test]
+    fn border_kills() {
+        let mut sim = bare(5, 1);
+        for _ in 0..2 { sim = advance(sim, None).0; }
+        assert_eq!(sim.head(), cell(4, 0));
+        let (sim, events) = advance(sim, None);
+        assert!(!sim.alive);
+        assert_eq!(sim.death, Some(DeathCause::Border));
+        assert_eq!(events, vec![SimEvent::Died(DeathCause::Border)]);
+        assert_eq!(sim.head(), cell(4, 0));
+    }
+
+    #[test]
+    fn wall_kills() {
+        let mut sim = bare(10, 10);
+        let ahead = sim.head().step(Direction::Right);
+        assert!(sim.add_wall(ahead));
+        let (sim, events) = advance(sim, None);
+        assert!(!sim.alive);
+        assert_eq!(events, vec![SimEvent::Died(DeathCause::Wall)]);
+    }
+
+    #[test]
+    fn a_phase_charge_passes_a_wall_run() {
+        let mut sim = bare(12, 10);
+        sim.enable_phase();
+        let head = sim.head();
+        for dx in 1..=2 { assert!(sim.add_wall(cell(head.x + dx, head.y))); }
+        sim.phase.as_mut().unwrap().charges = 1;
+        let (sim, events) = advance(sim, None);
+        assert!(sim.alive);
+        assert_eq!(events, vec![SimEvent::Phased { at: cell(head.x + 1, head.y), charges: 0 }, SimEvent::Moved { head: sim.head() }]);
+        // The rest of the wall comes free, and so does the way out
+        let mut sim = sim;
+        for _ in 0..3 { sim = advance(sim, None).0; }
+        assert!(sim.alive);
+        assert_eq!(sim.head(), cell(head.x + 4, head.y));
+        // Left behind, the wall cells don't become free for food
+        assert!(!sim.free.contains(cell(head.x + 1, head.y)));
+    }
+
+    #[test]
+    fn a_phase_charge_comes_every_ten_points() {
+        let mut sim = bare(10, 10);
+        sim.enable_phase();
+        sim.score = PHASE_EVERY - 1;
+        assert!(sim.place_food(sim.head().step(Direction::Right)));
+        let (sim, events) = advance(sim, None);
+        assert!(events.contains(&SimEvent::PhaseEarned { charges: 1 }));
+        assert_eq!(sim.phase.unwrap().next_at, 2 * PHASE_EVERY);
+    }
+
+    #[test]
+    fn the_border_kills_even_with_charges() {
+        let mut sim = bare(12, 10);
+        sim.enable_phase();
+        sim.phase.as_mut().unwrap().charges = 1;
+        assert!(sim.add_wall(cell(11, sim.head().y)));
+        while sim.alive { sim = advance(sim, None).0; }
+        assert_eq!(sim.death, Some(DeathCause::Wall));
+        assert_eq!(sim.head(), cell(10, 5));
+        assert_eq!(sim.phase.unwrap().charges, 1);
+    }
+
+    // A closed one-cell gate straight ahead of the head, opening in `ticks_left`
+    fn gated(ticks_left: u32) -> SnakeSim {
+        let mut sim = bare(12, 10);
+        let at = sim.head().step(Direction::Right);
+        sim.map.gates.push(Gate { cells: vec![at], open_ticks: 3, closed_ticks: 5, open: false, ticks_left });
+        sim.rebuild_occupancy();
+        sim
+    }
+
+    #[test]
+    fn a_closed_gate_kills() {
+        let (sim, events) = advance(gated(5), None);
+        assert_eq!(events, vec![SimEvent::Died(DeathCause::Wall)]);
+        assert!(!sim.alive);
+    }
+
+    #[test]
+    fn an_open_gate_lets_the_snake_through_and_waits_for_it() {
+        let sim = gated(1);
+        let gate = sim.head().step(Direction::Right);
+        assert!(!sim.free.contains(gate));
+        // Opens at the end of this tick, so the next move goes through
+        let (sim, _) = advance(sim, Some(Direction::Up));
+        assert!(sim.map.gates[0].open);
+        let (sim, _) = advance(sim, Some(Direction::Right));
+        let (mut sim, _) = advance(sim, Some(Direction::Down));
+        assert_eq!(sim.head(), gate);
+        // Due to shut while the body is still in it, so it stays open
+        assert_eq!(sim.map.gates[0].ticks_left, 1);
+        sim = advance(sim, None).0;
+        assert!(sim.alive);
+        assert!(sim.map.gates[0].open && sim.occupied.contains(&gate));
+        for _ in 0..2 { sim = advance(sim, None).0; }
+        assert!(!sim.map.gates[0].open);
+        // Left behind, it doesn't become a place for food
+        assert!(!sim.free.contains(gate));
+    }
+
+    #[test]
+    fn a_conveyor_carries_the_head_a_cell_further() {
+        let mut sim = bare(12, 10);
+        let head = sim.head();
+        sim.map.conveyors.push((cell(head.x + 1, head.y), Direction::Down));
+        let (sim, events) = advance(sim, None);
+        assert_eq!(sim.head(), cell(head.x + 1, head.y + 1));
+        assert_eq!(sim.snake.len(), 3);
+        assert_eq!(events.iter().filter(|e| matches!(e, SimEvent::Moved { .. })).count(), 2);
+        // Still heading the way the player steered
+        assert_eq!(sim.direction, Direction::Right);
+    }
+
+    #[test]
+    fn a_blocked_conveyor_does_nothing() {
+        let mut sim = bare(12, 10);
+        let head = sim.head();
+        sim.map.conveyors.push((cell(head.x + 1, head.y), Direction::Down));
+        assert!(sim.add_wall(cell(head.x + 1, head.y + 1)));
+        let (sim, _) = advance(sim, None);
+        assert!(sim.alive);
+        assert_eq!(sim.head(), cell(head.x + 1, head.y));
+    }
+
+    #[test]
+    fn crossing_a_plate_flips_its_walls() {
+        let mut sim = bare(12, 10);
+        let head = sim.head();
+        let door = cell(head.x + 3, head.y);
+        assert!(sim.toggle_plate(1, cell(head.x + 1, head.y)));
+        assert!(sim.toggle_switch_wall(1, door));
+        let (sim, events) = advance(sim, None);
+        assert!(events.contains(&SimEvent::Switched { group: 1, solid: true }));
+        assert!(sim.map.is_wall(door));
+        // The door is shut now, so running on into it kills
+        let (sim, _) = advance(sim, None);
+        let (sim, events) = advance(sim, None);
+        assert_eq!(events, vec![SimEvent::Died(DeathCause::Wall)]);
+        assert!(!sim.free.contains(door));
+    }
+
+    #[test]
+    fn wall_index_follows_edits() {
+        let mut sim = bare(12, 10);
+        let (wall, door) = (cell(3, 3), cell(5, 2));
+        assert!(sim.add_wall(wall) && sim.map.is_wall(wall));
+        assert!(sim.toggle_switch_wall(0, door) && !sim.map.is_wall(door));
+        sim.map.switches[0].solid = true;
+        sim.map.refresh(door);
+        assert!(sim.map.is_wall(door));
+        assert!(sim.remove_wall(wall) && !sim.map.is_wall(wall));
+        assert!(sim.toggle_switch_wall(0, door) && !sim.map.is_wall(door));
+        // Nothing drifted from what a rebuild works out
+        let solid = sim.map.solid.clone();
+        sim.map.reindex();
+        assert_eq!(sim.map.solid, solid);
+    }
+
+    #[test]
+    fn stairs_take_the_head_to_their_other_end() {
+        let mut sim = bare(12, 10);
+        let head = sim.head();
+        sim.map.stairs.push((cell(head.x + 1, head.y), cell(2, 2)));
+        let (sim, _) = advance(sim, None);
+        assert_eq!(sim.head(), cell(2, 2));
+        assert_eq!(sim.snake.len(), 3);
+        let (sim, _) = advance(sim, None);
+        assert_eq!(sim.head(), cell(3, 2));
+    }
+
+    #[test]
+    fn food_is_worth_more_at_night() {
+        let mut sim = bare(12, 10);
+        sim.enable_daylight(1.0);
+        let Some(daylight) = &mut sim.daylight else { unreachable!() };
+        // One tick before night falls, halfway through dusk
+        daylight.tick = daylight.length / 2 - daylight.fade / 2 - 1;
+        assert!(sim.place_food(cell(sim.head().x + 2, sim.head().y)));
+        let (sim, events) = advance(sim, None);
+        assert!(events.contains(&SimEvent::Nightfall { night: true }));
+        let (sim, events) = advance(sim, None);
+        assert!(events.contains(&SimEvent::FoodEaten { at: sim.head(), score: 2, points: 2 }));
+        assert_eq!(sim.daylight.map(|d| d.bonus), Some(NIGHT_BONUS));
+    }
+
+    #[test]
+    fn meals_past_the_length_cap_shed_the_tail() {
+        let mut sim = bare(12, 10);
+        sim.enable_length_cap(1.0);
+        let Some(cap) = &mut sim.length_cap else { unreachable!() };
+        cap.cap = 3;
+        cap.lifetime = 2;
+        let tail = *sim.snake.back().unwrap();
+        assert!(sim.place_food(cell(sim.head().x + 1, sim.head().y)));
+        let (sim, _) = advance(sim, None);
+        assert_eq!(sim.snake.len(), 3);
+        assert_eq!(sim.score, 1);
+        assert!(sim.map.is_wall(tail));
+        assert!(!sim.free.contains(tail));
+        // It crumbles once its lifetime is up
+        let (sim, _) = advance(sim, None);
+        assert!(!sim.map.is_wall(tail));
+        assert!(sim.free.contains(tail));
+    }
+
+    #[test]
+    fn a_chain_eaten_in_order_pays_its_bonus() {
+        let mut sim = bare(12, 10);
+        sim.enable_chain();
+        let head = sim.head();
+        // Lay the set in a row ahead of the head, in order
+        let Some(chain) = &mut sim.chain else { unreachable!() };
+        chain.foods = [1, 2, 3].map(|i| Some(cell(head.x + i, head.y)));
+        sim.rebuild_occupancy();
+        let (sim, events) = advance(sim, None);
+        assert!(events.contains(&SimEvent::ChainEaten { at: cell(head.x + 1, head.y), kind: 0, score: 1, points: 1, in_order: true }));
+        let (sim, _) = advance(sim, None);
+        let (sim, events) = advance(sim, None);
+        assert!(events.contains(&SimEvent::ChainEaten { at: cell(head.x + 3, head.y), kind: 2, score: 3 + CHAIN_BONUS, points: 1 + CHAIN_BONUS, in_order: true }));
+        // A new set is out
+        assert_eq!(sim.chain.as_ref().and_then(|c| c.next()), Some(0));
+    }
+
+    #[test]
+    fn a_chain_food_out_of_order_breaks_the_set() {
+        let mut sim = bare(12, 10);
+        sim.enable_chain();
+        let head = sim.head();
+        let Some(chain) = &mut sim.chain else { unreachable!() };
+        chain.foods = [Some(cell(2, 2)), Some(cell(head.x + 1, head.y)), Some(cell(3, 2))];
+        sim.rebuild_occupancy();
+        let (sim, events) = advance(sim, None);
+        assert!(events.contains(&SimEvent::ChainEaten { at: cell(head.x + 1, head.y), kind: 1, score: 1, points: 1, in_order: false }));
+        assert!(sim.chain.as_ref().is_some_and(|c| c.broken && c.next() == Some(0)));
+    }
+
+    #[test]
+    fn glitch_food_costs_points_and_inverts_the_controls() {
+        let mut sim = bare(60, 10);
+        sim.enable_glitch();
+        sim.score = 5;
+        let at = cell(sim.head().x + 1, sim.head().y);
+        if let Some(glitch) = &mut sim.glitch {
+            glitch.at = Some(at);
+            glitch.ticks_left = 10;
+        }
+        sim.rebuild_occupancy();
+        let (sim, events) = advance(sim, None);
+        assert!(events.contains(&SimEvent::GlitchEaten { at, score: 5 - GLITCH_PENALTY }));
+        assert_eq!(sim.snake.len(), 3);
+        assert!(sim.inverted());
+        let sim = (0..INVERT_TICKS).fold(sim, |sim, _| advance(sim, None).0);
+        assert!(!sim.inverted());
+    }
+
+    #[test]
+    fn running_into_the_body_kills() {
+        let mut sim = bare(10, 10);
+        // Heading right with the body curled below the head
+        sim.snake = VecDeque::from([cell(5, 5), cell(4, 5), cell(4, 6), cell(5, 6), cell(6, 6)]);
+        sim.rebuild_occupancy();
+        let (sim, events) = advance(sim, Some(Direction::Down));
+        assert!(!sim.alive);
+        assert_eq!(events, vec![SimEvent::Died(DeathCause::SelfCollision)]);
+    }
+
+    #[test]
+    fn dead_snakes_stay_put() {
+        let mut sim = bare(10, 10);
+        assert!(sim.add_wall(sim.head().step(Direction::Right)));
+        let (sim, _) = advance(sim, None);
+        let hash = sim.state_hash();
+        let (sim, events) = advance(sim, Some(Direction::Up));
+        assert!(events.is_empty());
+        assert_eq!(sim.state_hash(), hash);
+    }
+
+    #[test]
+    fn eating_grows_the_snake_by_one() {
+        let mut sim = bare(10, 10);
+        let food = sim.head().step(Direction::Right);
+        assert!(sim.place_food(food));
+        let (sim, events) = advance(sim, None);
+        assert_eq!(sim.score, 1);
+        assert_eq!(sim.snake.len(), 4);
+        assert!(events.contains(&SimEvent::FoodEaten { at: food, score: 1, points: 1 }));
+        let food = sim.food.expect("new food placed");
+        assert!(!sim.occupied.contains(&food) && !sim.map.is_wall(food));
+        // Growth is one segment per meal, not a lasting stretch
+        let (sim, _) = advance(sim, Some(Direction::Up));
+        assert_eq!(sim.snake.len(), 4);
+    }
+
+    #[test]
+    fn filling_the_board_wins() {
+        // Head, body and tail take three of the four cells; the food is in the last one
+        let sim = SnakeSim::new(open_map(4, 1));
+        assert_eq!(sim.food, Some(cell(3, 0)));
+        let (sim, events) = advance(sim, None);
+        assert!(sim.won);
+        assert!(!sim.alive);
+        assert_eq!(sim.food, None);
+        assert_eq!(sim.snake.len(), 4);
+        assert_eq!(events.last(), Some(&SimEvent::Won));
+    }
+
+    #[test]
+    fn pickups_on_the_last_cells_are_not_a_win() {
+        // Food in the fourth cell and premium food in the fifth, the only two left free
+        let mut sim = bare(5, 1);
+        sim.enable_premium();
+        let Some(premium) = &mut sim.premium else { unreachable!() };
+        premium.at = Some(cell(4, 0));
+        premium.ticks_left = PREMIUM_TICKS;
+        sim.rebuild_occupancy();
+        assert!(sim.place_food(cell(3, 0)));
+        let (sim, events) = advance(sim, None);
+        assert!(!events.contains(&SimEvent::Won));
+        assert!(sim.alive && sim.food_owed);
+        // Eating the premium food fills the board
+        let (sim, events) = advance(sim, None);
+        assert!(sim.won);
+        assert_eq!(sim.snake.len(), 5);
+        assert_eq!(events.last(), Some(&SimEvent::Won));
+    }
+}
@@ -0,0 +1,82 @@
+//! Wave mode schedule: every WAVE_FOOD meals a new wave starts and brings the next hazard in
+//! the cycle, each one stronger than the last time round. The sim applies the hazards; this
+//! only decides what comes when and keeps the per-wave state.
+use serde::{Deserialize, Serialize};
+
+use crate::map::{Cell, Direction};
+use crate::rng::Rng;
+
+pub const WAVE_FOOD: u32 = 5; // meals that finish a wave
+pub const WALLS_PER_WAVE: usize = 6; // extra walls a Walls wave adds, per time round the cycle
+pub const SAFE_RADIUS: i32 = 4; // new walls and patrollers keep this far from the head
+pub const PATROL_EVERY: u32 = 2; // ticks per patroller step
+const SPEEDUP: f32 = 0.85; // tick interval factor per Faster wave
+const MIN_SPEED: f32 = 0.35;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Hazard {
+    Walls,
+    Patroller,
+    Faster,
+}
+
+impl Hazard {
+    const CYCLE: [Hazard; 3] = [Hazard::Walls, Hazard::Patroller, Hazard::Faster];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Hazard::Walls => "More walls",
+            Hazard::Patroller => "Patroller",
+            Hazard::Faster => "Faster ticks",
+        }
+    }
+}
+
+/// A hazard that paces back and forth in a straight line and kills on contact
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Patroller {
+    pub at: Cell,
+    pub dir: Direction,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Waves {
+    pub wave: u32, // from 1; wave 1 is the plain board
+    pub scores: Vec<u32>, // food eaten in each wave, the current one last
+    pub patrollers: Vec<Patroller>,
+    pub speed: f32, // multiplier on the tick interval, lowered by Faster waves
+    pub ticks: u32, // since the run started, to pace the patrollers
+    pub rng: Rng, // hazard placement, kept apart from the food stream
+}
+
+impl Waves {
+    pub fn new(rng: Rng) -> Self {
+        Self { wave: 1, scores: vec![0], patrollers: Vec::new(), speed: 1.0, ticks: 0, rng }
+    }
+
+    /// Hazard that opens `wave`
+    pub fn hazard(wave: u32) -> Option<Hazard> {
+        (wave >= 2).then(|| Hazard::CYCLE[((wave - 2) % Hazard::CYCLE.len() as u32) as usize])
+    }
+
+    /// How many times the cycle has come round to `wave`'s hazard, from 1
+    pub fn round(wave: u32) -> u32 {
+        wave.saturating_sub(2) / Hazard::CYCLE.len() as u32 + 1
+    }
+
+    /// Counts a meal; returns the hazard of the wave it started, if it finished one
+    pub fn on_food(&mut self) -> Option<Hazard> {
+        let current = self.scores.last_mut()?;
+        *current += 1;
+        if *current < WAVE_FOOD { return None; }
+        self.wave += 1;
+        self.scores.push(0);
+        let hazard = Self::hazard(self.wave)?;
+        if hazard == Hazard::Faster { self.speed = (self.speed * SPEEDUP).max(MIN_SPEED); }
+        Some(hazard)
+    }
+
+    pub fn patroller_at(&self, cell: Cell) -> bool {
+        self.patrollers.iter().any(|p| p.at == cell)
+    }
+}
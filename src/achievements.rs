@@ -0,0 +1,70 @@
+// Achievements: milestones over lifetime stats plus feats within a single run. Unlock dates
+// live in the save's `progress` section, keyed by id.
+use std::collections::BTreeMap;
+
+use crate::game::SnakeGame;
+use crate::leaderboard::daily_seed;
+use crate::stats::Stats;
+
+pub enum Goal {
+    // Reached once a lifetime counter gets to the target; shown with a progress bar
+    Total(fn(&Stats) -> u64, u64),
+    // Reached by a single finished run
+    Run(fn(&SnakeGame) -> bool),
+}
+
+pub struct Achievement {
+    pub id: &'static str, // stored in saves, so keep it stable
+    pub name: &'static str,
+    pub description: &'static str,
+    pub goal: Goal,
+}
+
+pub const ALL: &[Achievement] = &[
+    Achievement { id: "first_bite", name: "First Bite", description: "Eat your first food", goal: Goal::Total(|s| s.food, 1) },
+    Achievement { id: "score_25", name: "Growing", description: "Score 25 in one run", goal: Goal::Run(|g| g.sim.score >= 25) },
+    Achievement { id: "score_50", name: "Serpent", description: "Score 50 in one run", goal: Goal::Run(|g| g.sim.score >= 50) },
+    Achievement { id: "survivor", name: "Survivor", description: "Last 1000 ticks in one run", goal: Goal::Run(|g| g.replay.ticks >= 1000) },
+    Achievement {
+        id: "spelunker",
+        name: "Spelunker",
+        description: "Score 10 on a Caves map",
+        goal: Goal::Run(|g| g.sim.map.style == "Caves" && g.sim.score >= 10),
+    },
+    Achievement { id: "daily", name: "Daily Driver", description: "Finish a run on the daily seed", goal: Goal::Run(|g| g.sim.map.seed == daily_seed()) },
+    Achievement { id: "clean_sweep", name: "Clean Sweep", description: "Clear a whole board", goal: Goal::Total(|s| s.boards_cleared as u64, 1) },
+    Achievement { id: "starved", name: "Running on Empty", description: "Starve in Hunger mode", goal: Goal::Total(|s| s.deaths.starved as u64, 1) },
+    Achievement { id: "regular", name: "Regular", description: "Play 50 games", goal: Goal::Total(|s| s.games as u64, 50) },
+    Achievement { id: "glutton", name: "Glutton", description: "Eat 1000 food", goal: Goal::Total(|s| s.food, 1000) },
+    Achievement { id: "marathon", name: "Marathon", description: "Play for an hour in total", goal: Goal::Total(|s| s.play_seconds as u64 / 60, 60) },
+    Achievement { id: "week_streak", name: "Habit", description: "Play 7 days in a row", goal: Goal::Total(|s| s.best_day_streak as u64, 7) },
+];
+
+impl Achievement {
+    // (current, target) for lifetime goals
+    pub fn progress(&self, stats: &Stats) -> Option<(u64, u64)> {
+        match self.goal {
+            Goal::Total(value, target) => Some((value(stats).min(target), target)),
+            Goal::Run(_) => None,
+        }
+    }
+
+    fn reached(&self, stats: &Stats, game: &SnakeGame) -> bool {
+        match self.goal {
+            Goal::Total(value, target) => value(stats) >= target,
+            Goal::Run(check) => check(game),
+        }
+    }
+}
+
+// Unlocks whatever the run just finished (already counted in `stats`) earned; returns the new ones
+pub fn check(unlocked: &mut BTreeMap<String, u64>, stats: &Stats, game: &SnakeGame, now: u64) -> Vec<&'static Achievement> {
+    let mut new = Vec::new();
+    for a in ALL {
+        if !unlocked.contains_key(a.id) && a.reached(stats, game) {
+            unlocked.insert(a.id.to_string(), now);
+            new.push(a);
+        }
+    }
+    new
+}
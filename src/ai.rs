@@ -0,0 +1,92 @@
+// Computer-controlled steering: the shortest safe path to the nearest food, and when no food
+// can be reached, the move that keeps the most room to wander. Works on any board through a
+// `blocked` test, so it doesn't depend on how the caller keeps its snakes.
+use std::collections::VecDeque;
+
+use crate::map::{Cell, Direction, GridConfig};
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+// Direction for a snake at `head` heading `dir`; `blocked` covers walls and every body
+pub fn steer(grid: GridConfig, head: Cell, dir: Direction, food: &[Cell], blocked: impl Fn(Cell) -> bool) -> Direction {
+    let open = |c: Cell| grid.contains(c) && !blocked(c);
+    let moves: Vec<Direction> = DIRECTIONS.iter().copied().filter(|d| *d != dir.opposite() && open(head.step(*d))).collect();
+    if moves.is_empty() { return dir; }
+
+    // Breadth-first from the head, remembering the first move that led to each cell
+    let mut first = vec![None; grid.cell_count()];
+    let slot = |c: Cell| (c.y * grid.width + c.x) as usize;
+    let mut queue = VecDeque::new();
+    for d in &moves {
+        let c = head.step(*d);
+        first[slot(c)] = Some(*d);
+        queue.push_back(c);
+    }
+    while let Some(c) = queue.pop_front() {
+        let Some(d) = first[slot(c)] else { continue; };
+        if food.contains(&c) && room(grid, c, &open) > 4 { return d; }
+        for next in DIRECTIONS.map(|n| c.step(n)) {
+            if open(next) && next != head && first[slot(next)].is_none() {
+                first[slot(next)] = Some(d);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    // Nothing to eat in reach: stay where there's the most space
+    moves.into_iter().max_by_key(|d| room(grid, head.step(*d), &open)).unwrap_or(dir)
+}
+
+// The shortest way from `head` to the nearest food, first step to the food itself; empty when
+// no food can be reached. The same search as `steer`, without its caution about dead ends.
+pub fn path(grid: GridConfig, head: Cell, dir: Direction, food: &[Cell], blocked: impl Fn(Cell) -> bool) -> Vec<Cell> {
+    let open = |c: Cell| grid.contains(c) && !blocked(c);
+    // The cell each one was reached from, so the path can be walked back from the food
+    let mut from: Vec<Option<Cell>> = vec![None; grid.cell_count()];
+    let slot = |c: Cell| (c.y * grid.width + c.x) as usize;
+    let mut queue = VecDeque::new();
+    for d in DIRECTIONS.iter().filter(|d| **d != dir.opposite()) {
+        let c = head.step(*d);
+        if open(c) {
+            from[slot(c)] = Some(head);
+            queue.push_back(c);
+        }
+    }
+    while let Some(c) = queue.pop_front() {
+        if food.contains(&c) {
+            let mut path = vec![c];
+            let mut at = c;
+            while let Some(prev) = from[slot(at)].filter(|p| *p != head) {
+                path.push(prev);
+                at = prev;
+            }
+            path.reverse();
+            return path;
+        }
+        for next in DIRECTIONS.map(|n| c.step(n)) {
+            if open(next) && next != head && from[slot(next)].is_none() {
+                from[slot(next)] = Some(c);
+                queue.push_back(next);
+            }
+        }
+    }
+    Vec::new()
+}
+
+// Open cells reachable from `from`, counted up to a cap since only small pockets matter
+fn room(grid: GridConfig, from: Cell, open: &impl Fn(Cell) -> bool) -> usize {
+    const CAP: usize = 64;
+    let mut seen = vec![false; grid.cell_count()];
+    let mut stack = vec![from];
+    let mut count = 0;
+    while let Some(c) = stack.pop() {
+        if !open(c) { continue; }
+        let i = (c.y * grid.width + c.x) as usize;
+        if seen[i] { continue; }
+        seen[i] = true;
+        count += 1;
+        if count >= CAP { break; }
+        stack.extend(DIRECTIONS.map(|d| c.step(d)));
+    }
+    count
+}
@@ -0,0 +1,173 @@
+// Several snakes on one board, for battle royale (the player is snake 0 and the rest are
+// steered by `ai`) and co-op (two players). All snakes move at once each tick, so collisions are resolved against
+// where everyone is about to be: tails that are moving away are fair to enter, and two heads
+// landing on the same cell both die.
+use std::collections::{HashSet, VecDeque};
+
+use crate::ai;
+use crate::map::{Cell, Direction, GridConfig, Map};
+use crate::rng::Rng;
+
+pub const ARENA_GRID: GridConfig = GridConfig { width: 56, height: 36 };
+pub const MIN_RIVALS: usize = 3;
+pub const MAX_RIVALS: usize = 5;
+pub const TIME_LIMIT: u32 = 1800; // ticks; then the highest score still alive wins
+const ARENA_STREAM: u64 = 8; // food placement, apart from the single-player streams
+const START_LENGTH: usize = 3;
+const SPAWN_CLEARANCE: i32 = 3; // walls this close to a spawn are knocked down
+
+#[derive(Clone)]
+pub struct ArenaSnake {
+    pub body: VecDeque<Cell>, // head at the front
+    pub direction: Direction,
+    pub next_direction: Direction,
+    pub alive: bool,
+    pub score: u32,
+    pub grow: bool,
+    pub out_at: Option<u32>, // tick it died on
+}
+
+impl ArenaSnake {
+    pub fn head(&self) -> Cell { self.body[0] }
+
+    // Same rule as the single-player snake: no turning back onto the neck
+    pub fn turn(&mut self, dir: Direction) {
+        if dir != self.direction.opposite() { self.next_direction = dir; }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ArenaEvent {
+    Ate { snake: usize },
+    Died { snake: usize },
+}
+
+pub struct Arena {
+    pub map: Map,
+    pub snakes: Vec<ArenaSnake>,
+    pub food: Vec<Cell>, // one per snake that started
+    pub tick: u32,
+    rng: Rng,
+}
+
+impl Arena {
+    // `count` snakes spread round the centre, each with a little clearing and heading outward
+    pub fn new(mut map: Map, count: usize) -> Self {
+        let grid = map.grid;
+        let center = grid.center();
+        let (rx, ry) = (grid.width as f32 * 0.3, grid.height as f32 * 0.3);
+        let mut snakes = Vec::with_capacity(count);
+        for i in 0..count {
+            let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+            let spawn = Cell { x: center.x + (angle.cos() * rx) as i32, y: center.y + (angle.sin() * ry) as i32 };
+            let direction = if spawn.x < center.x { Direction::Left } else { Direction::Right };
+            map.walls.retain(|w| (w.x - spawn.x).abs() > SPAWN_CLEARANCE || (w.y - spawn.y).abs() > SPAWN_CLEARANCE);
+            let mut body = VecDeque::new();
+            let mut c = spawn;
+            for _ in 0..START_LENGTH {
+                body.push_back(c);
+                c = c.step(direction.opposite());
+            }
+            snakes.push(ArenaSnake { body, direction, next_direction: direction, alive: true, score: 0, grow: false, out_at: None });
+        }
+        map.reindex();
+        let rng = Rng::with_stream(map.seed, ARENA_STREAM);
+        let mut arena = Self { map, snakes, food: Vec::new(), tick: 0, rng };
+        for _ in 0..count { arena.spawn_food(); }
+        arena
+    }
+
+    pub fn alive(&self) -> usize {
+        self.snakes.iter().filter(|s| s.alive).count()
+    }
+
+    // Over when at most one snake is left or time is up
+    pub fn finished(&self) -> bool {
+        self.alive() <= 1 || self.tick >= TIME_LIMIT
+    }
+
+    // The last snake standing, else the highest scorer among the survivors, else whoever
+    // lasted longest; ties go to the lower index, so the player wins them
+    pub fn winner(&self) -> Option<usize> {
+        (0..self.snakes.len()).max_by_key(|i| {
+            let s = &self.snakes[*i];
+            (s.alive, s.score, s.out_at, std::cmp::Reverse(*i))
+        })
+    }
+
+    // Walls or any living snake's body
+    pub fn blocked(&self, c: Cell) -> bool {
+        self.map.is_wall(c) || self.snakes.iter().any(|s| s.alive && s.body.contains(&c))
+    }
+
+    // Where snake `i` wants to go next, for the computer-controlled ones
+    pub fn ai_direction(&self, i: usize) -> Direction {
+        let snake = &self.snakes[i];
+        // Cells another head could reach this tick count as taken, to avoid head-on crashes
+        let threats: HashSet<Cell> = self.snakes.iter().enumerate()
+            .filter(|(j, s)| *j != i && s.alive)
+            .flat_map(|(_, s)| [Direction::Up, Direction::Down, Direction::Left, Direction::Right].map(|d| s.head().step(d)))
+            .collect();
+        ai::steer(self.map.grid, snake.head(), snake.direction, &self.food, |c| self.blocked(c) || threats.contains(&c))
+    }
+
+    pub fn tick(&mut self) -> Vec<ArenaEvent> {
+        let mut events = Vec::new();
+        self.tick += 1;
+        let heads: Vec<Option<Cell>> = self.snakes.iter_mut().map(|s| {
+            s.alive.then(|| {
+                s.direction = s.next_direction;
+                s.head().step(s.direction)
+            })
+        }).collect();
+
+        // Bodies as they'll be once this tick's tails have moved on
+        let mut taken = HashSet::new();
+        for s in self.snakes.iter().filter(|s| s.alive) {
+            let keep = if s.grow { s.body.len() } else { s.body.len() - 1 };
+            taken.extend(s.body.iter().take(keep).copied());
+        }
+        let dead: Vec<bool> = heads.iter().enumerate().map(|(i, head)| {
+            let Some(head) = *head else { return false; };
+            let head_on = heads.iter().enumerate().any(|(j, other)| j != i && *other == Some(head));
+            !self.map.grid.contains(head) || self.map.is_wall(head) || taken.contains(&head) || head_on
+        }).collect();
+
+        for i in 0..self.snakes.len() {
+            let Some(head) = heads[i] else { continue; };
+            if dead[i] {
+                let s = &mut self.snakes[i];
+                s.alive = false;
+                s.out_at = Some(self.tick);
+                s.body.clear();
+                events.push(ArenaEvent::Died { snake: i });
+                continue;
+            }
+            let s = &mut self.snakes[i];
+            s.body.push_front(head);
+            if s.grow { s.grow = false; } else { s.body.pop_back(); }
+            if let Some(f) = self.food.iter().position(|f| *f == head) {
+                self.food.swap_remove(f);
+                let s = &mut self.snakes[i];
+                s.score += 1;
+                s.grow = true;
+                events.push(ArenaEvent::Ate { snake: i });
+            }
+        }
+        while self.food.len() < self.snakes.len() && self.spawn_food() {}
+        events
+    }
+
+    // Food on a random open cell; false if a few tries found none
+    fn spawn_food(&mut self) -> bool {
+        let grid = self.map.grid;
+        for _ in 0..64 {
+            let c = Cell { x: self.rng.range_usize(1, grid.width as usize - 1) as i32, y: self.rng.range_usize(1, grid.height as usize - 1) as i32 };
+            if !self.blocked(c) && !self.food.contains(&c) {
+                self.food.push(c);
+                return true;
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,247 @@
+use std::cell::Cell;
+
+
+use crate::config::config;
+use crate::error::GameError;
+use crate::events::GameEvent;
+use crate::missions::Outcome;
+use crate::music;
+use crate::powerups::PowerUp;
+
+const SAMPLE_RATE: u32 = 44100;
+
+// Simple WAV (PCM16 mono) generator for tones
+pub fn generate_wav_sine(frequency_hz: f32, duration_seconds: f32, volume: f32) -> Vec<u8> {
+    let num_samples = (duration_seconds * SAMPLE_RATE as f32) as u32;
+    let two_pi = std::f32::consts::TAU;
+    let amplitude: f32 = (volume.clamp(0.0, 1.0)) * 0.7;
+    let samples: Vec<f32> = (0..num_samples)
+        .map(|n| amplitude * (two_pi * frequency_hz * n as f32 / SAMPLE_RATE as f32).sin())
+        .collect();
+    wav_from_samples(&samples)
+}
+
+// A square wave that jumps to a new pitch, up to two octaves over `frequency_hz`, every few
+// milliseconds and drops out now and then: the sound of the snake glitching through a wall.
+// The pitches come from a fixed LCG, so the sound is the same every launch.
+pub fn generate_wav_glitch(frequency_hz: f32, duration_seconds: f32, volume: f32) -> Vec<u8> {
+    const SLICE: u32 = SAMPLE_RATE / 100; // 10 ms per pitch
+    let num_samples = (duration_seconds * SAMPLE_RATE as f32) as u32;
+    let amplitude: f32 = (volume.clamp(0.0, 1.0)) * 0.5;
+    let mut state: u32 = 0x2545_f491;
+    let (mut hz, mut on) = (frequency_hz, true);
+    let samples: Vec<f32> = (0..num_samples)
+        .map(|n| {
+            if n % SLICE == 0 {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                hz = frequency_hz * 2f32.powf((state >> 16) as f32 / 65536.0 * 2.0);
+                on = state & 0x7 != 0;
+            }
+            let phase = (hz * n as f32 / SAMPLE_RATE as f32).fract();
+            if !on { 0.0 } else if phase < 0.5 { amplitude } else { -amplitude }
+        })
+        .collect();
+    wav_from_samples(&samples)
+}
+
+// PCM16 mono WAV file around samples in -1..1
+pub fn wav_from_samples(samples: &[f32]) -> Vec<u8> {
+    let sample_rate = SAMPLE_RATE;
+    let num_samples = samples.len() as u32;
+    let mut data: Vec<u8> = Vec::with_capacity((num_samples as usize) * 2 + 44);
+
+    let block_align: u16 = 2; // mono 16-bit
+    let byte_rate: u32 = sample_rate * block_align as u32;
+    let data_size: u32 = num_samples * 2;
+    let chunk_size: u32 = 36 + data_size;
+
+    // RIFF header
+    data.extend_from_slice(b"RIFF");
+    data.extend_from_slice(&chunk_size.to_le_bytes());
+    data.extend_from_slice(b"WAVE");
+    // fmt chunk
+    data.extend_from_slice(b"fmt ");
+    data.extend_from_slice(&16u32.to_le_bytes()); // PCM chunk size
+    data.extend_from_slice(&1u16.to_le_bytes()); // PCM format
+    data.extend_from_slice(&1u16.to_le_bytes()); // channels
+    data.extend_from_slice(&sample_rate.to_le_bytes());
+    data.extend_from_slice(&byte_rate.to_le_bytes());
+    data.extend_from_slice(&block_align.to_le_bytes());
+    data.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    // data chunk
+    data.extend_from_slice(b"data");
+    data.extend_from_slice(&data_size.to_le_bytes());
+
+    for s in samples {
+        let sample = (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        data.extend_from_slice(&sample.to_le_bytes());
+    }
+    data
+}
+
+const COMBO_PITCHES: usize = 12; // an octave of semitones above the eat blip; longer combos stay on the top one
+
+// Ducking: the music drops to DUCK_GAIN under a death or a fanfare, stays there for DUCK_HOLD
+// seconds while the sound plays, then ramps back to full over DUCK_RELEASE
+const DUCK_GAIN: f32 = 0.3;
+const DUCK_HOLD: f32 = 0.25;
+const DUCK_RELEASE: f32 = 1.0;
+
+// One channel's gain over time, a multiplier on its volume setting
+#[derive(Clone, Copy)]
+pub struct GainEnvelope {
+    low: f32, // gain the last duck dropped to
+    elapsed: f32, // seconds since it
+}
+
+impl GainEnvelope {
+    pub const FULL: GainEnvelope = GainEnvelope { low: 1.0, elapsed: f32::INFINITY };
+
+    pub fn gain(&self) -> f32 {
+        let ramp = ((self.elapsed - DUCK_HOLD) / DUCK_RELEASE).clamp(0.0, 1.0);
+        self.low + (1.0 - self.low) * ramp
+    }
+
+    // Drops to `gain` and starts over from there, unless the channel is already lower
+    pub fn duck(&mut self, gain: f32) {
+        if gain <= self.gain() { *self = GainEnvelope { low: gain, elapsed: 0.0 }; }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed += dt;
+    }
+}
+
+// The only code that touches the audio device. Without the `audio` feature (headless machines,
+// platforms where the backend won't start) sounds are empty stand-ins that play nothing, and
+// the WAVs behind them are never generated.
+#[cfg(feature = "audio")]
+mod backend {
+    use macroquad::audio::{self, PlaySoundParams};
+
+    // None for the silent stand-ins used when the sounds couldn't be loaded
+    #[derive(Clone)]
+    pub struct Sound(Option<audio::Sound>);
+
+    pub fn silent() -> Sound { Sound(None) }
+
+    pub async fn load(wav: impl FnOnce() -> Vec<u8>) -> Result<Sound, String> {
+        audio::load_sound_from_bytes(&wav()).await.map(|s| Sound(Some(s))).map_err(|e| format!("{:?}", e))
+    }
+
+    pub fn play(sound: &Sound, looped: bool, volume: f32) {
+        if let Some(s) = &sound.0 { audio::play_sound(s, PlaySoundParams { looped, volume }); }
+    }
+
+    pub fn set_volume(sound: &Sound, volume: f32) {
+        if let Some(s) = &sound.0 { audio::set_sound_volume(s, volume); }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod backend {
+    #[derive(Clone)]
+    pub struct Sound;
+
+    pub fn silent() -> Sound { Sound }
+
+    pub async fn load(_wav: impl FnOnce() -> Vec<u8>) -> Result<Sound, String> { Ok(Sound) }
+
+    pub fn play(_sound: &Sound, _looped: bool, _volume: f32) {}
+
+    pub fn set_volume(_sound: &Sound, _volume: f32) {}
+}
+
+use backend::Sound;
+
+// Generated sound effects shared by every run
+#[derive(Clone)]
+pub struct GameSounds {
+    pub eat: Sound,
+    pub die: Sound,
+    pub countdown: Sound,
+    pub go: Sound,
+    pub rumble: Sound,
+    pub glitch: Sound,
+    combo: Vec<Sound>, // the eat blip raised 1..=COMBO_PITCHES semitones
+    music: Sound, // loops from startup; its volume follows the settings every frame
+    music_gain: Cell<GainEnvelope>,
+}
+
+impl GameSounds {
+    // Any sound failing to load fails the lot, and the game goes on with `silent`
+    pub async fn load() -> Result<Self, GameError> {
+        // Sounds (simple generated beeps)
+        let a = &config().audio;
+        let eat = backend::load(|| generate_wav_sine(a.eat_hz, a.eat_ms / 1000.0, 0.6)).await.map_err(GameError::Audio)?;
+        let die = backend::load(|| generate_wav_sine(a.die_hz, a.die_ms / 1000.0, 0.7)).await.map_err(GameError::Audio)?;
+        let countdown = backend::load(|| generate_wav_sine(a.countdown_hz, a.countdown_ms / 1000.0, 0.6)).await.map_err(GameError::Audio)?;
+        let go = backend::load(|| generate_wav_sine(a.go_hz, a.go_ms / 1000.0, 0.6)).await.map_err(GameError::Audio)?;
+        let rumble = backend::load(|| generate_wav_sine(a.rumble_hz, a.rumble_ms / 1000.0, 0.9)).await.map_err(GameError::Audio)?;
+        let glitch = backend::load(|| generate_wav_glitch(a.glitch_hz, a.glitch_ms / 1000.0, 0.7)).await.map_err(GameError::Audio)?;
+        let mut combo = Vec::with_capacity(COMBO_PITCHES);
+        for step in 1..=COMBO_PITCHES {
+            let hz = a.eat_hz * 2f32.powf(step as f32 / 12.0);
+            combo.push(backend::load(|| generate_wav_sine(hz, a.eat_ms / 1000.0, 0.6)).await.map_err(GameError::Audio)?);
+        }
+        let music = backend::load(|| wav_from_samples(&music::load_song().render(SAMPLE_RATE))).await.map_err(GameError::Audio)?;
+        Ok(Self { eat, die, countdown, go, rumble, glitch, combo, music, music_gain: Cell::new(GainEnvelope::FULL) })
+    }
+
+    // Stand-ins for every sound, so the game plays on without audio
+    pub fn silent() -> Self {
+        let silent = backend::silent;
+        let combo = vec![silent(); COMBO_PITCHES];
+        Self { eat: silent(), die: silent(), countdown: silent(), go: silent(), rumble: silent(), glitch: silent(), combo, music: silent(), music_gain: Cell::new(GainEnvelope::FULL) }
+    }
+
+    // Audio consumer of the game event queue
+    pub fn on_event(&self, event: &GameEvent, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        if matches!(event, GameEvent::Died(_) | GameEvent::LevelUp { .. } | GameEvent::Won | GameEvent::WaveStarted { .. } | GameEvent::MissionEnded(Outcome::Completed { .. })) {
+            self.duck_music();
+        }
+        match event {
+            GameEvent::FoodEaten { combo, .. } | GameEvent::ChainEaten { in_order: true, combo, .. } => play_sfx(self.eat_pitch(*combo), 0.35 * volume),
+            GameEvent::ChainEaten { in_order: false, .. } => play_sfx(&self.die, 0.2 * volume),
+            GameEvent::LevelUp { .. } | GameEvent::Won | GameEvent::WaveStarted { .. } => play_sfx(&self.eat, 0.6 * volume),
+            GameEvent::MissionEnded(Outcome::Completed { .. }) | GameEvent::PremiumEaten { .. } => play_sfx(&self.eat, 0.6 * volume),
+            GameEvent::PowerUp { kind: PowerUp::Bomb, .. } => play_sfx(&self.rumble, 0.8 * volume),
+            GameEvent::PowerUp { .. } | GameEvent::PhaseEarned { .. } => play_sfx(&self.go, 0.5 * volume),
+            GameEvent::Phased { .. } | GameEvent::GlitchEaten { .. } => play_sfx(&self.glitch, 0.6 * volume),
+            GameEvent::Switched { .. } | GameEvent::Nightfall { .. } => play_sfx(&self.countdown, 0.5 * volume),
+            GameEvent::MissionEnded(Outcome::Failed) => play_sfx(&self.die, 0.2 * volume),
+            GameEvent::Shrank { .. } | GameEvent::ScoreDecayed { .. } => play_sfx(&self.die, 0.2 * volume),
+            GameEvent::Died(_) => play_sfx(&self.die, 0.6 * volume),
+        }
+    }
+
+    pub fn start_music(&self, volume: f32) {
+        backend::play(&self.music, true, volume.clamp(0.0, 1.0));
+    }
+
+    // Once a frame: moves the ducking envelope on and applies it to the music's `volume`
+    pub fn update_music(&self, dt: f32, volume: f32) {
+        let mut gain = self.music_gain.get();
+        gain.advance(dt);
+        self.music_gain.set(gain);
+        backend::set_volume(&self.music, volume.clamp(0.0, 1.0) * gain.gain());
+    }
+
+    // Makes room for a sound that should stand out
+    pub fn duck_music(&self) {
+        let mut gain = self.music_gain.get();
+        gain.duck(DUCK_GAIN);
+        self.music_gain.set(gain);
+    }
+
+    // The eat blip a semitone higher for each meal of a combo after the first
+    fn eat_pitch(&self, combo: u32) -> &Sound {
+        let step = (combo.saturating_sub(1) as usize).min(self.combo.len());
+        if step == 0 { &self.eat } else { &self.combo[step - 1] }
+    }
+}
+
+pub fn play_sfx(sound: &Sound, volume: f32) {
+    backend::play(sound, false, volume);
+}
@@ -0,0 +1,154 @@
+// Headless simulation benchmark: `cargo run --release --bin bench -- [--ticks N] [--grid WxH] [--bot greedy|random]`
+//
+// Runs snake_core's simulation, the same code the game ships, so the numbers measure exactly
+// what players get.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use snake_core::map::{Cell, Direction, GridConfig, Map};
+use snake_core::rng::Rng;
+use snake_core::sim::SnakeSim;
+
+// Counts heap allocations so per-tick allocation regressions show up next to the speed
+struct CountingAlloc;
+
+static ALLOCS: AtomicU64 = AtomicU64::new(0);
+static ALLOC_BYTES: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        ALLOC_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Bot {
+    Random,
+    Greedy,
+}
+
+struct Options {
+    ticks: u64,
+    seed: u64,
+    density: f32,
+    bot: Bot,
+    grid: GridConfig,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut opts = Options { ticks: 1_000_000, seed: 1, density: 0.10, bot: Bot::Greedy, grid: GridConfig::DEFAULT };
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--ticks" => opts.ticks = value()?.parse().map_err(|_| "--ticks expects a number".to_string())?,
+            "--seed" => opts.seed = value()?.parse().map_err(|_| "--seed expects a number".to_string())?,
+            "--density" => {
+                let pct: f32 = value()?.parse().map_err(|_| "--density expects a percentage".to_string())?;
+                opts.density = (pct / 100.0).clamp(0.0, 0.35);
+            }
+            "--grid" => {
+                let text = value()?;
+                let (w, h) = text.split_once('x').ok_or_else(|| "--grid expects WxH".to_string())?;
+                opts.grid = GridConfig {
+                    width: w.parse().map_err(|_| "--grid expects WxH".to_string())?,
+                    height: h.parse().map_err(|_| "--grid expects WxH".to_string())?,
+                };
+                if !opts.grid.is_valid() { return Err(format!("--grid {} is out of range", text)); }
+            }
+            "--bot" => {
+                opts.bot = match value()?.as_str() {
+                    "random" => Bot::Random,
+                    "greedy" => Bot::Greedy,
+                    other => return Err(format!("Unknown bot '{}' (random, greedy)", other)),
+                }
+            }
+            other => return Err(format!("Unknown option '{}'", other)),
+        }
+    }
+    Ok(opts)
+}
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+fn is_safe(sim: &SnakeSim, cell: Cell) -> bool {
+    sim.map.grid.contains(cell)
+        && !sim.map.is_wall(cell)
+        && !sim.occupied.contains(&cell)
+}
+
+// Moves toward the food when that is safe, otherwise any safe direction
+fn choose(sim: &SnakeSim, bot: Bot, rng: &mut Rng) -> Direction {
+    let head = sim.head();
+    let safe: Vec<Direction> = DIRECTIONS
+        .iter()
+        .copied()
+        .filter(|d| *d != sim.direction.opposite() && is_safe(sim, head.step(*d)))
+        .collect();
+    if safe.is_empty() { return sim.direction; }
+    if bot == Bot::Greedy && let Some(food) = sim.food {
+        let dist = |d: &Direction| {
+            let c = head.step(*d);
+            (c.x - food.x).abs() + (c.y - food.y).abs()
+        };
+        if let Some(best) = safe.iter().min_by_key(|d| dist(d)) { return *best; }
+    }
+    safe[rng.range_usize(0, safe.len())]
+}
+
+fn main() {
+    let opts = match parse_options() {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let mut bot_rng = Rng::with_stream(opts.seed, 99);
+    let mut seed = opts.seed;
+    let mut sim = SnakeSim::new(Map::generate(opts.grid, seed, opts.density));
+    let mut games = 0u64; // finished games
+    let mut total_score = 0u64;
+    let mut best = 0u32;
+
+    let allocs_before = ALLOCS.load(Ordering::Relaxed);
+    let bytes_before = ALLOC_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    for _ in 0..opts.ticks {
+        let dir = choose(&sim, opts.bot, &mut bot_rng);
+        sim.turn(dir);
+        sim.tick();
+        if !sim.alive {
+            total_score += sim.score as u64;
+            best = best.max(sim.score);
+            seed = seed.wrapping_add(1);
+            sim = SnakeSim::new(Map::generate(opts.grid, seed, opts.density));
+            games += 1;
+        }
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let allocs = ALLOCS.load(Ordering::Relaxed) - allocs_before;
+    let bytes = ALLOC_BYTES.load(Ordering::Relaxed) - bytes_before;
+    let ticks = opts.ticks.max(1) as f64;
+
+    println!("ticks:          {}", opts.ticks);
+    println!("elapsed:        {:.3} s", elapsed);
+    println!("ticks/second:   {:.0}", opts.ticks as f64 / elapsed.max(1e-9));
+    println!("games finished: {} (avg score {:.1}, best {})", games, total_score as f64 / games.max(1) as f64, best);
+    println!("allocations:    {} ({:.2} per tick)", allocs, allocs as f64 / ticks);
+    println!("allocated:      {} bytes ({:.1} per tick)", bytes, bytes as f64 / ticks);
+}
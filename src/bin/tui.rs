@@ -0,0 +1,414 @@
+// Terminal frontend: `cargo run --release --features tui --bin tui -- [--seed N] [--density PCT] [--speed MS] [--grid WxH] [--style NAME] [--ascii]`
+//
+// Plays the classic mode in a text terminal, two characters to a cell so the board keeps its
+// shape. The simulation and the map styles come from snake_core like the game's, runs start
+// from the lobby's last setup in the shared save, and a new best score is recorded there too.
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use serde_json::{json, Value};
+
+use snake_core::map::{Cell, Direction, GridConfig, DEFAULT_STYLE};
+use snake_core::powerups::PowerUp;
+use snake_core::save::{unix_now, write_atomic};
+use snake_core::sim::{self, SimEvent, SnakeSim};
+use snake_core::{logging, mapgen};
+
+const SAVE_PATH: &str = "snake_save.json";
+const QUEUED_TURNS: usize = 2; // turns typed ahead of the next tick, like the graphical game
+
+struct Options {
+    seed: Option<u64>,
+    density: Option<f32>,
+    interval: Option<f32>,
+    grid: GridConfig,
+    style: Option<String>,
+    ascii: bool,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut opts = Options { seed: None, density: None, interval: None, grid: GridConfig::DEFAULT, style: None, ascii: false };
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--seed" => opts.seed = Some(value()?.parse().map_err(|_| "--seed expects a number".to_string())?),
+            "--density" => {
+                let pct: f32 = value()?.parse().map_err(|_| "--density expects a percentage".to_string())?;
+                opts.density = Some((pct / 100.0).clamp(0.0, 0.35));
+            }
+            "--speed" => {
+                let ms: f32 = value()?.parse().map_err(|_| "--speed expects milliseconds per move".to_string())?;
+                opts.interval = Some((ms / 1000.0).clamp(0.05, 0.35));
+            }
+            "--grid" => {
+                let text = value()?;
+                let (w, h) = text.split_once('x').ok_or_else(|| "--grid expects WxH".to_string())?;
+                opts.grid = GridConfig {
+                    width: w.parse().map_err(|_| "--grid expects WxH".to_string())?,
+                    height: h.parse().map_err(|_| "--grid expects WxH".to_string())?,
+                };
+                if !opts.grid.is_valid() { return Err(format!("--grid {} is out of range", text)); }
+            }
+            "--style" => {
+                let style = value()?;
+                if !mapgen::is_registered(style) {
+                    return Err(format!("Unknown map style '{}' ({})", style, mapgen::names().join(", ")));
+                }
+                opts.style = Some(style.clone());
+            }
+            "--ascii" => opts.ascii = true,
+            other => return Err(format!("Unknown option '{}'", other)),
+        }
+    }
+    Ok(opts)
+}
+
+// The graphical game's save, kept as plain JSON so the fields this frontend doesn't know about
+// go back out exactly as they came in
+struct Profile {
+    data: Value,
+    writable: bool, // false when the file exists but doesn't parse; the game recovers those itself
+}
+
+impl Profile {
+    fn load() -> Self {
+        match fs::read_to_string(SAVE_PATH) {
+            Ok(text) => match serde_json::from_str::<Value>(&text) {
+                Ok(data) if data.is_object() => Self { data, writable: true },
+                _ => {
+                    logging::warn(format!("Save {} unreadable; the best score won't be recorded", SAVE_PATH));
+                    Self { data: json!({}), writable: false }
+                }
+            },
+            Err(_) => Self { data: json!({ "version": 2 }), writable: true },
+        }
+    }
+
+    fn history(&self, key: &str) -> Option<&Value> {
+        self.data.get("history")?.get(key)
+    }
+
+    fn settings(&self, key: &str) -> Option<&Value> {
+        self.data.get("settings")?.get(key)
+    }
+
+    // Saves from before the sections were split keep the best score at the top level
+    fn legacy(&self) -> bool {
+        self.data.get("best_score").is_some() && self.data.get("progress").is_none()
+    }
+
+    fn best(&self) -> u32 {
+        let best = if self.legacy() { self.data.get("best_score") } else { self.data.get("progress").and_then(|p| p.get("best_score")) };
+        best.and_then(Value::as_u64).unwrap_or(0) as u32
+    }
+
+    fn record(&mut self, score: u32) {
+        if score <= self.best() || !self.writable { return; }
+        let legacy = self.legacy();
+        let Some(obj) = self.data.as_object_mut() else { return; };
+        if legacy {
+            obj.insert("best_score".to_string(), json!(score));
+        } else {
+            let progress = obj.entry("progress").or_insert_with(|| json!({}));
+            if !progress.is_object() { *progress = json!({}); }
+            progress["best_score"] = json!(score);
+        }
+        obj.insert("saved_at".to_string(), json!(unix_now()));
+        // Same steps as the game's own write: back up the old file, then swap the new one in
+        if fs::metadata(SAVE_PATH).is_ok() && let Err(e) = fs::copy(SAVE_PATH, format!("{}.bak", SAVE_PATH)) {
+            logging::warn(format!("Could not back up save: {}", e));
+        }
+        let text = serde_json::to_string_pretty(&self.data).unwrap_or_default();
+        match write_atomic(SAVE_PATH, &text) {
+            Ok(()) => logging::info(format!("Save written (best {})", score)),
+            Err(e) => logging::error(format!("Writing save {} failed: {}", SAVE_PATH, e)),
+        }
+    }
+}
+
+// One run's setup: command line first, then the lobby's last choices, then the defaults
+struct Setup {
+    seed: u64,
+    density: f32,
+    interval: f32,
+    grid: GridConfig,
+    style: String,
+    premium: bool,  // the optional pickups chosen in Settings > Gameplay
+    powerups: bool,
+    phase: bool,
+    glitch: bool,
+}
+
+impl Setup {
+    fn new(opts: &Options, profile: &Profile) -> Self {
+        let f32_of = |key| profile.history(key).and_then(Value::as_f64).map(|v| v as f32);
+        let last_seed = profile.history("last_seed").and_then(Value::as_u64).filter(|s| *s != 0);
+        let time_seed = || SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(1);
+        let last_style = profile.history("last_map_style").and_then(Value::as_str).filter(|s| mapgen::is_registered(s));
+        let extra = |key| profile.settings("extras").and_then(|e| e.get(key)).and_then(Value::as_bool).unwrap_or(false);
+        Self {
+            seed: opts.seed.or(last_seed).unwrap_or_else(time_seed),
+            density: opts.density.or(f32_of("last_wall_density").filter(|d| (0.0..=0.35).contains(d))).unwrap_or(0.10),
+            interval: opts.interval.or(f32_of("last_move_interval").filter(|i| (0.05..=0.35).contains(i))).unwrap_or(0.12),
+            grid: opts.grid,
+            style: opts.style.clone().or(last_style.map(str::to_string)).unwrap_or_else(|| DEFAULT_STYLE.to_string()),
+            premium: extra("premium"),
+            powerups: extra("powerups"),
+            phase: extra("phase"),
+            glitch: extra("glitch"),
+        }
+    }
+
+    // Classic rules as the graphical game plays them, with the extras the settings switch on
+    fn start(&self) -> SnakeSim {
+        let mut sim = SnakeSim::new(mapgen::generate(&self.style, self.grid, self.seed, self.density));
+        if self.premium { sim.enable_premium(); }
+        if self.powerups { sim.enable_powerups(&PowerUp::ALL); }
+        if self.phase { sim.enable_phase(); }
+        if self.glitch { sim.enable_glitch(); }
+        sim
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tile {
+    Empty,
+    Wall,
+    Head,
+    Body,
+    Food,
+    Premium,
+    PowerUp(PowerUp),
+    Conveyor(Direction),
+    Plate,
+    Stairs,
+    Chain(usize),
+    Glitch,
+}
+
+impl Tile {
+    fn glyph(self, ascii: bool) -> &'static str {
+        match (self, ascii) {
+            (Tile::Empty, _) => "  ",
+            (Tile::Wall, false) => "██",
+            (Tile::Wall, true) => "##",
+            (Tile::Head, false) => "▓▓",
+            (Tile::Head, true) => "@@",
+            (Tile::Body, false) => "░░",
+            (Tile::Body, true) => "[]",
+            (Tile::Food, false) => "◖◗",
+            (Tile::Food, true) => "()",
+            (Tile::Premium, false) => "◆◆",
+            (Tile::Premium, true) => "<>",
+            (Tile::PowerUp(PowerUp::Reverse), _) => "&&",
+            (Tile::PowerUp(PowerUp::Bomb), _) => "%%",
+            (Tile::Conveyor(Direction::Up), _) => "^^",
+            (Tile::Conveyor(Direction::Down), _) => "vv",
+            (Tile::Conveyor(Direction::Left), _) => "<<",
+            (Tile::Conveyor(Direction::Right), _) => ">>",
+            (Tile::Plate, _) => "oo",
+            (Tile::Stairs, _) => "HH",
+            (Tile::Chain(_), _) => "**",
+            (Tile::Glitch, false) => "◖◗",
+            (Tile::Glitch, true) => "()",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Tile::Empty => Color::Reset,
+            Tile::Wall => Color::DarkGrey,
+            Tile::Head => Color::Green,
+            Tile::Body => Color::DarkGreen,
+            Tile::Food => Color::Red,
+            Tile::Premium => Color::Yellow,
+            Tile::PowerUp(_) => Color::Magenta,
+            Tile::Conveyor(_) => Color::DarkGrey,
+            Tile::Plate => Color::Cyan,
+            Tile::Stairs => Color::White,
+            Tile::Glitch => Color::DarkRed,
+            Tile::Chain(kind) => [Color::Green, Color::White, Color::Yellow][kind],
+        }
+    }
+}
+
+fn tile_at(sim: &SnakeSim, cell: Cell) -> Tile {
+    // The snake first, as it can be phasing through a wall
+    if sim.head() == cell { return Tile::Head; }
+    if sim.occupied.contains(&cell) { return Tile::Body; }
+    if sim.map.is_wall(cell) { return Tile::Wall; }
+    if sim.food == Some(cell) { return Tile::Food; }
+    if sim.premium.as_ref().and_then(|p| p.at) == Some(cell) { return Tile::Premium; }
+    if let Some(kind) = sim.chain.as_ref().and_then(|c| c.kind_at(cell)) { return Tile::Chain(kind); }
+    if sim.glitch.as_ref().and_then(|g| g.at) == Some(cell) { return Tile::Glitch; }
+    if sim.map.plate_at(cell).is_some() { return Tile::Plate; }
+    if sim.map.stair_exit(cell).is_some() { return Tile::Stairs; }
+    match sim.powerups.as_ref().and_then(|p| p.at) {
+        Some((kind, at)) if at == cell => Tile::PowerUp(kind),
+        _ => sim.map.conveyor_at(cell).map_or(Tile::Empty, Tile::Conveyor),
+    }
+}
+
+struct Tui {
+    out: Stdout,
+    ascii: bool,
+}
+
+impl Tui {
+    fn open(ascii: bool) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut out = io::stdout();
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide, terminal::Clear(terminal::ClearType::All))?;
+        Ok(Self { out, ascii })
+    }
+
+    fn draw(&mut self, sim: &SnakeSim, status: &str, hint: &str) -> io::Result<()> {
+        let grid = sim.map.grid;
+        let (cols, rows) = terminal::size()?;
+        if (cols as i32) < grid.width * 2 || (rows as i32) < grid.height + 2 {
+            queue!(self.out, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))?;
+            queue!(self.out, Print(format!("Terminal too small: {}x{} needed, or pick a smaller --grid", grid.width * 2, grid.height + 2)))?;
+            return self.out.flush();
+        }
+        for y in 0..grid.height {
+            queue!(self.out, cursor::MoveTo(0, y as u16))?;
+            let mut color = None;
+            for x in 0..grid.width {
+                let tile = tile_at(sim, Cell { x, y });
+                if color != Some(tile.color()) {
+                    color = Some(tile.color());
+                    queue!(self.out, SetForegroundColor(tile.color()))?;
+                }
+                queue!(self.out, Print(tile.glyph(self.ascii)))?;
+            }
+        }
+        queue!(self.out, ResetColor)?;
+        for (row, line) in [status, hint].into_iter().enumerate() {
+            queue!(self.out, cursor::MoveTo(0, (grid.height + row as i32) as u16), terminal::Clear(terminal::ClearType::CurrentLine), Print(line))?;
+        }
+        self.out.flush()
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        let _ = execute!(self.out, ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+enum Key {
+    Turn(Direction),
+    Pause,
+    Restart,
+    NewMap,
+    Quit,
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> Option<Key> {
+    Some(match code {
+        KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('k') => Key::Turn(Direction::Up),
+        KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('j') => Key::Turn(Direction::Down),
+        KeyCode::Left | KeyCode::Char('a') | KeyCode::Char('h') => Key::Turn(Direction::Left),
+        KeyCode::Right | KeyCode::Char('d') | KeyCode::Char('l') => Key::Turn(Direction::Right),
+        KeyCode::Char('p') | KeyCode::Char(' ') => Key::Pause,
+        KeyCode::Char('r') => Key::Restart,
+        KeyCode::Char('n') => Key::NewMap,
+        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => Key::Quit,
+        KeyCode::Char('q') | KeyCode::Esc => Key::Quit,
+        _ => return None,
+    })
+}
+
+fn run(tui: &mut Tui, mut setup: Setup, profile: &mut Profile) -> io::Result<()> {
+    let mut sim = setup.start();
+    let mut turns: VecDeque<Direction> = VecDeque::new();
+    let mut paused = false;
+    let mut next_tick = Instant::now();
+    let mut redraw = true;
+    loop {
+        if redraw {
+            let best = profile.best().max(sim.score);
+            let phase = sim.phase.map_or(String::new(), |p| format!("  Phase: {}", p.charges));
+            let inverted = if sim.inverted() { "  INVERTED" } else { "" };
+            let status = format!("Score: {}  Best: {}{}{}  Seed: {}  {}", sim.score, best, phase, inverted, setup.seed, setup.style);
+            let hint = match sim.death {
+                _ if sim.won => "Board cleared! R: Again  N: New map  Q: Quit".to_string(),
+                Some(cause) => format!("{}  R: Again  N: New map  Q: Quit", cause.label()),
+                None if paused => "Paused - P: Resume  Q: Quit".to_string(),
+                None => "Arrows/WASD: Steer  P: Pause  R: Restart  Q: Quit".to_string(),
+            };
+            tui.draw(&sim, &status, &hint)?;
+            redraw = false;
+        }
+
+        let timeout = next_tick.saturating_duration_since(Instant::now());
+        if event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(k) if k.kind != KeyEventKind::Release => match key(k.code, k.modifiers) {
+                    Some(Key::Turn(dir)) if turns.len() < QUEUED_TURNS => turns.push_back(dir),
+                    Some(Key::Pause) if sim.alive => paused = !paused,
+                    Some(again @ (Key::Restart | Key::NewMap)) => {
+                        if matches!(again, Key::NewMap) { setup.seed = setup.seed.wrapping_add(1); }
+                        sim = setup.start();
+                        turns.clear();
+                        paused = false;
+                    }
+                    Some(Key::Quit) => return Ok(()),
+                    _ => {}
+                },
+                Event::Resize(..) => execute!(tui.out, terminal::Clear(terminal::ClearType::All))?,
+                _ => {}
+            }
+            redraw = true;
+            continue;
+        }
+
+        next_tick += Duration::from_secs_f32(setup.interval);
+        // Catch up after a stall rather than racing through the missed ticks
+        if next_tick < Instant::now() { next_tick = Instant::now(); }
+        if paused || !sim.alive { continue; }
+        let turn = turns.pop_front().map(|d| if sim.inverted() { d.opposite() } else { d });
+        let (next, events) = sim::advance(sim, turn);
+        sim = next;
+        redraw = true;
+        if events.iter().any(|e| matches!(e, SimEvent::Died(_) | SimEvent::Won)) {
+            profile.record(sim.score);
+        }
+    }
+}
+
+fn main() {
+    let opts = match parse_options() {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let mut profile = Profile::load();
+    let setup = Setup::new(&opts, &profile);
+    logging::info(format!("Terminal run: seed {}, {} map, {}x{}", setup.seed, setup.style, setup.grid.width, setup.grid.height));
+
+    // Put the terminal back before the panic message prints, or it lands in the alternate screen
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(io::stdout(), ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+        default_hook(info);
+    }));
+
+    let result = Tui::open(opts.ascii).and_then(|mut tui| run(&mut tui, setup, &mut profile));
+    if let Err(e) = result {
+        logging::error(format!("Terminal error: {}", e));
+        eprintln!("Terminal error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,169 @@
+// External bot protocol (--bot-server): before every tick the game sends its state as one
+// JSON line and waits for one line back: "up", "down", "left", "right" (or u/d/l/r) to turn,
+// "none" to keep going. After a state with "alive": false, "restart" starts another run.
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+use crate::game::SnakeGame;
+use crate::logging;
+use crate::map::{Cell, Direction, GridConfig};
+
+// Where the bot talks to the game
+#[derive(Copy, Clone)]
+pub enum BotTarget {
+    Stdio,
+    Tcp(u16), // on localhost
+}
+
+enum BotEvent {
+    Connected,
+    Line(String),
+    Closed(String),
+}
+
+pub enum BotCommand {
+    Turn(Option<Direction>),
+    Restart,
+}
+
+// One tick as the bot sees it; the snake is listed head first
+#[derive(Serialize)]
+struct BotState {
+    tick: u32,
+    alive: bool,
+    won: bool,
+    score: u32,
+    grid: GridConfig,
+    direction: Direction,
+    snake: Vec<Cell>,
+    food: Option<Cell>,
+    walls: Vec<Cell>,
+    conveyors: Vec<(Cell, Direction)>,
+    stairs: Vec<(Cell, Cell)>,
+    chain: Vec<Cell>, // food chain: the foods left, in the order to eat them
+    glitch: Option<Cell>,
+    hunger: Option<u32>, // moves left before starving, in Hunger mode
+}
+
+fn parse_command(line: &str) -> Result<BotCommand, String> {
+    match line.trim().to_ascii_lowercase().as_str() {
+        "up" | "u" => Ok(BotCommand::Turn(Some(Direction::Up))),
+        "down" | "d" => Ok(BotCommand::Turn(Some(Direction::Down))),
+        "left" | "l" => Ok(BotCommand::Turn(Some(Direction::Left))),
+        "right" | "r" => Ok(BotCommand::Turn(Some(Direction::Right))),
+        "none" | "" => Ok(BotCommand::Turn(None)),
+        "restart" => Ok(BotCommand::Restart),
+        other => Err(format!("Unknown bot command '{}'", other)),
+    }
+}
+
+// Writes queued states on their own thread and forwards every line the bot sends
+fn pump(reader: impl BufRead, mut writer: impl Write + Send + 'static, events: &Sender<BotEvent>, states: Receiver<String>) -> Result<(), String> {
+    std::thread::spawn(move || {
+        for state in states {
+            if writeln!(writer, "{}", state).and_then(|_| writer.flush()).is_err() { break; }
+        }
+    });
+    for line in reader.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if events.send(BotEvent::Line(line)).is_err() { return Ok(()); }
+    }
+    Err("The bot closed the connection".to_string())
+}
+
+fn serve(target: BotTarget, events: Sender<BotEvent>, states: Receiver<String>) -> Result<(), String> {
+    match target {
+        BotTarget::Stdio => {
+            let _ = events.send(BotEvent::Connected);
+            pump(std::io::stdin().lock(), std::io::stdout(), &events, states)
+        }
+        BotTarget::Tcp(port) => {
+            let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|e| format!("Can't listen on port {}: {}", port, e))?;
+            let (stream, addr) = listener.accept().map_err(|e| e.to_string())?;
+            logging::info(format!("Bot connected from {}", addr));
+            let _ = stream.set_nodelay(true);
+            let _ = events.send(BotEvent::Connected);
+            let reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+            pump(reader, stream, &events, states)
+        }
+    }
+}
+
+// The game's end of the connection; the I/O runs on background threads
+pub struct BotLink {
+    pub status: Option<String>, // shown over the board while there's no working bot
+    events: Receiver<BotEvent>,
+    states: Sender<String>,
+    waiting: bool, // a state was sent and its answer hasn't arrived yet
+}
+
+impl BotLink {
+    pub fn start(target: BotTarget) -> Self {
+        let (event_tx, events) = mpsc::channel();
+        let (states, state_rx) = mpsc::channel();
+        let status = match target {
+            BotTarget::Stdio => "Waiting for the bot on stdin".to_string(),
+            BotTarget::Tcp(port) => format!("Waiting for a bot on 127.0.0.1:{}", port),
+        };
+        logging::info(&status);
+        std::thread::spawn(move || {
+            if let Err(e) = serve(target, event_tx.clone(), state_rx) { let _ = event_tx.send(BotEvent::Closed(e)); }
+        });
+        Self { status: Some(status), events, states, waiting: false }
+    }
+
+    pub fn is_waiting(&self) -> bool { self.waiting }
+
+    // Queues the current state; it goes out as soon as a bot is connected
+    pub fn send_state(&mut self, game: &SnakeGame) {
+        let sim = &game.sim;
+        // Closed gates, solid switch walls and shed tail segments go out as walls for this tick
+        let map = &sim.map;
+        let closed = map.gates.iter().filter(|g| !g.open).flat_map(|g| g.cells.iter())
+            .chain(map.switches.iter().filter(|s| s.solid).flat_map(|s| s.walls.iter()))
+            .chain(map.shed.iter().map(|(c, _)| c));
+        let mut walls: Vec<Cell> = sim.map.walls.iter().chain(closed).copied().collect();
+        walls.sort_by_key(|c| (c.y, c.x));
+        let state = BotState {
+            tick: game.replay.ticks,
+            alive: sim.alive,
+            won: sim.won,
+            score: sim.score,
+            grid: sim.map.grid,
+            direction: sim.direction,
+            snake: sim.snake.iter().copied().collect(),
+            food: sim.food,
+            walls,
+            conveyors: sim.map.conveyors.clone(),
+            stairs: sim.map.stairs.clone(),
+            chain: sim.chain.iter().flat_map(|c| c.foods).flatten().collect(),
+            glitch: sim.glitch.as_ref().and_then(|g| g.at),
+            hunger: sim.hunger.map(|h| h.moves_left),
+        };
+        if let Ok(line) = serde_json::to_string(&state) && self.states.send(line).is_ok() { self.waiting = true; }
+    }
+
+    // The bot's next command, if it has sent one
+    pub fn poll(&mut self) -> Option<BotCommand> {
+        loop {
+            match self.events.try_recv() {
+                Ok(BotEvent::Connected) => self.status = None,
+                Ok(BotEvent::Line(line)) => {
+                    self.waiting = false;
+                    // A typo shouldn't stall the lockstep: it counts as "none"
+                    return Some(parse_command(&line).unwrap_or_else(|e| {
+                        logging::warn(e);
+                        BotCommand::Turn(None)
+                    }));
+                }
+                Ok(BotEvent::Closed(e)) => {
+                    logging::warn(format!("Bot: {}", e));
+                    self.status = Some(e);
+                }
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,260 @@
+// Spectating over WebSocket (--broadcast PORT): the run on screen goes out as one small JSON
+// text message per change, so a web page, a stream overlay or another copy of the game
+// (--spectate HOST:PORT) can follow it live.
+//
+//   {"type":"board","seed":7,"width":32,"height":24,"walls":[[0,0],[1,0],...]}
+//   {"type":"tick","tick":41,"score":3,"alive":true,"won":false,"snake":[[12,9],[11,9]],
+//    "food":[20,4],"premium":null,"powerup":null,"patrollers":[]}
+//
+// Cells are [x, y] with the snake head first. A board message comes first, and again whenever
+// the map changes (a new run, waves adding walls, a bomb); tick messages follow the run.
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+
+use crate::game::SnakeGame;
+use crate::logging;
+use crate::map::Cell;
+use crate::powerups::PowerUp;
+
+pub type Xy = (i32, i32);
+
+fn xy(c: Cell) -> Xy { (c.x, c.y) }
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Update {
+    Board { seed: u64, width: i32, height: i32, walls: Vec<Xy> },
+    Tick(TickState),
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct TickState {
+    pub tick: u32,
+    pub score: u32,
+    pub alive: bool,
+    pub won: bool,
+    pub snake: Vec<Xy>,
+    pub food: Option<Xy>,
+    pub premium: Option<Xy>,
+    pub powerup: Option<(PowerUp, Xy)>,
+    pub patrollers: Vec<Xy>,
+}
+
+impl TickState {
+    fn of(game: &SnakeGame) -> Self {
+        let sim = &game.sim;
+        Self {
+            tick: game.replay.ticks,
+            score: sim.score,
+            alive: sim.alive,
+            won: sim.won,
+            snake: sim.snake.iter().copied().map(xy).collect(),
+            food: sim.food.map(xy),
+            premium: sim.premium.as_ref().and_then(|p| p.at).map(xy),
+            powerup: sim.powerups.as_ref().and_then(|p| p.at).map(|(kind, at)| (kind, xy(at))),
+            patrollers: sim.waves.iter().flat_map(|w| &w.patrollers).map(|p| xy(p.at)).collect(),
+        }
+    }
+}
+
+// What the game hands the server thread; the latest of each is kept for spectators who join later
+#[cfg_attr(not(all(feature = "broadcast", not(target_arch = "wasm32"))), allow(dead_code))]
+enum Outgoing {
+    Board(String),
+    Tick(String),
+}
+
+// What the spectating thread reports back; the stub build only ever fails
+#[cfg_attr(not(all(feature = "broadcast", not(target_arch = "wasm32"))), allow(dead_code))]
+pub enum SpectatorEvent {
+    Connected,
+    Update(Update),
+    Failed(String),
+}
+
+#[cfg(all(feature = "broadcast", not(target_arch = "wasm32")))]
+mod ws {
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tungstenite::{Message, WebSocket};
+
+    use super::{Outgoing, SpectatorEvent};
+    use crate::logging;
+
+    // How often the server looks for new spectators, and the spectator for the stop flag
+    const POLL: Duration = Duration::from_millis(50);
+    // A spectator that can't take a message this fast is dropped rather than holding up the rest
+    const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+    fn greet(stream: TcpStream, latest: &[&Option<String>]) -> Result<WebSocket<TcpStream>, String> {
+        stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+        stream.set_read_timeout(Some(WRITE_TIMEOUT)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT)).map_err(|e| e.to_string())?;
+        let mut socket = tungstenite::accept(stream).map_err(|e| e.to_string())?;
+        for text in latest.iter().copied().flatten() {
+            socket.send(Message::text(text.clone())).map_err(|e| e.to_string())?;
+        }
+        Ok(socket)
+    }
+
+    pub fn serve(port: u16, rx: Receiver<Outgoing>) -> Result<(), String> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| format!("Can't listen on port {}: {}", port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        std::thread::spawn(move || {
+            let mut spectators: Vec<WebSocket<TcpStream>> = Vec::new();
+            let (mut board, mut tick) = (None, None);
+            loop {
+                while let Ok((stream, addr)) = listener.accept() {
+                    match greet(stream, &[&board, &tick]) {
+                        Ok(socket) => {
+                            logging::info(format!("Broadcast: {} is watching", addr));
+                            spectators.push(socket);
+                        }
+                        Err(e) => logging::warn(format!("Broadcast: {} couldn't connect: {}", addr, e)),
+                    }
+                }
+                let text = match rx.recv_timeout(POLL) {
+                    Ok(Outgoing::Board(text)) => board.insert(text).clone(),
+                    Ok(Outgoing::Tick(text)) => tick.insert(text).clone(),
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                spectators.retain_mut(|s| s.send(Message::text(text.clone())).is_ok());
+            }
+            for mut s in spectators { let _ = s.close(None); }
+        });
+        Ok(())
+    }
+
+    fn run(address: &str, tx: &Sender<SpectatorEvent>, stop: &AtomicBool) -> Result<(), String> {
+        let stream = TcpStream::connect(address).map_err(|e| format!("Can't connect to {}: {}", address, e))?;
+        let (mut socket, _) = tungstenite::client(format!("ws://{}/", address), stream).map_err(|e| e.to_string())?;
+        socket.get_mut().set_read_timeout(Some(POLL)).map_err(|e| e.to_string())?;
+        let _ = tx.send(SpectatorEvent::Connected);
+        while !stop.load(Ordering::Relaxed) {
+            match socket.read() {
+                Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                    Ok(update) => { let _ = tx.send(SpectatorEvent::Update(update)); }
+                    Err(e) => logging::warn(format!("Spectate: unreadable message: {}", e)),
+                },
+                Ok(Message::Close(_)) => return Err("The broadcast ended".to_string()),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e)) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+                Err(tungstenite::Error::ConnectionClosed) => return Err("The broadcast ended".to_string()),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        let _ = socket.close(None);
+        Ok(())
+    }
+
+    pub fn watch(address: String, tx: Sender<SpectatorEvent>, stop: Arc<AtomicBool>) {
+        std::thread::spawn(move || {
+            if let Err(e) = run(&address, &tx, &stop) { let _ = tx.send(SpectatorEvent::Failed(e)); }
+        });
+    }
+}
+
+// Without the `broadcast` feature (and on the web build, which has no raw sockets) both ends
+// report why nothing will happen
+#[cfg(not(all(feature = "broadcast", not(target_arch = "wasm32"))))]
+mod ws {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc::{Receiver, Sender};
+    use std::sync::Arc;
+
+    use super::{Outgoing, SpectatorEvent};
+
+    const UNAVAILABLE: &str = "Spectating is not available in this build";
+
+    pub fn serve(_port: u16, _rx: Receiver<Outgoing>) -> Result<(), String> { Err(UNAVAILABLE.to_string()) }
+
+    pub fn watch(_address: String, tx: Sender<SpectatorEvent>, _stop: Arc<AtomicBool>) {
+        let _ = tx.send(SpectatorEvent::Failed(UNAVAILABLE.to_string()));
+    }
+}
+
+// The sending end, fed the spectated run once a frame; only changes go out
+pub struct Broadcaster {
+    tx: Sender<Outgoing>,
+    board: Option<(u64, i32, i32, usize)>, // seed, size and wall count of the last board sent
+    tick: Option<TickState>,
+}
+
+impl Broadcaster {
+    pub fn start(port: u16) -> Result<Self, String> {
+        let (tx, rx) = mpsc::channel();
+        ws::serve(port, rx)?;
+        logging::info(format!("Broadcasting on ws://0.0.0.0:{}", port));
+        Ok(Self { tx, board: None, tick: None })
+    }
+
+    pub fn publish(&mut self, game: Option<&SnakeGame>) {
+        let Some(game) = game else { return; };
+        let map = &game.sim.map;
+        let key = (map.seed, map.grid.width, map.grid.height, map.walls.len());
+        if self.board != Some(key) {
+            self.board = Some(key);
+            let mut walls: Vec<Xy> = map.walls.iter().copied().map(xy).collect();
+            walls.sort_unstable();
+            let board = Update::Board { seed: map.seed, width: map.grid.width, height: map.grid.height, walls };
+            let _ = self.tx.send(Outgoing::Board(serde_json::to_string(&board).unwrap_or_default()));
+        }
+        let tick = TickState::of(game);
+        if self.tick.as_ref() == Some(&tick) { return; }
+        let _ = self.tx.send(Outgoing::Tick(serde_json::to_string(&Update::Tick(tick.clone())).unwrap_or_default()));
+        self.tick = Some(tick);
+    }
+}
+
+// The receiving end: a connection to another game's broadcast
+pub struct Spectator {
+    pub address: String,
+    pub status: String,
+    pub connected: bool,
+    rx: Receiver<SpectatorEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Spectator {
+    pub fn connect(address: &str) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        logging::info(format!("Spectate: connecting to {}", address));
+        ws::watch(address.to_string(), tx, stop.clone());
+        Self { address: address.to_string(), status: format!("Connecting to {}...", address), connected: false, rx, stop }
+    }
+
+    // Everything received since the last frame, oldest first
+    pub fn poll(&mut self) -> Vec<Update> {
+        let mut updates = Vec::new();
+        loop {
+            match self.rx.try_recv() {
+                Ok(SpectatorEvent::Connected) => {
+                    self.connected = true;
+                    self.status = format!("Watching {}", self.address);
+                }
+                Ok(SpectatorEvent::Update(update)) => updates.push(update),
+                Ok(SpectatorEvent::Failed(e)) => {
+                    logging::warn(format!("Spectate: {}", e));
+                    self.connected = false;
+                    self.status = e;
+                }
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => break,
+            }
+        }
+        updates
+    }
+}
+
+impl Drop for Spectator {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
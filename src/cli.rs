@@ -0,0 +1,130 @@
+use std::sync::OnceLock;
+
+use crate::bot::BotTarget;
+use crate::game::GameMode;
+use crate::map::GridConfig;
+
+pub const USAGE: &str = "\
+Usage: snake_macroquad [options]
+
+Options:
+  --seed N          Map seed
+  --density PCT     Wall density in percent (0-35)
+  --speed MS        Move interval in milliseconds (50-350)
+  --windowed        Start in a window instead of fullscreen
+  --grid WxH        Grid size in tiles, e.g. 40x30 (12x10 to 200x150)
+  --mode NAME       Game mode (Classic, Twitch, Hunger)
+  --skip-lobby      Start playing immediately
+  --bot-server TO   Let a bot program play; TO is stdio or a localhost TCP port
+  --broadcast PORT  Stream the run on screen to WebSocket spectators on PORT
+  --spectate ADDR   Watch another game's broadcast, ADDR being HOST:PORT
+  --reset-config    Rewrite snake.toml with the default tunables
+  -h, --help        Show this help";
+
+// Launch configuration from the command line; anything left as None falls back to the save
+#[derive(Default)]
+pub struct CliArgs {
+    pub seed: Option<u64>,
+    pub density: Option<f32>,
+    pub move_interval: Option<f32>,
+    pub windowed: bool,
+    pub grid: Option<GridConfig>,
+    pub mode: Option<GameMode>,
+    pub skip_lobby: bool,
+    pub bot_server: Option<BotTarget>,
+    pub broadcast: Option<u16>,
+    pub spectate: Option<String>,
+    pub reset_config: bool,
+}
+
+fn value<'a>(flag: &str, it: &mut impl Iterator<Item = &'a String>) -> Result<&'a String, String> {
+    it.next().ok_or_else(|| format!("{} needs a value", flag))
+}
+
+fn number<T: std::str::FromStr>(flag: &str, text: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("{}: '{}' is not a valid number", flag, text))
+}
+
+pub fn parse(args: &[String]) -> Result<CliArgs, String> {
+    let mut out = CliArgs::default();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--seed" => out.seed = Some(number(arg, value(arg, &mut it)?)?),
+            "--density" => {
+                let pct: f32 = number(arg, value(arg, &mut it)?)?;
+                if !(0.0..=35.0).contains(&pct) { return Err(format!("--density must be 0-35, got {}", pct)); }
+                out.density = Some(pct / 100.0);
+            }
+            "--speed" => {
+                let ms: f32 = number(arg, value(arg, &mut it)?)?;
+                if !(50.0..=350.0).contains(&ms) { return Err(format!("--speed must be 50-350 ms, got {}", ms)); }
+                out.move_interval = Some(ms / 1000.0);
+            }
+            "--windowed" => out.windowed = true,
+            "--grid" => {
+                let text = value(arg, &mut it)?;
+                let (w, h) = text.split_once(['x', 'X']).ok_or_else(|| format!("--grid expects WxH, got '{}'", text))?;
+                let grid = GridConfig { width: number(arg, w)?, height: number(arg, h)? };
+                if !grid.is_valid() {
+                    return Err(format!(
+                        "--grid must be between {}x{} and {}x{}, got {}",
+                        GridConfig::MIN.width, GridConfig::MIN.height, GridConfig::MAX.width, GridConfig::MAX.height, text
+                    ));
+                }
+                out.grid = Some(grid);
+            }
+            "--mode" => {
+                let name = value(arg, &mut it)?;
+                let mode = GameMode::ALL.iter().find(|m| m.label().eq_ignore_ascii_case(name));
+                out.mode = Some(*mode.ok_or_else(|| format!("Unknown mode '{}'", name))?);
+            }
+            "--skip-lobby" => out.skip_lobby = true,
+            "--bot-server" => {
+                let text = value(arg, &mut it)?;
+                out.bot_server = Some(if text.eq_ignore_ascii_case("stdio") {
+                    BotTarget::Stdio
+                } else {
+                    let port: u16 = number(arg, text)?;
+                    if port == 0 { return Err("--bot-server port must be 1-65535".to_string()); }
+                    BotTarget::Tcp(port)
+                });
+            }
+            "--broadcast" => {
+                let port: u16 = number(arg, value(arg, &mut it)?)?;
+                if port == 0 { return Err("--broadcast port must be 1-65535".to_string()); }
+                out.broadcast = Some(port);
+            }
+            "--spectate" => {
+                let address = value(arg, &mut it)?;
+                let port = address.rsplit_once(':').and_then(|(host, port)| (!host.is_empty()).then_some(port));
+                if port.and_then(|p| p.parse::<u16>().ok()).is_none_or(|p| p == 0) {
+                    return Err(format!("--spectate expects HOST:PORT, got '{}'", address));
+                }
+                out.spectate = Some(address.clone());
+            }
+            "--reset-config" => out.reset_config = true,
+            other => return Err(format!("Unknown option '{}'", other)),
+        }
+    }
+    Ok(out)
+}
+
+// Parsed once, on first use (window_conf runs before main); bad arguments print usage and exit
+pub fn args() -> &'static CliArgs {
+    static ARGS: OnceLock<CliArgs> = OnceLock::new();
+    ARGS.get_or_init(|| {
+        let raw: Vec<String> = std::env::args().skip(1).collect();
+        if raw.iter().any(|a| a == "-h" || a == "--help") {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+        match parse(&raw) {
+            Ok(args) => args,
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        }
+    })
+}
@@ -0,0 +1,77 @@
+// Cloud copy of the save at an address set under Settings > Data. GET there returns the
+// last save PUT there (404 while there's none yet); a token, if set, goes along as a bearer
+// token, so a gist-style API or a small personal endpoint both work. The game syncs in the
+// background on startup or on request, and once more, blocking, on the way out.
+use crate::logging;
+use crate::net::{self, Pending};
+use crate::save::{load_save, sync_save_text, SaveData, Settings};
+
+pub fn enabled(settings: &Settings) -> bool {
+    !settings.sync_url.is_empty()
+}
+
+#[derive(Default)]
+pub struct CloudSync {
+    pull: Option<Pending<Option<String>>>,
+    push: Option<Pending<()>>,
+}
+
+impl CloudSync {
+    pub fn start(&mut self, settings: &Settings) {
+        if !enabled(settings) || self.busy() { return; }
+        let (url, token) = (settings.sync_url.clone(), settings.sync_token.clone());
+        self.pull = Some(Pending::spawn(move || net::fetch(&url, &token)));
+    }
+
+    pub fn busy(&self) -> bool {
+        self.pull.is_some() || self.push.is_some()
+    }
+
+    // Once a frame. When the cloud copy arrives it's merged into the local save on this thread,
+    // so it can't race the game's own writes, and the result goes back up. Returns the
+    // merged save once that's written.
+    pub fn poll(&mut self) -> Option<Result<SaveData, String>> {
+        if let Some(push) = &self.push && let Some(result) = push.poll() {
+            match result {
+                Ok(()) => logging::info("Save uploaded to the cloud"),
+                Err(e) => logging::warn(format!("Cloud upload failed: {}", e)),
+            }
+            self.push = None;
+        }
+        let fetched = self.pull.as_ref()?.poll()?;
+        self.pull = None;
+        let merged = merge(fetched);
+        if let Ok(save) = &merged {
+            let (url, token, text) = upload(save);
+            self.push = Some(Pending::spawn(move || net::put_json(&url, &text, &token)));
+        }
+        Some(merged)
+    }
+}
+
+// Merges what the fetch brought back; with nothing in the cloud yet the local save goes up as is
+fn merge(fetched: Result<Option<String>, String>) -> Result<SaveData, String> {
+    match fetched {
+        Ok(Some(text)) => sync_save_text(&text),
+        Ok(None) => Ok(load_save()),
+        Err(e) => Err(format!("Cloud sync failed: {}", e)),
+    }
+}
+
+fn upload(save: &SaveData) -> (String, String, String) {
+    let text = serde_json::to_string_pretty(save).unwrap_or_default();
+    (save.settings.sync_url.clone(), save.settings.sync_token.clone(), text)
+}
+
+// The sync on quit, after the last local write
+pub fn sync_blocking(settings: &Settings) {
+    if !enabled(settings) { return; }
+    let result = merge(net::fetch(&settings.sync_url, &settings.sync_token)).and_then(|save| {
+        let (url, token, text) = upload(&save);
+        net::put_json(&url, &text, &token).map_err(|e| format!("Cloud upload failed: {}", e))
+    });
+    match result {
+        Ok(()) => logging::info("Save synced to the cloud"),
+        Err(e) => logging::warn(e),
+    }
+}
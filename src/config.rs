@@ -0,0 +1,353 @@
+use macroquad::prelude::{Color, BLACK};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::game::{DEFAULT_MOVE_INTERVAL, DEFAULT_WALL_DENSITY};
+use crate::logging;
+use crate::render::{Backdrop, Palette};
+use crate::save::write_atomic;
+
+pub const CONFIG_PATH: &str = "snake.toml";
+
+const HEADER: &str = "\
+# Snake tunables. Delete a line (or the whole file) to fall back to the built-in default,
+# or run the game with --reset-config to rewrite this file with defaults.
+";
+
+// Optional tunables read from `snake.toml` at startup. Every field has a default, so a
+// partial file only overrides what it names; invalid values are reported and reset.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub gameplay: GameplayConfig,
+    pub colors: ColorsConfig,
+    pub rain: RainConfig,
+    pub audio: AudioConfig,
+    pub hints: HintsConfig,
+    pub online: OnlineConfig,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameplayConfig {
+    pub default_speed_ms: f32,
+    pub default_density_pct: f32,
+    pub food_per_level: u32,
+}
+
+impl Default for GameplayConfig {
+    fn default() -> Self {
+        Self {
+            default_speed_ms: DEFAULT_MOVE_INTERVAL * 1000.0,
+            default_density_pct: DEFAULT_WALL_DENSITY * 100.0,
+            food_per_level: 5,
+        }
+    }
+}
+
+// Hex colors ("#RRGGBB" or "#RRGGBBAA") that replace the theme's color for every theme
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorsConfig {
+    pub head: Option<String>,
+    pub body: Option<String>,
+    pub wall: Option<String>,
+    pub food: Option<String>,
+    pub rain: Option<String>,
+    pub backdrop: Option<String>, // behind the board when Settings > Video > Background is Solid color
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RainConfig {
+    pub columns: i32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+}
+
+impl Default for RainConfig {
+    fn default() -> Self { Self { columns: 16, min_speed: 6.0, max_speed: 18.0 } }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioConfig {
+    pub eat_hz: f32,
+    pub eat_ms: f32,
+    pub die_hz: f32,
+    pub die_ms: f32,
+    pub countdown_hz: f32, // 3, 2, 1 before a run
+    pub countdown_ms: f32,
+    pub go_hz: f32,
+    pub go_ms: f32,
+    pub rumble_hz: f32, // a bomb going off
+    pub rumble_ms: f32,
+    pub glitch_hz: f32, // a phase charge taking the head through a wall
+    pub glitch_ms: f32,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            eat_hz: 880.0,
+            eat_ms: 80.0,
+            die_hz: 110.0,
+            die_ms: 250.0,
+            countdown_hz: 440.0,
+            countdown_ms: 120.0,
+            go_hz: 1320.0,
+            go_ms: 250.0,
+            rumble_hz: 55.0,
+            rumble_ms: 450.0,
+            glitch_hz: 220.0,
+            glitch_ms: 200.0,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HintsConfig {
+    pub playing: String,
+    pub game_over: String,
+}
+
+impl Default for HintsConfig {
+    fn default() -> Self {
+        Self {
+            playing: "Arrows/WASD to move".to_string(),
+            game_over: "Game Over - R to restart, Enter to lobby".to_string(),
+        }
+    }
+}
+
+// Online leaderboard; nothing is sent unless `submit_scores` is turned on
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OnlineConfig {
+    pub submit_scores: bool,
+    pub leaderboard_url: String,
+    pub player_name: String,
+}
+
+impl Default for OnlineConfig {
+    fn default() -> Self {
+        Self { submit_scores: false, leaderboard_url: String::new(), player_name: "Player".to_string() }
+    }
+}
+
+pub fn parse_hex_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if hex.len() != 6 && hex.len() != 8 { return None; }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some(Color::from_rgba(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+impl Config {
+    // Resets out-of-range values to their defaults and describes each fix
+    fn validate(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let defaults = Config::default();
+
+        let g = &mut self.gameplay;
+        if !(50.0..=350.0).contains(&g.default_speed_ms) {
+            problems.push(format!("gameplay.default_speed_ms must be 50-350, got {}", g.default_speed_ms));
+            g.default_speed_ms = defaults.gameplay.default_speed_ms;
+        }
+        if !(0.0..=35.0).contains(&g.default_density_pct) {
+            problems.push(format!("gameplay.default_density_pct must be 0-35, got {}", g.default_density_pct));
+            g.default_density_pct = defaults.gameplay.default_density_pct;
+        }
+        if g.food_per_level == 0 {
+            problems.push("gameplay.food_per_level must be at least 1".to_string());
+            g.food_per_level = defaults.gameplay.food_per_level;
+        }
+
+        let c = &mut self.colors;
+        for (name, value) in [("head", &mut c.head), ("body", &mut c.body), ("wall", &mut c.wall), ("food", &mut c.food), ("rain", &mut c.rain), ("backdrop", &mut c.backdrop)] {
+            if let Some(text) = value.as_ref() && parse_hex_color(text).is_none() {
+                problems.push(format!("colors.{} is not a #RRGGBB color: {}", name, text));
+                *value = None;
+            }
+        }
+
+        let r = &mut self.rain;
+        if !(1..=200).contains(&r.columns) {
+            problems.push(format!("rain.columns must be 1-200, got {}", r.columns));
+            r.columns = defaults.rain.columns;
+        }
+        if !(r.min_speed > 0.0 && r.min_speed <= r.max_speed && r.max_speed <= 200.0) {
+            problems.push("rain speeds must satisfy 0 < min_speed <= max_speed <= 200".to_string());
+            r.min_speed = defaults.rain.min_speed;
+            r.max_speed = defaults.rain.max_speed;
+        }
+
+        let a = &mut self.audio;
+        for (name, hz, ms, default_hz, default_ms) in [
+            ("eat", &mut a.eat_hz, &mut a.eat_ms, defaults.audio.eat_hz, defaults.audio.eat_ms),
+            ("die", &mut a.die_hz, &mut a.die_ms, defaults.audio.die_hz, defaults.audio.die_ms),
+            ("countdown", &mut a.countdown_hz, &mut a.countdown_ms, defaults.audio.countdown_hz, defaults.audio.countdown_ms),
+            ("go", &mut a.go_hz, &mut a.go_ms, defaults.audio.go_hz, defaults.audio.go_ms),
+            ("rumble", &mut a.rumble_hz, &mut a.rumble_ms, defaults.audio.rumble_hz, defaults.audio.rumble_ms),
+            ("glitch", &mut a.glitch_hz, &mut a.glitch_ms, defaults.audio.glitch_hz, defaults.audio.glitch_ms),
+        ] {
+            if !(20.0..=20_000.0).contains(hz) {
+                problems.push(format!("audio.{}_hz must be 20-20000, got {}", name, hz));
+                *hz = default_hz;
+            }
+            if !(10.0..=2_000.0).contains(ms) {
+                problems.push(format!("audio.{}_ms must be 10-2000, got {}", name, ms));
+                *ms = default_ms;
+            }
+        }
+        let o = &mut self.online;
+        if !o.leaderboard_url.is_empty() && !o.leaderboard_url.starts_with("http://") && !o.leaderboard_url.starts_with("https://") {
+            problems.push(format!("online.leaderboard_url must start with http:// or https://, got {}", o.leaderboard_url));
+            o.leaderboard_url.clear();
+        }
+        let name = o.player_name.trim();
+        if name.is_empty() || name.chars().count() > 16 {
+            problems.push("online.player_name must be 1-16 characters".to_string());
+            o.player_name = defaults.online.player_name.clone();
+        }
+        problems
+    }
+
+    // The window's clear color for the chosen background
+    pub fn backdrop(&self, backdrop: Backdrop) -> Color {
+        backdrop.color(self.colors.backdrop.as_deref().and_then(parse_hex_color).unwrap_or(BLACK))
+    }
+
+    // Theme palette with any configured color overrides applied
+    pub fn palette(&self, base: Palette) -> Palette {
+        let pick = |over: &Option<String>, fallback: Color| over.as_deref().and_then(parse_hex_color).unwrap_or(fallback);
+        Palette {
+            head: pick(&self.colors.head, base.head),
+            body: pick(&self.colors.body, base.body),
+            wall: pick(&self.colors.wall, base.wall),
+            food: pick(&self.colors.food, base.food),
+            rain: pick(&self.colors.rain, base.rain),
+        }
+    }
+}
+
+fn load() -> (Config, Vec<String>) {
+    let Ok(text) = fs::read_to_string(CONFIG_PATH) else { return (Config::default(), Vec::new()); };
+    match toml::from_str::<Config>(&text) {
+        Ok(mut config) => {
+            let problems = config.validate();
+            logging::info(format!("Loaded {}", CONFIG_PATH));
+            for p in &problems { logging::warn(format!("{}: {}", CONFIG_PATH, p)); }
+            (config, problems)
+        }
+        Err(e) => {
+            let msg = format!("{} ignored: {}", CONFIG_PATH, e.message());
+            logging::warn(&msg);
+            (Config::default(), vec![msg])
+        }
+    }
+}
+
+static LOADED: OnceLock<(Config, Vec<String>)> = OnceLock::new();
+
+pub fn config() -> &'static Config { &LOADED.get_or_init(load).0 }
+
+// Problems found while loading, suitable for a notice
+pub fn config_problems() -> &'static [String] { &LOADED.get_or_init(load).1 }
+
+// Overwrite the config file with the defaults; the old file is kept as a .bak
+pub fn reset_config() -> std::io::Result<()> {
+    let _ = fs::rename(CONFIG_PATH, format!("{}.bak", CONFIG_PATH));
+    let body = toml::to_string_pretty(&Config::default()).unwrap_or_default();
+    write_atomic(CONFIG_PATH, &format!("{}\n{}", HEADER, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Config, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Config::default().validate().is_empty());
+    }
+
+    #[test]
+    fn written_defaults_read_back_the_same() {
+        let text = toml::to_string_pretty(&Config::default()).unwrap();
+        let mut config = parse(&text).expect("default file parses");
+        assert!(config.validate().is_empty());
+        assert_eq!(toml::to_string_pretty(&config).unwrap(), text);
+    }
+
+    #[test]
+    fn partial_file_only_overrides_what_it_names() {
+        let config = parse("[gameplay]\ndefault_density_pct = 20.0\n\n[rain]\ncolumns = 40\n").unwrap();
+        let defaults = Config::default();
+        assert_eq!(config.gameplay.default_density_pct, 20.0);
+        assert_eq!(config.rain.columns, 40);
+        assert_eq!(config.gameplay.default_speed_ms, defaults.gameplay.default_speed_ms);
+        assert_eq!(config.rain.max_speed, defaults.rain.max_speed);
+        assert_eq!(config.hints.playing, defaults.hints.playing);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(parse("[gameplay]\ndefault_speed = 100.0\n").is_err());
+        assert!(parse("[sound]\neat_hz = 440.0\n").is_err());
+    }
+
+    #[test]
+    fn out_of_range_values_are_reset_and_reported() {
+        let mut config = parse("[gameplay]\ndefault_speed_ms = 10.0\ndefault_density_pct = 35.0\nfood_per_level = 0\n\n[rain]\ncolumns = 0\n\n[audio]\neat_hz = 5.0\ndie_ms = 5000.0\n").unwrap();
+        let problems = config.validate();
+        let defaults = Config::default();
+        assert_eq!(problems.len(), 5, "{:?}", problems);
+        assert_eq!(config.gameplay.default_speed_ms, defaults.gameplay.default_speed_ms);
+        assert_eq!(config.gameplay.default_density_pct, 35.0); // the top of the range is allowed
+        assert_eq!(config.gameplay.food_per_level, defaults.gameplay.food_per_level);
+        assert_eq!(config.rain.columns, defaults.rain.columns);
+        assert_eq!(config.audio.eat_hz, defaults.audio.eat_hz);
+        assert_eq!(config.audio.die_ms, defaults.audio.die_ms);
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn rain_speeds_reset_together() {
+        let mut config = parse("[rain]\nmin_speed = 30.0\nmax_speed = 10.0\n").unwrap();
+        assert_eq!(config.validate().len(), 1);
+        let defaults = RainConfig::default();
+        assert_eq!((config.rain.min_speed, config.rain.max_speed), (defaults.min_speed, defaults.max_speed));
+    }
+
+    #[test]
+    fn bad_colors_are_dropped_and_good_ones_kept() {
+        let mut config = parse("[colors]\nhead = \"#00FF00\"\nbody = \"green\"\nwall = \"#12345\"\n").unwrap();
+        assert_eq!(config.validate().len(), 2);
+        assert_eq!(config.colors.head.as_deref(), Some("#00FF00"));
+        assert_eq!(config.colors.body, None);
+        assert_eq!(config.colors.wall, None);
+    }
+
+    #[test]
+    fn online_settings_are_checked() {
+        let mut config = parse("[online]\nleaderboard_url = \"ftp://scores\"\nplayer_name = \"   \"\n").unwrap();
+        assert_eq!(config.validate().len(), 2);
+        assert!(config.online.leaderboard_url.is_empty());
+        assert_eq!(config.online.player_name, "Player");
+    }
+
+    #[test]
+    fn hex_colors_parse() {
+        assert_eq!(parse_hex_color("#FF0000"), Some(Color::from_rgba(255, 0, 0, 255)));
+        assert_eq!(parse_hex_color("#00ff0080"), Some(Color::from_rgba(0, 255, 0, 128)));
+        assert_eq!(parse_hex_color("FF0000"), None);
+        assert_eq!(parse_hex_color("#FF00"), None);
+        assert_eq!(parse_hex_color("#GG0000"), None);
+    }
+}
@@ -0,0 +1,61 @@
+// Adaptive mode's difficulty controller. A single rating from 0 (gentlest) to 1 (hardest)
+// climbs with every meal and drops on every death; the drop is sized so the rating settles
+// where the player eats about `food_per_life` per run for the chosen challenge. New runs
+// take their wall density and speed from it, and within a run each level ticks a bit faster.
+use serde::{Deserialize, Serialize};
+
+use crate::config::config;
+use crate::events::GameEvent;
+
+const STEP: f32 = 0.01; // rating gained per meal
+const MIN_DENSITY: f32 = 0.02;
+const MAX_DENSITY: f32 = 0.25;
+const SLOWEST: f32 = 0.16; // move interval at rating 0
+const FASTEST: f32 = 0.07; // and at rating 1
+const LEVEL_SPEEDUP: f32 = 0.04; // tick interval cut per level, scaled up with the rating
+const MIN_PACE: f32 = 0.6;
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Difficulty {
+    pub rating: f32,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self { Self { rating: 0.3 } }
+}
+
+// An Adaptive run's level at `score`: one more every `food_per_level` points
+pub fn level(score: u32) -> u32 {
+    score / config().gameplay.food_per_level + 1
+}
+
+// Meals per run the rating settles at: 25 at challenge 0 down to 5 at challenge 1
+fn food_per_life(challenge: f32) -> f32 {
+    25.0 - 20.0 * challenge.clamp(0.0, 1.0)
+}
+
+impl Difficulty {
+    pub fn wall_density(&self) -> f32 {
+        MIN_DENSITY + (MAX_DENSITY - MIN_DENSITY) * self.rating
+    }
+
+    pub fn move_interval(&self) -> f32 {
+        SLOWEST + (FASTEST - SLOWEST) * self.rating
+    }
+
+    // Multiplier on the tick interval once a run reaches `level`
+    pub fn pace(&self, level: u32) -> f32 {
+        (1.0 - LEVEL_SPEEDUP * (0.5 + self.rating)).powi(level.saturating_sub(1) as i32).max(MIN_PACE)
+    }
+
+    // Consumer of an Adaptive run's events; `challenge` is the target from settings
+    pub fn on_event(&mut self, event: &GameEvent, challenge: f32) {
+        let change = match event {
+            GameEvent::FoodEaten { .. } | GameEvent::PremiumEaten { .. } | GameEvent::ChainEaten { .. } => STEP,
+            GameEvent::Died(_) => -STEP * food_per_life(challenge),
+            _ => return,
+        };
+        self.rating = (self.rating + change).clamp(0.0, 1.0);
+    }
+}
@@ -0,0 +1,40 @@
+// Failures the game survives. Each one is logged and shown on the error screen, which says
+// what the game does instead: play on without sound, or drop the screen that failed and go
+// back to the lobby.
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub enum GameError {
+    Audio(String), // a sound couldn't be loaded; the game runs silent
+    Crash(String), // a screen panicked mid-frame; the panic message
+}
+
+impl GameError {
+    pub fn title(&self) -> &'static str {
+        match self {
+            GameError::Audio(_) => "Sound could not be loaded",
+            GameError::Crash(_) => "Something went wrong",
+        }
+    }
+
+    // The underlying message, as technical as it came
+    pub fn detail(&self) -> &str {
+        match self {
+            GameError::Audio(e) | GameError::Crash(e) => e,
+        }
+    }
+
+    // What the game does about it
+    pub fn fallback(&self) -> &'static str {
+        match self {
+            GameError::Audio(_) => "The game will play without sound.",
+            GameError::Crash(_) => "That screen was closed. An unfinished run can be resumed from the lobby.",
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.title(), self.detail())
+    }
+}
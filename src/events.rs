@@ -0,0 +1,132 @@
+use macroquad::prelude::*;
+
+use crate::i18n::{tr, trf};
+use crate::map::Cell;
+use crate::missions::Outcome;
+use crate::powerups::{PowerUp, BOMB_RADIUS};
+use crate::render::{draw_glyph_at_pos_scaled, Palette, MATRIX_GLYPHS};
+use crate::sim::{DeathCause, GLITCH_PENALTY, NIGHT_BONUS, PREMIUM_POINTS};
+use crate::ui::{draw_centered, draw_text};
+use crate::waves::Hazard;
+
+// Gameplay events raised by `SnakeGame::step` and drained once per frame by the systems
+// that react to them (audio, HUD popups), so the game logic never calls into those directly
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GameEvent {
+    FoodEaten { at: Cell, score: u32, points: u32, combo: u32 }, // `combo`: meals in a row within the combo window, from 1
+    PremiumEaten { at: Cell, score: u32 },
+    ChainEaten { at: Cell, points: u32, in_order: bool, combo: u32 }, // food chain; out of order halved the combo
+    Shrank { at: Cell }, // Hunger mode: a tail segment starved away
+    ScoreDecayed { at: Cell }, // score decay took a point; `at` is the head
+    GlitchEaten { at: Cell }, // glitch food took points and inverted the controls
+    Died(DeathCause),
+    LevelUp { level: u32 }, // Adaptive mode
+    Won,
+    WaveStarted { wave: u32, hazard: Hazard }, // Waves mode
+    MissionEnded(Outcome),
+    PowerUp { kind: PowerUp, at: Cell },
+    PhaseEarned { charges: u32 },
+    Phased { at: Cell }, // a phase charge took the head into a wall
+    Switched { at: Cell, solid: bool }, // a plate flipped its walls; `at` is the head
+    Nightfall { night: bool }, // day/night modifier: night fell or day broke
+}
+
+const POPUP_LIFETIME: f32 = 0.8;
+const BANNER_LIFETIME: f32 = 2.0;
+const BURST_LIFETIME: f32 = 0.6;
+const BURST_SHARDS: usize = 24;
+
+#[derive(Clone)]
+struct Popup {
+    text: String,
+    at: Cell,
+    born: f32,
+}
+
+// Short floating texts over the board ("+1", "BOARD CLEARED"), a centred banner for new waves
+// and the shards a bomb throws out
+#[derive(Clone, Default)]
+pub struct Popups {
+    items: Vec<Popup>,
+    banner: Option<(String, String, f32)>, // title, subtitle, born
+    bursts: Vec<(Cell, f32)>, // centre, born
+}
+
+impl Popups {
+    pub fn on_event(&mut self, event: &GameEvent, head: Cell, now: f32) {
+        let (text, at) = match *event {
+            GameEvent::FoodEaten { at, points, .. } => (format!("+{}", points), at),
+            GameEvent::PremiumEaten { at, .. } => (format!("+{}", PREMIUM_POINTS), at),
+            GameEvent::ChainEaten { at, points, in_order: true, .. } => (format!("+{}", points), at),
+            GameEvent::ChainEaten { at, in_order: false, .. } => (tr("COMBO HALVED").to_owned(), at),
+            GameEvent::Shrank { at } | GameEvent::ScoreDecayed { at } => ("-1".to_owned(), at),
+            GameEvent::GlitchEaten { at } => (trf!("-{} INVERTED", GLITCH_PENALTY), at),
+            GameEvent::LevelUp { level } => (trf!("LEVEL {}", level), head),
+            GameEvent::Won => (tr("BOARD CLEARED").to_owned(), head),
+            GameEvent::MissionEnded(Outcome::Completed { bonus }) => (trf!("MISSION +{}", bonus), head),
+            GameEvent::MissionEnded(Outcome::Failed) => (tr("MISSION FAILED").to_owned(), head),
+            GameEvent::PowerUp { kind, at } => {
+                if kind == PowerUp::Bomb { self.bursts.push((at, now)); }
+                (tr(kind.label()).to_owned(), at)
+            }
+            GameEvent::PhaseEarned { charges } => (trf!("PHASE x{}", charges), head),
+            GameEvent::Phased { at } => (tr("PHASE").to_owned(), at),
+            GameEvent::Switched { at, solid } => (tr(if solid { "SHUT" } else { "OPEN" }).to_owned(), at),
+            GameEvent::Nightfall { night: true } => {
+                self.banner = Some((tr("NIGHT").to_owned(), trf!("Food is worth {} points", 1 + NIGHT_BONUS), now));
+                return;
+            }
+            GameEvent::Nightfall { night: false } => {
+                self.banner = Some((tr("DAY").to_owned(), String::new(), now));
+                return;
+            }
+            GameEvent::WaveStarted { wave, hazard } => {
+                self.banner = Some((trf!("WAVE {}", wave), tr(hazard.label()).to_owned(), now));
+                return;
+            }
+            GameEvent::Died(_) => return,
+        };
+        self.items.push(Popup { text, at, born: now });
+    }
+
+    pub fn draw(&mut self, now: f32, palette: &Palette, tile_w: f32, tile_h: f32, off_x: f32, off_y: f32) {
+        self.items.retain(|p| now - p.born < POPUP_LIFETIME);
+        for p in &self.items {
+            let t = (now - p.born) / POPUP_LIFETIME;
+            let x = off_x + p.at.x as f32 * tile_w;
+            let y = off_y + (p.at.y as f32 - t * 2.0) * tile_h; // drift up two tiles
+            let mut color = palette.food;
+            color.a = 1.0 - t;
+            draw_text(&p.text, x, y, tile_h.max(12.0) * 1.4, color);
+        }
+
+        // Glyph shards fly out to the edge of the blast, fading as they go
+        self.bursts.retain(|(_, born)| now - born < BURST_LIFETIME);
+        for (at, born) in &self.bursts {
+            let t = (now - born) / BURST_LIFETIME;
+            for i in 0..BURST_SHARDS {
+                let angle = i as f32 / BURST_SHARDS as f32 * std::f32::consts::TAU;
+                let reach = BOMB_RADIUS as f32 * (0.6 + 0.1 * (i * 7 % 5) as f32) * t;
+                let pos = (at.x as f32 + angle.cos() * reach, at.y as f32 + angle.sin() * reach);
+                let ch = MATRIX_GLYPHS[i * 5 % MATRIX_GLYPHS.len()] as char;
+                draw_glyph_at_pos_scaled(ch, pos, Color { a: 1.0 - t, ..palette.head }, tile_w, tile_h, off_x, off_y);
+            }
+        }
+
+    }
+
+    // Over the board at window resolution; holds, then fades over its last half second
+    pub fn draw_banner(&mut self, now: f32, palette: &Palette) {
+        if let Some((title, subtitle, born)) = &self.banner {
+            let age = now - born;
+            if age >= BANNER_LIFETIME {
+                self.banner = None;
+                return;
+            }
+            let alpha = ((BANNER_LIFETIME - age) / 0.5).min(1.0);
+            let y = screen_height() * 0.4;
+            draw_centered(title, y, 48.0, Color { a: alpha, ..palette.head });
+            draw_centered(subtitle, y + 36.0, 24.0, Color { a: alpha, ..palette.food });
+        }
+    }
+}
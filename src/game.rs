@@ -0,0 +1,831 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+use crate::ai;
+use crate::config::config;
+use crate::difficulty;
+use crate::events::{GameEvent, Popups};
+use crate::i18n::{tr, trf};
+use crate::map::{Cell, Direction, Map};
+use crate::graveyard::CLEARANCE;
+use crate::mapgen;
+use crate::missions::{Missions, Objective};
+use crate::mods;
+use crate::powerups::PowerUp;
+use crate::replay::Replay;
+use crate::rng::Rng;
+use crate::sim::{Chain, Hunger, SimEvent, SnakeSim, GLYPH_STREAM, MAX_PHASE_CHARGES};
+use crate::skins::{Skin, Trail, TRAIL_TICKS};
+use crate::speedrun::SPLIT_EVERY;
+use crate::waves::{Waves, WAVE_FOOD};
+use crate::weekly::{Modifier, FOG_RADIUS};
+use crate::render::{draw_conveyors, draw_gates, draw_stairs, draw_switches, draw_glyph_at_cell_scaled, draw_glyph_at_pos_scaled, draw_wall_layer, matrix_char_for_cell, random_matrix_char, board_layout, draw_board_canvas, Look, Palette, CHAIN_COLORS};
+use crate::ui::{draw_text, measure_text, HudCorner, KeyBindings};
+
+pub use snake_core::rules::{Extras, GameMode};
+
+pub const DEFAULT_MOVE_INTERVAL: f32 = 0.12; // default snake speed (seconds)
+pub const DEFAULT_WALL_DENSITY: f32 = 0.10;
+// Longest frame fed to the accumulator; a stall beyond this is dropped instead of replayed as a burst of ticks
+pub const MAX_FRAME_DT: f32 = 0.25;
+const PREMIUM_TICKS_WARN: u32 = 20; // premium food and power-ups blink for their last ticks
+// Lighting: how far the head's light reaches, the food's own glow and the floor everything keeps
+const LIGHT_RADIUS: f32 = 9.0;
+const GLOW_RADIUS: f32 = 3.5;
+const AMBIENT_LIGHT: f32 = 0.15;
+// Day/night: what a cell away from the head and the food keeps at full night, and how bright
+// the food's glow gets then
+const NIGHT_LIGHT: f32 = 0.2;
+const NIGHT_GLOW: f32 = 0.2;
+const GLITCH_TINT: f32 = 0.35; // how far glitch food's colour is pushed toward red
+const GHOST_FLOOR: f32 = 0.25; // multi-floor maps: how much of the floors the head isn't on shows
+const COMBO_WINDOW: f32 = 2.0; // seconds of play a meal keeps the combo going for
+const HEATMAP_ALPHA: f32 = 0.35; // the most visited cell's tint on the game over screen
+const PATH_HINT_ALPHA: f32 = 0.3;
+
+// The playable game: wraps the headless `SnakeSim` with timing, input and glyphs; sound and
+// popups react to the `GameEvent`s it queues
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SnakeGame {
+    #[serde(flatten)]
+    pub sim: SnakeSim,
+    pub body_chars: VecDeque<char>,
+    pub food_char: char,
+    #[serde(default)]
+    pub accumulator: f32, // time banked toward the next fixed tick
+    pub move_interval: f32,
+    pub glyph_rng: Rng, // cosmetic glyph choice, kept apart from the food stream
+    #[serde(default)]
+    pub replay: Replay, // recorded as the run is played
+    // Set when the game is driven from a replay instead of the keyboard
+    #[serde(skip)]
+    pub playback: bool,
+    // Events raised since the last `drain_events`
+    #[serde(skip)]
+    pub events: Vec<GameEvent>,
+    #[serde(skip)]
+    pub popups: Popups,
+    // Tick at which the score reached each multiple of SPLIT_EVERY
+    #[serde(default)]
+    pub splits: Vec<u32>,
+    // Snake before the last tick, used to interpolate drawing between ticks
+    #[serde(skip)]
+    pub prev_snake: VecDeque<Cell>,
+    // Cells the tail left and the tick it left them, for skins with a trail
+    #[serde(skip)]
+    pub trail: VecDeque<(Cell, u32)>,
+    // Adaptive mode's multiplier on the tick interval, lowered level by level; timing only
+    #[serde(default = "full_pace")]
+    pub pace: f32,
+    // Graveyard modifier: walls left by earlier deaths on this map, drawn dimmer
+    #[serde(default)]
+    pub corpses: HashSet<Cell>,
+    // Sandbox practice: the HUD leaves out the score, which doesn't count there
+    #[serde(skip)]
+    pub practice: bool,
+    // Ticks each cell spent under the snake, row by row, for the game over heatmap
+    #[serde(default)]
+    pub visits: Vec<u32>,
+    // The cell the run ended on: what the head ran into, or where it stood if that's off the board
+    #[serde(default)]
+    pub death_at: Option<Cell>,
+    // Meals in a row, each eaten within COMBO_WINDOW of the last, and the play time left to extend it
+    #[serde(skip)]
+    pub combo: u32,
+    #[serde(skip)]
+    combo_left: f32,
+    // The longest of those this run, and how many cells the head has moved, for the game over analysis
+    #[serde(default)]
+    pub best_combo: u32,
+    #[serde(default)]
+    pub distance: u32,
+    // The path hint was on at some point: the run stays off the High Scores table
+    #[serde(default)]
+    pub assisted: bool,
+}
+
+fn full_pace() -> f32 { 1.0 }
+
+impl SnakeGame {
+    pub fn new(map: Map, move_interval: f32) -> Self {
+        let replay = Replay::new(&map, move_interval);
+        let sim = SnakeSim::new(map);
+        let mut glyph_rng = Rng::with_stream(sim.map.seed, GLYPH_STREAM);
+        let body_chars = sim.snake.iter().map(|_| random_matrix_char(&mut glyph_rng)).collect();
+        Self {
+            sim,
+            body_chars,
+            food_char: random_matrix_char(&mut glyph_rng),
+            accumulator: 0.0,
+            move_interval,
+            glyph_rng,
+            replay,
+            playback: false,
+            events: Vec::new(),
+            popups: Popups::default(),
+            splits: Vec::new(),
+            prev_snake: VecDeque::new(),
+            trail: VecDeque::new(),
+            pace: 1.0,
+            corpses: HashSet::new(),
+            practice: false,
+            visits: Vec::new(),
+            death_at: None,
+            combo: 0,
+            combo_left: 0.0,
+            best_combo: 0,
+            assisted: false,
+            distance: 0,
+        }
+    }
+
+    // Fresh game on a replay's map, ready to be fed the recorded turns
+    pub fn from_replay(replay: &Replay) -> Self {
+        let mut map = mapgen::generate(&replay.map_style, replay.grid, replay.seed, replay.wall_density);
+        if !replay.conveyors() { map.conveyors.clear(); }
+        let mut game = SnakeGame::new(map, replay.move_interval);
+        game.set_mode(replay.mode);
+        game.set_missions(replay.missions);
+        game.set_modifier(replay.modifier);
+        game.set_corpses(&replay.corpses);
+        // What the replay's version had, not what `set_extras` would switch on today
+        game.replay.extras = replay.extras;
+        replay.apply_extras(&mut game.sim);
+        game.playback = true;
+        game
+    }
+
+    pub fn restart(&mut self) {
+        self.restart_on(self.sim.map.seed);
+    }
+
+    // A fresh run with the same settings on the map `seed` generates
+    pub fn restart_on(&mut self, seed: u64) {
+        // Regenerated from its parameters, which also undoes any edits mods made
+        let map = &self.sim.map;
+        self.sim = SnakeSim::new(mapgen::generate(&map.style, map.grid, seed, map.wall_density));
+        self.move_interval = self.replay.move_interval;
+        let (mode, missions, modifier, extras) = (self.replay.mode, self.replay.missions, self.replay.modifier, self.replay.extras);
+        self.replay = Replay::new(&self.sim.map, self.move_interval);
+        self.set_extras(extras);
+        self.set_mode(mode);
+        self.set_missions(missions);
+        self.set_modifier(modifier);
+        self.glyph_rng = Rng::with_stream(self.sim.map.seed, GLYPH_STREAM);
+        self.body_chars = self.sim.snake.iter().map(|_| random_matrix_char(&mut self.glyph_rng)).collect();
+        self.food_char = random_matrix_char(&mut self.glyph_rng);
+        self.accumulator = 0.0;
+        self.splits.clear();
+        self.prev_snake.clear();
+        self.trail.clear();
+        self.pace = 1.0;
+        self.corpses.clear();
+        self.visits.clear();
+        self.death_at = None;
+        self.combo = 0;
+        self.combo_left = 0.0;
+        self.best_combo = 0;
+        self.distance = 0;
+        self.assisted = false;
+    }
+
+    // Graveyard modifier: turns earlier deaths' bodies into walls, except near the starting
+    // head or where something already is; the ones placed go into the replay
+    pub fn set_corpses(&mut self, cells: &[Cell]) {
+        let head = self.sim.head();
+        for c in cells {
+            if (c.x - head.x).abs() + (c.y - head.y).abs() <= CLEARANCE { continue; }
+            if self.sim.add_wall(*c) {
+                self.corpses.insert(*c);
+                self.replay.corpses.push(*c);
+            }
+        }
+    }
+
+    // Adaptive mode: the map density and speed the next restart uses
+    pub fn retune(&mut self, wall_density: f32, move_interval: f32) {
+        self.sim.map.wall_density = wall_density;
+        self.replay.move_interval = move_interval;
+    }
+
+    // Recorded in the replay; modes with their own rules switch them on in the sim
+    pub fn set_mode(&mut self, mode: GameMode) {
+        self.replay.mode = mode;
+        mode.apply(&mut self.sim);
+    }
+
+    // Missions are recorded in the replay too, since their bonuses count toward the score
+    pub fn set_missions(&mut self, on: bool) {
+        self.replay.missions = on;
+        if on { self.sim.enable_missions(self.move_interval); }
+    }
+
+    // Pickups picked in settings, recorded in the replay
+    pub fn set_extras(&mut self, extras: Extras) {
+        self.replay.extras = extras;
+        extras.apply(&mut self.sim);
+    }
+
+    // Weekly challenge or settings rule, recorded in the replay
+    pub fn set_modifier(&mut self, modifier: Option<Modifier>) {
+        self.replay.modifier = modifier;
+        if let Some(modifier) = modifier { modifier.apply(&mut self.sim, self.move_interval); }
+    }
+
+    // Called when a saved run is resumed so it starts from a clean tick
+    pub fn resume(&mut self) {
+        self.sim.rebuild_occupancy();
+        self.accumulator = 0.0;
+        self.prev_snake.clear();
+    }
+
+    pub fn handle_input(&mut self, keys: &KeyBindings) {
+        let dir = if KeyBindings::pressed(&keys.up) {
+            Direction::Up
+        } else if KeyBindings::pressed(&keys.down) {
+            Direction::Down
+        } else if KeyBindings::pressed(&keys.left) {
+            Direction::Left
+        } else if KeyBindings::pressed(&keys.right) {
+            Direction::Right
+        } else {
+            return;
+        };
+        // Glitch food's inversion undoes Mirror's for a while
+        let mirror = (self.replay.modifier == Some(Modifier::Mirror)) != self.sim.inverted();
+        self.sim.turn(if mirror { dir.opposite() } else { dir });
+    }
+
+    // One-switch controls: each press turns the queued direction a quarter clockwise, skipping
+    // the reverse, so one press turns right and a quick double press turns left. Mirror and
+    // inverted controls turn the other way.
+    pub fn handle_switch(&mut self) {
+        if !KeyBindings::switch_pressed() { return; }
+        let mirror = (self.replay.modifier == Some(Modifier::Mirror)) != self.sim.inverted();
+        let rotate = |d: Direction| if mirror { d.clockwise().opposite() } else { d.clockwise() };
+        let mut dir = rotate(self.sim.next_direction);
+        if dir == self.sim.direction.opposite() { dir = rotate(dir); }
+        self.sim.turn(dir);
+    }
+
+    // Multi-floor maps: the head's floor in full, the others ghosted
+    fn ghost(&self, cell: Cell) -> f32 {
+        let map = &self.sim.map;
+        match (map.floor_of(cell), map.floor_of(self.sim.head())) {
+            (Some(floor), Some(active)) if floor != active => GHOST_FLOOR,
+            _ => 1.0,
+        }
+    }
+
+    // Fog: cells fade out toward FOG_RADIUS from the head and vanish past it; 1.0 without fog.
+    // Ghosted floors stay ghosted either way.
+    fn visibility(&self, cell: Cell) -> f32 {
+        let ghost = self.ghost(cell);
+        if self.replay.modifier != Some(Modifier::Fog) { return ghost; }
+        let head = self.sim.head();
+        let distance = (((cell.x - head.x).pow(2) + (cell.y - head.y).pow(2)) as f32).sqrt();
+        ghost * (1.0 - distance / FOG_RADIUS).clamp(0.0, 1.0).sqrt()
+    }
+
+    // Lighting: bright at the head, fading with distance, lifted near the food and never
+    // below a faint ambient level, so unlike fog nothing disappears
+    fn light(&self, cell: Cell) -> f32 {
+        let distance = |to: Cell| (((cell.x - to.x).pow(2) + (cell.y - to.y).pow(2)) as f32).sqrt();
+        let head = (1.0 - distance(self.sim.head()) / LIGHT_RADIUS).max(0.0).powi(2);
+        let glow = self.sim.food.map_or(0.0, |f| 0.7 * (1.0 - distance(f) / GLOW_RADIUS).max(0.0));
+        head.max(glow).max(AMBIENT_LIGHT)
+    }
+
+    // Day/night: how dark it is, 0.0 by day or without the modifier
+    fn darkness(&self) -> f32 {
+        self.sim.daylight.map_or(0.0, |d| d.darkness())
+    }
+
+    // Night dims everything but the cells around the head and the food's glow
+    fn night(&self, cell: Cell) -> f32 {
+        let dark = self.darkness();
+        let head = self.sim.head();
+        if dark <= 0.0 || ((cell.x - head.x).abs() <= 1 && (cell.y - head.y).abs() <= 1) { return 1.0; }
+        let glow = self.sim.food.map_or(0.0, |f| 1.0 - ((((cell.x - f.x).pow(2) + (cell.y - f.y).pow(2)) as f32).sqrt() / GLOW_RADIUS));
+        1.0 - dark * (1.0 - glow.max(NIGHT_LIGHT))
+    }
+
+    // Whether every wall is drawn the same as last frame unless the walls changed: no shading
+    // that moves with the head or the clock, and no snake phasing through one
+    fn walls_static(&self, look: Look) -> bool {
+        let map = &self.sim.map;
+        self.replay.modifier != Some(Modifier::Fog) && !look.lighting && self.darkness() <= 0.0 && map.floors.is_empty()
+            && !self.sim.snake.iter().any(|c| map.walls.contains(c))
+    }
+
+    // Tells one set of static walls from another, for the cached wall layer: every wall and
+    // graveyard corpse, however the sets happen to iterate, the wall colour and the tile size
+    fn wall_key(&self, palette: &Palette, tile: f32) -> u64 {
+        let mix = |c: &Cell| ((c.x as u32 as u64) << 32 | c.y as u32 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15).rotate_left(29);
+        let walls = self.sim.map.walls.iter().fold(0u64, |k, c| k.wrapping_add(mix(c)));
+        let corpses = self.corpses.iter().filter(|c| self.sim.map.walls.contains(c)).fold(0u64, |k, c| k.wrapping_add(mix(c)));
+        let color = palette.wall;
+        let mut key = walls ^ corpses.rotate_left(17) ^ (self.sim.map.walls.len() as u64).rotate_left(40);
+        for part in [color.r, color.g, color.b, color.a, tile] {
+            key = (key ^ part.to_bits() as u64).wrapping_mul(0x1000_0000_01B3);
+        }
+        key
+    }
+
+    // How much of a cell shows: fog, then lighting when it's on, then the time of day
+    fn shade(&self, cell: Cell, look: Look) -> f32 {
+        self.visibility(cell) * if look.lighting { self.light(cell) } else { 1.0 } * self.night(cell)
+    }
+
+    // Advance by `dt` seconds of wall time, running as many fixed ticks as have accumulated
+    pub fn step(&mut self, dt: f32) {
+        if !self.sim.alive { return; }
+        self.accumulator += dt.clamp(0.0, MAX_FRAME_DT);
+        while self.sim.alive && self.accumulator >= self.tick_interval() {
+            self.accumulator -= self.tick_interval();
+            self.tick();
+        }
+    }
+
+    // Like `step` but runs at most one tick, for callers that act between ticks; true if it ticked
+    pub fn step_single(&mut self, dt: f32) -> bool {
+        if !self.sim.alive { return false; }
+        self.accumulator += dt.clamp(0.0, MAX_FRAME_DT);
+        let interval = self.tick_interval();
+        if self.accumulator < interval { return false; }
+        self.accumulator = (self.accumulator - interval).min(interval);
+        self.tick();
+        true
+    }
+
+    // Run one tick with the direction recorded for it, ignoring the keyboard
+    pub fn playback_tick(&mut self, source: &Replay) {
+        if let Some(dir) = source.turn_at(self.replay.ticks) { self.sim.next_direction = dir; }
+        self.tick();
+    }
+
+    fn tick(&mut self) {
+        if !self.playback && self.sim.next_direction != self.sim.direction {
+            self.replay.turns.push((self.replay.ticks, self.sim.next_direction));
+        }
+        self.replay.ticks += 1;
+        self.prev_snake = self.sim.snake.clone();
+        let level = difficulty::level(self.sim.score);
+        let ahead = self.sim.head().step(self.sim.next_direction);
+        let ahead = self.sim.map.stair_exit(ahead).unwrap_or(ahead);
+        let events = self.sim.tick();
+        self.combo_left -= self.tick_interval();
+        if self.combo_left <= 0.0 { self.combo = 0; }
+        for event in events.iter().copied() {
+            match event {
+                SimEvent::Moved { .. } => {
+                    self.body_chars.push_front(random_matrix_char(&mut self.glyph_rng));
+                    self.distance += 1;
+                }
+                SimEvent::FoodEaten { at, score, points } => {
+                    self.food_char = random_matrix_char(&mut self.glyph_rng);
+                    self.combo += 1;
+                    self.combo_left = COMBO_WINDOW;
+                    self.best_combo = self.best_combo.max(self.combo);
+                    self.events.push(GameEvent::FoodEaten { at, score, points, combo: self.combo });
+                }
+                SimEvent::ChainEaten { at, points, in_order, .. } => {
+                    // In order it keeps the combo going like any meal; out of order halves it
+                    if in_order {
+                        self.combo += 1;
+                        self.combo_left = COMBO_WINDOW;
+                        self.best_combo = self.best_combo.max(self.combo);
+                    } else {
+                        self.combo /= 2;
+                    }
+                    self.events.push(GameEvent::ChainEaten { at, points, in_order, combo: self.combo });
+                }
+                SimEvent::Shrank { tail } => self.events.push(GameEvent::Shrank { at: tail }),
+                SimEvent::GlitchEaten { at, .. } => self.events.push(GameEvent::GlitchEaten { at }),
+                SimEvent::ScoreDecayed { .. } => self.events.push(GameEvent::ScoreDecayed { at: self.sim.head() }),
+                SimEvent::Died(cause) => {
+                    // A crash stops the head short of the cell it hit
+                    let moved = events.iter().any(|e| matches!(e, SimEvent::Moved { .. }));
+                    self.death_at = Some(if !moved && self.sim.map.grid.contains(ahead) { ahead } else { self.sim.head() });
+                    self.events.push(GameEvent::Died(cause));
+                }
+                SimEvent::Won => self.events.push(GameEvent::Won),
+                SimEvent::Switched { solid, .. } => self.events.push(GameEvent::Switched { at: self.sim.head(), solid }),
+                SimEvent::Nightfall { night } => self.events.push(GameEvent::Nightfall { night }),
+                SimEvent::WaveStarted { wave, hazard } => self.events.push(GameEvent::WaveStarted { wave, hazard }),
+                SimEvent::MissionEnded(outcome) => self.events.push(GameEvent::MissionEnded(outcome)),
+                SimEvent::PremiumSpawned { .. } => {}
+                SimEvent::PremiumEaten { at, score } => self.events.push(GameEvent::PremiumEaten { at, score }),
+                SimEvent::PowerUpSpawned { .. } => {}
+                SimEvent::PhaseEarned { charges } => self.events.push(GameEvent::PhaseEarned { charges }),
+                SimEvent::Phased { at, .. } => self.events.push(GameEvent::Phased { at }),
+                SimEvent::PowerUpTaken { kind, at } => {
+                    // The glyphs turn with the body, and there's nothing to slide from
+                    if kind == PowerUp::Reverse {
+                        self.body_chars.truncate(self.sim.snake.len());
+                        self.body_chars.make_contiguous().reverse();
+                        self.prev_snake = self.sim.snake.clone();
+                    }
+                    self.events.push(GameEvent::PowerUp { kind, at });
+                }
+            }
+        }
+        // Only Adaptive runs have levels. Compared before and after, since a mission bonus can
+        // skip past a level boundary
+        let reached = difficulty::level(self.sim.score);
+        if self.replay.mode == GameMode::Adaptive && reached > level { self.events.push(GameEvent::LevelUp { level: reached }); }
+        self.body_chars.truncate(self.sim.snake.len());
+        if let Some(tail) = self.prev_snake.back().filter(|c| !self.sim.occupied.contains(c)) {
+            self.trail.push_front((*tail, self.replay.ticks));
+        }
+        while self.trail.back().is_some_and(|(_, t)| self.replay.ticks - t >= TRAIL_TICKS) { self.trail.pop_back(); }
+        self.count_visits();
+        mods::after_tick(self, &events);
+        self.replay.score = self.sim.score;
+        while (self.splits.len() as u32 + 1) * SPLIT_EVERY <= self.sim.score {
+            self.splits.push(self.replay.ticks);
+        }
+    }
+
+    fn count_visits(&mut self) {
+        let grid = self.sim.map.grid;
+        self.visits.resize((grid.width * grid.height) as usize, 0);
+        for c in &self.sim.snake {
+            if let Some(n) = self.visits.get_mut((c.y * grid.width + c.x) as usize) { *n += 1; }
+        }
+    }
+
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    // Seconds per tick right now: the run's speed, sped up by any Faster waves and Adaptive's pace
+    pub fn tick_interval(&self) -> f32 {
+        self.move_interval * self.sim.waves.as_ref().map_or(1.0, |w| w.speed) * self.pace
+    }
+
+    // Fraction of the way from the previous tick to the next one
+    pub fn alpha(&self) -> f32 {
+        let interval = self.tick_interval();
+        if interval <= 0.0 { return 1.0; }
+        (self.accumulator / interval).clamp(0.0, 1.0)
+    }
+
+    // The cell under a screen position, laid out the way `draw` lays out the board
+    pub fn cell_at(&self, x: f32, y: f32, look: Look) -> Option<Cell> {
+        let grid = self.sim.map.grid;
+        let cell = board_layout(grid, look).cell_at(x, y);
+        grid.contains(cell).then_some(cell)
+    }
+
+    // The board goes through the offscreen canvas; the banner and HUD are drawn over it at
+    // window resolution
+    pub fn draw(&mut self, palette: &Palette, skin: &Skin, look: Look) {
+        let layout = board_layout(self.sim.map.grid, look);
+        draw_board_canvas(&layout, look.filter, || self.draw_board(palette, skin, look, layout.tile));
+        self.popups.draw_banner(get_time() as f32, palette);
+        self.draw_hud(palette, look.hud);
+    }
+
+    // Everything on the grid, in canvas pixels of `tile` per cell
+    fn draw_board(&mut self, palette: &Palette, skin: &Skin, look: Look, tile: f32) {
+        let grid = self.sim.map.grid;
+        let (tile_w, tile_h, off_x, off_y) = (tile, tile, 0.0, 0.0);
+
+        // The food's glow on the floor around it, brighter at night
+        let glow_alpha = if look.lighting { 0.12 } else { 0.0 } + NIGHT_GLOW * self.darkness();
+        if glow_alpha > 0.0 && let Some(food) = self.sim.food {
+            let reach = GLOW_RADIUS.ceil() as i32;
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let c = Cell { x: food.x + dx, y: food.y + dy };
+                    let glow = (1.0 - ((dx * dx + dy * dy) as f32).sqrt() / GLOW_RADIUS).max(0.0) * self.visibility(c);
+                    if glow <= 0.0 || !grid.contains(c) { continue; }
+                    draw_rectangle(off_x + c.x as f32 * tile_w, off_y + c.y as f32 * tile_h, tile_w, tile_h, Color { a: glow_alpha * glow, ..palette.food });
+                }
+            }
+        }
+
+        // Draw walls: from the cached layer while every wall looks the same frame to frame,
+        // glyph by glyph while fog, lighting, night, other floors or a phasing snake make
+        // some of them differ
+        let draw_walls = || {
+            for c in &self.sim.map.walls {
+                // A snake phasing through is drawn instead
+                if self.sim.occupied.contains(c) { continue; }
+                let seen = self.shade(*c, look);
+                if seen <= 0.0 { continue; }
+                let ch = matrix_char_for_cell(*c);
+                let dim = if self.corpses.contains(c) { 0.45 } else { 1.0 };
+                draw_glyph_at_cell_scaled(ch, *c, Color { a: palette.wall.a * seen * dim, ..palette.wall }, tile_w, tile_h, off_x, off_y);
+            }
+        };
+        if self.walls_static(look) {
+            draw_wall_layer(self.wall_key(palette, tile), tile * grid.width as f32, tile * grid.height as f32, draw_walls);
+        } else {
+            draw_walls();
+        }
+        // Shed tail segments fade as they crumble
+        if let Some(cap) = self.sim.length_cap {
+            for (c, ticks_left) in &self.sim.map.shed {
+                if self.sim.occupied.contains(c) { continue; }
+                let left = *ticks_left as f32 / cap.lifetime as f32;
+                let color = Color { a: palette.body.a * self.shade(*c, look) * (0.3 + 0.7 * left), ..palette.body };
+                draw_glyph_at_cell_scaled(matrix_char_for_cell(*c), *c, color, tile_w, tile_h, off_x, off_y);
+            }
+        }
+        let shade = |c: Cell| if self.sim.occupied.contains(&c) { 0.0 } else { self.shade(c, look) };
+        draw_gates(&self.sim.map, palette, shade, tile_w, tile_h, off_x, off_y);
+        draw_conveyors(&self.sim.map, palette, shade, tile_w, tile_h, off_x, off_y);
+        draw_switches(&self.sim.map, shade, tile_w, tile_h, off_x, off_y);
+        draw_stairs(&self.sim.map, palette, shade, tile_w, tile_h, off_x, off_y);
+
+        // Trail left by the tail, fading out
+        let t = if self.sim.alive { self.alpha() } else { 1.0 };
+        if skin.trail == Trail::Fade {
+            let len = self.sim.snake.len();
+            for (cell, tick) in &self.trail {
+                let age = (self.replay.ticks - tick) as f32 + t;
+                let mut color = if look.body_gradient { skin.aged_color(len - 1, len as f32, len, palette) } else { skin.color(len - 1, len, palette) };
+                color.a *= 0.5 * (1.0 - age / TRAIL_TICKS as f32).max(0.0) * self.shade(*cell, look);
+                draw_glyph_at_cell_scaled(matrix_char_for_cell(*cell), *cell, color, tile_w, tile_h, off_x, off_y);
+            }
+        }
+
+        // Draw snake in the skin's glyphs and colors, sliding each segment from where it was on the last tick
+        let len = self.sim.snake.len();
+        for (i, (c, ch)) in self.sim.snake.iter().zip(self.body_chars.iter()).enumerate() {
+            // Segments that just took the stairs appear at the other end rather than slide there
+            let from = self.prev_snake.get(i).copied().filter(|f| (f.x - c.x).abs() + (f.y - c.y).abs() <= 1).unwrap_or(*c);
+            let pos = (from.x as f32 + (c.x - from.x) as f32 * t, from.y as f32 + (c.y - from.y) as f32 * t);
+            let mut color = if look.body_gradient { skin.aged_color(i, i as f32 + t, len, palette) } else { skin.color(i, len, palette) };
+            if look.lighting { color.a *= self.light(*c); }
+            color.a *= self.ghost(*c) * self.night(*c);
+            draw_glyph_at_pos_scaled(skin.glyph(i, *ch), pos, color, tile_w, tile_h, off_x, off_y);
+        }
+
+        // Draw patrollers
+        if let Some(waves) = &self.sim.waves {
+            for p in &waves.patrollers {
+                let seen = self.shade(p.at, look);
+                draw_glyph_at_cell_scaled('X', p.at, Color { a: seen, ..palette.food }, tile_w, tile_h, off_x, off_y);
+            }
+        }
+
+        // Premium food pulses, and blinks once it's about to go
+        if let Some(premium) = &self.sim.premium && let Some(at) = premium.at {
+            let time = get_time() as f32;
+            let blink = premium.ticks_left < PREMIUM_TICKS_WARN && (time * 6.0) as i64 % 2 == 0;
+            let seen = self.shade(at, look) * if blink { 0.2 } else { 0.7 + 0.3 * (time * 5.0).sin() };
+            draw_glyph_at_cell_scaled('$', at, Color { a: seen, ..palette.head }, tile_w, tile_h, off_x, off_y);
+        }
+
+        // Power-ups blink like premium food before they go
+        if let Some(powerups) = &self.sim.powerups && let Some((kind, at)) = powerups.at {
+            let blink = powerups.ticks_left < PREMIUM_TICKS_WARN && (get_time() * 6.0) as i64 % 2 == 0;
+            let seen = self.shade(at, look) * if blink { 0.2 } else { 1.0 };
+            draw_glyph_at_cell_scaled(kind.glyph(), at, Color { a: seen, ..palette.head }, tile_w, tile_h, off_x, off_y);
+        }
+
+        // Glitch food passes for the real thing at a glance: the same glyph, tinted red, that
+        // now and then flickers to another
+        if let Some(at) = self.sim.glitch.as_ref().and_then(|g| g.at) {
+            let time = get_time() as f32;
+            let ch = if (time * 1.7).fract() < 0.08 { matrix_char_for_cell(at) } else { self.food_char };
+            let tint = Color { r: (palette.food.r + GLITCH_TINT).min(1.0), g: palette.food.g * (1.0 - GLITCH_TINT), b: palette.food.b * (1.0 - GLITCH_TINT), a: palette.food.a * self.shade(at, look) };
+            draw_glyph_at_cell_scaled(ch, at, tint, tile_w, tile_h, off_x, off_y);
+        }
+
+        // Draw food glyph
+        if let Some(food) = self.sim.food {
+            let seen = self.visibility(food);
+            draw_glyph_at_cell_scaled(self.food_char, food, Color { a: palette.food.a * seen, ..palette.food }, tile_w, tile_h, off_x, off_y);
+        }
+
+        // Chain foods in their kinds' colours, the one to eat next pulsing
+        if let Some(chain) = &self.sim.chain {
+            let pulse = 0.7 + 0.3 * (get_time() as f32 * 5.0).sin();
+            for (kind, (at, color)) in chain.foods.iter().zip(CHAIN_COLORS).enumerate() {
+                let Some(at) = *at else { continue; };
+                let seen = self.visibility(at) * if chain.next() == Some(kind) { pulse } else { 1.0 };
+                draw_glyph_at_cell_scaled(self.food_char, at, Color { a: seen, ..color }, tile_w, tile_h, off_x, off_y);
+            }
+        }
+
+        self.popups.draw(get_time() as f32, palette, tile_w, tile_h, off_x, off_y);
+    }
+
+    // Game over: a faint tint over every cell the snake passed through, stronger the longer it
+    // was there, and a red X where the run ended. Drawn at window resolution over the board.
+    pub fn draw_heatmap(&self, palette: &Palette, look: Look) {
+        let grid = self.sim.map.grid;
+        let dest = board_layout(grid, look).dest;
+        let (tile_w, tile_h) = (dest.w / grid.width as f32, dest.h / grid.height as f32);
+        let most = self.visits.iter().copied().max().unwrap_or(0);
+        if most > 0 {
+            for (i, n) in self.visits.iter().enumerate().filter(|(_, n)| **n > 0) {
+                let (x, y) = ((i as i32 % grid.width) as f32, (i as i32 / grid.width) as f32);
+                let heat = (*n as f32 / most as f32).sqrt();
+                draw_rectangle(dest.x + x * tile_w, dest.y + y * tile_h, tile_w, tile_h, Color { a: HEATMAP_ALPHA * heat, ..palette.head });
+            }
+        }
+        if let Some(at) = self.death_at {
+            draw_glyph_at_cell_scaled('X', at, RED, tile_w, tile_h, dest.x, dest.y);
+        }
+    }
+
+    // One-switch controls: an arrow on the cell the head moves into next, bright while a
+    // pressed turn waits for the tick. Drawn at window resolution over the board.
+    pub fn draw_next_direction(&self, palette: &Palette, look: Look) {
+        if !self.sim.alive { return; }
+        let grid = self.sim.map.grid;
+        let dest = board_layout(grid, look).dest;
+        let (tile_w, tile_h) = (dest.w / grid.width as f32, dest.h / grid.height as f32);
+        let dir = self.sim.next_direction;
+        let arrow = match dir {
+            Direction::Up => '^',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+            Direction::Right => '>',
+        };
+        let color = if dir != self.sim.direction { palette.head } else { Color { a: 0.4, ..palette.head } };
+        draw_glyph_at_cell_scaled(arrow, self.sim.head().step(dir), color, tile_w, tile_h, dest.x, dest.y);
+    }
+
+    // Casual assist: dim dots along the shortest way to the food, around walls and the body,
+    // searched again each frame so it follows every tick
+    pub fn draw_path_hint(&self, palette: &Palette, look: Look) {
+        if !self.sim.alive { return; }
+        let sim = &self.sim;
+        let grid = sim.map.grid;
+        let dest = board_layout(grid, look).dest;
+        let (tile_w, tile_h) = (dest.w / grid.width as f32, dest.h / grid.height as f32);
+        let glitch = sim.glitch.as_ref().and_then(|g| g.at);
+        let food: Vec<Cell> = sim.food.into_iter().chain(sim.premium.as_ref().and_then(|p| p.at)).collect();
+        let path = ai::path(grid, sim.head(), sim.direction, &food, |c| {
+            sim.map.is_wall(c) || sim.occupied.contains(&c) || glitch == Some(c)
+        });
+        // The food draws itself at the end of it
+        let steps = path.len().saturating_sub(1);
+        for c in &path[..steps] {
+            draw_glyph_at_cell_scaled('.', *c, Color { a: PATH_HINT_ALPHA, ..palette.head }, tile_w, tile_h, dest.x, dest.y);
+        }
+    }
+
+    // Sandbox practice draws its own. Lines are laid out as if in the top-left corner, then
+    // the block moves to `corner`; the mission panel takes the other corner on that edge
+    fn draw_hud(&self, palette: &Palette, corner: HudCorner) {
+        if self.practice { return; }
+        let hints = &config().hints;
+        let status = if self.sim.alive { &hints.playing } else { &hints.game_over };
+        // (text, baseline, size, color)
+        let mut score = trf!("Score: {}  x{}", self.sim.score, format!("{:.2}", self.replay.score_multiplier()));
+        if let Some(phase) = self.sim.phase { score = format!("{}  {}", score, trf!("Phase: {}/{}", phase.charges, MAX_PHASE_CHARGES)); }
+        if let Some(glitch) = self.sim.glitch.as_ref().filter(|g| g.inverted > 0) {
+            let left = glitch.inverted as f32 * self.tick_interval();
+            score = format!("{}  {}", score, trf!("INVERTED {}s", format!("{:.0}", left.ceil())));
+        }
+        if let Some(cap) = self.sim.length_cap { score = format!("{}  {}", score, trf!("Length: {}/{}", self.sim.snake.len(), cap.cap)); }
+        let mut lines = vec![
+            (score, 16.0, 24.0, palette.body),
+            (tr(status).to_string(), 36.0, 18.0, palette.wall),
+        ];
+        if let Some(waves) = &self.sim.waves {
+            let eaten = waves.scores.last().copied().unwrap_or(0);
+            let next = Waves::hazard(waves.wave + 1).map_or("", |h| tr(h.label()));
+            lines.push((trf!("Wave {}  {}/{}  Next: {}", waves.wave, eaten, WAVE_FOOD, next), 56.0, 18.0, palette.body));
+        }
+        if let Some(modifier) = self.replay.modifier {
+            lines.push((trf!("Modifier: {}", tr(modifier.label())), 72.0, 18.0, palette.food));
+        }
+        if let Some(daylight) = self.sim.daylight {
+            let status = &mut lines[1].0;
+            *status = format!("{}  {}", tr(if daylight.is_night() { "Night" } else { "Day" }), status);
+        }
+        if let Some(floor) = self.sim.map.floor_of(self.sim.head()) {
+            let status = &mut lines[1].0;
+            *status = format!("{}  {}", trf!("Floor {}/{}", floor + 1, self.sim.map.floors.len()), status);
+        }
+        if self.sim.chain.is_some() {
+            lines.push((tr("Order:").to_string(), CHAIN_ORDER_Y, 18.0, palette.body));
+        }
+        if let Some(decay) = self.sim.decay {
+            let left = decay.seconds_left(self.tick_interval());
+            let color = if left <= 3.0 { palette.food } else { palette.body };
+            lines.push((trf!("Decay in {}s  Peak: {}", format!("{:.0}", left.ceil()), decay.peak), 90.0, 18.0, color));
+        }
+        let (mut width, mut height) = (0.0, lines.last().map_or(0.0, |(_, y, _, _)| *y));
+        if self.sim.hunger.is_some() {
+            width = HUNGER_BAR_WIDTH + 8.0 + measure_text(tr("STARVING"), 18.0).width;
+            height = f32::max(height, HUNGER_BAR_Y + 8.0);
+        }
+        // The combo meter goes under everything else while a combo is going
+        let meter = (self.combo >= 2).then(|| {
+            let label = format!("x{}", self.combo);
+            width = f32::max(width, COMBO_BAR_WIDTH + 8.0 + measure_text(&label, COMBO_TEXT_SIZE).width);
+            let y = height + COMBO_TEXT_SIZE;
+            height = y + 4.0;
+            (label, y)
+        });
+        let order = if self.sim.chain.is_some() { measure_text(tr("Order:"), 18.0).width + 8.0 + ORDER_STEP * CHAIN_COLORS.len() as f32 } else { 0.0 };
+        let width = lines.iter().map(|(text, _, size, _)| measure_text(text, *size).width).fold(width.max(order), f32::max);
+        let at = corner.origin(vec2(width + 16.0, height + 8.0));
+        for (text, y, size, color) in &lines {
+            draw_text(text, at.x + 8.0, at.y + y, *size, *color);
+        }
+        if let Some(hunger) = self.sim.hunger { draw_hunger_bar(&hunger, palette, at); }
+        if let Some(chain) = &self.sim.chain { draw_chain_order(chain, at); }
+        if let Some((label, y)) = meter {
+            // Drains between ticks too, so it moves smoothly rather than a tick at a time
+            let fill = ((self.combo_left - self.accumulator) / COMBO_WINDOW).clamp(0.0, 1.0);
+            draw_combo_meter(&label, fill, palette, at + vec2(0.0, y));
+        }
+        if let Some(missions) = &self.sim.missions { draw_mission_panel(missions, self.sim.snake.len(), palette, corner.across()); }
+    }
+}
+
+// The current mission, how far along it is and the bonus it pays, in `corner`
+fn draw_mission_panel(missions: &Missions, length: usize, palette: &Palette, corner: HudCorner) {
+    let (goal, progress) = match missions.objective {
+        Objective::EatWithin { food, seconds } => (
+            trf!("Eat {} food in {} seconds", food, seconds),
+            format!("{}/{}  {:.0}s", missions.progress, food, missions.seconds_left().unwrap_or(0.0).ceil()),
+        ),
+        Objective::ReachLength { length: goal } => (
+            trf!("Reach length {} without touching the edge", goal),
+            format!("{}/{}", length, goal),
+        ),
+        Objective::Streak { food, moves } => (
+            trf!("Eat {} food in a row, each within {} moves", food, moves),
+            format!("{}/{}  {}", missions.progress, food, moves.saturating_sub(missions.since_food)),
+        ),
+    };
+    let lines = [
+        (trf!("MISSION  +{}", missions.objective.bonus()), palette.head),
+        (goal, WHITE),
+        (progress, palette.body),
+        (trf!("Completed: {}", missions.completed), GRAY),
+    ];
+    let width = lines.iter().map(|(t, _)| measure_text(t, 16.0).width).fold(0.0, f32::max) + 16.0;
+    let height = 16.0 + lines.len() as f32 * 18.0;
+    let at = corner.origin(vec2(width + 16.0, height + 16.0)) + vec2(8.0, 8.0);
+    draw_rectangle(at.x, at.y, width, height, Color::new(0.0, 0.0, 0.0, 0.6));
+    for (i, (text, color)) in lines.iter().enumerate() {
+        draw_text(text, at.x + 8.0, at.y + 18.0 + i as f32 * 18.0, 16.0, *color);
+    }
+}
+
+const CHAIN_ORDER_Y: f32 = 90.0;
+const ORDER_STEP: f32 = 22.0;
+
+// The chain's kinds after the "Order:" label, first to last: eaten ones faint and the next
+// one underlined
+fn draw_chain_order(chain: &Chain, at: Vec2) {
+    let x = at.x + 8.0 + measure_text(tr("Order:"), 18.0).width + 8.0;
+    let y = at.y + CHAIN_ORDER_Y;
+    for (kind, color) in CHAIN_COLORS.iter().enumerate() {
+        let left = x + kind as f32 * ORDER_STEP;
+        let alpha = if chain.foods[kind].is_some() { 1.0 } else { 0.2 };
+        draw_rectangle(left, y - 12.0, 14.0, 14.0, Color { a: alpha, ..*color });
+        if chain.next() == Some(kind) { draw_rectangle(left, y + 4.0, 14.0, 2.0, WHITE); }
+    }
+}
+
+const COMBO_BAR_WIDTH: f32 = 160.0;
+const COMBO_TEXT_SIZE: f32 = 36.0;
+
+// The combo timer as a bar draining toward the next meal's deadline, going from the body colour
+// through gold to the food colour as it runs out, with the combo count in large text beside it.
+// `at` is the text baseline at the HUD's left edge.
+fn draw_combo_meter(label: &str, fill: f32, palette: &Palette, at: Vec2) {
+    let (x, y, w, h) = (at.x + 8.0, at.y - 14.0, COMBO_BAR_WIDTH, 10.0);
+    let color = if fill > 0.5 {
+        palette.body
+    } else if fill > 0.25 {
+        GOLD
+    } else if fill > 0.1 || (get_time() * 8.0) as i64 % 2 == 0 {
+        palette.food
+    } else {
+        Color { a: 0.3, ..palette.food }
+    };
+    draw_rectangle(x, y, w, h, Color::new(1.0, 1.0, 1.0, 0.15));
+    draw_rectangle(x, y, w * fill, h, color);
+    draw_text(label, x + w + 8.0, at.y, COMBO_TEXT_SIZE, color);
+}
+
+const HUNGER_BAR_WIDTH: f32 = 160.0;
+const HUNGER_BAR_Y: f32 = 46.0;
+
+// Refills on every meal; flashes once the moves have run out and the tail is starving. Sits
+// under the status line of a HUD whose top-left is `at`
+fn draw_hunger_bar(hunger: &Hunger, palette: &Palette, at: Vec2) {
+    let (x, y, w, h) = (at.x + 8.0, at.y + HUNGER_BAR_Y, HUNGER_BAR_WIDTH, 8.0);
+    let fill = if hunger.limit == 0 { 0.0 } else { hunger.moves_left as f32 / hunger.limit as f32 };
+    draw_rectangle(x, y, w, h, Color::new(1.0, 1.0, 1.0, 0.15));
+    let color = if fill < 0.25 { palette.food } else { palette.body };
+    draw_rectangle(x, y, w * fill, h, color);
+    if hunger.moves_left == 0 && (get_time() * 4.0) as i64 % 2 == 0 {
+        draw_text(tr("STARVING"), x + w + 8.0, y + h, 18.0, palette.food);
+    }
+}
@@ -0,0 +1,34 @@
+// Graveyard modifier: where a snake dies, its body stays behind as walls for every later
+// attempt on the same map until the game is closed. Kept per map on `App`, never saved;
+// each run records the corpses it started with in its replay.
+use std::collections::HashMap;
+
+use crate::map::{Cell, Map};
+
+pub const MAX_CORPSE_CELLS: usize = 300; // per map; the oldest go first past this
+pub const CLEARANCE: i32 = 3; // corpses this close to the starting head are left out
+
+#[derive(Default)]
+pub struct Graveyard {
+    maps: HashMap<String, Vec<Cell>>, // map key -> corpse cells, oldest first
+}
+
+// The parameters the map was generated from, which stay put however the run edits it
+fn key(map: &Map) -> String {
+    format!("{}|{}|{}x{}|{}", map.style, map.seed, map.grid.width, map.grid.height, map.wall_density)
+}
+
+impl Graveyard {
+    pub fn bury(&mut self, map: &Map, body: impl IntoIterator<Item = Cell>) {
+        let cells = self.maps.entry(key(map)).or_default();
+        for c in body {
+            if !cells.contains(&c) { cells.push(c); }
+        }
+        let excess = cells.len().saturating_sub(MAX_CORPSE_CELLS);
+        cells.drain(..excess);
+    }
+
+    pub fn corpses(&self, map: &Map) -> &[Cell] {
+        self.maps.get(&key(map)).map_or(&[], |cells| cells.as_slice())
+    }
+}
@@ -0,0 +1,518 @@
+// UI translations. The English text is the key: `tr` looks it up in the current language's
+// table and falls back to the English when a line isn't translated. Templates mark their
+// arguments with `{}`, filled in order by `trf!`.
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Russian,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Russian];
+
+    // In the language itself, so it can be found whatever is currently shown
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Russian => "Русский",
+        }
+    }
+
+    // The built-in pixel font only covers ASCII
+    pub fn needs_unicode_font(self) -> bool {
+        self != Language::English
+    }
+
+    fn table(self) -> Option<&'static HashMap<&'static str, &'static str>> {
+        static RUSSIAN: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+        match self {
+            Language::English => None,
+            Language::Russian => Some(RUSSIAN.get_or_init(|| RU.iter().copied().collect())),
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Language> = const { Cell::new(Language::English) };
+}
+
+// Set every frame from the settings in effect, so a change in Settings shows at once
+pub fn set_language(language: Language) {
+    CURRENT.with(|c| c.set(language));
+}
+
+pub fn language() -> Language {
+    CURRENT.with(|c| c.get())
+}
+
+pub fn tr(key: &str) -> &str {
+    language().table().and_then(|t| t.get(key).copied()).unwrap_or(key)
+}
+
+// Replaces each `{}` in `template` with the next argument
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len() + 16);
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() { out.push_str(first); }
+    for part in parts {
+        if let Some(arg) = args.next() { out.push_str(&arg.to_string()); }
+        out.push_str(part);
+    }
+    out
+}
+
+// trf!("Best: {}", best) - the translated template with its arguments filled in
+macro_rules! trf {
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::tr($key), &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+pub(crate) use trf;
+
+// Russian, grouped roughly by screen
+const RU: &[(&str, &str)] = &[
+    ("LEVEL {}", "УРОВЕНЬ {}"),
+    ("BOARD CLEARED", "ПОЛЕ ЗАПОЛНЕНО"),
+    ("Score: {}  x{}", "Счёт: {}  x{}"),
+    ("STARVING", "ГОЛОД"),
+    ("Arrows/WASD to move", "Стрелки/WASD - движение"),
+    ("Game Over - R to restart, Enter to lobby", "Конец игры - R: заново, Enter: в меню"),
+    ("GO!", "ВПЕРЁД!"),
+    ("Chat votes #{}", "Голоса чата #{}"),
+    ("Up", "Вверх"),
+    ("Down", "Вниз"),
+    ("Left", "Влево"),
+    ("Right", "Вправо"),
+    ("Hold Q to quit", "Удерживайте Q для выхода"),
+    (" (+{} more)", " (и ещё {})"),
+    ("Config: {}{}", "Конфиг: {}{}"),
+    ("Mods: {}{}", "Моды: {}{}"),
+    ("Screenshot saved: {}", "Скриншот сохранён: {}"),
+    ("Screenshot failed: {}", "Не удалось сделать скриншот: {}"),
+    ("Quit?", "Выйти?"),
+    ("Sound could not be loaded", "Не удалось загрузить звук"),
+    ("Something went wrong", "Что-то пошло не так"),
+    ("The game will play without sound.", "Игра продолжится без звука."),
+    ("That screen was closed. An unfinished run can be resumed from the lobby.", "Этот экран закрыт. Незаконченный забег можно продолжить из лобби."),
+    ("Details are in {}", "Подробности в {}"),
+    ("Enter: Continue  Esc: Quit", "Enter: Продолжить  Esc: Выйти"),
+    ("Y: Quit   N: Stay", "Y: Выйти   N: Остаться"),
+    ("Classic", "Классика"),
+    ("Twitch", "Twitch"),
+    ("Hunger", "Голод"),
+    ("Waves", "Волны"),
+    ("Scattered", "Россыпь"),
+    ("Caves", "Пещеры"),
+    ("Gates", "Шлюзы"),
+    ("Switches", "Переключатели"),
+    ("Floors", "Этажи"),
+    ("Matrix", "Матрица"),
+    ("Amber", "Янтарь"),
+    ("Ice", "Лёд"),
+    ("Fullscreen", "Полный экран"),
+    ("Windowed", "В окне"),
+    ("Stream window", "Окно для трансляции"),
+    ("Background", "Фон"),
+    ("Solid color", "Сплошной цвет"),
+    ("Chroma green", "Хромакей: зелёный"),
+    ("Chroma blue", "Хромакей: синий"),
+    ("Chroma magenta", "Хромакей: пурпурный"),
+    ("HUD corner", "Угол интерфейса"),
+    ("Top left", "Слева сверху"),
+    ("Top right", "Справа сверху"),
+    ("Bottom left", "Слева снизу"),
+    ("Bottom right", "Справа снизу"),
+    ("Replace", "Заменить"),
+    ("Merge", "Объединить"),
+    ("On", "Вкл"),
+    ("Off", "Выкл"),
+    ("Esc: Back", "Esc: Назад"),
+    ("Up/Down: Scroll  Esc: Back", "Вверх/Вниз: Прокрутка  Esc: Назад"),
+    ("Exported to {}", "Экспортировано в {}"),
+    ("Export failed: {}", "Ошибка экспорта: {}"),
+    ("File: {}{}", "Файл: {}{}"),
+    ("SNAKE", "ЗМЕЙКА"),
+    ("Start", "Старт"),
+    ("Resume last run", "Продолжить забег"),
+    ("score {}", "счёт {}"),
+    ("Map style", "Тип карты"),
+    ("Wall density", "Плотность стен"),
+    ("Quit", "Выход"),
+    ("Up/Down: Select  Left/Right: Adjust  Enter/Click: Activate", "Вверх/Вниз: Выбор  Влево/Вправо: Изменить  Enter/Клик: Выбрать"),
+    ("Grid: {}x{}", "Поле: {}x{}"),
+    ("S: Settings  P: Replays  W: Watch replay  T: High scores  V: Recent seeds  L: Leaderboard", "S: Настройки  P: Повторы  W: Смотреть  T: Рекорды  V: Недавние сиды  L: Рейтинг"),
+    ("D: Daily seed  K: Weekly  M: Mode  G: Map style  E: Export map  N: Versus  B: Battle royale", "D: Сид дня  K: Неделя  M: Режим  G: Карта  E: Экспорт  N: Дуэль  B: Королевская битва"),
+    ("BATTLE ROYALE", "КОРОЛЕВСКАЯ БИТВА"),
+    ("Rivals: < {} >", "Соперники: < {} >"),
+    ("Last snake alive wins; when time runs out, the top score among the survivors", "Побеждает последняя выжившая змейка; по истечении времени - лучший счёт среди выживших"),
+    ("Left/Right: Rivals  Enter: Start  Esc: Back", "Влево/Вправо: Соперники  Enter: Старт  Esc: Назад"),
+    ("You win the battle royale!", "Вы победили в королевской битве!"),
+    ("{} wins", "Победа: {}"),
+    ("Rival {}", "Соперник {}"),
+    ("Alive: {}/{}  Time left: {}s", "Живы: {}/{}  Осталось: {} с"),
+    ("STANDINGS", "ПОЛОЖЕНИЕ"),
+    ("  (out)", "  (выбыл)"),
+    ("You're out - watching the rest play it out", "Вы выбыли - досматриваем до конца"),
+    ("Esc: Leave", "Esc: Выйти"),
+    ("J: Co-op  U: Tournament  X: Sandbox  H: Help  I: Stats  A: Achievements  O: About", "J: Вдвоём  U: Турнир  X: Песочница  H: Помощь  I: Статистика  A: Достижения  O: Об игре"),
+    ("Score: {}  Best: {}", "Счёт: {}  Рекорд: {}"),
+    ("PRACTICE  Speed: {}ms  Rewind: {} ticks", "ТРЕНИРОВКА  Скорость: {} мс  Перемотка: {} ходов"),
+    ("Click: Wall  Right-click: Food  Space: Pause  Backspace: Rewind  -/=: Speed  R: Restart  Esc: Back", "Клик: Стена  Правый клик: Еда  Пробел: Пауза  Backspace: Назад во времени  -/=: Скорость  R: Заново  Esc: Назад"),
+    ("Crashed - hold Backspace to rewind or press R to restart", "Столкновение - удерживайте Backspace для перемотки или нажмите R"),
+    ("Paused - press a direction or Space to go", "Пауза - нажмите направление или Пробел"),
+    ("TOURNAMENT", "ТУРНИР"),
+    ("Everyone plays seed {} once; the best score wins", "Каждый играет сид {} один раз; побеждает лучший счёт"),
+    ("Name can't be empty", "Имя не может быть пустым"),
+    ("That name is too long", "Слишком длинное имя"),
+    ("The tournament is full", "Мест в турнире больше нет"),
+    ("That name is already taken", "Это имя уже занято"),
+    ("Enter: Add player, or start on an empty line  Backspace: Remove  Esc: Back", "Enter: Добавить игрока, на пустой строке - начать  Backspace: Убрать  Esc: Назад"),
+    ("Type a name and press Enter (2-8 players)  Esc: Back", "Введите имя и нажмите Enter (2-8 игроков)  Esc: Назад"),
+    ("Next up: {} - pass the keyboard", "Следующий: {} - передайте клавиатуру"),
+    ("Enter: Play  Esc: Abandon tournament", "Enter: Играть  Esc: Прервать турнир"),
+    ("PODIUM", "ПЬЕДЕСТАЛ"),
+    ("Enter: Back to lobby", "Enter: В лобби"),
+    ("{}'s turn", "Ход: {}"),
+    ("Press a direction to start", "Нажмите направление, чтобы начать"),
+    ("Player {} crashed", "Игрок {} разбился"),
+    ("Both snakes crashed", "Обе змейки разбились"),
+    ("P{}: {}", "И{}: {}"),
+    ("{} - new co-op best!  R: Restart  Enter: Back", "{} - новый рекорд вдвоём!  R: Заново  Enter: Назад"),
+    ("{}  R: Restart  Enter: Back", "{}  R: Заново  Enter: Назад"),
+    ("P1: WASD  P2: Arrow keys  -  move to start", "И1: WASD  И2: Стрелки  -  начните движение"),
+    ("Best: {}  This week: {}", "Рекорд: {}  За неделю: {}"),
+    ("Snake skin", "Облик змейки"),
+    ("Binary", "Двоичный"),
+    ("Ember", "Уголь"),
+    ("Ghost", "Призрак"),
+    ("Comet", "Комета"),
+    ("Unlock: achievement {}", "Открыть: достижение {}"),
+    ("Unlock: score {}", "Открыть: счёт {}"),
+    ("Skin unlocked: {}", "Открыт облик: {}"),
+    ("Weekly challenge", "Испытание недели"),
+    ("Mirror controls", "Зеркальное управление"),
+    ("Fog", "Туман"),
+    ("Modifier: {}", "Модификатор: {}"),
+    ("Score decay", "Утечка очков"),
+    ("Rule modifier", "Модификатор правил"),
+    ("Graveyard", "Кладбище"),
+    ("Day and night", "День и ночь"),
+    ("Length cap", "Предел длины"),
+    ("Food chain", "Цепочка еды"),
+    ("Graveyard modifier: where you die, your body stays as walls for the next tries on that map", "Кладбище: там, где вы погибли, тело остаётся стеной для следующих попыток на этой карте"),
+    ("Adaptive", "Адаптивный"),
+    ("Adaptive challenge", "Сложность адаптивного режима"),
+    ("Difficulty: {}%  Next run: {}% walls, {}ms", "Сложность: {}%  Следующий забег: стены {}%, {} мс"),
+    ("Adaptive - wall density and speed follow how well you play; Settings sets the challenge", "Адаптивный - плотность стен и скорость подстраиваются под игру; сложность задаётся в настройках"),
+    ("Decay in {}s  Peak: {}", "Потеря очка через {} с  Пик: {}"),
+    ("Peak score: {}  Lost to decay: {}", "Пик очков: {}  Потеряно: {}"),
+    ("New weekly challenge best!", "Новый рекорд испытания недели!"),
+    ("Submitting score...", "Отправка результата..."),
+    ("Score submitted", "Результат отправлен"),
+    ("Score not submitted: {}", "Результат не отправлен: {}"),
+    ("GIF saved: {}", "GIF сохранён: {}"),
+    ("Saving GIF failed: {}", "Не удалось сохранить GIF: {}"),
+    ("Saving GIF...", "Сохранение GIF..."),
+    ("YOU WIN", "ПОБЕДА"),
+    ("GAME OVER", "КОНЕЦ ИГРЫ"),
+    ("Achievement unlocked: {}", "Новое достижение: {}"),
+    ("NEW HIGH SCORE - #{}", "НОВЫЙ РЕКОРД - №{}"),
+    ("Name: {}{}", "Имя: {}{}"),
+    ("Type a name  Enter: Save  Esc: Skip", "Введите имя  Enter: Сохранить  Esc: Пропустить"),
+    ("R: Same map  Shift+R: New map  Enter/Esc: Lobby  Q: Quit", "R: Та же карта  Shift+R: Новая карта  Enter/Esc: В меню  Q: Выход"),
+    ("G: Save GIF of this run", "G: Сохранить GIF забега"),
+    ("Time: {}", "Время: {}"),
+    ("Time: {}  New best pace!", "Время: {}  Новый лучший темп!"),
+    ("HIGH SCORES", "РЕКОРДЫ"),
+    ("Name", "Имя"),
+    ("Score", "Счёт"),
+    ("Seed", "Сид"),
+    ("Map", "Карта"),
+    ("Walls", "Стены"),
+    ("Speed", "Шаг"),
+    ("Grid", "Поле"),
+    ("Mode", "Режим"),
+    ("Date", "Дата"),
+    ("No scores yet - finish a run to get on the table", "Рекордов пока нет - завершите забег"),
+    ("Up/Down: Select  Enter/Click: Play this seed again  Esc: Back", "Вверх/Вниз: Выбор  Enter/Клик: Сыграть этот сид  Esc: Назад"),
+    ("Loading...", "Загрузка..."),
+    ("No scores yet today", "Сегодня результатов ещё нет"),
+    ("DAILY LEADERBOARD", "РЕЙТИНГ ДНЯ"),
+    ("Today's seed: {}", "Сид дня: {}"),
+    ("R: Refresh  Esc: Back  (D in the lobby plays today's seed)", "R: Обновить  Esc: Назад  (D в меню - сыграть сид дня)"),
+    ("Saved {}", "Сохранено: {}"),
+    ("EXPORT MAP", "ЭКСПОРТ КАРТЫ"),
+    ("< {} px per tile >   {}x{} px", "< {} пикс. на клетку >   {}x{} пикс."),
+    ("{} map, seed {}", "Карта {}, сид {}"),
+    ("Left/Right: Scale  Enter: Save PNG  Esc: Back", "Влево/Вправо: Масштаб  Enter: Сохранить PNG  Esc: Назад"),
+    ("Seed {}  Score {}", "Сид {}  Счёт {}"),
+    ("REPLAYS", "ПОВТОРЫ"),
+    ("No replays yet - finish a run to record one", "Повторов пока нет - завершите забег"),
+    ("Up/Down: Select  Enter: Play  X: Export  C: Export as cast  Esc: Back", "Вверх/Вниз: Выбор  Enter: Смотреть  X: Экспорт  C: Экспорт в cast  Esc: Назад"),
+    ("WATCH REPLAY", "ПРОСМОТР ПОВТОРА"),
+    ("No .snakereplay files here - press F to type a path", "Файлов .snakereplay нет - нажмите F и введите путь"),
+    ("Up/Down: Select  F: Edit path  Enter: Verify and play  Esc: Back", "Вверх/Вниз: Выбор  F: Путь  Enter: Проверить и смотреть  Esc: Назад"),
+    ("  END", "  КОНЕЦ"),
+    ("  PAUSED", "  ПАУЗА"),
+    ("REPLAY  Tick {}/{}  Seed {}{}", "ПОВТОР  Такт {}/{}  Сид {}{}"),
+    ("Enter: Watch again  Left: Seek back  Esc: Back", "Enter: Ещё раз  Влево: Назад по времени  Esc: Назад"),
+    ("Space: Pause  Left/Right: Seek  Esc: Back", "Пробел: Пауза  Влево/Вправо: Перемотка  Esc: Назад"),
+    ("Imported save (best {})", "Сохранение импортировано (рекорд {})"),
+    ("SETTINGS", "НАСТРОЙКИ"),
+    ("Audio", "Звук"),
+    ("Video", "Экран"),
+    ("Controls", "Управление"),
+    ("Gameplay", "Игра"),
+    ("Accessibility", "Доступность"),
+    ("Data", "Данные"),
+    ("Volume", "Громкость"),
+    ("Music volume", "Громкость музыки"),
+    ("Theme", "Тема"),
+    ("Display", "Режим экрана"),
+    ("Body gradient", "Градиент тела"),
+    ("Lighting", "Освещение"),
+    ("Board scaling", "Масштабирование поля"),
+    ("Crisp", "Чёткое"),
+    ("Smooth", "Сглаженное"),
+    ("Power saving", "Энергосбережение"),
+    ("Matrix rain", "Цифровой дождь"),
+    ("Record GIF", "Запись GIF"),
+    ("Missions", "Задания"),
+    ("Premium food", "Премиум-еда"),
+    ("Power-ups", "Бонусы"),
+    ("Phase charges", "Фазовые заряды"),
+    ("Glitch food", "Глючная еда"),
+    ("The others, and phase charges, only show up when switched on in Settings > Gameplay", "Остальные, как и фазовые заряды, появляются, только если включены в Настройки > Игра"),
+    ("Eat {} food in {} seconds", "Съешьте {} еды за {} секунд"),
+    ("Reach length {} without touching the edge", "Дорастите до длины {}, не касаясь края"),
+    ("Eat {} food in a row, each within {} moves", "Съешьте {} еды подряд, каждую не дольше {} ходов"),
+    ("MISSION  +{}", "ЗАДАНИЕ  +{}"),
+    ("MISSION +{}", "ЗАДАНИЕ +{}"),
+    ("MISSION FAILED", "ЗАДАНИЕ ПРОВАЛЕНО"),
+    ("REVERSE", "РАЗВОРОТ"),
+    ("BOOM", "БУМ"),
+    ("Completed: {}", "Выполнено: {}"),
+    ("Speedrun timer", "Таймер спидрана"),
+    ("Twitch channel", "Канал Twitch"),
+    ("UI scale", "Масштаб интерфейса"),
+    ("Language", "Язык"),
+    ("One-switch controls", "Управление одной кнопкой"),
+    ("Space / click - Turn clockwise with One-switch controls on; press twice quickly to turn the other way", "Пробел / клик - поворот по часовой стрелке при управлении одной кнопкой; двойное нажатие - поворот в другую сторону"),
+    ("File", "Файл"),
+    ("Import mode", "Режим импорта"),
+    ("Export save to file", "Экспорт сохранения в файл"),
+    ("Import save from file", "Импорт сохранения из файла"),
+    ("Copy save to clipboard", "Копировать сохранение в буфер"),
+    ("Paste save from clipboard", "Вставить сохранение из буфера"),
+    ("Cloud sync URL", "Адрес облачной синхронизации"),
+    ("Cloud sync token", "Токен облачной синхронизации"),
+    ("Sync now", "Синхронизировать"),
+    ("Set a cloud sync URL first", "Сначала укажите адрес синхронизации"),
+    ("Syncing...", "Синхронизация..."),
+    ("Save synced with the cloud", "Сохранение синхронизировано с облаком"),
+    ("Sync URLs start with http:// or https://", "Адрес синхронизации начинается с http:// или https://"),
+    ("[Enter]", "[Enter]"),
+    ("(none)", "(нет)"),
+    ("press a key...", "нажмите клавишу..."),
+    ("Channel names use only letters, digits and _ (max 25)", "Имя канала: только буквы, цифры и _ (до 25)"),
+    ("That key can't be bound", "Эту клавишу нельзя назначить"),
+    ("Save copied to clipboard", "Сохранение скопировано в буфер обмена"),
+    ("Import failed: {}", "Ошибка импорта: {}"),
+    ("Clipboard is empty", "Буфер обмена пуст"),
+    ("Changes reverted", "Изменения отменены"),
+    ("Left/Right: Change", "Влево/Вправо: Изменить"),
+    ("Left/Right or drag: Change", "Влево/Вправо или перетащите: Изменить"),
+    ("Left/Right/Enter: Toggle", "Влево/Вправо/Enter: Переключить"),
+    ("Type, then Enter: Done", "Введите текст, Enter: Готово"),
+    ("Enter: Edit", "Enter: Изменить"),
+    ("Press the new alternate key  Esc: Cancel", "Нажмите новую доп. клавишу  Esc: Отмена"),
+    ("Enter: Rebind the alternate key", "Enter: Назначить доп. клавишу"),
+    ("Enter: Run", "Enter: Выполнить"),
+    ("Left/Right: Page  Down: Items", "Влево/Вправо: Страница  Вниз: Пункты"),
+    ("Tab: Next page  Up/Down: Select  R: Revert changes  M: Mute", "Tab: След. страница  Вверх/Вниз: Выбор  R: Отменить  M: Звук"),
+    ("H/F1: Help  Esc: Keep changes and go back", "H/F1: Помощь  Esc: Сохранить и выйти"),
+    ("STATS", "СТАТИСТИКА"),
+    ("Games played: {}", "Сыграно игр: {}"),
+    ("Food eaten: {}", "Съедено еды: {}"),
+    ("Play time: {}", "Время в игре: {}"),
+    ("Longest snake: {}  Longest run: {} ticks", "Самая длинная змейка: {}  Самый долгий забег: {} тактов"),
+    ("Day streak: {} (best {})  Boards cleared: {}", "Дней подряд: {} (лучшее {})  Полей заполнено: {}"),
+    ("Deaths by cause", "Причины гибели"),
+    ("Border", "Граница"),
+    ("Wall", "Стена"),
+    ("Own tail", "Свой хвост"),
+    ("Starved", "Голод"),
+    ("Patroller", "Патруль"),
+    ("Finish a run to start the counters", "Завершите забег, чтобы начать подсчёт"),
+    ("Tab: Recent runs  Esc: Back", "Tab: Последние забеги  Esc: Назад"),
+    ("RECENT RUNS", "ПОСЛЕДНИЕ ЗАБЕГИ"),
+    ("No runs logged yet", "Забегов пока нет"),
+    ("Ended by", "Причина"),
+    ("Time", "Время"),
+    ("Board cleared", "Поле заполнено"),
+    ("Up/Down: Select  Enter/Click: Play this setup again  Tab: Totals  Esc: Back", "Вверх/Вниз: Выбор  Enter/Клик: Сыграть снова  Tab: Итоги  Esc: Назад"),
+    ("{}h {}m", "{}ч {}м"),
+    ("{}m {}s", "{}м {}с"),
+    ("ACHIEVEMENTS", "ДОСТИЖЕНИЯ"),
+    ("{} of {} unlocked", "Открыто {} из {}"),
+    ("Unlocked {}", "Открыто {}"),
+    ("Locked", "Закрыто"),
+    ("First Bite", "Первый кусочек"),
+    ("Eat your first food", "Съешьте первую еду"),
+    ("Growing", "Подрастаем"),
+    ("Score 25 in one run", "Наберите 25 очков за забег"),
+    ("Serpent", "Змей"),
+    ("Score 50 in one run", "Наберите 50 очков за забег"),
+    ("Survivor", "Выживший"),
+    ("Last 1000 ticks in one run", "Продержитесь 1000 тактов за забег"),
+    ("Spelunker", "Спелеолог"),
+    ("Score 10 on a Caves map", "Наберите 10 очков на карте Пещеры"),
+    ("Daily Driver", "Каждый день"),
+    ("Finish a run on the daily seed", "Завершите забег на сиде дня"),
+    ("Clean Sweep", "Чистая работа"),
+    ("Clear a whole board", "Заполните всё поле"),
+    ("Running on Empty", "На пустой желудок"),
+    ("Starve in Hunger mode", "Умрите от голода в режиме Голод"),
+    ("Regular", "Завсегдатай"),
+    ("Play 50 games", "Сыграйте 50 игр"),
+    ("Glutton", "Обжора"),
+    ("Eat 1000 food", "Съешьте 1000 еды"),
+    ("Marathon", "Марафон"),
+    ("Play for an hour in total", "Проведите в игре час"),
+    ("Habit", "Привычка"),
+    ("Play 7 days in a row", "Играйте 7 дней подряд"),
+    ("unknown", "неизвестно"),
+    ("SNAKE - MATRIX STYLE", "ЗМЕЙКА - В СТИЛЕ МАТРИЦЫ"),
+    ("Version {}  (built {})", "Версия {}  (сборка {})"),
+    ("CONTROLS", "УПРАВЛЕНИЕ"),
+    ("Arrow keys / WASD - Steer", "Стрелки / WASD - поворот"),
+    ("Esc - Pause, or back in menus", "Esc - пауза или назад в меню"),
+    ("F3 - Debug overlay   F11 - Fullscreen   F12 - Screenshot", "F3 - отладка   F11 - полный экран   F12 - скриншот"),
+    ("Hold Q - Quit", "Удерживать Q - выход"),
+    ("LICENSE", "ЛИЦЕНЗИЯ"),
+    ("Open source: free to use and modify", "Открытый код: можно свободно использовать и изменять"),
+    ("BUILT WITH", "СДЕЛАНО С ПОМОЩЬЮ"),
+    ("macroquad - windowing, drawing and audio", "macroquad - окно, графика и звук"),
+    ("serde, serde_json and toml - saves and config", "serde, serde_json и toml - сохранения и конфиг"),
+    ("image and gif - screenshots and recordings", "image и gif - скриншоты и записи"),
+    ("rhai - mods (scripting feature)", "rhai - моды (функция scripting)"),
+    ("ureq - online leaderboard (online feature)", "ureq - онлайн-рейтинг (функция online)"),
+    ("THANKS", "СПАСИБО"),
+    ("Everyone who filed an issue or sent a patch", "Всем, кто сообщал об ошибках и присылал патчи"),
+    ("HOW TO PLAY", "КАК ИГРАТЬ"),
+    ("RULES", "ПРАВИЛА"),
+    ("Steer the snake to the food: each one scores a point and adds a segment", "Ведите змейку к еде: каждая даёт очко и новый сегмент"),
+    ("The snake never stops, and it can't reverse straight into itself", "Змейка не останавливается и не может развернуться назад"),
+    ("Fill every free cell with snake to clear the board and win", "Заполните змейкой все свободные клетки, чтобы победить"),
+    ("HAZARDS", "ОПАСНОСТИ"),
+    ("The border and the walls inside the map end the run", "Граница и стены на карте заканчивают забег"),
+    ("So does running into your own tail", "Как и столкновение со своим хвостом"),
+    ("Hunger mode: out of moves, the tail shrinks every {} ticks until you starve", "Голод: без еды хвост укорачивается каждые {} такта до гибели"),
+    ("PICKUPS", "ПРЕДМЕТЫ"),
+    ("Food: the glyph that differs from the snake", "Еда: символ, непохожий на змейку"),
+    ("$ - premium food worth {} points, now and then in a dead end or narrow corridor; it doesn't wait long", "$ - особая еда на {} очка, иногда в тупике или узком проходе; ждёт недолго"),
+    ("& - reverse: head and tail swap places, a way out of a dead end", "& - разворот: голова и хвост меняются местами, выход из тупика"),
+    ("% - bomb: destroys the walls within {} cells for the rest of the run", "% - бомба: до конца забега сносит стены в радиусе {} клеток"),
+    ("MODES", "РЕЖИМЫ"),
+    ("Classic - the plain game", "Классика - обычная игра"),
+    ("Twitch - a Twitch channel's chat votes on every turn", "Twitch - чат канала голосует за каждый поворот"),
+    ("Hunger - eat within the move budget shown in the HUD", "Голод - успейте поесть, пока не кончился запас ходов"),
+    ("Waves - every {} food starts a wave with a new hazard: more walls, a patroller or faster ticks", "Волны - каждые {} еды начинается волна с новой опасностью: больше стен, патруль или быстрее такты"),
+    ("Waves mode: a patroller (X) paces back and forth and kills on contact", "Волны: патруль (X) ходит туда-сюда и убивает при касании"),
+    ("Score decay modifier: a point is lost every {} seconds without food", "Утечка очков: каждые {} с без еды теряется очко"),
+    ("More walls", "Больше стен"),
+    ("Faster ticks", "Быстрее такты"),
+    ("Wave {}  {}/{}  Next: {}", "Волна {}  {}/{}  Дальше: {}"),
+    ("WAVE {}", "ВОЛНА {}"),
+    ("Reached wave {}  Per wave: {}", "Дошли до волны {}  По волнам: {}"),
+    ("KEYS", "КЛАВИШИ"),
+    ("Esc - Pause (S there opens settings) or go back a screen", "Esc - пауза (там S открывает настройки) или назад на экран"),
+    ("R - Retry the map after game over (Shift+R: a new map)   G - Save a GIF of the run", "R - эта карта заново после проигрыша (Shift+R: новая карта)   G - сохранить GIF забега"),
+    ("Q - Quit (hold it during a run)", "Q - выход (во время забега - удерживать)"),
+    ("Up/Down: Scroll (more below)  Esc: Back", "Вверх/Вниз: Прокрутка (ниже ещё)  Esc: Назад"),
+    ("Connected - waiting for the host...", "Подключено - ждём хоста..."),
+    ("Host runs netplay version {}, this game uses {}", "У хоста сетевая версия {}, у этой игры {}"),
+    ("Host uses map style '{}', which isn't installed here", "Хост использует карту '{}', которой здесь нет"),
+    ("Waiting for an opponent on port {}...", "Ждём соперника на порту {}..."),
+    ("Connecting to {}...", "Подключение к {}..."),
+    ("VERSUS", "ДУЭЛЬ"),
+    ("Mode: {}", "Режим: {}"),
+    ("Host", "Хост"),
+    ("Join", "Подключиться"),
+    ("Seed {}  Map {}  Density {}%  Speed {}ms  Port {}", "Сид {}  Карта {}  Стены {}%  Шаг {}мс  Порт {}"),
+    ("Address: {}{}", "Адрес: {}{}"),
+    ("Tab: Host/Join  F: Edit address  Enter: Start  Esc: Back", "Tab: Хост/Подключение  F: Адрес  Enter: Старт  Esc: Назад"),
+    ("Connection lost: {}", "Соединение потеряно: {}"),
+    ("no word from the opponent for {} seconds", "соперник молчит уже {} с"),
+    ("You win!", "Вы победили!"),
+    ("You lose", "Вы проиграли"),
+    ("Both crashed - you win on score!", "Разбились оба - победа по очкам!"),
+    ("Both crashed - you lose on score", "Разбились оба - поражение по очкам"),
+    ("Draw", "Ничья"),
+    ("Desync detected at tick {} - match stopped", "Рассинхронизация на такте {} - матч остановлен"),
+    ("Opponent left", "Соперник вышел"),
+    ("You", "Вы"),
+    ("Opponent", "Соперник"),
+    ("  (crashed)", "  (разбился)"),
+    ("{}  -  Enter: Back", "{}  -  Enter: Назад"),
+    ("Waiting for opponent...", "Ждём соперника..."),
+    ("Tick {}  Esc: Leave", "Такт {}  Esc: Выйти"),
+    ("SPECTATING  Score: {}", "ПРОСМОТР  Счёт: {}"),
+    ("R: Reconnect  Esc: Lobby", "R: Переподключиться  Esc: Лобби"),
+    ("Game over - waiting for the next run", "Игра окончена - ждём следующий забег"),
+    ("New best score: {}", "Новый рекорд: {}"),
+    ("Couldn't save: {}", "Не удалось сохранить: {}"),
+    ("Rain density", "Плотность дождя"),
+    ("Pause on focus loss", "Пауза при потере фокуса"),
+    ("Mute while paused", "Без звука на паузе"),
+    ("Paused", "Пауза"),
+    ("Cause of death: {}", "Причина смерти: {}"),
+    ("{}  Length: {}  Time: {}", "{}  Длина: {}  Время: {}"),
+    ("Food per minute: {}  Longest combo: {}  Distance: {} cells", "Еды в минуту: {}  Лучшее комбо: {}  Пройдено клеток: {}"),
+    ("RECENT SEEDS", "НЕДАВНИЕ СИДЫ"),
+    ("Best", "Лучший"),
+    ("Runs", "Забеги"),
+    ("Last played", "Последний раз"),
+    ("Up/Down: Select  Enter/Click: Load into the lobby  Esc: Back", "Вверх/Вниз: Выбор  Enter/Клик: Загрузить в меню  Esc: Назад"),
+    ("Score {} x{} = {}", "Счёт {} x{} = {}"),
+    ("Multiplier: x{}", "Множитель: x{}"),
+    ("Phase: {}/{}", "Фаза: {}/{}"),
+    ("PHASE x{}", "ФАЗА x{}"),
+    ("PHASE", "ФАЗА"),
+    ("SHUT", "ЗАКРЫТО"),
+    ("OPEN", "ОТКРЫТО"),
+    ("Shift+Click: Switch wall  Shift+Right-click: Plate  Tab: Switch colour ({})", "Shift+Клик: Стена переключателя  Shift+Правый клик: Плита  Tab: Цвет переключателя ({})"),
+    ("Floor {}/{}", "Этаж {}/{}"),
+    ("Day and night modifier: at night only the cells around the head and the food show, and food is worth {} points", "День и ночь: ночью видно только клетки у головы и у еды, а еда стоит {} очка"),
+    ("NIGHT", "НОЧЬ"),
+    ("DAY", "ДЕНЬ"),
+    ("Food is worth {} points", "Еда стоит {} очка"),
+    ("Night", "Ночь"),
+    ("Day", "День"),
+    ("Length: {}/{}", "Длина: {}/{}"),
+    ("Length cap modifier: past {} segments, each meal leaves the tail behind as a wall for {} seconds", "Предел длины: после {} сегментов каждая еда оставляет хвост стеной на {} секунд"),
+    ("Food chain modifier: three foods at once, eaten green, white, gold for {} points on top; out of order halves the combo", "Цепочка еды: три еды сразу; съеденные по порядку (зелёная, белая, золотая) дают ещё {} очка, не по порядку - комбо делится пополам"),
+    ("Order:", "Порядок:"),
+    ("COMBO HALVED", "КОМБО /2"),
+    ("Glitch food: a red-tinted copy of the food; it costs {} points and inverts your controls for a while", "Сбойная еда: красноватая копия еды; отнимает {} очка и ненадолго инвертирует управление"),
+    ("INVERTED {}s", "ИНВЕРСИЯ {}с"),
+    ("-{} INVERTED", "-{} ИНВЕРСИЯ"),
+    ("Press any key", "Нажмите любую клавишу"),
+    ("Path hint", "Подсказка пути"),
+    ("Assisted run: not ranked", "Пробег с подсказкой: не в таблице"),
+    ("Minimum window", "Минимальное окно"),
+    ("Window shape", "Форма окна"),
+    ("Letterbox", "С полосами"),
+    ("Stretch", "Растянуть"),
+    ("Rescale grid", "Подогнать сетку"),
+    ("Press any key to resume", "Нажмите любую клавишу, чтобы продолжить"),
+    ("S: Settings  Hold Q: Quit", "S: Настройки  Удерживайте Q: Выход"),
+];
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::config;
+use crate::net::{self, Pending};
+use crate::rng::fnv1a;
+use crate::replay::Replay;
+
+pub const TOP_COUNT: usize = 20;
+
+// Same seed for everybody on a given UTC day
+pub fn daily_seed() -> u64 {
+    let day = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() / 86_400).unwrap_or(0);
+    fnv1a(&day.to_le_bytes()) % 1_000_000_000
+}
+
+#[derive(Serialize)]
+pub struct Submission {
+    pub name: String,
+    pub score: u32,
+    pub seed: u64,
+    pub daily: bool,
+    pub settings_hash: String,
+    pub replay_checksum: String,
+}
+
+impl Submission {
+    pub fn from_replay(replay: &Replay) -> Self {
+        let settings = replay.settings_key();
+        let replay_json = serde_json::to_string(replay).unwrap_or_default();
+        Self {
+            name: config().online.player_name.clone(),
+            score: replay.score,
+            seed: replay.seed,
+            daily: replay.seed == daily_seed(),
+            settings_hash: format!("{:016x}", fnv1a(settings.as_bytes())),
+            replay_checksum: format!("{:016x}", fnv1a(replay_json.as_bytes())),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub score: u32,
+}
+
+// Only when the player opted in and configured an endpoint
+pub fn submissions_enabled() -> bool {
+    let online = &config().online;
+    online.submit_scores && !online.leaderboard_url.is_empty()
+}
+
+pub fn submit(replay: &Replay) -> Pending<()> {
+    let url = format!("{}/scores", config().online.leaderboard_url.trim_end_matches('/'));
+    let body = serde_json::to_string(&Submission::from_replay(replay)).unwrap_or_default();
+    Pending::spawn(move || net::post_json(&url, &body).map(|_| ()))
+}
+
+pub fn fetch_daily_top() -> Pending<Vec<Entry>> {
+    let base = config().online.leaderboard_url.trim_end_matches('/').to_string();
+    let url = format!("{}/scores?seed={}&limit={}", base, daily_seed(), TOP_COUNT);
+    Pending::spawn(move || {
+        if base.is_empty() { return Err("No leaderboard_url set in snake.toml".to_string()); }
+        let text = net::get(&url)?;
+        let mut entries: Vec<Entry> = serde_json::from_str(&text).map_err(|e| format!("Bad leaderboard response: {}", e))?;
+        entries.truncate(TOP_COUNT);
+        Ok(entries)
+    })
+}
@@ -0,0 +1,80 @@
+// Standalone picture of a map for sharing: a square of `scale` pixels per tile, drawn on the
+// CPU so the result doesn't depend on the window size
+use macroquad::prelude::Color;
+
+use crate::map::{Cell, Map};
+use crate::render::Palette;
+use crate::screenshot::{encode_png, save_png};
+use crate::sim::SnakeSim;
+
+pub const SCALES: [u32; 4] = [4, 8, 16, 32];
+// Larger pictures take too long to encode without a progress display
+const MAX_SIDE: u32 = 4096;
+
+// Pixels per tile that keep the picture of a map this size within MAX_SIDE
+pub fn scales_for(map: &Map) -> Vec<u32> {
+    let side = map.grid.width.max(map.grid.height) as u32;
+    let scales: Vec<u32> = SCALES.iter().copied().filter(|s| side * s <= MAX_SIDE).collect();
+    if scales.is_empty() { vec![SCALES[0]] } else { scales }
+}
+
+fn rgba(c: Color) -> [u8; 4] {
+    [(c.r * 255.0) as u8, (c.g * 255.0) as u8, (c.b * 255.0) as u8, 255]
+}
+
+fn dim(c: Color, amount: f32) -> Color {
+    Color::new(c.r * amount, c.g * amount, c.b * amount, 1.0)
+}
+
+struct Canvas {
+    pixels: Vec<u8>,
+    width: u32,
+    scale: u32,
+}
+
+impl Canvas {
+    // Fills a tile, leaving `inset` pixels of background around it
+    fn tile(&mut self, c: Cell, inset: u32, color: Color) {
+        let px = rgba(color);
+        let (x0, y0) = (c.x as u32 * self.scale, c.y as u32 * self.scale);
+        for y in y0 + inset..y0 + self.scale - inset {
+            for x in x0 + inset..x0 + self.scale - inset {
+                let i = ((y * self.width + x) * 4) as usize;
+                self.pixels[i..i + 4].copy_from_slice(&px);
+            }
+        }
+    }
+}
+
+// RGBA pixels of the map with its spawn area, starting snake and first food
+pub fn render(map: &Map, scale: u32, palette: &Palette) -> (Vec<u8>, u32, u32) {
+    let (width, height) = (map.grid.width as u32 * scale, map.grid.height as u32 * scale);
+    let mut canvas = Canvas { pixels: [0, 0, 0, 255].repeat((width * height) as usize), width, scale };
+    // Walls get a one-pixel gap once tiles are big enough to show it
+    let gap = u32::from(scale >= 8);
+
+    for y in 0..map.grid.height {
+        for x in 0..map.grid.width {
+            let c = Cell { x, y };
+            if map.is_wall(c) {
+                canvas.tile(c, gap, palette.wall);
+            } else if map.grid.in_spawn_area(c) {
+                canvas.tile(c, 0, dim(palette.body, 0.2));
+            }
+        }
+    }
+    let sim = SnakeSim::new(map.clone());
+    for (i, c) in sim.snake.iter().enumerate() {
+        canvas.tile(*c, gap, if i == 0 { palette.head } else { palette.body });
+    }
+    if let Some(food) = sim.food { canvas.tile(food, scale / 4, palette.food); }
+    (canvas.pixels, width, height)
+}
+
+// Saves the map as `screenshots/map_<style>_<seed>_<scale>x.png` and returns where it went
+pub fn export(map: &Map, scale: u32, palette: &Palette) -> Result<String, String> {
+    let (pixels, width, height) = render(map, scale, palette);
+    let png = encode_png(&pixels, width, height)?;
+    let style: String = map.style.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect();
+    save_png(&format!("map_{}_{}_{}x", style, map.seed, scale), &png)
+}
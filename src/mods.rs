@@ -0,0 +1,314 @@
+// Rule mods: rhai scripts in `mods/` with optional hooks
+//   on_tick(tick), on_food_eaten(score), on_death(cause)   cause: "border", "wall", "self" or "starved"
+// that can read score(), tick_count(), snake_length(), head_x(), head_y(), grid_width(),
+// grid_height(), speed_ms(), is_wall(x, y), random(n) and change the run with
+// spawn_food(x, y), add_wall(x, y), remove_wall(x, y), set_speed(ms).
+// A mod can also add a lobby map style by defining map_style() -> name and
+// generate_map(seed, width, height, density) -> array of [x, y] wall cells.
+use crate::game::SnakeGame;
+use crate::sim::SimEvent;
+
+pub const MODS_DIR: &str = "mods";
+
+#[cfg(feature = "scripting")]
+mod host {
+    use rhai::{Array, CallFnOptions, Dynamic, Engine, Scope, AST};
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+    use std::fs;
+    use std::rc::Rc;
+
+    use super::MODS_DIR;
+    use crate::game::SnakeGame;
+    use crate::logging;
+    use crate::map::{Cell, Map};
+    use crate::mapgen::{self, MapGenerator, MapParams};
+    use crate::rng::Rng;
+    use crate::sim::{DeathCause, SimEvent, MOD_STREAM};
+
+    // Keeps a runaway script (an endless loop in on_tick) from freezing the game
+    const MAX_OPERATIONS: u64 = 200_000;
+
+    // What a hook asked for; applied to the game once the hook returns
+    enum Command {
+        SpawnFood(Cell),
+        AddWall(Cell),
+        RemoveWall(Cell),
+        SetSpeed(f32),
+    }
+
+    // Game state visible to scripts during a hook, plus the commands they queue
+    #[derive(Default)]
+    struct Context {
+        score: i64,
+        tick: i64,
+        length: i64,
+        head_x: i64,
+        head_y: i64,
+        width: i64,
+        height: i64,
+        speed_ms: i64,
+        walls: HashSet<Cell>,
+        rng: Option<Rng>,
+        commands: Vec<Command>,
+    }
+
+    struct Mod {
+        name: String,
+        ast: AST,
+        broken: bool, // set after a runtime error so one bad mod doesn't flood the log
+    }
+
+    pub struct Host {
+        engine: Engine,
+        mods: Vec<Mod>,
+        ctx: Rc<RefCell<Context>>,
+    }
+
+    fn cell(x: i64, y: i64) -> Cell {
+        Cell { x: x as i32, y: y as i32 }
+    }
+
+    fn has_fn(ast: &AST, name: &str, params: usize) -> bool {
+        ast.iter_functions().any(|f| f.name == name && f.params.len() == params)
+    }
+
+    // Map style provided by the mod at `index`; runs its generate_map() through the host
+    struct ScriptGenerator {
+        name: String,
+        index: usize,
+    }
+
+    impl MapGenerator for ScriptGenerator {
+        fn name(&self) -> &str { &self.name }
+
+        fn generate(&self, seed: u64, params: &MapParams) -> Map {
+            let cells = HOST.with(|h| match h.try_borrow_mut() {
+                Ok(mut host) => host.as_mut().ok_or("mods not loaded".to_string())?.generate_map(self.index, seed, params),
+                Err(_) => Err("called from inside a hook".to_string()),
+            });
+            match cells {
+                Ok(cells) => {
+                    let grid = params.grid;
+                    let mut walls = Map::border_walls(grid);
+                    walls.extend(cells.into_iter().filter(|c| grid.contains(*c) && !grid.in_spawn_area(*c)));
+                    Map { walls, seed, wall_density: params.wall_density, grid, style: self.name.clone(), gates: Vec::new(), conveyors: Vec::new(), switches: Vec::new(), floors: Vec::new(), stairs: Vec::new(), shed: Vec::new(), solid: Vec::new() }
+                }
+                Err(e) => {
+                    logging::error(format!("Map style {} failed, using scattered walls: {}", self.name, e));
+                    Map::generate(params.grid, seed, params.wall_density)
+                }
+            }
+        }
+    }
+
+    impl Host {
+        fn new() -> Self {
+            let ctx = Rc::new(RefCell::new(Context::default()));
+            let mut engine = Engine::new();
+            engine.set_max_operations(MAX_OPERATIONS);
+            engine.on_print(|text| logging::info(format!("mod: {}", text)));
+
+            let c = ctx.clone();
+            engine.register_fn("score", move || c.borrow().score);
+            let c = ctx.clone();
+            engine.register_fn("tick_count", move || c.borrow().tick);
+            let c = ctx.clone();
+            engine.register_fn("snake_length", move || c.borrow().length);
+            let c = ctx.clone();
+            engine.register_fn("head_x", move || c.borrow().head_x);
+            let c = ctx.clone();
+            engine.register_fn("head_y", move || c.borrow().head_y);
+            let c = ctx.clone();
+            engine.register_fn("grid_width", move || c.borrow().width);
+            let c = ctx.clone();
+            engine.register_fn("grid_height", move || c.borrow().height);
+            let c = ctx.clone();
+            engine.register_fn("speed_ms", move || c.borrow().speed_ms);
+            let c = ctx.clone();
+            engine.register_fn("is_wall", move |x: i64, y: i64| c.borrow().walls.contains(&cell(x, y)));
+            // Seeded from the map and tick, so a modded run still replays identically
+            let c = ctx.clone();
+            engine.register_fn("random", move |n: i64| {
+                let mut ctx = c.borrow_mut();
+                match ctx.rng.as_mut() {
+                    Some(rng) if n > 0 => rng.range_usize(0, n as usize) as i64,
+                    _ => 0,
+                }
+            });
+            let c = ctx.clone();
+            engine.register_fn("spawn_food", move |x: i64, y: i64| c.borrow_mut().commands.push(Command::SpawnFood(cell(x, y))));
+            let c = ctx.clone();
+            engine.register_fn("add_wall", move |x: i64, y: i64| c.borrow_mut().commands.push(Command::AddWall(cell(x, y))));
+            let c = ctx.clone();
+            engine.register_fn("remove_wall", move |x: i64, y: i64| c.borrow_mut().commands.push(Command::RemoveWall(cell(x, y))));
+            let c = ctx.clone();
+            engine.register_fn("set_speed", move |ms: i64| c.borrow_mut().commands.push(Command::SetSpeed(ms as f32 / 1000.0)));
+
+            Self { engine, mods: Vec::new(), ctx }
+        }
+
+        // Compiles every `mods/*.rhai`, in file name order; returns the problems found
+        fn load_dir(&mut self) -> Vec<String> {
+            let Ok(entries) = fs::read_dir(MODS_DIR) else { return Vec::new(); };
+            let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.extension().is_some_and(|x| x == "rhai")).collect();
+            paths.sort();
+            let mut problems = Vec::new();
+            for path in paths {
+                let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                let compiled = fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| self.engine.compile(&text).map_err(|e| e.to_string()));
+                match compiled {
+                    Ok(ast) => {
+                        logging::info(format!("Mod loaded: {}", name));
+                        self.mods.push(Mod { name, ast, broken: false });
+                    }
+                    Err(e) => {
+                        logging::warn(format!("Mod {} not loaded: {}", name, e));
+                        problems.push(format!("mod {}: {}", name, e));
+                    }
+                }
+            }
+            problems
+        }
+
+        // Registers the map styles of mods defining both map_style() and generate_map()
+        fn register_styles(&self) -> Vec<String> {
+            let mut problems = Vec::new();
+            for (index, m) in self.mods.iter().enumerate() {
+                if !has_fn(&m.ast, "map_style", 0) || !has_fn(&m.ast, "generate_map", 4) { continue; }
+                let options = CallFnOptions::new().eval_ast(false);
+                let registered = self.engine
+                    .call_fn_with_options::<String>(options, &mut Scope::new(), &m.ast, "map_style", ())
+                    .map_err(|e| e.to_string())
+                    .and_then(|name| mapgen::register(Rc::new(ScriptGenerator { name, index })));
+                if let Err(e) = registered {
+                    logging::warn(format!("Mod {}: {}", m.name, e));
+                    problems.push(format!("mod {}: {}", m.name, e));
+                }
+            }
+            problems
+        }
+
+        fn generate_map(&mut self, index: usize, seed: u64, params: &MapParams) -> Result<Vec<Cell>, String> {
+            let m = self.mods.get(index).ok_or("mod missing")?;
+            let args = (seed as i64, params.grid.width as i64, params.grid.height as i64, params.wall_density as f64);
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self.engine.call_fn_with_options::<Array>(options, &mut Scope::new(), &m.ast, "generate_map", args).map_err(|e| e.to_string())?;
+            result
+                .into_iter()
+                .map(|item| {
+                    let pair = item.try_cast::<Array>().filter(|p| p.len() == 2).ok_or("generate_map must return [x, y] pairs")?;
+                    match (pair[0].as_int(), pair[1].as_int()) {
+                        (Ok(x), Ok(y)) => Ok(cell(x, y)),
+                        _ => Err("generate_map must return integer coordinates".to_string()),
+                    }
+                })
+                .collect()
+        }
+
+        fn call(&mut self, hook: &str, arg: Dynamic) {
+            for m in &mut self.mods {
+                if m.broken || !has_fn(&m.ast, hook, 1) { continue; }
+                let options = CallFnOptions::new().eval_ast(false);
+                let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &m.ast, hook, (arg.clone(),));
+                if let Err(e) = result {
+                    logging::error(format!("Mod {} disabled: {} failed: {}", m.name, hook, e));
+                    m.broken = true;
+                }
+            }
+        }
+
+        pub fn after_tick(&mut self, game: &mut SnakeGame, events: &[SimEvent]) {
+            if self.mods.is_empty() { return; }
+            let sim = &game.sim;
+            *self.ctx.borrow_mut() = Context {
+                score: sim.score as i64,
+                tick: game.replay.ticks as i64,
+                length: sim.snake.len() as i64,
+                head_x: sim.head().x as i64,
+                head_y: sim.head().y as i64,
+                width: sim.map.grid.width as i64,
+                height: sim.map.grid.height as i64,
+                speed_ms: (game.move_interval * 1000.0).round() as i64,
+                walls: sim.map.walls.clone(),
+                rng: Some(Rng::with_stream(sim.map.seed.wrapping_add(game.replay.ticks as u64), MOD_STREAM)),
+                commands: Vec::new(),
+            };
+
+            for event in events {
+                match event {
+                    SimEvent::FoodEaten { score, .. } | SimEvent::PremiumEaten { score, .. } | SimEvent::ChainEaten { score, .. } => self.call("on_food_eaten", Dynamic::from(*score as i64)),
+                    SimEvent::Died(cause) => {
+                        let cause = match cause {
+                            DeathCause::Border => "border",
+                            DeathCause::Wall => "wall",
+                            DeathCause::SelfCollision => "self",
+                            DeathCause::Starved => "starved",
+                            DeathCause::Patroller => "patroller",
+                        };
+                        self.call("on_death", Dynamic::from(cause.to_string()));
+                    }
+                    SimEvent::Moved { .. } | SimEvent::Shrank { .. } | SimEvent::Won | SimEvent::WaveStarted { .. } | SimEvent::MissionEnded(_) => {}
+                    SimEvent::ScoreDecayed { .. } | SimEvent::GlitchEaten { .. } => {}
+                    SimEvent::PremiumSpawned { .. } | SimEvent::PowerUpSpawned { .. } | SimEvent::PowerUpTaken { .. } => {}
+                    SimEvent::PhaseEarned { .. } | SimEvent::Phased { .. } | SimEvent::Switched { .. } | SimEvent::Nightfall { .. } => {}
+                }
+            }
+            if game.sim.alive { self.call("on_tick", Dynamic::from(game.replay.ticks as i64)); }
+
+            let commands = std::mem::take(&mut self.ctx.borrow_mut().commands);
+            for command in commands {
+                match command {
+                    Command::SpawnFood(c) => { game.sim.place_food(c); }
+                    Command::AddWall(c) => { game.sim.add_wall(c); }
+                    Command::RemoveWall(c) => { game.sim.remove_wall(c); }
+                    Command::SetSpeed(s) => game.move_interval = s.clamp(0.05, 0.35),
+                }
+            }
+        }
+    }
+
+    thread_local! {
+        static HOST: RefCell<Option<Host>> = const { RefCell::new(None) };
+    }
+
+    pub fn load() -> Vec<String> {
+        let mut host = Host::new();
+        let mut problems = host.load_dir();
+        problems.extend(host.register_styles());
+        HOST.with(|h| *h.borrow_mut() = Some(host));
+        problems
+    }
+
+    pub fn after_tick(game: &mut SnakeGame, events: &[SimEvent]) {
+        HOST.with(|h| {
+            if let Some(host) = h.borrow_mut().as_mut() { host.after_tick(game, events); }
+        });
+    }
+}
+
+// Without the `scripting` feature mods are skipped, with a hint if some are present
+#[cfg(not(feature = "scripting"))]
+mod host {
+    use super::MODS_DIR;
+    use crate::game::SnakeGame;
+    use crate::logging;
+    use crate::sim::SimEvent;
+
+    pub fn load() -> Vec<String> {
+        if std::path::Path::new(MODS_DIR).is_dir() {
+            logging::warn(format!("{}/ ignored: this build has no scripting support", MODS_DIR));
+        }
+        Vec::new()
+    }
+
+    pub fn after_tick(_game: &mut SnakeGame, _events: &[SimEvent]) {}
+}
+
+// Compile the mods once at startup; returns load problems for the lobby notice
+pub fn load() -> Vec<String> { host::load() }
+
+// Runs the mods' hooks for a tick that just happened and applies what they asked for
+pub fn after_tick(game: &mut SnakeGame, events: &[SimEvent]) { host::after_tick(game, events); }
@@ -0,0 +1,191 @@
+// Background music written as text: a song is a few channels of notes in a tiny tracker-style
+// notation, rendered to one looping WAV at startup, so no binary audio has to be checked in.
+//
+//   # a comment line
+//   tempo 120                        beats per minute; a step is a quarter of a beat
+//   lead square 0.4: A4 . C5:2 E5 |  channel name, waveform, optional gain, then steps
+//
+// A note is a letter, an optional `#` or `b` and an octave (C4 is middle C); `.` is a rest. Either
+// can take `:n` to last n steps. `|` is ignored and can mark bars. A channel named again carries
+// on where its last line stopped. Shorter channels repeat to the length of the longest, so a
+// one-bar beat can run under a four-bar tune.
+use std::fs;
+
+use crate::logging;
+use crate::rng::Rng;
+
+pub const MUSIC_PATH: &str = "music.txt";
+const THEME: &str = include_str!("../assets/music/theme.txt");
+
+const STEPS_PER_BEAT: f32 = 4.0;
+const ATTACK: f32 = 0.005; // seconds, so notes start and stop without clicks
+const RELEASE: f32 = 0.03;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Wave {
+    Sine,
+    Square,
+    Triangle,
+    Saw,
+    Noise, // random levels held for one period of the note, so higher notes hiss brighter
+}
+
+impl Wave {
+    fn parse(s: &str) -> Option<Wave> {
+        match s {
+            "sine" => Some(Wave::Sine),
+            "square" => Some(Wave::Square),
+            "triangle" => Some(Wave::Triangle),
+            "saw" => Some(Wave::Saw),
+            "noise" => Some(Wave::Noise),
+            _ => None,
+        }
+    }
+}
+
+// A note's pitch in Hz, or None for a rest, and how many steps it lasts
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Note {
+    pub hz: Option<f32>,
+    pub steps: u32,
+}
+
+pub struct Channel {
+    pub name: String,
+    pub wave: Wave,
+    pub gain: f32,
+    pub notes: Vec<Note>,
+}
+
+impl Channel {
+    fn steps(&self) -> u32 {
+        self.notes.iter().map(|n| n.steps).sum()
+    }
+}
+
+pub struct Song {
+    pub tempo: f32,
+    pub channels: Vec<Channel>,
+}
+
+impl Song {
+    pub fn parse(text: &str) -> Result<Song, String> {
+        let mut song = Song { tempo: 120.0, channels: Vec::new() };
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue; }
+            song.parse_line(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        }
+        if song.channels.iter().all(|c| c.steps() == 0) { return Err("no notes".to_string()); }
+        Ok(song)
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        if let Some(bpm) = line.strip_prefix("tempo ") {
+            let bpm: f32 = bpm.trim().parse().map_err(|_| format!("bad tempo '{}'", bpm.trim()))?;
+            if !(20.0..=400.0).contains(&bpm) { return Err(format!("tempo {} is out of range (20-400)", bpm)); }
+            self.tempo = bpm;
+            return Ok(());
+        }
+        let Some((head, body)) = line.split_once(':') else { return Err("expected 'name wave [gain]: notes'".to_string()); };
+        let mut head = head.split_whitespace();
+        let (Some(name), Some(wave)) = (head.next(), head.next()) else { return Err("expected 'name wave [gain]: notes'".to_string()); };
+        let wave = Wave::parse(wave).ok_or_else(|| format!("unknown waveform '{}'", wave))?;
+        let gain = match head.next() {
+            Some(g) => g.parse::<f32>().ok().filter(|g| (0.0..=1.0).contains(g)).ok_or_else(|| format!("bad gain '{}' (0-1)", g))?,
+            None => 1.0,
+        };
+        let notes = body.split_whitespace().filter(|t| *t != "|").map(parse_note).collect::<Result<Vec<_>, _>>()?;
+        match self.channels.iter_mut().find(|c| c.name == name) {
+            Some(c) if c.wave != wave => return Err(format!("channel '{}' changes waveform", name)),
+            Some(c) => c.notes.extend(notes),
+            None => self.channels.push(Channel { name: name.to_string(), wave, gain, notes }),
+        }
+        Ok(())
+    }
+
+    // Mono samples in -1..1 for one pass through the song, which loops seamlessly
+    pub fn render(&self, sample_rate: u32) -> Vec<f32> {
+        let step_seconds = 60.0 / (self.tempo * STEPS_PER_BEAT);
+        let steps = self.channels.iter().map(Channel::steps).max().unwrap_or(0);
+        let len = (steps as f32 * step_seconds * sample_rate as f32) as usize;
+        let mut out = vec![0.0; len];
+        let mix = 1.0 / self.channels.len().max(1) as f32;
+        for channel in self.channels.iter().filter(|c| c.steps() > 0) {
+            let mut rng = Rng::new(1);
+            let mut step = 0;
+            while step < steps {
+                for note in &channel.notes {
+                    if step >= steps { break; }
+                    let start = (step as f32 * step_seconds * sample_rate as f32) as usize;
+                    step += note.steps;
+                    let end = ((step as f32 * step_seconds * sample_rate as f32) as usize).min(len);
+                    let Some(hz) = note.hz else { continue; };
+                    render_note(&mut out[start..end], channel.wave, hz, channel.gain * mix, sample_rate, &mut rng);
+                }
+            }
+        }
+        out
+    }
+}
+
+// `A4`, `C#5:2`, `Bb3`, `.` or `.:4`
+fn parse_note(token: &str) -> Result<Note, String> {
+    let (pitch, steps) = match token.split_once(':') {
+        Some((p, n)) => (p, n.parse::<u32>().ok().filter(|n| *n > 0).ok_or_else(|| format!("bad length in '{}'", token))?),
+        None => (token, 1),
+    };
+    if pitch == "." { return Ok(Note { hz: None, steps }); }
+    let bad = || format!("unknown note '{}'", token);
+    let mut chars = pitch.chars();
+    let semitone = match chars.next() {
+        Some('C') => 0, Some('D') => 2, Some('E') => 4, Some('F') => 5, Some('G') => 7, Some('A') => 9, Some('B') => 11,
+        _ => return Err(bad()),
+    };
+    let rest = chars.as_str();
+    let (accidental, octave) = match rest.chars().next() {
+        Some('#') => (1, &rest[1..]),
+        Some('b') => (-1, &rest[1..]),
+        _ => (0, rest),
+    };
+    let octave: i32 = octave.parse().ok().filter(|o| (0..=8).contains(o)).ok_or_else(bad)?;
+    // Semitones from A4 = 440 Hz
+    let from_a4 = (octave - 4) * 12 + semitone + accidental - 9;
+    Ok(Note { hz: Some(440.0 * 2f32.powf(from_a4 as f32 / 12.0)), steps })
+}
+
+fn render_note(out: &mut [f32], wave: Wave, hz: f32, gain: f32, sample_rate: u32, rng: &mut Rng) {
+    let len = out.len() as f32 / sample_rate as f32;
+    let (mut held, mut last_phase) = (0.0, 1.0);
+    for (n, sample) in out.iter_mut().enumerate() {
+        let t = n as f32 / sample_rate as f32;
+        let phase = (t * hz).fract();
+        let value = match wave {
+            Wave::Sine => (phase * std::f32::consts::TAU).sin(),
+            Wave::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+            Wave::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Wave::Saw => 2.0 * phase - 1.0,
+            Wave::Noise => {
+                if phase < last_phase { held = rng.range_f32(-1.0, 1.0); }
+                held
+            }
+        };
+        last_phase = phase;
+        let envelope = (t / ATTACK).min(1.0).min((len - t) / RELEASE).max(0.0);
+        *sample += value * envelope * gain;
+    }
+}
+
+// The song in `music.txt` next to the game if there is one and it parses, else the built-in theme
+pub fn load_song() -> Song {
+    if let Ok(text) = fs::read_to_string(MUSIC_PATH) {
+        match Song::parse(&text) {
+            Ok(song) => {
+                logging::info(format!("Loaded {}", MUSIC_PATH));
+                return song;
+            }
+            Err(e) => logging::warn(format!("{} ignored: {}", MUSIC_PATH, e)),
+        }
+    }
+    Song::parse(THEME).expect("built-in theme parses")
+}
@@ -0,0 +1,89 @@
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+// Result of background work, polled once per frame so the game never blocks on the network.
+// The web build has no threads (spawning one panics), so there the work runs on the spot;
+// its requests fail at once anyway, having no blocking sockets.
+pub struct Pending<T> {
+    rx: Receiver<Result<T, String>>,
+}
+
+impl<T: Send + 'static> Pending<T> {
+    pub fn spawn(work: impl FnOnce() -> Result<T, String> + Send + 'static) -> Self {
+        let (tx, rx) = mpsc::channel();
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(move || {
+            let _ = tx.send(work());
+        });
+        #[cfg(target_arch = "wasm32")]
+        let _ = tx.send(work());
+        Self { rx }
+    }
+
+    // Some once the work has finished; None while it is still running
+    pub fn poll(&self) -> Option<Result<T, String>> {
+        match self.rx.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("Request was dropped".to_string())),
+        }
+    }
+}
+
+#[cfg(all(feature = "online", not(target_arch = "wasm32")))]
+mod http {
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn agent() -> ureq::Agent {
+        ureq::AgentBuilder::new().timeout(TIMEOUT).build()
+    }
+
+    pub fn get(url: &str) -> Result<String, String> {
+        agent().get(url).call().map_err(|e| e.to_string())?.into_string().map_err(|e| e.to_string())
+    }
+
+    pub fn post_json(url: &str, body: &str) -> Result<String, String> {
+        agent()
+            .post(url)
+            .set("Content-Type", "application/json")
+            .send_string(body)
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())
+    }
+
+    // GET with an optional bearer token; Ok(None) when there's nothing at the URL yet
+    pub fn fetch(url: &str, token: &str) -> Result<Option<String>, String> {
+        let mut request = agent().get(url);
+        if !token.is_empty() { request = request.set("Authorization", &format!("Bearer {}", token)); }
+        match request.call() {
+            Ok(response) => response.into_string().map(Some).map_err(|e| e.to_string()),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn put_json(url: &str, body: &str, token: &str) -> Result<(), String> {
+        let mut request = agent().put(url).set("Content-Type", "application/json");
+        if !token.is_empty() { request = request.set("Authorization", &format!("Bearer {}", token)); }
+        request.send_string(body).map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+// Without the `online` feature (and on the web build, which has no blocking sockets) every
+// request fails with a readable reason instead of the callers needing their own cfgs
+#[cfg(not(all(feature = "online", not(target_arch = "wasm32"))))]
+mod http {
+    const UNAVAILABLE: &str = "Online features are not available in this build";
+
+    pub fn get(_url: &str) -> Result<String, String> { Err(UNAVAILABLE.to_string()) }
+
+    pub fn post_json(_url: &str, _body: &str) -> Result<String, String> { Err(UNAVAILABLE.to_string()) }
+
+    pub fn fetch(_url: &str, _token: &str) -> Result<Option<String>, String> { Err(UNAVAILABLE.to_string()) }
+
+    pub fn put_json(_url: &str, _body: &str, _token: &str) -> Result<(), String> { Err(UNAVAILABLE.to_string()) }
+}
+
+pub use http::{fetch, get, post_json, put_json};
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::map::{Direction, GridConfig};
+use crate::net::Pending;
+
+pub const NETPLAY_VERSION: u32 = 3;
+pub const DEFAULT_PORT: u16 = 7777;
+// Inputs are scheduled this many ticks ahead so the peer's input usually arrives before it is needed
+pub const INPUT_DELAY: u32 = 3;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// Seconds a match waits on a silent peer before calling the connection lost
+pub const PEER_TIMEOUT: f32 = 10.0;
+
+// Run parameters the host dictates so both ends generate the same map
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MatchParams {
+    pub seed: u64,
+    pub wall_density: f32,
+    pub move_interval: f32,
+    pub grid: GridConfig,
+    pub map_style: String,
+}
+
+// One JSON object per line over TCP
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Msg {
+    Hello { version: u32, params: MatchParams },
+    // Direction pressed for `tick` (None = keep going)
+    Input { tick: u32, dir: Option<Direction> },
+    // State hash of both sims after `tick`, for desync detection
+    Hash { tick: u32, hash: u64 },
+    Bye,
+}
+
+pub struct Peer {
+    stream: TcpStream,
+    buf: Vec<u8>,
+}
+
+impl Peer {
+    pub fn new(stream: TcpStream) -> Result<Self, String> {
+        stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let _ = stream.set_nodelay(true);
+        Ok(Self { stream, buf: Vec::new() })
+    }
+
+    pub fn send(&mut self, msg: &Msg) -> Result<(), String> {
+        let mut line = serde_json::to_string(msg).map_err(|e| e.to_string())?;
+        line.push('\n');
+        // Messages are tiny; a short blocking write keeps ordering simple
+        self.stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+        let result = self.stream.write_all(line.as_bytes()).map_err(|e| e.to_string());
+        let _ = self.stream.set_nonblocking(true);
+        result
+    }
+
+    // Everything received since the last call; Err once the connection is gone
+    pub fn recv(&mut self) -> Result<Vec<Msg>, String> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err("Connection closed".to_string()),
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        let mut msgs = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            let msg = serde_json::from_slice(&line).map_err(|e| format!("Bad message from peer: {}", e))?;
+            msgs.push(msg);
+        }
+        Ok(msgs)
+    }
+}
+
+// Non-blocking listener polled each frame until the guest connects
+pub struct Host {
+    listener: TcpListener,
+}
+
+impl Host {
+    pub fn listen(port: u16) -> Result<Self, String> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| format!("Can't listen on port {}: {}", port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        Ok(Self { listener })
+    }
+
+    pub fn poll(&self) -> Option<Result<TcpStream, String>> {
+        match self.listener.accept() {
+            Ok((stream, _)) => Some(Ok(stream)),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+            Err(e) => Some(Err(e.to_string())),
+        }
+    }
+}
+
+// Connects in the background; "host" or "host:port"
+pub fn join(address: &str) -> Pending<TcpStream> {
+    let address = if address.contains(':') { address.to_string() } else { format!("{}:{}", address, DEFAULT_PORT) };
+    Pending::spawn(move || {
+        let addr = address
+            .to_socket_addrs()
+            .map_err(|e| format!("Bad address {}: {}", address, e))?
+            .next()
+            .ok_or_else(|| format!("Bad address {}", address))?;
+        TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(|e| format!("Can't connect to {}: {}", address, e))
+    })
+}
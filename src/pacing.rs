@@ -0,0 +1,68 @@
+// Power saving: the game runs flat out only while someone is playing or using the menus. With
+// the window minimized, or no key, click or mouse movement for IDLE_AFTER seconds outside a
+// live run, frames are held to IDLE_FPS; the first input brings full speed back. After
+// SCREENSAVER_AFTER seconds the screensaver comes up as well.
+use macroquad::prelude::*;
+
+const IDLE_AFTER: f32 = 20.0;
+const IDLE_FPS: f32 = 10.0;
+const SCREENSAVER_AFTER: f32 = 120.0;
+// A frame this much later than the one before means the window went unseen for a while:
+// covered, on another desktop, or being dragged on Windows
+const STALL_SECONDS: f32 = 0.5;
+// Alt-tab, Cmd-Tab and the overview key all start with one of these going down
+const SWITCH_KEYS: [KeyCode; 4] = [KeyCode::LeftAlt, KeyCode::RightAlt, KeyCode::LeftSuper, KeyCode::RightSuper];
+
+pub struct Pacing {
+    last_input: f32,
+}
+
+impl Pacing {
+    pub fn new(now: f32) -> Self {
+        Self { last_input: now }
+    }
+
+    // Minimized windows report no size on the platforms that say anything at all
+    pub fn hidden() -> bool {
+        screen_width() < 1.0 || screen_height() < 1.0
+    }
+
+    // macroquad says nothing when the window loses focus, so the signs of it going are read
+    // instead: a minimized window, a window-switching key or a stall since the last frame
+    pub fn left_window(dt: f32) -> bool {
+        Self::hidden() || dt > STALL_SECONDS || SWITCH_KEYS.into_iter().any(is_key_pressed)
+    }
+
+    // Any key, click, touch or mouse movement this frame
+    pub fn any_input() -> bool {
+        let mouse = mouse_delta_position() != Vec2::ZERO || mouse_wheel() != (0.0, 0.0);
+        let clicked = [MouseButton::Left, MouseButton::Right, MouseButton::Middle].into_iter().any(is_mouse_button_down);
+        mouse || clicked || !get_keys_down().is_empty() || !touches().is_empty()
+    }
+
+    // Once a frame, before anything reads the input
+    pub fn note_input(&mut self, now: f32) {
+        if Self::any_input() { self.last_input = now; }
+    }
+
+    // Long enough without input that the screensaver should take over
+    pub fn screensaver_due(&self, now: f32) -> bool {
+        now - self.last_input > SCREENSAVER_AFTER
+    }
+
+    pub fn throttled(&self, now: f32, live: bool) -> bool {
+        Self::hidden() || (!live && now - self.last_input > IDLE_AFTER)
+    }
+
+    // Sleeps out the rest of a slow frame that began at `frame_start`. The web build leaves
+    // this to the browser, which already slows hidden tabs.
+    pub fn wait(&self, frame_start: f32) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let left = 1.0 / IDLE_FPS - (get_time() as f32 - frame_start);
+            if left > 0.0 { std::thread::sleep(std::time::Duration::from_secs_f32(left)); }
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = frame_start;
+    }
+}
@@ -0,0 +1,110 @@
+// Matrix rain backdrop. It falls on its own fixed grid behind every screen, and reacts to
+// the run being played: the columns over a meal pulse brighter and fall faster for a moment,
+// and a death turns the whole cascade red for a second.
+use macroquad::prelude::*;
+
+use crate::config::RainConfig;
+use crate::events::GameEvent;
+use crate::map::GridConfig;
+use crate::render::{board_layout, draw_glyph_at_pos_scaled, random_matrix_char, Look};
+use crate::rng::Rng;
+
+const PULSE_SECONDS: f32 = 0.8;
+const PULSE_REACH: f32 = 0.08; // fraction of the screen width either side of the meal
+const PULSE_SPEEDUP: f32 = 3.0; // fall speed multiplier at the start of a pulse
+const DEATH_SECONDS: f32 = 1.0;
+const DEATH_SPEEDUP: f32 = 2.0;
+const DEATH_TINT: Color = Color::new(1.0, 0.15, 0.1, 0.7);
+// Storm (the screensaver): every drop falls, this much faster and brighter
+const STORM_SPEEDUP: f32 = 2.5;
+const STORM_GLOW: f32 = 0.35;
+
+struct Drop {
+    x: i32,
+    y: f32, // fractional, so slow drops still move every frame
+    speed: f32, // cells per second
+}
+
+pub struct Rain {
+    grid: GridConfig,
+    drops: Vec<Drop>,
+    rng: Rng,
+    time: f32,
+    pulses: Vec<(f32, f32)>, // screen x as a fraction of the width, start time
+    death: Option<f32>, // start time
+}
+
+impl Rain {
+    // Twice the configured columns: the first half spread evenly, the second in the gaps
+    // between them, so any density up to double shows an even spread
+    pub fn new(config: &RainConfig, seed: u64) -> Self {
+        let grid = GridConfig::DEFAULT;
+        let mut rng = Rng::new(seed);
+        let columns = config.columns;
+        let drops = (0..columns * 2)
+            .map(|i| Drop {
+                x: ((i % columns) * grid.width / columns + (i / columns) * grid.width / (columns * 2)) % grid.width,
+                y: rng.range_i32(0, grid.height) as f32,
+                speed: rng.range_f32(config.min_speed, config.max_speed),
+            })
+            .collect();
+        Self { grid, drops, rng, time: 0.0, pulses: Vec::new(), death: None }
+    }
+
+    // Consumer of a run's events; `board` and `look` place the run's cells on screen
+    pub fn on_event(&mut self, event: &GameEvent, board: GridConfig, look: Look) {
+        match *event {
+            GameEvent::FoodEaten { at, .. } | GameEvent::PremiumEaten { at, .. } | GameEvent::ChainEaten { at, .. } => {
+                let layout = board_layout(board, look);
+                let x = layout.dest.x + (at.x as f32 + 0.5) / board.width as f32 * layout.dest.w;
+                self.pulses.push((x / screen_width(), self.time));
+            }
+            GameEvent::Died(_) => self.death = Some(self.time),
+            _ => {}
+        }
+    }
+
+    // How strongly a pulse still affects the drop column at `x` (0-1)
+    fn pulse(&self, x: f32) -> f32 {
+        self.pulses.iter()
+            .map(|(at, born)| {
+                let fade = 1.0 - (self.time - born) / PULSE_SECONDS;
+                let near = 1.0 - (x - at).abs() / PULSE_REACH;
+                fade.max(0.0) * near.max(0.0)
+            })
+            .fold(0.0, f32::max)
+    }
+
+    // Advances the drops by `dt` and draws them behind everything else; `density` (0-1) is
+    // the share of the drops that fall, half being the configured column count. A storm
+    // overrides it with all of them.
+    pub fn draw(&mut self, dt: f32, color: Color, density: f32, storm: bool) {
+        let (density, speed) = if storm { (1.0, STORM_SPEEDUP) } else { (density, 1.0) };
+        let color = if storm { Color::new(mix(color.r, 1.0, STORM_GLOW), mix(color.g, 1.0, STORM_GLOW), mix(color.b, 1.0, STORM_GLOW), mix(color.a, 1.0, STORM_GLOW)) } else { color };
+        self.time += dt;
+        let now = self.time;
+        self.pulses.retain(|(_, born)| now - born < PULSE_SECONDS);
+        let death = self.death.map_or(0.0, |born| (1.0 - (now - born) / DEATH_SECONDS).max(0.0));
+        if death == 0.0 { self.death = None; }
+
+        let tile_w = screen_width() / self.grid.width as f32;
+        let tile_h = screen_height() / self.grid.height as f32;
+        let shown = (self.drops.len() as f32 * density.clamp(0.0, 1.0)).round() as usize;
+        for i in 0..shown {
+            let pulse = self.pulse((self.drops[i].x as f32 + 0.5) / self.grid.width as f32);
+            let d = &mut self.drops[i];
+            d.y += d.speed * speed * (1.0 + (PULSE_SPEEDUP - 1.0) * pulse + (DEATH_SPEEDUP - 1.0) * death) * dt;
+            if d.y >= self.grid.height as f32 { d.y = 0.0; }
+
+            // Toward white in a pulse, then red over everything while a death plays out
+            let lit = Color::new(mix(color.r, 1.0, pulse * 0.7), mix(color.g, 1.0, pulse * 0.7), mix(color.b, 1.0, pulse * 0.7), mix(color.a, 1.0, pulse));
+            let c = Color::new(mix(lit.r, DEATH_TINT.r, death), mix(lit.g, DEATH_TINT.g, death), mix(lit.b, DEATH_TINT.b, death), mix(lit.a, DEATH_TINT.a, death));
+            let x = d.x.clamp(0, self.grid.width - 1) as f32;
+            draw_glyph_at_pos_scaled(random_matrix_char(&mut self.rng), (x, d.y.floor()), c, tile_w, tile_h, 0.0, 0.0);
+        }
+    }
+}
+
+fn mix(a: f32, b: f32, k: f32) -> f32 {
+    a + (b - a) * k
+}
@@ -0,0 +1,109 @@
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::BufWriter;
+
+use crate::screenshot::timestamp;
+
+pub const RECORDING_DIR: &str = "recordings";
+const GIF_FPS: f32 = 10.0;
+const GIF_SECONDS: f32 = 15.0;
+// Captured frames are shrunk to at most this width (keeping the aspect ratio)
+const MAX_WIDTH: usize = 320;
+// Hard cap on buffered pixels; frames are one palette index per pixel
+const MAX_BUFFER_BYTES: usize = 16 * 1024 * 1024;
+
+// 3-3-2 RGB palette: every color maps to an index with two shifts, so frames are stored
+// already quantized at a quarter of the RGBA size
+fn palette() -> Vec<u8> {
+    let mut colors = Vec::with_capacity(256 * 3);
+    for i in 0..=255u32 {
+        colors.push(((i >> 5) * 255 / 7) as u8);
+        colors.push((((i >> 2) & 7) * 255 / 7) as u8);
+        colors.push(((i & 3) * 255 / 3) as u8);
+    }
+    colors
+}
+
+fn quantize(r: u8, g: u8, b: u8) -> u8 {
+    (r & 0xe0) | ((g >> 3) & 0x1c) | (b >> 6)
+}
+
+// Ring buffer of the last GIF_SECONDS of downscaled frames of a run
+pub struct GifRecorder {
+    frames: VecDeque<Vec<u8>>,
+    width: usize,
+    height: usize,
+    capacity: usize,
+    last_capture: f32,
+}
+
+impl GifRecorder {
+    pub fn new() -> Self {
+        Self { frames: VecDeque::new(), width: 0, height: 0, capacity: 0, last_capture: f32::MIN }
+    }
+
+    pub fn is_empty(&self) -> bool { self.frames.is_empty() }
+
+    // Grab what has been drawn so far this frame, at most GIF_FPS times a second
+    pub fn capture(&mut self, now: f32) {
+        if now - self.last_capture < 1.0 / GIF_FPS { return; }
+        self.last_capture = now;
+
+        let screen = get_screen_data();
+        let (sw, sh) = (screen.width as usize, screen.height as usize);
+        if sw == 0 || sh == 0 { return; }
+        let width = sw.min(MAX_WIDTH);
+        let height = (sh * width / sw).max(1);
+        // A resized window changes the frame size; older frames can't share the GIF then
+        if (width, height) != (self.width, self.height) {
+            self.frames.clear();
+            self.width = width;
+            self.height = height;
+            self.capacity = ((GIF_FPS * GIF_SECONDS) as usize).min(MAX_BUFFER_BYTES / (width * height));
+        }
+
+        // Nearest-neighbour downscale; the framebuffer is read bottom-up
+        let mut pixels = if self.frames.len() >= self.capacity { self.frames.pop_front().unwrap_or_default() } else { Vec::new() };
+        pixels.clear();
+        for y in 0..height {
+            let src_y = sh - 1 - y * sh / height;
+            for x in 0..width {
+                let i = (src_y * sw + x * sw / width) * 4;
+                pixels.push(quantize(screen.bytes[i], screen.bytes[i + 1], screen.bytes[i + 2]));
+            }
+        }
+        self.frames.push_back(pixels);
+    }
+
+    // Hands the buffered frames over for encoding, leaving the recorder empty
+    pub fn take(&mut self) -> Recording {
+        Recording { frames: std::mem::take(&mut self.frames).into(), width: self.width, height: self.height }
+    }
+}
+
+// Frames detached from the recorder so they can be encoded off the main thread
+pub struct Recording {
+    frames: Vec<Vec<u8>>,
+    width: usize,
+    height: usize,
+}
+
+impl Recording {
+    // Writes an endlessly looping GIF to `recordings/` and returns its path
+    pub fn save(self, seed: u64, score: u32) -> Result<String, String> {
+        if self.frames.is_empty() { return Err("Nothing was recorded".to_string()); }
+        fs::create_dir_all(RECORDING_DIR).map_err(|e| format!("Could not create {}: {}", RECORDING_DIR, e))?;
+        let path = format!("{}/{}_{}_{}.gif", RECORDING_DIR, timestamp(), seed, score);
+        let file = File::create(&path).map_err(|e| format!("Could not create {}: {}", path, e))?;
+        let (w, h) = (self.width as u16, self.height as u16);
+        let mut encoder = gif::Encoder::new(BufWriter::new(file), w, h, &palette()).map_err(|e| e.to_string())?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(|e| e.to_string())?;
+        for pixels in self.frames {
+            let mut frame = gif::Frame::from_indexed_pixels(w, h, pixels, None);
+            frame.delay = (100.0 / GIF_FPS) as u16; // hundredths of a second
+            encoder.write_frame(&frame).map_err(|e| e.to_string())?;
+        }
+        Ok(path)
+    }
+}
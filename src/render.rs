@@ -0,0 +1,528 @@
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell as Flag, OnceCell, RefCell};
+use std::collections::HashMap;
+
+use crate::logging;
+use crate::map::{Cell, Direction, GridConfig, Map, SWITCH_GROUPS};
+use crate::rng::Rng;
+use crate::sim::CHAIN_LENGTH;
+use crate::ui::HudCorner;
+
+// Matrix-style palette
+pub const MATRIX_HEAD: Color = Color::new(0.64, 1.0, 0.64, 1.0); // bright green
+pub const MATRIX_BODY: Color = Color::new(0.25, 0.9, 0.25, 1.0); // medium green
+pub const MATRIX_WALL: Color = Color::new(0.08, 0.4, 0.08, 1.0); // dark green
+pub const MATRIX_FOOD: Color = Color::new(0.9, 1.0, 0.9, 1.0); // pale bright
+pub const MATRIX_RAIN: Color = Color::new(0.2, 0.8, 0.2, 0.5);
+
+// Optional effects on how a board is drawn, from the Video settings
+#[derive(Copy, Clone)]
+pub struct Look {
+    pub body_gradient: bool, // shade the snake by segment age
+    pub lighting: bool, // light falls off from the head, and the food glows
+    pub filter: BoardFilter, // how the board canvas is scaled to the window
+    pub fit: WindowFit, // and what happens when their shapes differ
+    pub hud: HudCorner, // where the score and status lines go
+}
+
+// What fills the window behind the board; the flat colours key out cleanly in OBS
+#[derive(Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Backdrop {
+    #[default]
+    Matrix, // black, with the matrix rain if it's on
+    Solid, // colors.backdrop from snake.toml, black if unset
+    ChromaGreen,
+    ChromaBlue,
+    ChromaMagenta,
+}
+
+impl Backdrop {
+    pub const ALL: [Backdrop; 5] = [Backdrop::Matrix, Backdrop::Solid, Backdrop::ChromaGreen, Backdrop::ChromaBlue, Backdrop::ChromaMagenta];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Backdrop::Matrix => "Matrix",
+            Backdrop::Solid => "Solid color",
+            Backdrop::ChromaGreen => "Chroma green",
+            Backdrop::ChromaBlue => "Chroma blue",
+            Backdrop::ChromaMagenta => "Chroma magenta",
+        }
+    }
+
+    // `solid` is the configured colour for Solid
+    pub fn color(self, solid: Color) -> Color {
+        match self {
+            Backdrop::Matrix => BLACK,
+            Backdrop::Solid => solid,
+            Backdrop::ChromaGreen => Color::from_rgba(0, 255, 0, 255),
+            Backdrop::ChromaBlue => Color::from_rgba(0, 0, 255, 255),
+            Backdrop::ChromaMagenta => Color::from_rgba(255, 0, 255, 255),
+        }
+    }
+}
+
+// Filter used when the board canvas is scaled up or down to the window
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoardFilter {
+    Nearest, // crisp pixels, at whole multiples when the window allows
+    Linear, // smooth at any size
+}
+
+impl BoardFilter {
+    pub const ALL: [BoardFilter; 2] = [BoardFilter::Nearest, BoardFilter::Linear];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BoardFilter::Nearest => "Crisp",
+            BoardFilter::Linear => "Smooth",
+        }
+    }
+}
+
+// What the board does in a window of a different shape than its grid
+#[derive(Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WindowFit {
+    #[default]
+    Letterbox, // keeps its aspect ratio, centred, with bars on the sides left over
+    Stretch, // fills the window, cells no longer square
+    Rescale, // new runs get a grid shaped like the window; letterboxed while it's resized
+}
+
+impl WindowFit {
+    pub const ALL: [WindowFit; 3] = [WindowFit::Letterbox, WindowFit::Stretch, WindowFit::Rescale];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WindowFit::Letterbox => "Letterbox",
+            WindowFit::Stretch => "Stretch",
+            WindowFit::Rescale => "Rescale grid",
+        }
+    }
+}
+
+// A grid the shape of a `w` by `h` window, as tall as the default one, for WindowFit::Rescale
+pub fn grid_for_window(w: f32, h: f32) -> GridConfig {
+    let height = GridConfig::DEFAULT.height;
+    let width = (height as f32 * w / h.max(1.0)).round() as i32;
+    GridConfig { width: width.clamp(GridConfig::MIN.width, GridConfig::MAX.width), height }
+}
+
+#[derive(Copy, Clone)]
+pub struct Palette {
+    pub head: Color,
+    pub body: Color,
+    pub wall: Color,
+    pub food: Color,
+    pub rain: Color,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    Matrix,
+    Amber,
+    Ice,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Matrix, Theme::Amber, Theme::Ice];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Matrix => "Matrix",
+            Theme::Amber => "Amber",
+            Theme::Ice => "Ice",
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Matrix => Palette { head: MATRIX_HEAD, body: MATRIX_BODY, wall: MATRIX_WALL, food: MATRIX_FOOD, rain: MATRIX_RAIN },
+            Theme::Amber => Palette {
+                head: Color::new(1.0, 0.85, 0.45, 1.0),
+                body: Color::new(1.0, 0.65, 0.1, 1.0),
+                wall: Color::new(0.45, 0.28, 0.02, 1.0),
+                food: Color::new(1.0, 0.95, 0.8, 1.0),
+                rain: Color::new(0.8, 0.5, 0.1, 0.5),
+            },
+            Theme::Ice => Palette {
+                head: Color::new(0.7, 0.95, 1.0, 1.0),
+                body: Color::new(0.3, 0.7, 1.0, 1.0),
+                wall: Color::new(0.08, 0.25, 0.45, 1.0),
+                food: Color::new(0.95, 0.98, 1.0, 1.0),
+                rain: Color::new(0.2, 0.5, 0.8, 0.5),
+            },
+        }
+    }
+}
+
+// Matrix glyph helpers
+pub const MATRIX_GLYPHS: &[u8] = b"01<>[]{}()/\\|-=+*;:.,^~ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+pub fn random_matrix_char(rng: &mut Rng) -> char {
+    let idx = rng.range_usize(0, MATRIX_GLYPHS.len());
+    MATRIX_GLYPHS[idx] as char
+}
+
+pub fn matrix_char_for_cell(c: Cell) -> char {
+    let hx = (c.x as i64).wrapping_mul(73_856_093);
+    let hy = (c.y as i64).wrapping_mul(19_349_663);
+    let h = (hx ^ hy).unsigned_abs() as usize;
+    MATRIX_GLYPHS[h % MATRIX_GLYPHS.len()] as char
+}
+
+pub fn draw_glyph_at_cell_scaled(
+    ch: char,
+    cell: Cell,
+    color: Color,
+    tile_w: f32,
+    tile_h: f32,
+    off_x: f32,
+    off_y: f32,
+){
+    draw_glyph_at_pos_scaled(ch, (cell.x as f32, cell.y as f32), color, tile_w, tile_h, off_x, off_y);
+}
+
+// Same as `draw_glyph_at_cell_scaled` but at a fractional grid position, for interpolated movement
+pub fn draw_glyph_at_pos_scaled(
+    ch: char,
+    (gx, gy): (f32, f32),
+    color: Color,
+    tile_w: f32,
+    tile_h: f32,
+    off_x: f32,
+    off_y: f32,
+){
+    let x = off_x + gx * tile_w + 1.0;
+    let y = off_y + ((gy + 1.0) * tile_h) - 1.0; // baseline
+    let size = tile_w.min(tile_h).max(6.0) as u16;
+    if draw_atlas_glyph(ch, x, y, size, color) { return; }
+    let params = TextParams { font_size: size, font_scale: 1.0, font_scale_aspect: 1.0, color, ..Default::default() };
+    draw_text_ex(&ch.to_string(), x, y, params);
+}
+
+// The matrix glyphs at one font size, drawn once into a texture. Every glyph after that is a
+// quad from the same texture, which macroquad batches into one draw call however many there
+// are, and skips the string and font cache lookups `draw_text_ex` makes per character.
+const ATLAS_COLUMNS: usize = 10;
+const ATLAS_SIZES: usize = 8; // font sizes kept at once; a resize can leave stale ones
+
+struct GlyphAtlas {
+    target: RenderTarget,
+    slot: f32, // logical pixels per glyph, each way
+    scale: f32, // texels per logical pixel
+}
+
+thread_local! {
+    // By font size and the DPI scale they were rasterized at, which moving the window to
+    // another monitor changes
+    static ATLASES: RefCell<HashMap<(u16, u32), GlyphAtlas>> = RefCell::new(HashMap::new());
+}
+
+impl GlyphAtlas {
+    fn new(size: u16, scale: f32, copy: &Material) -> Self {
+        // Room around the baseline for ascenders, descenders and glyphs wider than the cell
+        let slot = size as f32 * 2.0;
+        let rows = MATRIX_GLYPHS.len().div_ceil(ATLAS_COLUMNS);
+        let (w, h) = (slot * ATLAS_COLUMNS as f32, slot * rows as f32);
+        let target = render_target((w * scale).ceil() as u32, (h * scale).ceil() as u32);
+        target.texture.set_filter(FilterMode::Linear);
+        offscreen(&target, w, h, copy, || {
+            for (i, ch) in MATRIX_GLYPHS.iter().enumerate() {
+                let (sx, sy) = Self::origin(i, slot);
+                let params = TextParams { font_size: size, color: WHITE, ..Default::default() };
+                draw_text_ex(&(*ch as char).to_string(), sx + slot * 0.5, sy + slot * 0.75, params);
+            }
+        });
+        Self { target, slot, scale }
+    }
+
+    fn origin(i: usize, slot: f32) -> (f32, f32) {
+        ((i % ATLAS_COLUMNS) as f32 * slot, (i / ATLAS_COLUMNS) as f32 * slot)
+    }
+}
+
+// Draws `ch` with its baseline at (x, y) from the atlas for `size`, built on first use;
+// false if `ch` isn't a matrix glyph or the atlas can't be made, for `draw_text_ex` to do it
+fn draw_atlas_glyph(ch: char, x: f32, y: f32, size: u16, color: Color) -> bool {
+    let Some(index) = MATRIX_GLYPHS.iter().position(|g| *g as char == ch) else { return false; };
+    let Some(materials) = materials() else { return false; };
+    ATLASES.with_borrow_mut(|atlases| {
+        let scale = miniquad::window::dpi_scale();
+        let key = (size, scale.to_bits());
+        if !atlases.contains_key(&key) && atlases.len() >= ATLAS_SIZES { atlases.clear(); }
+        let atlas = atlases.entry(key).or_insert_with(|| GlyphAtlas::new(size, scale, &materials.copy));
+        let slot = atlas.slot;
+        let (sx, sy) = GlyphAtlas::origin(index, slot);
+        // Render targets come out upside down, so the slot is read from the other end
+        let (s, height) = (atlas.scale, atlas.target.texture.height());
+        let source = Rect::new(sx * s, height - (sy + slot) * s, slot * s, slot * s);
+        let params = DrawTextureParams { dest_size: Some(vec2(slot, slot)), source: Some(source), flip_y: true, ..Default::default() };
+        draw_texture_ex(&atlas.target.texture, x - slot * 0.5, y - slot * 0.75, color, params);
+    });
+    true
+}
+
+// Static walls of a board, drawn once into a canvas-sized texture and put down as a single
+// quad each frame, until `key` says they changed. `draw` draws them in canvas pixels.
+struct WallLayer {
+    target: RenderTarget,
+    size: (u32, u32),
+    key: u64,
+}
+
+thread_local! {
+    static WALLS: RefCell<Option<WallLayer>> = const { RefCell::new(None) };
+}
+
+// Only inside `draw_board_canvas`, `width` by `height` being its size: `draw` runs again only
+// when `key` or the size changes
+pub fn draw_wall_layer(key: u64, width: f32, height: f32, draw: impl FnOnce()) {
+    let Some(materials) = materials() else { return draw(); };
+    WALLS.with_borrow_mut(|slot| {
+        let size = (width as u32, height as u32);
+        let layer = match slot {
+            Some(l) if l.size == size && l.key == key => l,
+            _ => {
+                let target = slot.take().filter(|l| l.size == size).map_or_else(|| render_target(size.0, size.1), |l| l.target);
+                target.texture.set_filter(FilterMode::Nearest);
+                offscreen(&target, width, height, &materials.paint, draw);
+                slot.insert(WallLayer { target, size, key })
+            }
+        };
+        gl_use_material(&materials.blit);
+        let params = DrawTextureParams { dest_size: Some(vec2(width, height)), flip_y: true, ..Default::default() };
+        draw_texture_ex(&layer.target.texture, 0.0, 0.0, WHITE, params);
+        resume_material();
+    });
+}
+
+// Runs `draw` into `target`, `w` by `h` logical pixels, cleared first, and comes back to
+// whatever was being drawn to with the material that was in use
+fn offscreen(target: &RenderTarget, w: f32, h: f32, material: &Material, draw: impl FnOnce()) {
+    push_camera_state();
+    let mut camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, w, h));
+    camera.render_target = Some(target.clone());
+    set_camera(&camera);
+    clear_background(Color::new(0.0, 0.0, 0.0, 0.0));
+    gl_use_material(material);
+    draw();
+    pop_camera_state();
+    resume_material();
+}
+
+// Timed gates, drawn every frame over the walls: solid while closed, a faint ':' while open.
+// The colour counts down to the next change: an open gate reddens as it's about to shut, a
+// closed one brightens toward the body colour as it's about to open. `shade` dims a cell
+// (fog, lighting), 1.0 for fully seen.
+pub fn draw_gates(map: &Map, palette: &Palette, shade: impl Fn(Cell) -> f32, tile_w: f32, tile_h: f32, off_x: f32, off_y: f32) {
+    for gate in &map.gates {
+        let k = gate.progress();
+        let (ch, color) = if gate.open {
+            ('.', Color::from_vec(palette.body.to_vec().lerp(RED.to_vec(), k)).with_alpha(0.35 + 0.5 * k))
+        } else {
+            ('=', Color::from_vec(palette.wall.to_vec().lerp(palette.body.to_vec(), k)))
+        };
+        for c in &gate.cells {
+            let seen = shade(*c);
+            if seen <= 0.0 { continue; }
+            draw_glyph_at_cell_scaled(ch, *c, Color { a: color.a * seen, ..color }, tile_w, tile_h, off_x, off_y);
+        }
+    }
+}
+
+// Conveyor belts as arrows in their direction, with a brighter band running along each belt
+// so it reads as moving. `shade` works as for `draw_gates`.
+pub fn draw_conveyors(map: &Map, palette: &Palette, shade: impl Fn(Cell) -> f32, tile_w: f32, tile_h: f32, off_x: f32, off_y: f32) {
+    let time = get_time() as f32;
+    for (c, dir) in &map.conveyors {
+        let seen = shade(*c);
+        if seen <= 0.0 || map.is_wall(*c) { continue; }
+        let (arrow, along) = match dir {
+            Direction::Up => ('^', -c.y),
+            Direction::Down => ('v', c.y),
+            Direction::Left => ('<', -c.x),
+            Direction::Right => ('>', c.x),
+        };
+        let band = 0.5 + 0.5 * (time * 6.0 - along as f32 * 1.5).sin();
+        let color = Color::from_vec(palette.wall.to_vec().lerp(palette.body.to_vec(), band));
+        draw_glyph_at_cell_scaled(arrow, *c, Color { a: color.a * seen, ..color }, tile_w, tile_h, off_x, off_y);
+    }
+}
+
+// Stairs between floors as 'H', both ends of a pair alike. `shade` works as for `draw_gates`.
+pub fn draw_stairs(map: &Map, palette: &Palette, shade: impl Fn(Cell) -> f32, tile_w: f32, tile_h: f32, off_x: f32, off_y: f32) {
+    for c in map.stairs.iter().flat_map(|(a, b)| [*a, *b]) {
+        let seen = shade(c);
+        if seen <= 0.0 { continue; }
+        draw_glyph_at_cell_scaled('H', c, Color { a: seen, ..palette.head }, tile_w, tile_h, off_x, off_y);
+    }
+}
+
+// One colour per switch group, the same on every theme so plates and doors can be matched up
+pub const SWITCH_COLORS: [Color; SWITCH_GROUPS] = [ORANGE, SKYBLUE, VIOLET, GOLD];
+
+// Food chain kinds in the order they're eaten, likewise fixed across themes
+pub const CHAIN_COLORS: [Color; CHAIN_LENGTH] = [GREEN, WHITE, GOLD];
+
+// Switch groups in their colours: plates as 'o', walls as a solid '#' or a faint '.' when
+// open. `shade` works as for `draw_gates`.
+pub fn draw_switches(map: &Map, shade: impl Fn(Cell) -> f32, tile_w: f32, tile_h: f32, off_x: f32, off_y: f32) {
+    for (switch, color) in map.switches.iter().zip(SWITCH_COLORS) {
+        let (wall, alpha) = if switch.solid { ('#', 1.0) } else { ('.', 0.4) };
+        let cells = switch.walls.iter().map(|c| (*c, wall, alpha)).chain(switch.plates.iter().map(|c| (*c, 'o', 1.0)));
+        for (c, ch, alpha) in cells {
+            let seen = shade(c);
+            if seen <= 0.0 { continue; }
+            draw_glyph_at_cell_scaled(ch, c, Color { a: alpha * seen, ..color }, tile_w, tile_h, off_x, off_y);
+        }
+    }
+}
+
+// The board is drawn offscreen at a fixed logical resolution, a whole number of pixels per
+// cell, and the result is scaled into the window in one piece. Glyphs then never land on
+// fractional tile sizes, and the final blit is the place for post-processing.
+const CANVAS_TILE: f32 = 24.0; // logical pixels per cell
+const CANVAS_MAX: f32 = 2048.0; // longest canvas side; big grids get smaller cells
+const CANVAS_MIN_TILE: f32 = 6.0;
+
+// Where a board's canvas goes: its size in logical pixels and the window rect it fills
+pub struct BoardLayout {
+    pub tile: f32,
+    pub width: f32,
+    pub height: f32,
+    pub dest: Rect,
+}
+
+pub fn board_layout(grid: GridConfig, look: Look) -> BoardLayout {
+    let tile = (CANVAS_MAX / grid.width.max(grid.height) as f32).floor().clamp(CANVAS_MIN_TILE, CANVAS_TILE);
+    let (width, height) = (tile * grid.width as f32, tile * grid.height as f32);
+    let (sw, sh) = (screen_width(), screen_height());
+    if look.fit == WindowFit::Stretch { return BoardLayout { tile, width, height, dest: Rect::new(0.0, 0.0, sw, sh) }; }
+    let mut scale = (sw / width).min(sh / height);
+    if look.filter == BoardFilter::Nearest && scale >= 1.0 { scale = scale.floor(); }
+    let (w, h) = (width * scale, height * scale);
+    BoardLayout { tile, width, height, dest: Rect::new(((sw - w) * 0.5).floor(), ((sh - h) * 0.5).floor(), w, h) }
+}
+
+impl BoardLayout {
+    // The cell under a window position (may be off the grid)
+    pub fn cell_at(&self, x: f32, y: f32) -> Cell {
+        let (to_canvas_x, to_canvas_y) = (self.width / self.dest.w, self.height / self.dest.h);
+        Cell { x: ((x - self.dest.x) * to_canvas_x / self.tile).floor() as i32, y: ((y - self.dest.y) * to_canvas_y / self.tile).floor() as i32 }
+    }
+}
+
+const CANVAS_VERTEX: &str = "#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+varying lowp vec2 uv;
+varying lowp vec4 color;
+uniform mat4 Model;
+uniform mat4 Projection;
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}
+";
+
+const CANVAS_FRAGMENT: &str = "#version 100
+varying lowp vec4 color;
+varying lowp vec2 uv;
+uniform sampler2D Texture;
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+}
+";
+
+struct Canvas {
+    target: RenderTarget,
+    size: (u32, u32),
+}
+
+// Drawing into the canvas keeps correct coverage in its alpha (premultiplied), and the blit
+// composites that over whatever is behind it; copy writes glyphs as they are, for the atlas
+#[derive(Clone)]
+struct Materials {
+    paint: Material,
+    blit: Material,
+    copy: Material,
+}
+
+thread_local! {
+    static CANVAS: RefCell<Option<Canvas>> = const { RefCell::new(None) };
+    // None if the shaders failed
+    static MATERIALS: OnceCell<Option<Materials>> = const { OnceCell::new() };
+    // Set while `draw_board_canvas` runs its `draw`, so offscreen passes can go back to painting
+    static PAINTING: Flag<bool> = const { Flag::new(false) };
+}
+
+fn materials() -> Option<Materials> {
+    MATERIALS.with(|m| {
+        m.get_or_init(|| {
+            let over = BlendState::new(Equation::Add, BlendFactor::Value(BlendValue::SourceAlpha), BlendFactor::OneMinusValue(BlendValue::SourceAlpha));
+            let coverage = BlendState::new(Equation::Add, BlendFactor::One, BlendFactor::OneMinusValue(BlendValue::SourceAlpha));
+            let loaded = (|| Ok::<_, String>(Materials {
+                paint: canvas_material(Some(over), Some(coverage))?,
+                blit: canvas_material(Some(coverage), Some(coverage))?,
+                copy: canvas_material(None, None)?,
+            }))();
+            if let Err(e) = &loaded { logging::error(format!("Board canvas shaders failed, blending may be off: {}", e)); }
+            loaded.ok()
+        })
+        .clone()
+    })
+}
+
+// The material for what's being drawn now: the canvas paint inside `draw_board_canvas`
+fn resume_material() {
+    match materials().filter(|_| PAINTING.get()) {
+        Some(m) => gl_use_material(&m.paint),
+        None => gl_use_default_material(),
+    }
+}
+
+fn canvas_material(color: Option<BlendState>, alpha: Option<BlendState>) -> Result<Material, String> {
+    let pipeline_params = PipelineParams { color_blend: color, alpha_blend: alpha, ..Default::default() };
+    load_material(ShaderSource::Glsl { vertex: CANVAS_VERTEX, fragment: CANVAS_FRAGMENT }, MaterialParams { pipeline_params, ..Default::default() })
+        .map_err(|e| e.to_string())
+}
+
+// Runs `draw` into the board canvas, in the layout's logical pixels, then scales the canvas
+// into `layout.dest` with `filter`
+pub fn draw_board_canvas(layout: &BoardLayout, filter: BoardFilter, draw: impl FnOnce()) {
+    CANVAS.with_borrow_mut(|slot| {
+        let size = (layout.width as u32, layout.height as u32);
+        let canvas = match slot {
+            Some(c) if c.size == size => c,
+            _ => slot.insert(Canvas { target: render_target(size.0, size.1), size }),
+        };
+        canvas.target.texture.set_filter(match filter {
+            BoardFilter::Nearest => FilterMode::Nearest,
+            BoardFilter::Linear => FilterMode::Linear,
+        });
+
+        let mut camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, layout.width, layout.height));
+        camera.render_target = Some(canvas.target.clone());
+        set_camera(&camera);
+        clear_background(Color::new(0.0, 0.0, 0.0, 0.0));
+        let materials = materials();
+        if let Some(m) = &materials { gl_use_material(&m.paint); }
+        PAINTING.set(true);
+        draw();
+        PAINTING.set(false);
+        gl_use_default_material();
+        set_default_camera();
+
+        if let Some(m) = &materials { gl_use_material(&m.blit); }
+        let dest = layout.dest;
+        // Render targets come out upside down
+        let params = DrawTextureParams { dest_size: Some(vec2(dest.w, dest.h)), flip_y: true, ..Default::default() };
+        draw_texture_ex(&canvas.target.texture, dest.x, dest.y, WHITE, params);
+        gl_use_default_material();
+    });
+}
+
@@ -0,0 +1,129 @@
+// Every finished run, one JSON object per line in runs.jsonl. The file is only appended to,
+// so a crash mid-write costs at most its last line, and it's easy to read with other tools.
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Extras, GameMode, SnakeGame};
+use crate::graveyard::Graveyard;
+use crate::logging;
+use crate::map::GridConfig;
+use crate::mapgen;
+use crate::save::unix_now;
+use crate::sim::DeathCause;
+use crate::weekly::Modifier;
+
+pub const RUN_LOG_PATH: &str = "runs.jsonl";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub date: u64, // unix seconds the run ended
+    pub seed: u64,
+    pub map_style: String,
+    pub wall_density: f32,
+    pub move_interval: f32,
+    pub grid: GridConfig,
+    pub mode: GameMode,
+    pub missions: bool,
+    pub modifier: Option<Modifier>,
+    #[serde(default)]
+    pub extras: Extras, // optional pickups the run played with
+    pub score: u32,
+    pub death: Option<DeathCause>, // None for a cleared board
+    pub ticks: u32,
+    pub seconds: f32,
+}
+
+impl RunRecord {
+    pub fn from_game(game: &SnakeGame) -> Self {
+        let r = &game.replay;
+        Self {
+            date: unix_now(),
+            seed: r.seed,
+            map_style: r.map_style.clone(),
+            wall_density: r.wall_density,
+            move_interval: r.move_interval,
+            grid: r.grid,
+            mode: r.mode,
+            missions: r.missions,
+            modifier: r.modifier,
+            extras: r.extras,
+            score: game.sim.score,
+            death: game.sim.death,
+            ticks: r.ticks,
+            seconds: r.ticks as f32 * r.move_interval,
+        }
+    }
+
+    // Same map, speed and mode, so the runs can be compared
+    fn same_setup(&self, other: &RunRecord) -> bool {
+        self.seed == other.seed
+            && self.map_style == other.map_style
+            && self.wall_density == other.wall_density
+            && self.move_interval == other.move_interval
+            && self.grid == other.grid
+            && self.mode == other.mode
+    }
+
+    // A fresh run on the same map with the same rules
+    pub fn start(&self, graveyard: &Graveyard) -> SnakeGame {
+        let map = mapgen::generate(&self.map_style, self.grid, self.seed, self.wall_density);
+        let mut game = SnakeGame::new(map, self.move_interval);
+        game.set_mode(self.mode);
+        game.set_missions(self.missions);
+        game.set_extras(self.extras);
+        game.set_modifier(self.modifier);
+        if self.modifier == Some(Modifier::Graveyard) { game.set_corpses(graveyard.corpses(&game.sim.map)); }
+        game
+    }
+}
+
+pub fn append(record: &RunRecord) {
+    let result = serde_json::to_string(record).map_err(|e| e.to_string()).and_then(|line| {
+        let mut file = OpenOptions::new().create(true).append(true).open(RUN_LOG_PATH).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    });
+    if let Err(e) = result { logging::error(format!("Could not add the run to {}: {}", RUN_LOG_PATH, e)); }
+}
+
+// The last `count` runs, newest first; lines that don't parse are skipped
+pub fn recent(count: usize) -> Vec<RunRecord> {
+    let Ok(text) = fs::read_to_string(RUN_LOG_PATH) else { return Vec::new(); };
+    let mut bad = 0;
+    let records: Vec<RunRecord> = text
+        .lines()
+        .rev()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| serde_json::from_str(l).inspect_err(|_| bad += 1).ok())
+        .take(count)
+        .collect();
+    if bad > 0 { logging::warn(format!("{}: skipped {} unreadable lines", RUN_LOG_PATH, bad)); }
+    records
+}
+
+// A seed played lately: the setup it was last played on, the best score and the number of
+// runs on that setup
+pub struct RecentSeed {
+    pub last: RunRecord,
+    pub best: u32,
+    pub runs: u32,
+}
+
+// The last `count` different seeds, newest first, from the whole log
+pub fn recent_seeds(count: usize) -> Vec<RecentSeed> {
+    let mut seeds: Vec<RecentSeed> = Vec::new();
+    for run in recent(usize::MAX) {
+        let room = seeds.len() < count;
+        match seeds.iter_mut().find(|s| s.last.seed == run.seed) {
+            Some(s) if s.last.same_setup(&run) => {
+                s.best = s.best.max(run.score);
+                s.runs += 1;
+            }
+            Some(_) => {}
+            None if room => seeds.push(RecentSeed { best: run.score, runs: 1, last: run }),
+            None => {}
+        }
+    }
+    seeds
+}
@@ -0,0 +1,684 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::config::config;
+use crate::difficulty::Difficulty;
+use crate::game::{Extras, GameMode, SnakeGame};
+use crate::i18n::{trf, Language};
+use crate::logging;
+use crate::map::DEFAULT_STYLE;
+use crate::render::{Backdrop, BoardFilter, Theme, WindowFit};
+use crate::skins;
+use crate::stats::Stats;
+use crate::toast;
+use crate::twitch::normalize_channel;
+use crate::ui::{key_code, DisplayMode, HudCorner, KeyBindings, MinWindow, UI_SCALE_MAX, UI_SCALE_MIN};
+use crate::weekly::Modifier;
+
+pub use snake_core::save::{unix_now, write_atomic, Progress, ScoreEntry, NAME_MAX_LEN};
+
+// Persistent storage
+pub const SAVE_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SaveData {
+    pub version: u32,
+    pub saved_at: u64, // unix seconds of the last write, so cloud sync knows which copy is newer
+    pub settings: Settings,
+    pub progress: Progress,
+    pub history: History,
+    pub stats: Stats,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            saved_at: 0,
+            settings: Settings::default(),
+            progress: Progress::default(),
+            history: History::default(),
+            stats: Stats::default(),
+        }
+    }
+}
+
+// Player preferences, edited on the settings screen
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub sound_volume: f32,
+    pub theme: Theme,
+    pub rain: bool,
+    pub ui_scale: f32,
+    pub display_mode: DisplayMode,
+    pub key_bindings: KeyBindings,
+    pub twitch_channel: String, // chat read in Twitch mode
+    pub record_gif: bool, // keep the last seconds of each run for "Save GIF"
+    pub language: Language,
+    pub speedrun_timer: bool, // board time and splits on the HUD
+    pub missions: bool, // new runs start with rotating bonus missions
+    pub extras: Extras, // optional pickups new runs play with; none by default
+    pub modifier: Option<Modifier>, // rule twist for new runs; the weekly challenge brings its own
+    pub challenge: f32, // 0-1, how hard Adaptive mode aims to be
+    pub skin: String, // id of the equipped skin
+    pub body_gradient: bool, // snake shaded from head to tail by segment age
+    pub lighting: bool, // light around the head and the food, the rest of the board dim
+    pub board_filter: BoardFilter, // scaling of the board canvas to the window
+    pub music_volume: f32, // background music, on top of the overall volume
+    pub one_switch: bool, // a single button turns the snake instead of the direction keys
+    pub sync_url: String, // cloud copy of the save; empty turns sync off
+    // Sent as a bearer token, if set. It lives in its own file and is never written into the
+    // save, so exports and cloud uploads can't carry it; older saves that have it still load
+    #[serde(skip_serializing)]
+    pub sync_token: String,
+    pub power_saving: bool, // slow frame rate while minimized or idle
+    pub rain_density: f32, // 0-1; half is snake.toml's rain.columns
+    pub backdrop: Backdrop, // behind the board: matrix rain, a solid color or a chroma key
+    pub hud_corner: HudCorner, // where the in-game score and status go
+    pub auto_pause: bool, // a run pauses when the window loses focus
+    pub auto_pause_mute: bool, // and the music goes quiet until it resumes
+    pub path_hint: bool, // the way to the food drawn on the board; such runs aren't ranked
+    pub min_window: MinWindow, // smallest size a window can be dragged to
+    pub window_fit: WindowFit, // the board in a window of another shape
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            sound_volume: 1.0,
+            theme: Theme::Matrix,
+            rain: true,
+            ui_scale: 1.0,
+            display_mode: DisplayMode::Fullscreen,
+            key_bindings: KeyBindings::default(),
+            twitch_channel: String::new(),
+            record_gif: false,
+            language: Language::English,
+            speedrun_timer: false,
+            missions: false,
+            extras: Extras::default(),
+            modifier: None,
+            challenge: 0.5,
+            skin: skins::ALL[0].id.to_string(),
+            body_gradient: true,
+            lighting: false,
+            board_filter: BoardFilter::Linear,
+            music_volume: 0.5,
+            one_switch: false,
+            sync_url: String::new(),
+            sync_token: String::new(),
+            power_saving: true,
+            rain_density: 0.5,
+            backdrop: Backdrop::Matrix,
+            hud_corner: HudCorner::TopLeft,
+            auto_pause: true,
+            auto_pause_mute: false,
+            path_hint: false,
+            min_window: MinWindow::default(),
+            window_fit: WindowFit::default(),
+        }
+    }
+}
+
+// Last lobby selections, restored on the next launch
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct History {
+    pub last_seed: u64, // 0 = pick a fresh time-based seed
+    pub last_wall_density: f32,
+    pub last_move_interval: f32,
+    pub last_mode: GameMode,
+    pub last_map_style: String,
+    pub last_name: String, // prefills the next high score's name
+    pub difficulty: Difficulty, // where Adaptive mode left off
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            last_seed: 0,
+            last_wall_density: config().gameplay.default_density_pct / 100.0,
+            last_move_interval: config().gameplay.default_speed_ms / 1000.0,
+            last_mode: GameMode::Classic,
+            last_map_style: DEFAULT_STYLE.to_string(),
+            last_name: String::new(),
+            difficulty: Difficulty::default(),
+        }
+    }
+}
+
+// Flat layout used before the save was split into sections (zero meant "unset")
+#[derive(Deserialize)]
+struct LegacySaveData {
+    best_score: u32,
+    last_seed: u64,
+    last_wall_density: f32,
+    last_move_interval: f32,
+    #[serde(default)]
+    sound_volume: f32,
+}
+
+impl From<LegacySaveData> for SaveData {
+    fn from(old: LegacySaveData) -> Self {
+        let mut data = SaveData::default();
+        data.progress.best_score = old.best_score;
+        data.history.last_seed = old.last_seed;
+        if old.last_wall_density != 0.0 { data.history.last_wall_density = old.last_wall_density; }
+        if old.last_move_interval != 0.0 { data.history.last_move_interval = old.last_move_interval; }
+        if old.sound_volume != 0.0 { data.settings.sound_volume = old.sound_volume; }
+        data
+    }
+}
+
+fn parse_save(text: &str) -> Result<SaveData, String> {
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let obj = value.as_object().ok_or("Save is not a JSON object")?;
+    if obj.contains_key("best_score") && !obj.contains_key("progress") {
+        let legacy: LegacySaveData = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        return Ok(legacy.into());
+    }
+    if !["settings", "progress", "history"].iter().any(|k| obj.contains_key(*k)) {
+        return Err("Not a snake save file".to_string());
+    }
+    let mut data: SaveData = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    data.version = SAVE_VERSION;
+    Ok(data)
+}
+
+fn save_path() -> String { "snake_save.json".to_string() }
+
+fn backup_path() -> String { format!("{}.bak", save_path()) }
+
+fn credentials_path() -> String { "snake_credentials.json".to_string() }
+
+// Machine-local secrets, kept out of the save file
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Credentials {
+    sync_token: String,
+}
+
+// None when there's no credentials file yet (or it can't be read)
+fn read_sync_token(path: &str) -> Option<String> {
+    let text = fs::read_to_string(path).ok()?;
+    match serde_json::from_str::<Credentials>(&text) {
+        Ok(c) => Some(c.sync_token),
+        Err(e) => {
+            logging::warn(format!("Credentials {} unreadable: {}", path, e));
+            None
+        }
+    }
+}
+
+// An empty token removes the file
+fn write_sync_token(path: &str, token: &str) {
+    if read_sync_token(path).as_deref() == Some(token) { return; }
+    if token.is_empty() {
+        if let Err(e) = fs::remove_file(path) && Path::new(path).exists() {
+            logging::warn(format!("Could not remove {}: {}", path, e));
+        }
+        return;
+    }
+    let text = serde_json::to_string_pretty(&Credentials { sync_token: token.to_string() }).unwrap_or_default();
+    if let Err(e) = write_atomic(path, &text) {
+        logging::error(format!("Writing {} failed: {}", path, e));
+        return;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+    }
+}
+
+fn read_save_file(path: &str) -> Result<SaveData, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_save(&text)
+}
+
+pub fn load_save() -> SaveData { load_save_with_notice().0 }
+
+// The notice of the last recovery, kept until the lobby shows it: whichever load happens to
+// find the damaged file first (the volume lookup at startup, as a rule) repairs it, and every
+// load after that sees a healthy save
+static RECOVERY: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn take_recovery_notice() -> Option<String> {
+    RECOVERY.lock().ok()?.take()
+}
+
+// Loads the save, falling back to the backup when the main file is unreadable.
+// The notice describes any recovery that happened so it can be shown to the player.
+pub fn load_save_with_notice() -> (SaveData, Option<String>) {
+    let (mut data, notice) = recover_save(&save_path(), &backup_path());
+    // A save from before the credentials file keeps its token until the next write moves it
+    if let Some(token) = read_sync_token(&credentials_path()) { data.settings.sync_token = token; }
+    if let Some(notice) = &notice && let Ok(mut slot) = RECOVERY.lock() { *slot = Some(notice.clone()); }
+    (data, notice)
+}
+
+fn recover_save(path: &str, backup: &str) -> (SaveData, Option<String>) {
+    let main_exists = Path::new(path).exists();
+    if main_exists {
+        match read_save_file(path) {
+            Ok(data) => return (data, None),
+            Err(e) => logging::warn(format!("Save {} unreadable: {}", path, e)),
+        }
+    }
+    if !main_exists && !Path::new(backup).exists() {
+        return (SaveData::default(), None);
+    }
+    match read_save_file(backup) {
+        Ok(data) => {
+            match write_atomic(path, &serde_json::to_string_pretty(&data).unwrap_or_default()) {
+                Ok(()) => logging::warn(format!("Save restored from {}", backup)),
+                Err(e) => logging::error(format!("Restoring save from backup failed: {}", e)),
+            }
+            (data, Some("Save file was damaged - restored from backup".to_string()))
+        }
+        Err(e) => {
+            // Keep the broken file around for manual recovery instead of overwriting it
+            logging::error(format!("Backup save unusable ({}); starting fresh", e));
+            if let Err(e) = fs::rename(path, format!("{}.corrupt", path)) {
+                logging::error(format!("Could not move damaged save aside: {}", e));
+            }
+            (SaveData::default(), Some("Save file was damaged and no backup was usable - starting fresh".to_string()))
+        }
+    }
+}
+
+pub fn write_save(data: &SaveData) {
+    let data = &SaveData { saved_at: unix_now(), ..data.clone() };
+    let path = save_path();
+    // Rotate the previous good save into the backup slot before replacing it
+    if read_save_file(&path).is_ok() && let Err(e) = fs::copy(&path, backup_path()) {
+        logging::warn(format!("Could not back up save: {}", e));
+    }
+    match write_atomic(&path, &serde_json::to_string_pretty(data).unwrap_or_default()) {
+        Ok(()) => logging::info(format!("Save written (best {})", data.progress.best_score)),
+        Err(e) => {
+            logging::error(format!("Writing save {} failed: {}", path, e));
+            toast::error(trf!("Couldn't save: {}", e));
+        }
+    }
+    write_sync_token(&credentials_path(), &data.settings.sync_token);
+}
+
+// Save transfer (export/import between machines)
+pub const EXPORT_PATH: &str = "snake_export.json";
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ImportMode {
+    Replace, // imported save overwrites the local one
+    Merge,   // keep the higher best score, take imported settings where set
+}
+
+impl ImportMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            ImportMode::Replace => "Replace",
+            ImportMode::Merge => "Merge",
+        }
+    }
+}
+
+// Twitch channel names are up to 25 letters, digits or underscores; empty means none
+pub fn valid_channel(name: &str) -> bool {
+    let name = normalize_channel(name);
+    name.len() <= 25 && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Empty, or an http(s) address
+pub fn valid_sync_url(url: &str) -> bool {
+    url.is_empty() || ((url.starts_with("http://") || url.starts_with("https://")) && !url.contains(char::is_whitespace))
+}
+
+fn validate_save(data: &SaveData) -> Result<(), String> {
+    let h = &data.history;
+    if !(0.0..=0.35).contains(&h.last_wall_density) {
+        return Err(format!("Invalid wall density: {}", h.last_wall_density));
+    }
+    if !(0.05..=0.35).contains(&h.last_move_interval) {
+        return Err(format!("Invalid speed: {}", h.last_move_interval));
+    }
+    if !(0.0..=1.0).contains(&h.difficulty.rating) {
+        return Err(format!("Invalid adaptive difficulty: {}", h.difficulty.rating));
+    }
+    let st = &data.settings;
+    if !(0.0..=1.0).contains(&st.sound_volume) {
+        return Err(format!("Invalid volume: {}", st.sound_volume));
+    }
+    if !(0.0..=1.0).contains(&st.music_volume) {
+        return Err(format!("Invalid music volume: {}", st.music_volume));
+    }
+    if !(UI_SCALE_MIN..=UI_SCALE_MAX).contains(&st.ui_scale) {
+        return Err(format!("Invalid UI scale: {}", st.ui_scale));
+    }
+    if !(0.0..=1.0).contains(&st.challenge) {
+        return Err(format!("Invalid adaptive challenge: {}", st.challenge));
+    }
+    if !(0.0..=1.0).contains(&st.rain_density) {
+        return Err(format!("Invalid rain density: {}", st.rain_density));
+    }
+    for (action, keys) in st.key_bindings.actions() {
+        if keys.is_empty() || keys.iter().any(|k| key_code(k).is_none()) {
+            return Err(format!("Invalid key binding for {}", action));
+        }
+    }
+    if !valid_channel(&st.twitch_channel) {
+        return Err(format!("Invalid Twitch channel: {}", st.twitch_channel));
+    }
+    if !valid_sync_url(&st.sync_url) {
+        return Err(format!("Invalid cloud sync URL: {}", st.sync_url));
+    }
+    Ok(())
+}
+
+fn merge_save(current: SaveData, incoming: SaveData, mode: ImportMode) -> SaveData {
+    match mode {
+        ImportMode::Replace => incoming,
+        ImportMode::Merge => SaveData {
+            progress: {
+                let mut progress = current.progress;
+                progress.best_score = progress.best_score.max(incoming.progress.best_score);
+                for entry in incoming.progress.high_scores {
+                    // Importing the same save twice shouldn't list its runs twice
+                    let known = progress.high_scores.iter().any(|e| e.same_run(&entry));
+                    if !known { progress.record_score(entry); }
+                }
+                for (id, date) in incoming.progress.achievements {
+                    let kept = progress.achievements.entry(id).or_insert(date);
+                    *kept = (*kept).min(date);
+                }
+                for (key, splits) in incoming.progress.best_splits {
+                    progress.record_splits(key, &splits);
+                }
+                for (key, score) in incoming.progress.weekly_best {
+                    progress.record_weekly(key, score);
+                }
+                progress.record_coop(incoming.progress.coop_best);
+                progress
+            },
+            history: History {
+                last_seed: if incoming.history.last_seed != 0 { incoming.history.last_seed } else { current.history.last_seed },
+                ..incoming.history
+            },
+            settings: Settings {
+                twitch_channel: if incoming.settings.twitch_channel.is_empty() { current.settings.twitch_channel } else { incoming.settings.twitch_channel },
+                ..incoming.settings
+            },
+            // Totals can't be added up without counting shared runs twice: keep the longer record
+            stats: if incoming.stats.games > current.stats.games { incoming.stats } else { current.stats },
+            ..incoming
+        },
+    }
+}
+
+pub fn export_save_text() -> String {
+    serde_json::to_string_pretty(&load_save()).unwrap_or_default()
+}
+
+pub fn import_save_text(text: &str, mode: ImportMode) -> Result<SaveData, String> {
+    let incoming = parse_save(text).map_err(|e| format!("Invalid save: {}", e))?;
+    validate_save(&incoming)?;
+    let current = load_save();
+    // The token belongs to this machine; an export old enough to carry one doesn't replace it
+    let token = current.settings.sync_token.clone();
+    let mut merged = merge_save(current, incoming, mode);
+    merged.settings.sync_token = token;
+    write_save(&merged);
+    Ok(merged)
+}
+
+// Cloud sync: whichever copy was written last wins settings, history and stats, and progress
+// is merged from both. This machine keeps its own sync address and token either way.
+pub fn sync_save_text(text: &str) -> Result<SaveData, String> {
+    let remote = parse_save(text).map_err(|e| format!("Invalid cloud save: {}", e))?;
+    validate_save(&remote)?;
+    let merged = sync_merge(load_save(), remote);
+    write_save(&merged);
+    Ok(merged)
+}
+
+fn sync_merge(local: SaveData, remote: SaveData) -> SaveData {
+    let sync = (local.settings.sync_url.clone(), local.settings.sync_token.clone());
+    let mut merged = if remote.saved_at > local.saved_at {
+        merge_save(local, remote, ImportMode::Merge)
+    } else {
+        merge_save(remote, local, ImportMode::Merge)
+    };
+    (merged.settings.sync_url, merged.settings.sync_token) = sync;
+    merged
+}
+
+// In-progress run, kept separately from the save so it can be dropped once the run ends
+pub const AUTOSAVE_INTERVAL: f32 = 5.0; // seconds between autosaves while playing
+
+pub fn run_path() -> String { "snake_run.json".to_string() }
+
+pub fn save_run(game: &SnakeGame) {
+    match serde_json::to_string(game) {
+        Ok(text) => {
+            if let Err(e) = write_atomic(&run_path(), &text) { logging::error(format!("Autosave failed: {}", e)); }
+        }
+        Err(e) => logging::error(format!("Autosave could not serialize the run: {}", e)),
+    }
+}
+
+pub fn load_run() -> Option<SnakeGame> {
+    let text = fs::read_to_string(run_path()).ok()?;
+    match serde_json::from_str::<SnakeGame>(&text) {
+        Ok(game) => Some(game).filter(|g| g.sim.alive),
+        Err(e) => {
+            logging::warn(format!("Autosaved run unreadable: {}", e));
+            None
+        }
+    }
+}
+
+pub fn clear_run() {
+    if let Err(e) = fs::remove_file(run_path()) && e.kind() != std::io::ErrorKind::NotFound {
+        logging::warn(format!("Could not remove autosaved run: {}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::GridConfig;
+
+    fn save_with_best(best: u32) -> SaveData {
+        let mut data = SaveData::default();
+        data.progress.best_score = best;
+        data
+    }
+
+    fn entry(score: u32, date: u64) -> ScoreEntry {
+        ScoreEntry {
+            score,
+            seed: 42,
+            wall_density: 0.1,
+            move_interval: 0.12,
+            grid: GridConfig::DEFAULT,
+            mode: GameMode::Classic,
+            map_style: DEFAULT_STYLE.to_string(),
+            date,
+            name: String::new(),
+            multiplier: 1.0,
+        }
+    }
+
+    // A save file and its backup in a directory of their own, removed when the test is done
+    struct Files {
+        dir: std::path::PathBuf,
+    }
+
+    impl Files {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("snake_save_test_{}_{}", name, std::process::id()));
+            fs::remove_dir_all(&dir).ok(); // left over from an earlier run, if any
+            fs::create_dir_all(&dir).unwrap();
+            Self { dir }
+        }
+
+        fn path(&self, file: &str) -> String { self.dir.join(file).to_string_lossy().into_owned() }
+    }
+
+    impl Drop for Files {
+        fn drop(&mut self) { fs::remove_dir_all(&self.dir).ok(); }
+    }
+
+    #[test]
+    fn flat_legacy_saves_migrate_into_sections() {
+        let data = parse_save(r#"{"best_score": 42, "last_seed": 7, "last_wall_density": 0.0, "last_move_interval": 0.1, "sound_volume": 0.5}"#).unwrap();
+        assert_eq!(data.progress.best_score, 42);
+        assert_eq!(data.history.last_seed, 7);
+        // Zero meant "unset" in the flat layout
+        assert_eq!(data.history.last_wall_density, History::default().last_wall_density);
+        assert_eq!(data.history.last_move_interval, 0.1);
+        assert_eq!(data.settings.sound_volume, 0.5);
+        assert_eq!(data.version, SAVE_VERSION);
+    }
+
+    #[test]
+    fn files_that_are_not_saves_are_rejected() {
+        assert!(parse_save("[1, 2, 3]").is_err());
+        assert!(parse_save(r#"{"name": "something else"}"#).is_err());
+        assert!(parse_save("{ not json").is_err());
+    }
+
+    #[test]
+    fn a_damaged_save_is_restored_from_the_backup() {
+        let files = Files::new("restore");
+        let (path, backup) = (files.path("save.json"), files.path("save.json.bak"));
+        fs::write(&path, "{ damaged").unwrap();
+        fs::write(&backup, serde_json::to_string(&save_with_best(17)).unwrap()).unwrap();
+
+        let (data, notice) = recover_save(&path, &backup);
+        assert_eq!(data.progress.best_score, 17);
+        assert!(notice.is_some());
+        // The main file is whole again, so the next load needs no recovery
+        assert_eq!(recover_save(&path, &backup).1, None);
+        assert_eq!(read_save_file(&path).unwrap().progress.best_score, 17);
+    }
+
+    #[test]
+    fn a_damaged_save_without_a_backup_is_moved_aside() {
+        let files = Files::new("corrupt");
+        let (path, backup) = (files.path("save.json"), files.path("save.json.bak"));
+        fs::write(&path, "{ damaged").unwrap();
+
+        let (data, notice) = recover_save(&path, &backup);
+        assert_eq!(data.progress.best_score, 0);
+        assert!(notice.is_some());
+        assert!(!Path::new(&path).exists());
+        assert_eq!(fs::read_to_string(format!("{}.corrupt", path)).unwrap(), "{ damaged");
+    }
+
+    #[test]
+    fn no_save_at_all_starts_fresh_without_a_notice() {
+        let files = Files::new("fresh");
+        let (data, notice) = recover_save(&files.path("save.json"), &files.path("save.json.bak"));
+        assert_eq!(data.progress.best_score, 0);
+        assert_eq!(notice, None);
+    }
+
+    #[test]
+    fn merging_keeps_the_best_score_and_takes_the_imported_settings() {
+        let mut local = save_with_best(30);
+        local.settings.sound_volume = 0.2;
+        local.history.last_seed = 7;
+        let mut incoming = save_with_best(20);
+        incoming.settings.sound_volume = 0.8;
+        let merged = merge_save(local, incoming, ImportMode::Merge);
+        assert_eq!(merged.progress.best_score, 30);
+        assert_eq!(merged.settings.sound_volume, 0.8);
+        // A zero seed means "unset", so the local one stays
+        assert_eq!(merged.history.last_seed, 7);
+    }
+
+    #[test]
+    fn merging_lists_each_imported_run_once_in_score_order() {
+        let mut local = save_with_best(30);
+        local.progress.high_scores = vec![entry(30, 1), entry(10, 2)];
+        let mut incoming = save_with_best(20);
+        incoming.progress.high_scores = vec![entry(20, 3), entry(10, 2)];
+        let merged = merge_save(local, incoming.clone(), ImportMode::Merge);
+        let scores: Vec<u32> = merged.progress.high_scores.iter().map(|e| e.score).collect();
+        assert_eq!(scores, vec![30, 20, 10]);
+        // Importing the same save again changes nothing
+        let again = merge_save(merged, incoming, ImportMode::Merge);
+        assert_eq!(again.progress.high_scores.len(), 3);
+    }
+
+    #[test]
+    fn replacing_takes_the_imported_save_as_it_is() {
+        let merged = merge_save(save_with_best(30), save_with_best(5), ImportMode::Replace);
+        assert_eq!(merged.progress.best_score, 5);
+    }
+
+    #[test]
+    fn cloud_sync_takes_the_newer_settings_but_keeps_the_local_sync_address() {
+        let mut local = save_with_best(30);
+        local.progress.high_scores = vec![entry(30, 1)];
+        local.saved_at = 100;
+        local.settings.sound_volume = 0.2;
+        local.settings.sync_url = "https://local.example".to_string();
+        let mut remote = save_with_best(40);
+        remote.progress.high_scores = vec![entry(40, 2)];
+        remote.saved_at = 200;
+        remote.settings.sound_volume = 0.8;
+        remote.settings.sync_url = "https://remote.example".to_string();
+
+        let merged = sync_merge(local, remote);
+        assert_eq!(merged.settings.sound_volume, 0.8);
+        assert_eq!(merged.settings.sync_url, "https://local.example");
+        assert_eq!(merged.progress.best_score, 40);
+        assert_eq!(merged.progress.high_scores.len(), 2);
+    }
+
+    #[test]
+    fn the_sync_token_is_never_written_into_the_save() {
+        let mut data = SaveData::default();
+        data.settings.sync_url = "https://example.com/save".to_string();
+        data.settings.sync_token = "s3cret".to_string();
+        let text = serde_json::to_string_pretty(&data).unwrap();
+        assert!(!text.contains("s3cret"));
+        assert!(!text.contains("sync_token"));
+        assert!(text.contains("https://example.com/save"));
+    }
+
+    #[test]
+    fn older_saves_with_a_token_still_load_it() {
+        let mut value = serde_json::to_value(SaveData::default()).unwrap();
+        value["settings"]["sync_token"] = serde_json::json!("old");
+        let data = parse_save(&value.to_string()).unwrap();
+        assert_eq!(data.settings.sync_token, "old");
+    }
+
+    #[test]
+    fn the_token_round_trips_through_its_own_file() {
+        let files = Files::new("credentials");
+        let path = files.path("credentials.json");
+        assert_eq!(read_sync_token(&path), None);
+        write_sync_token(&path, "abc");
+        assert_eq!(read_sync_token(&path).as_deref(), Some("abc"));
+        write_sync_token(&path, "");
+        assert!(!Path::new(&path).exists());
+    }
+
+    #[test]
+    fn out_of_range_values_fail_validation() {
+        assert!(validate_save(&SaveData::default()).is_ok());
+        let mut data = SaveData::default();
+        data.settings.sound_volume = 1.5;
+        assert!(validate_save(&data).is_err());
+        let mut data = SaveData::default();
+        data.settings.key_bindings.up = vec!["NoSuchKey".to_string()];
+        assert!(validate_save(&data).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, Transition};
+use crate::i18n::{tr, trf};
+use crate::map::Cell;
+use crate::render::matrix_char_for_cell;
+use crate::rng::Rng;
+use crate::screenshot::date_string;
+use crate::ui::{draw_centered, draw_text};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const BUILD_UNIX_TIME: &str = env!("BUILD_UNIX_TIME"); // set by build.rs
+
+const RAIN_COLUMNS: usize = 40;
+const TRAIL: usize = 12; // glyphs per falling column
+const SCROLL_SPEED: f32 = 30.0; // credits, px per second at UI scale 1
+const LINE_HEIGHT: f32 = 26.0;
+
+const CREDITS: &[&str] = &[
+    "# CONTROLS",
+    "Arrow keys / WASD - Steer",
+    "Esc - Pause, or back in menus",
+    "F3 - Debug overlay   F12 - Screenshot",
+    "Hold Q - Quit",
+    "",
+    "# LICENSE",
+    "Open source: free to use and modify",
+    "",
+    "# BUILT WITH",
+    "macroquad - windowing, drawing and audio",
+    "serde, serde_json and toml - saves and config",
+    "image and gif - screenshots and recordings",
+    "rhai - mods (scripting feature)",
+    "ureq - online leaderboard (online feature)",
+    "",
+    "# THANKS",
+    "Everyone who filed an issue or sent a patch",
+];
+
+// Rolling credits over a falling-glyph backdrop that runs even with rain turned off
+pub struct AboutState {
+    rain: Vec<(f32, f32)>, // per column: head position in glyph rows, rows per second
+    scroll: f32,
+    next: Transition,
+}
+
+impl AboutState {
+    pub fn new() -> Self {
+        let mut rng = Rng::new((get_time() * 1_000_000.0) as u64);
+        let rain = (0..RAIN_COLUMNS).map(|_| (rng.range_f32(-20.0, 30.0), rng.range_f32(4.0, 14.0))).collect();
+        Self { rain, scroll: 0.0, next: Transition::None }
+    }
+}
+
+fn build_date() -> String {
+    BUILD_UNIX_TIME.parse().map(date_string).unwrap_or_else(|_| tr("unknown").to_string())
+}
+
+impl GameScreen for AboutState {
+    fn update(&mut self, _app: &mut App, frame: &Frame) {
+        let rows = screen_height() / 20.0;
+        for (y, speed) in &mut self.rain {
+            *y += *speed * frame.dt;
+            if *y - TRAIL as f32 > rows { *y = 0.0; }
+        }
+        // Up/Down scroll by hand; otherwise the credits roll on their own
+        let mut speed = SCROLL_SPEED;
+        if is_key_down(KeyCode::Down) { speed *= 6.0; }
+        if is_key_down(KeyCode::Up) { speed = -SCROLL_SPEED * 6.0; }
+        self.scroll = (self.scroll + speed * frame.dt * frame.ui).max(0.0);
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let (sw, sh) = (screen_width(), screen_height());
+        draw_rectangle(0.0, 0.0, sw, sh, BLACK);
+
+        // Backdrop: each column trails glyphs that fade behind its head
+        let col_w = sw / RAIN_COLUMNS as f32;
+        for (x, (head, _)) in self.rain.iter().enumerate() {
+            for i in 0..TRAIL {
+                let row = head.floor() - i as f32;
+                if row < 0.0 { break; }
+                let ch = matrix_char_for_cell(Cell { x: x as i32, y: row as i32 });
+                let alpha = if i == 0 { 0.6 } else { 0.35 * (1.0 - i as f32 / TRAIL as f32) };
+                let color = Color::new(palette.rain.r, palette.rain.g, palette.rain.b, alpha);
+                draw_text(&ch.to_string(), x as f32 * col_w, row * 20.0, 20.0, color);
+            }
+        }
+
+        // Title block and credits move up together and start over once they're gone
+        let line = LINE_HEIGHT * ui;
+        let height = (CREDITS.len() + 4) as f32 * line;
+        let mut y = sh * 0.9 - self.scroll % (height + sh * 0.8);
+        draw_centered(tr("SNAKE - MATRIX STYLE"), y, 36.0 * ui, palette.head);
+        y += line * 1.5;
+        draw_centered(&trf!("Version {}  (built {})", VERSION, build_date()), y, 20.0 * ui, WHITE);
+        y += line * 2.5;
+        for text in CREDITS {
+            match text.strip_prefix("# ") {
+                Some(heading) => draw_centered(tr(heading), y, 22.0 * ui, palette.body),
+                None => draw_centered(tr(text), y, 18.0 * ui, LIGHTGRAY),
+            }
+            y += line;
+        }
+
+        draw_rectangle(0.0, sh - 36.0 * ui, sw, 36.0 * ui, BLACK);
+        draw_centered(tr("Up/Down: Scroll  Esc: Back"), sh - 12.0 * ui, 18.0 * ui, GRAY);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+}
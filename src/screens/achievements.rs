@@ -0,0 +1,83 @@
+use macroquad::prelude::*;
+use std::collections::BTreeMap;
+
+use super::{App, Frame, GameScreen, Transition};
+use crate::achievements::ALL;
+use crate::i18n::{tr, trf};
+use crate::save::load_save;
+use crate::screenshot::date_string;
+use crate::stats::Stats;
+use crate::ui::{draw_centered, draw_text};
+
+const VISIBLE_ROWS: usize = 7;
+const ROW_HEIGHT: f32 = 48.0; // name line plus status line
+const BAR_WIDTH: f32 = 200.0;
+
+// Every achievement with its unlock date, or how far along a lifetime goal is
+pub struct AchievementsState {
+    unlocked: BTreeMap<String, u64>,
+    stats: Stats,
+    selected: usize,
+    next: Transition,
+}
+
+impl AchievementsState {
+    pub fn new() -> Self {
+        let save = load_save();
+        Self { unlocked: save.progress.achievements, stats: save.stats, selected: 0, next: Transition::None }
+    }
+}
+
+impl GameScreen for AchievementsState {
+    fn update(&mut self, _app: &mut App, _frame: &Frame) {
+        let len = ALL.len();
+        if is_key_pressed(KeyCode::Up) { self.selected = if self.selected == 0 { len - 1 } else { self.selected - 1 }; }
+        if is_key_pressed(KeyCode::Down) { self.selected = (self.selected + 1) % len; }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+
+        let mut y = screen_height() * 0.12;
+        draw_centered(tr("ACHIEVEMENTS"), y, 36.0 * ui, palette.head);
+        y += 32.0 * ui;
+        draw_centered(&trf!("{} of {} unlocked", self.unlocked.len(), ALL.len()), y, 18.0 * ui, palette.body);
+        y += 36.0 * ui;
+
+        // Scroll so the selection stays visible
+        let first = self.selected.saturating_sub(VISIBLE_ROWS - 1);
+        for (i, a) in ALL.iter().enumerate().skip(first).take(VISIBLE_ROWS) {
+            let date = self.unlocked.get(a.id);
+            let color = match (date.is_some(), i == self.selected) {
+                (true, true) => WHITE,
+                (true, false) => LIGHTGRAY,
+                (false, true) => GRAY,
+                (false, false) => DARKGRAY,
+            };
+            let mark = if date.is_some() { "[x]" } else { "[ ]" };
+            draw_centered(&format!("{} {} - {}", mark, tr(a.name), tr(a.description)), y, 20.0 * ui, color);
+
+            let status_y = y + 20.0 * ui;
+            if let Some(date) = date {
+                draw_centered(&trf!("Unlocked {}", date_string(*date)), status_y, 16.0 * ui, palette.body);
+            } else if let Some((current, target)) = a.progress(&self.stats) {
+                let (w, h) = (BAR_WIDTH * ui, 8.0 * ui);
+                let x = screen_width() * 0.5 - w * 0.5 - 40.0 * ui;
+                draw_rectangle_lines(x, status_y - h, w, h, 1.0, palette.wall);
+                draw_rectangle(x, status_y - h, w * current as f32 / target as f32, h, palette.body);
+                draw_text(&format!("{}/{}", current, target), x + w + 10.0 * ui, status_y, 16.0 * ui, GRAY);
+            } else {
+                draw_centered(tr("Locked"), status_y, 16.0 * ui, DARKGRAY);
+            }
+            y += ROW_HEIGHT * ui;
+        }
+
+        y += 8.0 * ui;
+        draw_centered(tr("Up/Down: Scroll  Esc: Back"), y, 18.0 * ui, GRAY);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+}
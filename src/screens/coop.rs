@@ -0,0 +1,142 @@
+use macroquad::prelude::*;
+
+use super::royale::{draw_board, snake_color};
+use super::{App, Frame, GameScreen, QuitGesture, Transition};
+use crate::arena::{Arena, ArenaEvent};
+use crate::audio::play_sfx;
+use crate::game::MAX_FRAME_DT;
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::map::{Direction, Map};
+use crate::save::{load_save, write_save};
+use crate::ui::{draw_centered, draw_text, measure_text};
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+// Player 1 on WASD, player 2 on the arrows, in `DIRECTIONS` order
+const PLAYER_KEYS: [[KeyCode; 4]; 2] = [
+    [KeyCode::W, KeyCode::S, KeyCode::A, KeyCode::D],
+    [KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right],
+];
+
+// Two snakes at one keyboard on the lobby's map: food either eats adds to one shared score,
+// and the run ends as soon as either of them crashes, into a wall, itself or the other.
+pub struct CoopState {
+    map: Map,
+    move_interval: f32,
+    arena: Arena,
+    started: bool, // waits for a first key so neither snake sets off unready
+    accumulator: f32,
+    outcome: Option<String>,
+    best: u32,
+    new_best: bool,
+    next: Transition,
+}
+
+impl CoopState {
+    pub fn new(map: Map, move_interval: f32) -> Self {
+        let arena = Arena::new(map.clone(), 2);
+        let best = load_save().progress.coop_best;
+        Self { map, move_interval, arena, started: false, accumulator: 0.0, outcome: None, best, new_best: false, next: Transition::None }
+    }
+
+    fn score(&self) -> u32 {
+        self.arena.snakes.iter().map(|s| s.score).sum()
+    }
+
+    fn restart(&mut self) {
+        self.arena = Arena::new(self.map.clone(), 2);
+        self.started = false;
+        self.accumulator = 0.0;
+        self.outcome = None;
+        self.new_best = false;
+    }
+
+    fn step(&mut self, app: &App) {
+        let volume = app.settings.sound_volume;
+        let mut crashed = Vec::new();
+        for event in self.arena.tick() {
+            match event {
+                ArenaEvent::Ate { .. } => play_sfx(&app.sounds.eat, 0.35 * volume),
+                ArenaEvent::Died { snake } => crashed.push(snake),
+            }
+        }
+        if crashed.is_empty() { return; }
+
+        play_sfx(&app.sounds.die, 0.6 * volume);
+        app.sounds.duck_music();
+        let score = self.score();
+        let mut s = load_save();
+        self.new_best = s.progress.record_coop(score);
+        self.best = s.progress.coop_best;
+        write_save(&s);
+        let outcome = match crashed.as_slice() {
+            [snake] => trf!("Player {} crashed", snake + 1),
+            _ => tr("Both snakes crashed").to_string(),
+        };
+        logging::info(format!("Co-op run ended at tick {} with score {}: {}", self.arena.tick, score, outcome));
+        self.outcome = Some(outcome);
+    }
+}
+
+impl GameScreen for CoopState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        if self.outcome.is_some() {
+            if is_key_pressed(KeyCode::R) { self.restart(); }
+            if is_key_pressed(KeyCode::Enter) { self.next = Transition::Pop; }
+            return;
+        }
+
+        for (snake, keys) in self.arena.snakes.iter_mut().zip(PLAYER_KEYS) {
+            for (key, dir) in keys.into_iter().zip(DIRECTIONS) {
+                if is_key_pressed(key) {
+                    snake.turn(dir);
+                    self.started = true;
+                }
+            }
+        }
+        if !self.started { return; }
+
+        self.accumulator += frame.dt.clamp(0.0, MAX_FRAME_DT);
+        while self.outcome.is_none() && self.accumulator >= self.move_interval {
+            self.accumulator -= self.move_interval;
+            self.step(app);
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let (sw, sh) = (screen_width(), screen_height());
+        let top = 32.0 * ui;
+        draw_board(&self.arena, &palette, Rect::new(8.0, top, sw - 16.0, sh - top - 40.0 * ui));
+
+        // Shared score on the left, what each snake brought in on the right
+        let size = 22.0 * ui;
+        draw_text(&trf!("Score: {}  Best: {}", self.score(), self.best), 8.0, size, size, palette.body);
+        let mut x = sw - 8.0;
+        for (i, snake) in self.arena.snakes.iter().enumerate().rev() {
+            let line = trf!("P{}: {}", i + 1, snake.score);
+            x -= measure_text(&line, size).width;
+            draw_text(&line, x, size, size, snake_color(i, true, &palette));
+            x -= 16.0 * ui;
+        }
+
+        let status = match &self.outcome {
+            Some(outcome) if self.new_best => trf!("{} - new co-op best!  R: Restart  Enter: Back", outcome),
+            Some(outcome) => trf!("{}  R: Restart  Enter: Back", outcome),
+            None if !self.started => tr("P1: WASD  P2: Arrow keys  -  move to start").to_string(),
+            None => tr("Esc: Leave").to_string(),
+        };
+        draw_centered(&status, sh - 16.0 * ui, 22.0 * ui, WHITE);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn quit_gesture(&self) -> QuitGesture {
+        if self.started && self.outcome.is_none() { QuitGesture::Hold } else { QuitGesture::Confirm }
+    }
+
+    fn is_live(&self) -> bool { self.started && self.outcome.is_none() }
+}
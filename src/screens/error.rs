@@ -0,0 +1,59 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, Transition};
+use crate::error::GameError;
+use crate::i18n::{tr, trf};
+use crate::logging::LOG_PATH;
+use crate::ui::{draw_centered, measure_text};
+
+const DETAIL_MAX_CHARS: usize = 120; // long panic messages are cut, the log has them whole
+
+// What went wrong and how the game carries on. Enter continues to `then`, Esc quits.
+pub struct ErrorState {
+    errors: Vec<GameError>,
+    then: Option<Box<dyn GameScreen>>,
+    next: Transition,
+}
+
+impl ErrorState {
+    pub fn new(errors: Vec<GameError>, then: Box<dyn GameScreen>) -> Self {
+        Self { errors, then: Some(then), next: Transition::None }
+    }
+}
+
+impl GameScreen for ErrorState {
+    fn update(&mut self, _app: &mut App, _frame: &Frame) {
+        if is_key_pressed(KeyCode::Enter) || is_mouse_button_pressed(MouseButton::Left) {
+            self.next = match self.then.take() {
+                Some(screen) => Transition::Replace(screen),
+                None => Transition::Quit,
+            };
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let ui = frame.ui;
+        let (sw, sh) = (screen_width(), screen_height());
+        draw_rectangle(0.0, 0.0, sw, sh, Color::new(0.0, 0.0, 0.0, 0.85));
+        let mut y = sh * 0.3;
+        for error in &self.errors {
+            draw_centered(tr(error.title()), y, 32.0 * ui, RED);
+            let mut detail: String = error.detail().chars().take(DETAIL_MAX_CHARS).collect();
+            // Cut further to fit the window, on a character boundary
+            while !detail.is_empty() && measure_text(&detail, 16.0 * ui).width > sw - 40.0 {
+                detail.pop();
+            }
+            draw_centered(&detail, y + 28.0 * ui, 16.0 * ui, GRAY);
+            draw_centered(tr(error.fallback()), y + 56.0 * ui, 20.0 * ui, WHITE);
+            y += 110.0 * ui;
+        }
+        draw_centered(&trf!("Details are in {}", LOG_PATH), y, 18.0 * ui, GRAY);
+        draw_centered(tr("Enter: Continue  Esc: Quit"), y + 32.0 * ui, 20.0 * ui, frame.palette.head);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn back(&mut self, _app: &mut App) -> Transition { Transition::Quit }
+}
@@ -0,0 +1,276 @@
+use macroquad::prelude::*;
+
+use super::lobby::next_seed;
+use super::{App, Frame, GameScreen, LobbyState, PlayingState, Transition};
+use crate::achievements;
+use crate::bot::BotCommand;
+use crate::game::{GameMode, SnakeGame};
+use crate::i18n::{tr, trf};
+use crate::leaderboard::{submissions_enabled, submit};
+use crate::logging;
+use crate::net::Pending;
+use crate::recorder::{GifRecorder, Recording};
+use crate::replay::save_replay;
+use crate::runlog::{self, RunRecord};
+use crate::save::{load_save, unix_now, write_save, ScoreEntry, NAME_MAX_LEN};
+use crate::skins::{self, Skin};
+use crate::toast;
+use crate::stats::{format_duration, RunSummary};
+use crate::speedrun::{delta_color, format_delta, format_time, run_key, split_delta, SPLIT_EVERY};
+use crate::ui::{draw_centered, draw_text, measure_text};
+use crate::weekly::{Challenge, Modifier};
+
+// Splits shown on the game-over summary
+const SUMMARY_SPLITS: usize = 3;
+
+// How the run's splits compare with the best pace it was chasing
+struct Pace {
+    previous: Option<Vec<u32>>, // best pace before this run
+    new_best: bool,
+}
+
+// A run that just made the High Scores table, waiting for its name
+struct NameEntry {
+    rank: usize,
+    entry: ScoreEntry,
+    name: String,
+}
+
+pub struct GameOverState {
+    pub game: SnakeGame,
+    naming: Option<NameEntry>,
+    pace: Pace,
+    summary: RunSummary,
+    submission: Option<Pending<()>>,
+    submit_status: Option<String>,
+    recording: Option<Recording>, // last seconds of the run, until saved as a GIF
+    gif_save: Option<Pending<String>>,
+    gif_status: Option<String>,
+    next: Transition,
+}
+
+impl GameOverState {
+    pub fn new(game: SnakeGame, recorder: Option<GifRecorder>) -> Self {
+        // Save best, the score table and lifetime stats
+        let mut s = load_save();
+        let locked: Vec<&Skin> = skins::ALL.iter().filter(|k| !k.is_unlocked(&s.progress)).collect();
+        // Runs played with the path hint count for stats, but not for any score table
+        let ranked = !game.assisted;
+        let best = ranked && game.sim.score > s.progress.best_score;
+        if best { s.progress.best_score = game.sim.score; }
+        let entry = ScoreEntry::from_replay(&game.replay);
+        let placed = if ranked { s.progress.record_score(entry.clone()) } else { None };
+        s.stats.record_run(&game);
+        let key = run_key(&game.replay);
+        let previous = s.progress.best_splits.get(&key).cloned();
+        let pace = Pace { previous, new_best: s.progress.record_splits(key, &game.splits) };
+        let challenge = Challenge::current();
+        let weekly_best = ranked && challenge.matches(&game.replay) && s.progress.record_weekly(challenge.key(), game.sim.score);
+        if best { toast::reward(trf!("New best score: {}", game.sim.score)); }
+        if weekly_best { toast::reward(tr("New weekly challenge best!")); }
+        for a in achievements::check(&mut s.progress.achievements, &s.stats, &game, unix_now()) {
+            logging::info(format!("Achievement unlocked: {}", a.name));
+            toast::reward(trf!("Achievement unlocked: {}", tr(a.name)));
+        }
+        for skin in locked.into_iter().filter(|k| k.is_unlocked(&s.progress)) {
+            toast::reward(trf!("Skin unlocked: {}", tr(skin.name)));
+        }
+        write_save(&s);
+        let naming = placed.map(|rank| {
+            // Keys typed while steering shouldn't end up in the name
+            clear_input_queue();
+            NameEntry { rank, entry, name: s.history.last_name.clone() }
+        });
+        save_replay(&game.replay);
+        runlog::append(&RunRecord::from_game(&game));
+        let (submission, submit_status) = if !ranked {
+            (None, Some(tr("Assisted run: not ranked").to_string()))
+        } else if submissions_enabled() {
+            (Some(submit(&game.replay)), Some(tr("Submitting score...").to_string()))
+        } else {
+            (None, None)
+        };
+        let recording = recorder.filter(|r| !r.is_empty()).map(|mut r| r.take());
+        let summary = RunSummary::of(&game);
+        Self { game, naming, pace, summary, submission, submit_status, recording, gif_save: None, gif_status: None, next: Transition::None }
+    }
+
+    // How the run went, three lines from y down; returns where the next line goes
+    fn draw_analysis(&self, y: f32, frame: &Frame) -> f32 {
+        let (s, ui) = (&self.summary, frame.ui);
+        let ending = match s.cause {
+            Some(cause) => trf!("Cause of death: {}", tr(cause.label())),
+            None => tr("Board cleared").to_string(),
+        };
+        let first = trf!("{}  Length: {}  Time: {}", ending, s.length, format_duration(s.seconds));
+        let replay = &self.game.replay;
+        draw_centered(&trf!("Score {} x{} = {}", replay.score, format!("{:.2}", replay.score_multiplier()), replay.rated_score()), y, 20.0 * ui, WHITE);
+        let y = y + 28.0 * ui;
+        let second = trf!("Food per minute: {}  Longest combo: {}  Distance: {} cells", format!("{:.1}", s.food_per_minute()), s.longest_combo, s.distance);
+        draw_centered(&first, y, 18.0 * ui, LIGHTGRAY);
+        draw_centered(&second, y + 24.0 * ui, 18.0 * ui, LIGHTGRAY);
+        y + 48.0 * ui
+    }
+
+    // Final time and the last few splits against the best pace
+    fn draw_pace(&self, y: f32, frame: &Frame) {
+        let ui = frame.ui;
+        let interval = self.game.replay.move_interval;
+        let time = format_time(self.game.replay.ticks as f32 * interval);
+        if self.pace.new_best {
+            draw_centered(&trf!("Time: {}  New best pace!", time), y, 20.0 * ui, frame.palette.food);
+        } else {
+            draw_centered(&trf!("Time: {}", time), y, 20.0 * ui, WHITE);
+        }
+        let splits = &self.game.splits;
+        let first = splits.len().saturating_sub(SUMMARY_SPLITS);
+        let parts: Vec<(String, Color)> = (first..splits.len())
+            .map(|i| {
+                let text = format!("{}: {}", (i as u32 + 1) * SPLIT_EVERY, format_time(splits[i] as f32 * interval));
+                match self.pace.previous.as_deref().and_then(|best| split_delta(splits, best, i, interval)) {
+                    Some(delta) => (format!("{} ({})", text, format_delta(delta)), delta_color(delta, &frame.palette)),
+                    None => (text, LIGHTGRAY),
+                }
+            })
+            .collect();
+        // Each split keeps its own color, so the row is laid out piece by piece
+        let size = 16.0 * ui;
+        let gap = 24.0 * ui;
+        let total: f32 = parts.iter().map(|(t, _)| measure_text(t, size).width).sum::<f32>() + gap * parts.len().saturating_sub(1) as f32;
+        let mut x = (screen_width() - total) * 0.5;
+        for (text, color) in &parts {
+            x += draw_text(text, x, y + 22.0 * ui, size, *color).width + gap;
+        }
+    }
+}
+
+impl GameScreen for GameOverState {
+    fn update(&mut self, app: &mut App, _frame: &Frame) {
+        // Bot runs go straight on without waiting for a name
+        if app.bot.is_some() { self.naming = None; }
+        if let Some(naming) = &mut self.naming {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() && naming.name.chars().count() < NAME_MAX_LEN { naming.name.push(c); }
+            }
+            if is_key_pressed(KeyCode::Backspace) { naming.name.pop(); }
+            if is_key_pressed(KeyCode::Enter) {
+                let name = naming.name.trim();
+                let mut s = load_save();
+                s.progress.name_score(&naming.entry, name);
+                s.history.last_name = name.to_string();
+                write_save(&s);
+                self.naming = None;
+            }
+            if is_key_pressed(KeyCode::Escape) { self.naming = None; }
+            return;
+        }
+        if let Some(result) = self.submission.as_ref().and_then(|p| p.poll()) {
+            self.submission = None;
+            self.submit_status = Some(match result {
+                Ok(()) => tr("Score submitted").to_string(),
+                Err(e) => {
+                    logging::warn(format!("Score submission failed: {}", e));
+                    trf!("Score not submitted: {}", e)
+                }
+            });
+        }
+        if let Some(result) = self.gif_save.as_ref().and_then(|p| p.poll()) {
+            self.gif_save = None;
+            self.gif_status = Some(match result {
+                Ok(path) => {
+                    logging::info(format!("GIF saved: {}", path));
+                    trf!("GIF saved: {}", path)
+                }
+                Err(e) => {
+                    logging::error(trf!("Saving GIF failed: {}", e));
+                    format!("Saving GIF failed: {}", e)
+                }
+            });
+        }
+        if is_key_pressed(KeyCode::G) && let Some(recording) = self.recording.take() {
+            // Encoding a few hundred frames takes a moment, so it runs in the background
+            let (seed, score) = (self.game.sim.map.seed, self.game.sim.score);
+            self.gif_save = Some(Pending::spawn(move || recording.save(seed, score)));
+            self.gif_status = Some(tr("Saving GIF...").to_string());
+        }
+        let bot_restart = app.bot.as_mut().and_then(|b| b.poll()).is_some_and(|c| matches!(c, BotCommand::Restart));
+        if is_key_pressed(KeyCode::R) || bot_restart {
+            // Same map and speed, fresh snake; with Shift, the next seed on the same settings
+            let new_map = !bot_restart && (is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift));
+            let mut game = self.game.clone();
+            if game.replay.mode == GameMode::Adaptive {
+                game.retune(app.difficulty.wall_density(), app.difficulty.move_interval());
+            }
+            if new_map {
+                let seed = next_seed(game.sim.map.seed);
+                logging::info(format!("New map from the game over screen: seed {}", seed));
+                game.restart_on(seed);
+                // The lobby picks up from here next time
+                let mut s = load_save();
+                s.history.last_seed = seed;
+                write_save(&s);
+            } else {
+                game.restart();
+            }
+            if game.replay.modifier == Some(Modifier::Graveyard) { game.set_corpses(app.graveyard.corpses(&game.sim.map)); }
+            self.next = Transition::Replace(Box::new(PlayingState::new(game)));
+        }
+        if is_key_pressed(KeyCode::Enter) { self.next = Transition::Replace(Box::new(LobbyState::new())); }
+    }
+
+    fn draw(&mut self, app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+
+        self.game.draw(&palette, frame.skin, frame.look);
+        // Overlay
+        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.4));
+        self.game.draw_heatmap(&palette, frame.look);
+        let sh = screen_height();
+        let title = if self.game.sim.won { tr("YOU WIN") } else { tr("GAME OVER") };
+        draw_centered(title, sh * 0.4, 36.0 * ui, palette.head);
+        if let Some(naming) = &self.naming {
+            let cursor = if (get_time() * 2.0) as i64 % 2 == 0 { "_" } else { "" };
+            draw_centered(&trf!("NEW HIGH SCORE - #{}", naming.rank + 1), sh * 0.4 + 60.0 * ui, 22.0 * ui, palette.food);
+            draw_centered(&trf!("Name: {}{}", naming.name, cursor), sh * 0.4 + 90.0 * ui, 22.0 * ui, palette.head);
+            draw_centered(tr("Type a name  Enter: Save  Esc: Skip"), sh * 0.4 + 118.0 * ui, 18.0 * ui, GRAY);
+            return;
+        }
+        draw_centered(tr("R: Same map  Shift+R: New map  Enter/Esc: Lobby  Q: Quit"), sh * 0.4 + 56.0 * ui, 22.0 * ui, WHITE);
+        let mut y = self.draw_analysis(sh * 0.4 + 84.0 * ui, frame);
+        if let Some(waves) = &self.game.sim.waves {
+            let scores: Vec<String> = waves.scores.iter().map(|s| s.to_string()).collect();
+            draw_centered(&trf!("Reached wave {}  Per wave: {}", waves.wave, scores.join(" / ")), y, 18.0 * ui, palette.body);
+            y += 24.0 * ui;
+        }
+        if self.game.replay.mode == GameMode::Adaptive {
+            let text = trf!("Difficulty: {}%  Next run: {}% walls, {}ms", format!("{:.0}", app.difficulty.rating * 100.0), format!("{:.0}", app.difficulty.wall_density() * 100.0), format!("{:.0}", app.difficulty.move_interval() * 1000.0));
+            draw_centered(&text, y, 18.0 * ui, palette.body);
+            y += 24.0 * ui;
+        }
+        if let Some(decay) = self.game.sim.decay {
+            draw_centered(&trf!("Peak score: {}  Lost to decay: {}", decay.peak, decay.lost), y, 18.0 * ui, palette.body);
+            y += 24.0 * ui;
+        }
+        if let Some(status) = &self.submit_status {
+            draw_centered(status, y, 18.0 * ui, palette.body);
+            y += 24.0 * ui;
+        }
+        if self.recording.is_some() {
+            draw_centered(tr("G: Save GIF of this run"), y, 18.0 * ui, WHITE);
+            y += 24.0 * ui;
+        } else if let Some(status) = &self.gif_status {
+            draw_centered(status, y, 18.0 * ui, palette.body);
+            y += 24.0 * ui;
+        }
+        if app.settings.speedrun_timer { self.draw_pace(y + 12.0 * ui, frame); }
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn is_typing(&self) -> bool { self.naming.is_some() }
+
+    fn back(&mut self, _app: &mut App) -> Transition { Transition::Replace(Box::new(LobbyState::new())) }
+}
@@ -0,0 +1,122 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, Transition};
+use crate::i18n::{tr, trf};
+use crate::powerups::BOMB_RADIUS;
+use crate::sim::{CHAIN_BONUS, DECAY_SECONDS, GLITCH_PENALTY, LENGTH_CAP, NIGHT_BONUS, PREMIUM_POINTS, SHED_SECONDS, STARVE_EVERY};
+use crate::ui::{draw_centered, KeyBindings};
+use crate::waves::WAVE_FOOD;
+
+const LINE_HEIGHT: f32 = 22.0;
+
+enum Line {
+    Heading(&'static str),
+    Text(String),
+    Gap,
+}
+
+fn text(s: &str) -> Line { Line::Text(tr(s).to_string()) }
+
+// How to play, with the movement keys taken from the bindings in effect when it opened
+fn lines(bindings: &KeyBindings) -> Vec<Line> {
+    let mut lines = vec![
+        Line::Heading("RULES"),
+        text("Steer the snake to the food: each one scores a point and adds a segment"),
+        text("The snake never stops, and it can't reverse straight into itself"),
+        text("Fill every free cell with snake to clear the board and win"),
+        Line::Gap,
+        Line::Heading("HAZARDS"),
+        text("The border and the walls inside the map end the run"),
+        text("So does running into your own tail"),
+        Line::Text(trf!("Hunger mode: out of moves, the tail shrinks every {} ticks until you starve", STARVE_EVERY)),
+        text("Waves mode: a patroller (X) paces back and forth and kills on contact"),
+        Line::Text(trf!("Score decay modifier: a point is lost every {} seconds without food", DECAY_SECONDS)),
+        text("Graveyard modifier: where you die, your body stays as walls for the next tries on that map"),
+        Line::Text(trf!("Length cap modifier: past {} segments, each meal leaves the tail behind as a wall for {} seconds", LENGTH_CAP, SHED_SECONDS)),
+        Line::Text(trf!("Food chain modifier: three foods at once, eaten green, white, gold for {} points on top; out of order halves the combo", CHAIN_BONUS)),
+        Line::Text(trf!("Day and night modifier: at night only the cells around the head and the food show, and food is worth {} points", 1 + NIGHT_BONUS)),
+        Line::Gap,
+        Line::Heading("PICKUPS"),
+        text("Food: the glyph that differs from the snake"),
+        text("The others, and phase charges, only show up when switched on in Settings > Gameplay"),
+        Line::Text(trf!("$ - premium food worth {} points, now and then in a dead end or narrow corridor; it doesn't wait long", PREMIUM_POINTS)),
+        Line::Text(trf!("Glitch food: a red-tinted copy of the food; it costs {} points and inverts your controls for a while", GLITCH_PENALTY)),
+        text("& - reverse: head and tail swap places, a way out of a dead end"),
+        Line::Text(trf!("% - bomb: destroys the walls within {} cells for the rest of the run", BOMB_RADIUS)),
+        Line::Gap,
+        Line::Heading("MODES"),
+        text("Classic - the plain game"),
+        text("Twitch - a Twitch channel's chat votes on every turn"),
+        text("Hunger - eat within the move budget shown in the HUD"),
+        Line::Text(trf!("Waves - every {} food starts a wave with a new hazard: more walls, a patroller or faster ticks", WAVE_FOOD)),
+        text("Adaptive - wall density and speed follow how well you play; Settings sets the challenge"),
+        Line::Gap,
+        Line::Heading("KEYS"),
+    ];
+    for (action, keys) in bindings.actions() {
+        lines.push(Line::Text(format!("{} - {}", keys.join(" / "), tr(action))));
+    }
+    lines.extend([
+        text("Space / click - Turn clockwise with One-switch controls on; press twice quickly to turn the other way"),
+        text("Esc - Pause (S there opens settings) or go back a screen"),
+        text("R - Retry the map after game over (Shift+R: a new map)   G - Save a GIF of the run"),
+        text("F3 - Debug overlay   F11 - Fullscreen   F12 - Screenshot"),
+        text("Q - Quit (hold it during a run)"),
+    ]);
+    lines
+}
+
+pub struct HelpState {
+    lines: Vec<Line>,
+    first: usize, // topmost line shown
+    next: Transition,
+}
+
+impl HelpState {
+    pub fn new(bindings: &KeyBindings) -> Self {
+        Self { lines: lines(bindings), first: 0, next: Transition::None }
+    }
+}
+
+// Lines that fit between the title and the hint at the bottom
+fn visible_lines(ui: f32) -> usize {
+    ((screen_height() * 0.85 - 120.0 * ui) / (LINE_HEIGHT * ui)).max(1.0) as usize
+}
+
+impl GameScreen for HelpState {
+    fn update(&mut self, _app: &mut App, frame: &Frame) {
+        let last = self.lines.len().saturating_sub(visible_lines(frame.ui));
+        if is_key_pressed(KeyCode::Up) { self.first = self.first.saturating_sub(1); }
+        if is_key_pressed(KeyCode::Down) { self.first = (self.first + 1).min(last); }
+        if is_key_pressed(KeyCode::H) || is_key_pressed(KeyCode::F1) {
+            self.next = Transition::Pop;
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+
+        let mut y = screen_height() * 0.12;
+        draw_centered(tr("HOW TO PLAY"), y, 36.0 * ui, palette.head);
+        y += 48.0 * ui;
+
+        let shown = visible_lines(ui);
+        for line in self.lines.iter().skip(self.first).take(shown) {
+            match line {
+                Line::Heading(h) => draw_centered(tr(h), y, 22.0 * ui, palette.body),
+                Line::Text(t) => draw_centered(t, y, 18.0 * ui, LIGHTGRAY),
+                Line::Gap => {}
+            }
+            y += LINE_HEIGHT * ui;
+        }
+
+        let more = self.first + shown < self.lines.len();
+        let hint = if more { tr("Up/Down: Scroll (more below)  Esc: Back") } else { tr("Up/Down: Scroll  Esc: Back") };
+        draw_centered(hint, y + 12.0 * ui, 18.0 * ui, GRAY);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+}
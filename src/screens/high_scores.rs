@@ -0,0 +1,130 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, PlayingState, Transition};
+use crate::game::SnakeGame;
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::mapgen;
+use crate::save::{clear_run, load_save, ScoreEntry};
+use crate::screenshot::date_string;
+use crate::ui::{draw_centered, draw_text};
+
+const ROW_HEIGHT: f32 = 24.0;
+// Fits the default 800 px window at UI scale 1, with room for a 20-digit seed
+const TABLE_WIDTH: f32 = 780.0;
+// Left edge of each column, relative to the table
+const COLUMNS: [(&str, f32); 10] = [
+    ("#", 0.0),
+    ("Name", 26.0),
+    ("Score", 140.0),
+    ("Seed", 196.0),
+    ("Map", 372.0),
+    ("Walls", 452.0),
+    ("Speed", 504.0),
+    ("Grid", 562.0),
+    ("Mode", 618.0),
+    ("Date", 690.0),
+];
+
+// The local score table; Enter (or a click) plays the selected run's map again
+pub struct HighScoresState {
+    entries: Vec<ScoreEntry>,
+    selected: usize,
+    next: Transition,
+}
+
+impl HighScoresState {
+    pub fn new() -> Self {
+        Self { entries: load_save().progress.high_scores, selected: 0, next: Transition::None }
+    }
+
+    fn play_again(&mut self, app: &mut App) {
+        let Some(entry) = self.entries.get(self.selected) else { return; };
+        let map = mapgen::generate(&entry.map_style, entry.grid, entry.seed, entry.wall_density);
+        logging::info(format!("Run started from High Scores: seed {} style {}", entry.seed, entry.map_style));
+        let mut game = SnakeGame::new(map, entry.move_interval);
+        game.set_mode(entry.mode);
+        clear_run();
+        app.last_autosave = get_time() as f32;
+        self.next = Transition::Root(Box::new(PlayingState::new(game)));
+    }
+}
+
+// Baseline of the first row and the table's left edge
+fn table_origin(ui: f32) -> (f32, f32) {
+    ((screen_width() - TABLE_WIDTH * ui) * 0.5, screen_height() * 0.15 + 84.0 * ui)
+}
+
+fn cells(rank: usize, e: &ScoreEntry) -> [String; 10] {
+    [
+        format!("{}", rank + 1),
+        if e.name.is_empty() { "---".to_string() } else { e.name.clone() },
+        format!("{}", e.rated()),
+        format!("{}", e.seed),
+        tr(&e.map_style).to_string(),
+        format!("{:.0}%", e.wall_density * 100.0),
+        format!("{:.0}ms", e.move_interval * 1000.0),
+        format!("{}x{}", e.grid.width, e.grid.height),
+        tr(e.mode.label()).to_string(),
+        date_string(e.date),
+    ]
+}
+
+impl GameScreen for HighScoresState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        let len = self.entries.len();
+        if len > 0 && is_key_pressed(KeyCode::Up) {
+            self.selected = if self.selected == 0 { len - 1 } else { self.selected - 1 };
+        }
+        if len > 0 && is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % len;
+        }
+
+        // Hovering a row selects it, clicking plays it
+        let (left, top) = table_origin(frame.ui);
+        let (mx, my) = mouse_position();
+        let row_h = ROW_HEIGHT * frame.ui;
+        let hovered = (mx >= left && mx <= left + TABLE_WIDTH * frame.ui && my > top - row_h)
+            .then(|| ((my - (top - row_h)) / row_h) as usize)
+            .filter(|i| *i < len);
+        if let Some(i) = hovered && mouse_delta_position() != Vec2::ZERO { self.selected = i; }
+        let clicked = hovered.is_some() && is_mouse_button_pressed(MouseButton::Left);
+
+        if is_key_pressed(KeyCode::Enter) || clicked { self.play_again(app); }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let size = 16.0 * ui;
+
+        draw_centered(tr("HIGH SCORES"), screen_height() * 0.15, 36.0 * ui, palette.head);
+        let (left, mut y) = table_origin(ui);
+        for (title, x) in COLUMNS {
+            draw_text(tr(title), left + x * ui, y - 30.0 * ui, 16.0 * ui, palette.body);
+        }
+        if self.entries.is_empty() {
+            draw_centered(tr("No scores yet - finish a run to get on the table"), y, 20.0 * ui, GRAY);
+            y += ROW_HEIGHT * ui;
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            let color = if i == self.selected { WHITE } else { GRAY };
+            for (text, (_, x)) in cells(i, entry).iter().zip(COLUMNS) {
+                draw_text(text, left + x * ui, y, size, color);
+            }
+            y += ROW_HEIGHT * ui;
+        }
+        // Scores are ranked with the settings' multiplier applied; the selected one is broken down
+        if let Some(e) = self.entries.get(self.selected) {
+            y += 8.0 * ui;
+            draw_centered(&trf!("Score {} x{} = {}", e.score, format!("{:.2}", e.multiplier), e.rated()), y, 18.0 * ui, palette.body);
+            y += ROW_HEIGHT * ui;
+        }
+        y += 16.0 * ui;
+        draw_centered(tr("Up/Down: Select  Enter/Click: Play this seed again  Esc: Back"), y, 18.0 * ui, GRAY);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+}
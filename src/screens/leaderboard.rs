@@ -0,0 +1,65 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, Transition};
+use crate::i18n::{tr, trf};
+use crate::leaderboard::{daily_seed, fetch_daily_top, Entry};
+use crate::net::Pending;
+use crate::ui::draw_centered;
+
+// Global top scores for today's seed, fetched in the background
+pub struct LeaderboardState {
+    request: Option<Pending<Vec<Entry>>>,
+    entries: Vec<Entry>,
+    status: Option<String>,
+    next: Transition,
+}
+
+impl LeaderboardState {
+    pub fn new() -> Self {
+        Self { request: Some(fetch_daily_top()), entries: Vec::new(), status: Some(tr("Loading...").to_string()), next: Transition::None }
+    }
+}
+
+impl GameScreen for LeaderboardState {
+    fn update(&mut self, _app: &mut App, _frame: &Frame) {
+        if let Some(result) = self.request.as_ref().and_then(|r| r.poll()) {
+            self.request = None;
+            match result {
+                Ok(entries) => {
+                    self.status = entries.is_empty().then(|| tr("No scores yet today").to_string());
+                    self.entries = entries;
+                }
+                Err(e) => self.status = Some(e),
+            }
+        }
+        if is_key_pressed(KeyCode::R) && self.request.is_none() {
+            self.request = Some(fetch_daily_top());
+            self.status = Some(tr("Loading...").to_string());
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let sh = screen_height();
+
+        let mut y = sh * 0.12;
+        draw_centered(tr("DAILY LEADERBOARD"), y, 36.0 * ui, palette.head);
+        y += 32.0 * ui;
+        draw_centered(&trf!("Today's seed: {}", daily_seed()), y, 18.0 * ui, LIGHTGRAY);
+        y += 32.0 * ui;
+
+        for (i, e) in self.entries.iter().enumerate() {
+            draw_centered(&format!("{:>2}. {:<16} {:>5}", i + 1, e.name, e.score), y, 20.0 * ui, if i == 0 { WHITE } else { GRAY });
+            y += 22.0 * ui;
+        }
+        if let Some(status) = &self.status {
+            draw_centered(status, y + 8.0 * ui, 18.0 * ui, palette.body);
+        }
+        draw_centered(tr("R: Refresh  Esc: Back  (D in the lobby plays today's seed)"), sh - 24.0 * ui, 18.0 * ui, GRAY);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+}
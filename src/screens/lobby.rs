@@ -0,0 +1,637 @@
+use macroquad::prelude::*;
+
+use super::{AboutState, AchievementsState, App, CoopState, Frame, GameScreen, HelpState, HighScoresState, LeaderboardState, MapExportState, PlayingState, QuitDialogState, RecentSeedsState, ReplaysState, RoyaleState, SandboxState, SettingsState, StatsState, TournamentParams, TournamentState, Transition, VersusSetupState, WatchReplayState};
+use crate::cli::CliArgs;
+use crate::game::{GameMode, SnakeGame, DEFAULT_MOVE_INTERVAL};
+use crate::i18n::{tr, trf};
+use crate::leaderboard::daily_seed;
+use crate::logging;
+use crate::map::{GridConfig, Map, DEFAULT_STYLE};
+use crate::mapgen;
+use crate::netplay::MatchParams;
+use crate::render::{grid_for_window, WindowFit, draw_conveyors, draw_gates, draw_stairs, draw_switches, draw_glyph_at_cell_scaled, matrix_char_for_cell};
+use crate::rules;
+use crate::runlog::RunRecord;
+use crate::save::{clear_run, load_run, load_save, write_save};
+use crate::twitch::VOTE_WINDOW;
+use crate::weekly::{Challenge, Modifier};
+use crate::ui::{draw_centered, draw_slider, draw_text, drag_slider, measure_text, SLIDER_WIDTH};
+
+pub struct LobbyState {
+    pub seed: u64,
+    pub wall_density: f32,
+    pub move_interval: f32,
+    pub mode: GameMode,
+    pub map_style: String,
+    pub grid: GridConfig,
+    fitted: bool, // the grid may follow the window's shape; not once --grid or a logged run set it
+    pub selected: usize, // index into MENU
+    seed_history: Vec<u64>, // seeds left behind by Reseed, so Left can step back
+    hits: Vec<RowHit>, // where the rows were drawn last frame, for the mouse
+    dragging: Option<(usize, Rect)>, // the slider row the mouse holds, and its track at the start
+    pub preview: SnakeGame, // the run Enter would start, never stepped
+    pub notice: Option<String>,
+    pub resume_score: Option<u32>, // score of an autosaved run that can be resumed
+    next: Transition,
+}
+
+impl LobbyState {
+    pub fn new() -> Self {
+        let h = load_save().history;
+        let time_seed = (get_time() as f64 * 1_000_000.0) as u64;
+        let seed = if h.last_seed == 0 { time_seed } else { h.last_seed };
+        let wall_density = h.last_wall_density;
+        let move_interval = h.last_move_interval;
+        let mode = h.last_mode;
+        // A style from a mod that is gone now falls back to the default
+        let map_style = if mapgen::is_registered(&h.last_map_style) { h.last_map_style } else { DEFAULT_STYLE.to_string() };
+        let grid = GridConfig::DEFAULT;
+        let preview = new_game(mapgen::generate(&map_style, grid, seed, wall_density), mode, move_interval);
+        Self {
+            seed,
+            wall_density,
+            move_interval,
+            mode,
+            map_style,
+            grid,
+            fitted: true,
+            selected: 0,
+            seed_history: Vec::new(),
+            hits: Vec::new(),
+            dragging: None,
+            preview,
+            notice: None,
+            resume_score: load_run().map(|g| g.sim.score),
+            next: Transition::None,
+        }
+    }
+
+    // Command-line values take precedence over the remembered history
+    pub fn apply_cli(&mut self, args: &CliArgs) {
+        if let Some(seed) = args.seed { self.seed = seed; }
+        if let Some(density) = args.density { self.wall_density = density; }
+        if let Some(interval) = args.move_interval { self.move_interval = interval; }
+        if let Some(mode) = args.mode { self.mode = mode; }
+        if let Some(grid) = args.grid {
+            self.grid = grid;
+            self.fitted = false;
+        }
+        self.refresh_preview();
+    }
+
+    fn generate_map(&self) -> Map {
+        mapgen::generate(&self.map_style, self.grid, self.seed, self.wall_density)
+    }
+
+    // Called after anything that changes the map or the mode
+    fn refresh_preview(&mut self) {
+        self.preview = new_game(self.generate_map(), self.mode, self.move_interval);
+    }
+
+    fn reseed(&mut self) {
+        self.seed_history.push(self.seed);
+        self.seed = next_seed(self.seed);
+        self.refresh_preview();
+    }
+
+    fn previous_seed(&mut self) {
+        if let Some(seed) = self.seed_history.pop() {
+            self.seed = seed;
+            self.refresh_preview();
+        }
+    }
+
+    fn daily(&mut self) {
+        if self.seed != daily_seed() { self.seed_history.push(self.seed); }
+        self.seed = daily_seed();
+        self.refresh_preview();
+    }
+
+    // The setup a logged run was played on, from Recent seeds; Left on the seed row goes back
+    pub fn load_setup(&mut self, run: &RunRecord) {
+        if self.seed != run.seed { self.seed_history.push(self.seed); }
+        self.seed = run.seed;
+        self.wall_density = run.wall_density;
+        self.move_interval = run.move_interval;
+        self.mode = run.mode;
+        self.grid = run.grid;
+        self.fitted = false;
+        self.map_style = if mapgen::is_registered(&run.map_style) { run.map_style.clone() } else { DEFAULT_STYLE.to_string() };
+        self.refresh_preview();
+    }
+
+    // `wrap` is for Enter: past the end it starts over from the other end
+    fn step_density(&mut self, dir: f32, wrap: bool) {
+        let next = self.wall_density + 0.02 * dir;
+        self.wall_density = if wrap && next > MAX_DENSITY + 0.001 { 0.0 } else { next.clamp(0.0, MAX_DENSITY) };
+        self.refresh_preview();
+    }
+
+    // From the slider, in whole percent
+    fn set_density(&mut self, density: f32) {
+        let density = (density * 100.0).round() / 100.0;
+        if density != self.wall_density {
+            self.wall_density = density;
+            self.refresh_preview();
+        }
+    }
+
+    // `dir` +1 is faster, i.e. a shorter move interval
+    fn step_speed(&mut self, dir: f32, wrap: bool) {
+        let next = self.move_interval - 0.02 * dir;
+        self.move_interval = if wrap && next < MIN_INTERVAL - 0.001 { MAX_INTERVAL } else { next.clamp(MIN_INTERVAL, MAX_INTERVAL) };
+    }
+
+    fn cycle_mode(&mut self, dir: i32) {
+        let len = GameMode::ALL.len() as i32;
+        let idx = GameMode::ALL.iter().position(|m| *m == self.mode).unwrap_or(0) as i32;
+        self.mode = GameMode::ALL[((idx + dir + len) % len) as usize];
+        self.refresh_preview();
+    }
+
+    fn cycle_style(&mut self, dir: i32) {
+        let names = mapgen::names();
+        let len = names.len() as i32;
+        let idx = names.iter().position(|n| *n == self.map_style).unwrap_or(0) as i32;
+        self.map_style = names[((idx + dir + len) % len) as usize].clone();
+        self.refresh_preview();
+    }
+
+    // The weekly challenge plays on fixed settings, so the lobby's own ones are left alone
+    fn weekly(&mut self, app: &mut App) {
+        let challenge = Challenge::current();
+        let game = challenge.start();
+        logging::info(format!("Weekly challenge started: {} seed {} modifier {}", challenge.key(), challenge.seed, challenge.modifier.label()));
+        clear_run();
+        app.last_autosave = get_time() as f32;
+        self.next = Transition::Replace(Box::new(PlayingState::new(game)));
+    }
+
+    fn start(&mut self, app: &mut App) {
+        let game = self.start_game(app);
+        self.next = Transition::Replace(Box::new(PlayingState::new(game)));
+    }
+
+    fn resume(&mut self, app: &mut App) {
+        if let Some(mut game) = load_run() {
+            game.resume();
+            app.last_autosave = get_time() as f32;
+            self.next = Transition::Replace(Box::new(PlayingState::new(game)));
+        }
+    }
+
+    fn versus(&mut self) {
+        let params = MatchParams {
+            seed: self.seed,
+            wall_density: self.wall_density,
+            move_interval: self.move_interval,
+            grid: self.grid,
+            map_style: self.map_style.clone(),
+        };
+        self.next = Transition::Push(Box::new(VersusSetupState::new(params)));
+    }
+
+    fn push(&mut self, screen: Box<dyn GameScreen>) {
+        self.next = Transition::Push(screen);
+    }
+
+    fn shown(&self, item: &MenuItem) -> bool {
+        item.label != RESUME || self.resume_score.is_some()
+    }
+
+    // Next visible row from `from` in direction `dir`, wrapping around
+    fn step_selection(&self, from: usize, dir: i32) -> usize {
+        let len = MENU.len() as i32;
+        let mut i = from as i32;
+        loop {
+            i = (i + dir + len) % len;
+            if self.shown(&MENU[i as usize]) { return i as usize; }
+        }
+    }
+
+    // What Start's score would be multiplied by on the High Scores table
+    fn score_multiplier(&self, app: &App) -> f32 {
+        let (wall_density, move_interval) = self.tuning(app);
+        rules::score_multiplier(wall_density, move_interval, self.grid, self.mode, app.settings.modifier)
+    }
+
+    // Adaptive mode brings its own density and speed; the lobby's are kept for the others
+    fn tuning(&self, app: &App) -> (f32, f32) {
+        if self.mode == GameMode::Adaptive {
+            (app.difficulty.wall_density(), app.difficulty.move_interval())
+        } else {
+            (self.wall_density, self.move_interval)
+        }
+    }
+
+    // New game with the current lobby parameters, remembered for next launch
+    pub fn start_game(&self, app: &mut App) -> SnakeGame {
+        let (wall_density, move_interval) = self.tuning(app);
+        let started = std::time::Instant::now();
+        let map = mapgen::generate(&self.map_style, self.grid, self.seed, wall_density);
+        let (walls, gen_ms) = (map.walls.len(), started.elapsed().as_secs_f64() * 1000.0);
+        let mut game = new_game(map, self.mode, move_interval);
+        game.set_missions(app.settings.missions);
+        game.set_extras(app.settings.extras);
+        game.set_modifier(app.settings.modifier);
+        if app.settings.modifier == Some(Modifier::Graveyard) { game.set_corpses(app.graveyard.corpses(&game.sim.map)); }
+        logging::info(format!(
+            "Run started: seed {} style {} density {:.0}% speed {:.0}ms grid {}x{}; map generated in {:.2} ms ({} walls)",
+            self.seed,
+            self.map_style,
+            wall_density * 100.0,
+            game.move_interval * 1000.0,
+            self.grid.width,
+            self.grid.height,
+            gen_ms,
+            walls
+        ));
+        let mut s = load_save();
+        s.history.last_seed = self.seed;
+        s.history.last_wall_density = self.wall_density;
+        s.history.last_move_interval = self.move_interval;
+        s.history.last_mode = self.mode;
+        s.history.last_map_style = self.map_style.clone();
+        write_save(&s);
+        clear_run();
+        app.last_autosave = get_time() as f32;
+        game
+    }
+}
+
+// The seed Reseed moves on to, also used for a new map from the game over screen
+pub fn next_seed(seed: u64) -> u64 {
+    seed.wrapping_mul(6364136223846793005).wrapping_add(1)
+}
+
+const MAX_DENSITY: f32 = 0.35;
+const MIN_INTERVAL: f32 = 0.05;
+const MAX_INTERVAL: f32 = 0.35;
+const RESUME: &str = "Resume last run";
+
+type Action = fn(&mut LobbyState, &mut App);
+
+fn nothing(_: &mut LobbyState, _: &mut App) {}
+
+// One lobby row: Left/Right adjust it, Enter or a click activates it, and its shortcut keys
+// do the same from any row
+struct MenuItem {
+    label: &'static str,
+    value: fn(&LobbyState) -> Option<String>,
+    on_left: Action,
+    on_right: Action,
+    on_activate: Action,
+    slider: Option<Slider>, // drawn as a slider the mouse can drag, rather than < value >
+    keys: [&'static [KeyCode]; 3], // shortcuts for left, right and activate
+}
+
+// A row's value as 0-1 along its slider, and setting it from there
+type Slider = (fn(&LobbyState) -> f32, fn(&mut LobbyState, f32));
+
+const MENU: &[MenuItem] = &[
+    MenuItem {
+        label: "Start",
+        value: |_| None,
+        on_left: nothing,
+        on_right: nothing,
+        on_activate: |l, app| l.start(app),
+        slider: None,
+        keys: [&[], &[], &[]],
+    },
+    MenuItem {
+        label: RESUME,
+        value: |l| l.resume_score.map(|score| trf!("score {}", score)),
+        on_left: nothing,
+        on_right: nothing,
+        on_activate: |l, app| l.resume(app),
+        slider: None,
+        keys: [&[], &[], &[KeyCode::C]],
+    },
+    MenuItem {
+        label: "Weekly challenge",
+        value: |_| {
+            let challenge = Challenge::current();
+            Some(format!("{}: {}", challenge.key(), tr(challenge.modifier.label())))
+        },
+        on_left: nothing,
+        on_right: nothing,
+        on_activate: |l, app| l.weekly(app),
+        slider: None,
+        keys: [&[], &[], &[KeyCode::K]],
+    },
+    MenuItem {
+        label: "Seed",
+        value: |l| Some(l.seed.to_string()),
+        on_left: |l, _| l.previous_seed(),
+        on_right: |l, _| l.reseed(),
+        on_activate: |l, _| l.reseed(),
+        slider: None,
+        keys: [&[], &[], &[KeyCode::R]],
+    },
+    MenuItem {
+        label: "Map style",
+        value: |l| Some(tr(&l.map_style).to_string()),
+        on_left: |l, _| l.cycle_style(-1),
+        on_right: |l, _| l.cycle_style(1),
+        on_activate: |l, _| l.cycle_style(1),
+        slider: None,
+        keys: [&[], &[], &[KeyCode::G]],
+    },
+    MenuItem {
+        label: "Wall density",
+        value: |l| Some(format!("{:.0}%", l.wall_density * 100.0)),
+        on_left: |l, _| l.step_density(-1.0, false),
+        on_right: |l, _| l.step_density(1.0, false),
+        on_activate: |l, _| l.step_density(1.0, true),
+        slider: Some((|l| l.wall_density / MAX_DENSITY, |l, f| l.set_density(f * MAX_DENSITY))),
+        keys: [&[KeyCode::Minus], &[KeyCode::Equal], &[]],
+    },
+    MenuItem {
+        label: "Speed",
+        value: |l| Some(format!("{:.0}ms", l.move_interval * 1000.0)),
+        on_left: |l, _| l.step_speed(-1.0, false),
+        on_right: |l, _| l.step_speed(1.0, false),
+        on_activate: |l, _| l.step_speed(1.0, true),
+        // Right is faster, as with the arrow keys
+        slider: Some((
+            |l| (MAX_INTERVAL - l.move_interval) / (MAX_INTERVAL - MIN_INTERVAL),
+            |l, f| l.move_interval = ((MAX_INTERVAL - f * (MAX_INTERVAL - MIN_INTERVAL)) * 100.0).round() / 100.0,
+        )),
+        keys: [&[KeyCode::LeftBracket], &[KeyCode::RightBracket], &[]],
+    },
+    MenuItem {
+        label: "Mode",
+        value: |l| Some(tr(l.mode.label()).to_string()),
+        on_left: |l, _| l.cycle_mode(-1),
+        on_right: |l, _| l.cycle_mode(1),
+        on_activate: |l, _| l.cycle_mode(1),
+        slider: None,
+        keys: [&[], &[], &[KeyCode::M]],
+    },
+    MenuItem {
+        label: "Quit",
+        value: |_| None,
+        on_left: nothing,
+        on_right: nothing,
+        on_activate: |l, _| l.push(Box::new(QuitDialogState::new())),
+        slider: None,
+        keys: [&[], &[], &[]],
+    },
+];
+
+// Keys that open other screens or act on the whole lobby rather than one row
+const SHORTCUTS: &[(&[KeyCode], Action)] = &[
+    (&[KeyCode::S], |l, app| l.push(Box::new(SettingsState::new(app.settings.clone())))),
+    (&[KeyCode::P], |l, _| l.push(Box::new(ReplaysState::new()))),
+    (&[KeyCode::W], |l, _| l.push(Box::new(WatchReplayState::new()))),
+    (&[KeyCode::T], |l, _| l.push(Box::new(HighScoresState::new()))),
+    (&[KeyCode::V], |l, _| l.push(Box::new(RecentSeedsState::new()))),
+    (&[KeyCode::L], |l, _| l.push(Box::new(LeaderboardState::new()))),
+    (&[KeyCode::D], |l, _| l.daily()),
+    (&[KeyCode::E], |l, _| {
+        let map = l.preview.sim.map.clone();
+        l.push(Box::new(MapExportState::new(map)));
+    }),
+    (&[KeyCode::N], |l, _| l.versus()),
+    (&[KeyCode::B], |l, _| {
+        let royale = RoyaleState::new(l.seed, l.map_style.clone(), l.wall_density, l.move_interval);
+        l.push(Box::new(royale));
+    }),
+    (&[KeyCode::J], |l, _| {
+        let coop = CoopState::new(l.generate_map(), l.move_interval);
+        l.push(Box::new(coop));
+    }),
+    (&[KeyCode::X], |l, _| {
+        let sandbox = SandboxState::new(SnakeGame::new(l.generate_map(), l.move_interval));
+        l.push(Box::new(sandbox));
+    }),
+    (&[KeyCode::U], |l, _| {
+        let params = TournamentParams { seed: l.seed, map_style: l.map_style.clone(), wall_density: l.wall_density, move_interval: l.move_interval, grid: l.grid };
+        l.push(Box::new(TournamentState::new(params)));
+    }),
+    (&[KeyCode::H, KeyCode::F1], |l, app| l.push(Box::new(HelpState::new(&app.settings.key_bindings)))),
+    (&[KeyCode::I], |l, _| l.push(Box::new(StatsState::new()))),
+    (&[KeyCode::A], |l, _| l.push(Box::new(AchievementsState::new()))),
+    (&[KeyCode::O], |l, _| l.push(Box::new(AboutState::new()))),
+];
+
+// A drawn row and its arrows, so clicks land on the same item the keys would
+struct RowHit {
+    item: usize,
+    row: Rect,
+    left: Option<Rect>,
+    right: Option<Rect>,
+    track: Option<Rect>, // slider rows
+}
+
+// The game the lobby parameters start: the preview shows this same map, spawn snake,
+// first food and glyphs before it's played
+fn new_game(map: Map, mode: GameMode, move_interval: f32) -> SnakeGame {
+    let move_interval = match mode {
+        GameMode::TwitchPlays => move_interval.max(VOTE_WINDOW),
+        GameMode::Classic | GameMode::Hunger | GameMode::Waves | GameMode::Adaptive => move_interval,
+    };
+    let mut game = SnakeGame::new(map, move_interval);
+    game.set_mode(mode);
+    game
+}
+
+impl GameScreen for LobbyState {
+    fn update(&mut self, app: &mut App, _frame: &Frame) {
+        // Rescale grid: the next run takes the window's shape, and the preview follows a resize
+        if app.settings.window_fit == WindowFit::Rescale && self.fitted {
+            let grid = grid_for_window(screen_width(), screen_height());
+            if grid != self.grid {
+                self.grid = grid;
+                self.refresh_preview();
+            }
+        }
+        if is_key_pressed(KeyCode::Up) { self.selected = self.step_selection(self.selected, -1); }
+        if is_key_pressed(KeyCode::Down) { self.selected = self.step_selection(self.selected, 1); }
+
+        let item = &MENU[self.selected];
+        if is_key_pressed(KeyCode::Left) { (item.on_left)(self, app); }
+        if is_key_pressed(KeyCode::Right) { (item.on_right)(self, app); }
+        if is_key_pressed(KeyCode::Enter) { (item.on_activate)(self, app); }
+
+        // A slider follows the mouse from a press on its track until the button is let go
+        for i in 0..self.hits.len() {
+            let (item, Some(track)) = (self.hits[i].item, self.hits[i].track) else { continue; };
+            if self.dragging.is_some_and(|(held, _)| held != item) { continue; }
+            let mut grabbed = self.dragging.map(|(_, r)| r);
+            let fraction = drag_slider(track, &mut grabbed);
+            self.dragging = grabbed.map(|r| (item, r));
+            if let (Some(f), Some((_, set))) = (fraction, MENU[item].slider) {
+                self.selected = item;
+                set(self, f);
+            }
+        }
+
+        // Hovering a row selects it; a click on an arrow adjusts it, anywhere else activates it
+        let (mx, my) = mouse_position();
+        let mouse = vec2(mx, my);
+        let hovered = self.hits.iter().filter(|_| self.dragging.is_none()).find(|h| h.row.contains(mouse)).map(|h| {
+            let item = &MENU[h.item];
+            let action = if h.left.is_some_and(|r| r.contains(mouse)) {
+                item.on_left
+            } else if h.right.is_some_and(|r| r.contains(mouse)) {
+                item.on_right
+            } else {
+                item.on_activate
+            };
+            (h.item, action)
+        });
+        if let Some((i, action)) = hovered {
+            if mouse_delta_position() != Vec2::ZERO { self.selected = i; }
+            if is_mouse_button_pressed(MouseButton::Left) { action(self, app); }
+        }
+
+        for item in MENU {
+            if !self.shown(item) { continue; }
+            let [left, right, activate] = item.keys;
+            if left.iter().any(|k| is_key_pressed(*k)) { (item.on_left)(self, app); }
+            if right.iter().any(|k| is_key_pressed(*k)) { (item.on_right)(self, app); }
+            if activate.iter().any(|k| is_key_pressed(*k)) { (item.on_activate)(self, app); }
+        }
+        for (keys, action) in SHORTCUTS {
+            if keys.iter().any(|k| is_key_pressed(*k)) { action(self, app); }
+        }
+    }
+
+    fn draw(&mut self, app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+
+        let sw = screen_width();
+        let sh = screen_height();
+
+        let mut y = sh * 0.25;
+        draw_centered(tr("SNAKE"), y, 40.0 * ui, palette.head);
+        y += 56.0 * ui;
+
+        let size = 20.0 * ui;
+        let row_h = 24.0 * ui;
+        self.hits.clear();
+        for (i, item) in MENU.iter().enumerate() {
+            if !self.shown(item) { continue; }
+            let color = if self.selected == i { WHITE } else { GRAY };
+            let label = tr(item.label);
+            let value = (item.value)(self);
+            if let (Some((get, _)), Some(value)) = (item.slider, &value) {
+                // Label, track and value laid out as one centred row
+                let (gap, track_w) = (16.0 * ui, SLIDER_WIDTH * ui);
+                let (label_w, value_w) = (measure_text(label, size).width, measure_text(value, size).width);
+                let w = label_w + gap + track_w + gap + value_w;
+                let x = (sw - w) * 0.5;
+                draw_text(label, x, y, size, color);
+                let track = Rect::new(x + label_w + gap, y - 14.0 * ui, track_w, 14.0 * ui);
+                draw_slider(track, get(self), color, palette.head);
+                draw_text(value, track.x + track_w + gap, y, size, color);
+                let row = Rect::new(x, y - row_h * 0.8, w, row_h);
+                self.hits.push(RowHit { item: i, row, left: None, right: None, track: Some(track) });
+                y += row_h;
+                continue;
+            }
+            let text = match &value {
+                Some(value) => format!("{}   < {} >", label, value),
+                None => label.to_string(),
+            };
+            draw_centered(&text, y, size, color);
+
+            let w = measure_text(&text, size).width;
+            let x = (sw - w) * 0.5;
+            let row = Rect::new(x, y - row_h * 0.8, w, row_h);
+            let (left, right) = if value.is_some() {
+                let arrow = measure_text("<", size).width + 8.0 * ui; // a little slack either side
+                let lx = x + measure_text(&format!("{}   ", label), size).width - 4.0 * ui;
+                (Some(Rect::new(lx, row.y, arrow, row_h)), Some(Rect::new(x + w - arrow + 4.0 * ui, row.y, arrow, row_h)))
+            } else {
+                (None, None)
+            };
+            self.hits.push(RowHit { item: i, row, left, right, track: None });
+            y += row_h;
+        }
+        y += 8.0 * ui;
+        draw_centered(tr("Up/Down: Select  Left/Right: Adjust  Enter/Click: Activate"), y, 18.0 * ui, palette.body);
+        y += 28.0 * ui;
+
+        draw_centered(tr("S: Settings  P: Replays  W: Watch replay  T: High scores  V: Recent seeds  L: Leaderboard"), y, 20.0 * ui, GRAY);
+        y += 24.0 * ui;
+        draw_centered(tr("D: Daily seed  K: Weekly  M: Mode  G: Map style  E: Export map  N: Versus  B: Battle royale"), y, 20.0 * ui, GRAY);
+        y += 24.0 * ui;
+        draw_centered(tr("J: Co-op  U: Tournament  X: Sandbox  H: Help  I: Stats  A: Achievements  O: About"), y, 20.0 * ui, GRAY);
+
+        let progress = load_save().progress;
+        let weekly = progress.weekly_best.get(&Challenge::current().key()).copied().unwrap_or(0);
+        draw_centered(&trf!("Best: {}  This week: {}", progress.best_score, weekly), sh - 64.0 * ui, 20.0 * ui, palette.body);
+
+        if let Some(notice) = &self.notice {
+            draw_centered(notice, sh - 88.0 * ui, 18.0 * ui, ORANGE);
+        }
+
+        let grid = trf!("Grid: {}x{}", self.grid.width, self.grid.height);
+        let multiplier = trf!("Multiplier: x{}", format!("{:.2}", self.score_multiplier(app)));
+        draw_centered(&format!("{}  {}", grid, multiplier), sh - 40.0 * ui, 18.0 * ui, LIGHTGRAY);
+
+        // Preview panel that reacts to difficulty
+        // Target 85% of screen, maintain grid aspect and center
+        let target_w = sw * 0.85;
+        let target_h = sh * 0.85;
+        let scale = (target_w / self.grid.width as f32)
+            .min(target_h / self.grid.height as f32);
+        let tile_w = scale;
+        let tile_h = scale;
+        let pw = tile_w * self.grid.width as f32;
+        let ph = tile_h * self.grid.height as f32;
+        let off_x = (sw - pw) * 0.5;
+        let off_y = (sh - ph) * 0.5;
+
+        // Exactly what Enter starts: the map, the spawn snake and the first food
+        let sim = &self.preview.sim;
+        for c in &sim.map.walls {
+            let ch = matrix_char_for_cell(*c);
+            draw_glyph_at_cell_scaled(
+                ch,
+                *c,
+                Color::new(palette.wall.r, palette.wall.g, palette.wall.b, 0.8),
+                tile_w,
+                tile_h,
+                off_x,
+                off_y,
+            );
+        }
+        draw_gates(&sim.map, &palette, |_| 0.8, tile_w, tile_h, off_x, off_y);
+        draw_conveyors(&sim.map, &palette, |_| 0.8, tile_w, tile_h, off_x, off_y);
+        draw_switches(&sim.map, |_| 0.8, tile_w, tile_h, off_x, off_y);
+        draw_stairs(&sim.map, &palette, |_| 0.8, tile_w, tile_h, off_x, off_y);
+        if let Some(food) = sim.food {
+            draw_glyph_at_cell_scaled(self.preview.food_char, food, palette.food, tile_w, tile_h, off_x, off_y);
+        }
+
+        // Head color shifts with speed
+        let speed_factor = (DEFAULT_MOVE_INTERVAL / self.move_interval)
+            .clamp(0.5, 4.0);
+        let head_color = Color::new(
+            (0.15 * speed_factor).min(1.0),
+            (0.9 * (1.0 / speed_factor)).min(1.0),
+            0.2,
+            1.0,
+        );
+        let len = sim.snake.len();
+        for (i, (c, ch)) in sim.snake.iter().zip(&self.preview.body_chars).enumerate() {
+            let color = match i {
+                0 => head_color,
+                _ if frame.look.body_gradient => frame.skin.aged_color(i, i as f32, len, &palette),
+                _ => frame.skin.color(i, len, &palette),
+            };
+            draw_glyph_at_cell_scaled(frame.skin.glyph(i, *ch), *c, color, tile_w, tile_h, off_x, off_y);
+        }
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    // Nothing is below the lobby, so backing out of it is quitting, asked like Q
+    fn back(&mut self, _app: &mut App) -> Transition { Transition::Push(Box::new(QuitDialogState::new())) }
+}
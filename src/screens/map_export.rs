@@ -0,0 +1,76 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, Transition};
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::map::Map;
+use crate::mapimage::{export, scales_for};
+use crate::ui::draw_centered;
+
+// Lobby overlay that saves the previewed map as a PNG at a chosen tile size
+pub struct MapExportState {
+    map: Map,
+    scales: Vec<u32>,
+    selected: usize,
+    status: Option<String>,
+    next: Transition,
+}
+
+impl MapExportState {
+    pub fn new(map: Map) -> Self {
+        let scales = scales_for(&map);
+        // Start on 16 px per tile when the map is small enough for it
+        let selected = scales.iter().position(|s| *s == 16).unwrap_or(scales.len() - 1);
+        Self { map, scales, selected, status: None, next: Transition::None }
+    }
+}
+
+impl GameScreen for MapExportState {
+    fn update(&mut self, _app: &mut App, frame: &Frame) {
+        if is_key_pressed(KeyCode::Left) { self.selected = self.selected.saturating_sub(1); }
+        if is_key_pressed(KeyCode::Right) { self.selected = (self.selected + 1).min(self.scales.len() - 1); }
+        if is_key_pressed(KeyCode::Enter) {
+            self.status = Some(match export(&self.map, self.scales[self.selected], &frame.palette) {
+                Ok(path) => {
+                    logging::info(format!("Map image saved: {}", path));
+                    trf!("Saved {}", path)
+                }
+                Err(e) => {
+                    logging::error(format!("Map image export failed: {}", e));
+                    trf!("Export failed: {}", e)
+                }
+            });
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.7));
+
+        let mut y = screen_height() * 0.35;
+        draw_centered(tr("EXPORT MAP"), y, 36.0 * ui, palette.head);
+        y += 48.0 * ui;
+        let scale = self.scales[self.selected];
+        let size = trf!(
+            "< {} px per tile >   {}x{} px",
+            scale,
+            self.map.grid.width as u32 * scale,
+            self.map.grid.height as u32 * scale
+        );
+        draw_centered(&size, y, 22.0 * ui, WHITE);
+        y += 28.0 * ui;
+        draw_centered(&trf!("{} map, seed {}", tr(&self.map.style), self.map.seed), y, 18.0 * ui, LIGHTGRAY);
+        y += 36.0 * ui;
+        draw_centered(tr("Left/Right: Scale  Enter: Save PNG  Esc: Back"), y, 18.0 * ui, GRAY);
+        if let Some(status) = &self.status {
+            draw_centered(status, y + 32.0 * ui, 18.0 * ui, palette.body);
+        }
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn is_overlay(&self) -> bool { true }
+}
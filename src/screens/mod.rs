@@ -0,0 +1,220 @@
+mod about;
+mod achievements;
+mod coop;
+mod error;
+mod game_over;
+mod help;
+mod high_scores;
+mod leaderboard;
+mod lobby;
+mod map_export;
+mod paused;
+mod playing;
+mod quit_dialog;
+mod recent_seeds;
+mod replays;
+mod royale;
+mod sandbox;
+mod screensaver;
+mod settings;
+mod spectate;
+mod stats;
+mod tournament;
+mod versus;
+
+use macroquad::prelude::{is_key_pressed, KeyCode};
+
+use crate::audio::GameSounds;
+use crate::cloud::CloudSync;
+use crate::bot::BotLink;
+use crate::difficulty::Difficulty;
+use crate::game::SnakeGame;
+use crate::graveyard::Graveyard;
+use crate::rain::Rain;
+use crate::render::{Look, Palette};
+use crate::save::Settings;
+use crate::skins::Skin;
+
+pub use about::AboutState;
+pub use achievements::AchievementsState;
+pub use coop::CoopState;
+pub use error::ErrorState;
+pub use game_over::GameOverState;
+pub use help::HelpState;
+pub use high_scores::HighScoresState;
+pub use leaderboard::LeaderboardState;
+pub use lobby::LobbyState;
+pub use map_export::MapExportState;
+pub use paused::PausedState;
+pub use playing::PlayingState;
+pub use quit_dialog::QuitDialogState;
+pub use recent_seeds::RecentSeedsState;
+pub use replays::{ReplaysState, WatchReplayState};
+pub use royale::RoyaleState;
+pub use sandbox::SandboxState;
+pub use screensaver::ScreensaverState;
+pub use settings::SettingsState;
+pub use spectate::SpectateState;
+pub use stats::StatsState;
+pub use tournament::{TournamentParams, TournamentState};
+pub use versus::VersusSetupState;
+
+// State shared by all screens for the lifetime of the app
+pub struct App {
+    pub settings: Settings,
+    pub sounds: GameSounds,
+    pub last_autosave: f32,
+    pub debug_overlay: bool, // F3: recent log lines over the game
+    pub bot: Option<BotLink>, // --bot-server: an external program plays instead of the keyboard
+    pub difficulty: Difficulty, // Adaptive mode's controller, saved whenever a run of it ends
+    pub graveyard: Graveyard, // Graveyard modifier corpses, for this session only
+    pub rain: Rain, // background rain, fed the events of whatever run is on screen
+    pub cloud: CloudSync, // the startup sync of the save, while it runs
+}
+
+// Per-frame values computed once in the main loop
+pub struct Frame {
+    pub now: f32,
+    pub dt: f32,
+    pub palette: Palette,
+    pub skin: &'static Skin,
+    pub look: Look,
+    pub ui: f32,
+}
+
+// What the screen stack should do after a screen's update
+#[derive(Default)]
+pub enum Transition {
+    #[default]
+    None,
+    // Open a screen on top; the one below is paused until it pops
+    Push(Box<dyn GameScreen>),
+    Pop,
+    Replace(Box<dyn GameScreen>),
+    // Close every screen and start over from this one
+    Root(Box<dyn GameScreen>),
+    Quit,
+}
+
+// What pressing Q does on a screen
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum QuitGesture {
+    // Ask "Quit? Y/N" first
+    Confirm,
+    // Mid-run: Q has to be held, so a slip of the finger doesn't end the game
+    Hold,
+    // The screen handles Q itself
+    None,
+}
+
+pub trait GameScreen {
+    // Input and logic for one frame; only the top screen is updated
+    fn update(&mut self, app: &mut App, frame: &Frame);
+    fn draw(&mut self, app: &App, frame: &Frame);
+    // Screen change requested by the last update, taken once
+    fn transition(&mut self) -> Transition;
+
+    // Overlays let the screen below show through
+    fn is_overlay(&self) -> bool { false }
+    // True while the screen is capturing raw key presses (text entry, key rebinding)
+    fn is_typing(&self) -> bool { false }
+    // Settings being edited, used for a live theme/UI scale preview
+    fn preview_settings(&self) -> Option<&Settings> { None }
+    fn quit_gesture(&self) -> QuitGesture { QuitGesture::Confirm }
+    // Esc steps back: by default to the screen below. Not asked while the screen is typing,
+    // where Esc belongs to the text being entered.
+    fn back(&mut self, _app: &mut App) -> Transition { Transition::Pop }
+    // A run is playing out here, so it keeps full speed without input
+    fn is_live(&self) -> bool { false }
+    // The single-snake run shown here, for --broadcast to send to spectators
+    fn spectated(&self) -> Option<&SnakeGame> { None }
+    // Music stays silent while this screen is up
+    fn mutes_music(&self) -> bool { false }
+    // The rain falls denser, faster and brighter while this screen is up, whatever the settings
+    fn storms_rain(&self) -> bool { false }
+    // The app is about to exit
+    fn on_quit(&mut self) {}
+}
+
+pub struct ScreenStack {
+    screens: Vec<Box<dyn GameScreen>>,
+}
+
+impl ScreenStack {
+    pub fn new(root: Box<dyn GameScreen>) -> Self {
+        Self { screens: vec![root] }
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.screens.last().is_some_and(|s| s.is_typing())
+    }
+
+    // Q does nothing while text is being typed
+    pub fn quit_gesture(&self) -> QuitGesture {
+        if self.is_typing() { return QuitGesture::None; }
+        self.screens.last().map_or(QuitGesture::None, |s| s.quit_gesture())
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.screens.last().is_some_and(|s| s.is_live())
+    }
+
+    pub fn mutes_music(&self) -> bool {
+        self.screens.last().is_some_and(|s| s.mutes_music())
+    }
+
+    pub fn storms_rain(&self) -> bool {
+        self.screens.last().is_some_and(|s| s.storms_rain())
+    }
+
+    // The topmost run being played or watched, under any menus opened over it
+    pub fn spectated(&self) -> Option<&SnakeGame> {
+        self.screens.iter().rev().find_map(|s| s.spectated())
+    }
+
+    pub fn push(&mut self, screen: Box<dyn GameScreen>) {
+        self.screens.push(screen);
+    }
+
+    pub fn preview_settings(&self) -> Option<&Settings> {
+        self.screens.iter().rev().find_map(|s| s.preview_settings())
+    }
+
+    // Runs one frame: updates the top screen, steps back on Esc, draws the visible part of the stack and
+    // applies the requested transition. Returns false once the app should quit.
+    pub fn run_frame(&mut self, app: &mut App, frame: &Frame) -> bool {
+        let Some(top) = self.screens.last_mut() else { return false; };
+        // Asked before the update, so the Esc that ends a text entry doesn't also leave the screen
+        let typing = top.is_typing();
+        top.update(app, frame);
+        let mut transition = top.transition();
+        if matches!(transition, Transition::None) && !typing && is_key_pressed(KeyCode::Escape) {
+            transition = top.back(app);
+        }
+
+        let mut first = self.screens.len() - 1;
+        while first > 0 && self.screens[first].is_overlay() { first -= 1; }
+        for screen in &mut self.screens[first..] {
+            screen.draw(app, frame);
+        }
+
+        match transition {
+            Transition::None => {}
+            Transition::Push(screen) => self.screens.push(screen),
+            Transition::Pop => { self.screens.pop(); }
+            Transition::Replace(screen) => {
+                self.screens.pop();
+                self.screens.push(screen);
+            }
+            Transition::Root(screen) => self.screens = vec![screen],
+            Transition::Quit => return false,
+        }
+        !self.screens.is_empty()
+    }
+
+    pub fn on_quit(&mut self) {
+        for screen in &mut self.screens {
+            screen.on_quit();
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, QuitGesture, SettingsState, Transition};
+use crate::i18n::tr;
+use crate::pacing::Pacing;
+use crate::ui::draw_centered;
+
+// Put over a run by Esc, or when the window loses focus ("Pause on focus loss"). Coming back
+// doesn't resume it: that takes a key or a click once the window is in front again. S opens
+// the settings over it, and Q is still held to quit, as in the run.
+pub struct PausedState {
+    mute: bool, // music silenced until the run goes on
+    next: Transition,
+}
+
+impl PausedState {
+    pub fn new(mute: bool) -> Self {
+        Self { mute, next: Transition::None }
+    }
+}
+
+// Neither the keys that switched windows away nor the ones with a job here count as coming back
+fn resumes(key: KeyCode) -> bool {
+    !matches!(key, KeyCode::LeftAlt | KeyCode::RightAlt | KeyCode::LeftSuper | KeyCode::RightSuper | KeyCode::Tab | KeyCode::Q | KeyCode::S)
+}
+
+impl GameScreen for PausedState {
+    fn update(&mut self, app: &mut App, _frame: &Frame) {
+        if Pacing::hidden() { return; }
+        if is_key_pressed(KeyCode::S) {
+            self.next = Transition::Push(Box::new(SettingsState::new(app.settings.clone())));
+            return;
+        }
+        let clicked = [MouseButton::Left, MouseButton::Right].into_iter().any(is_mouse_button_pressed);
+        if clicked || get_keys_pressed().into_iter().any(resumes) {
+            self.next = Transition::Pop;
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let ui = frame.ui;
+        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.6));
+        draw_centered(tr("Paused"), screen_height() * 0.5 - 8.0 * ui, 48.0 * ui, frame.palette.head);
+        draw_centered(tr("Press any key to resume"), screen_height() * 0.5 + 32.0 * ui, 20.0 * ui, WHITE);
+        draw_centered(tr("S: Settings  Hold Q: Quit"), screen_height() * 0.5 + 60.0 * ui, 18.0 * ui, GRAY);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn is_overlay(&self) -> bool { true }
+
+    fn quit_gesture(&self) -> QuitGesture { QuitGesture::Hold }
+
+    fn mutes_music(&self) -> bool { self.mute }
+}
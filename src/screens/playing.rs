@@ -0,0 +1,204 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameOverState, GameScreen, PausedState, QuitGesture, Transition};
+use crate::bot::BotCommand;
+use crate::audio::play_sfx;
+use crate::events::GameEvent;
+use crate::game::{GameMode, SnakeGame, MAX_FRAME_DT};
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::pacing::Pacing;
+use crate::recorder::GifRecorder;
+use crate::render::Palette;
+use crate::save::{clear_run, load_save, save_run, write_save, AUTOSAVE_INTERVAL};
+use crate::speedrun::{draw_timer, run_key};
+use crate::twitch::ChatVotes;
+use crate::ui::{draw_centered, draw_text};
+use crate::weekly::Modifier;
+
+// Seconds of frozen 3-2-1 before a run moves, then how long "GO!" stays up
+const COUNTDOWN_SECONDS: f32 = 3.0;
+const GO_SECONDS: f32 = 0.6;
+
+pub struct PlayingState {
+    pub game: SnakeGame,
+    countdown: f32, // seconds until the snake moves; "GO!" shows until it reaches -GO_SECONDS
+    countdown_beeped: i32, // last countdown number a beep was played for (0 = GO)
+    chat: Option<ChatVotes>, // Twitch mode only, connected on the first update
+    recorder: Option<GifRecorder>, // while "Record GIF" is on
+    bot_answered: bool, // the bot replied to the current state, so the next tick may run
+    best_splits: Option<Vec<u32>>, // best pace on this seed and settings, for the timer
+    started: bool, // past the first frame, whose dt may include setting the run up
+    next: Transition,
+}
+
+impl PlayingState {
+    pub fn new(game: SnakeGame) -> Self {
+        let best_splits = load_save().progress.best_splits.remove(&run_key(&game.replay));
+        Self {
+            game,
+            countdown: COUNTDOWN_SECONDS,
+            countdown_beeped: COUNTDOWN_SECONDS as i32 + 1,
+            chat: None,
+            recorder: None,
+            bot_answered: false,
+            best_splits,
+            started: false,
+            next: Transition::None,
+        }
+    }
+
+    // Big "3", "2", "1", "GO!" in the middle of the board, each one shrinking as its second passes
+    fn draw_countdown(&self, frame: &Frame) {
+        let (text, progress) = if self.countdown > 0.0 {
+            (format!("{}", self.countdown.ceil() as i32), self.countdown.ceil() - self.countdown)
+        } else {
+            (tr("GO!").to_string(), -self.countdown / GO_SECONDS)
+        };
+        let size = 120.0 * frame.ui * (1.3 - 0.3 * progress);
+        draw_centered(&text, screen_height() * 0.5 + size * 0.35, size, frame.palette.head);
+    }
+}
+
+// Vote counts for the current tick in the top-right corner
+fn draw_vote_tally(chat: &ChatVotes, palette: &Palette, ui: f32) {
+    let size = 20.0 * ui;
+    let line_h = 22.0 * ui;
+    let w = 260.0 * ui;
+    let x = screen_width() - w - 8.0;
+    let leader = chat.leader();
+    draw_rectangle(x - 6.0, 4.0, w + 12.0, line_h * 6.0 + 8.0, Color::new(0.0, 0.0, 0.0, 0.7));
+    draw_text(&trf!("Chat votes #{}", chat.channel), x, line_h, size, palette.head);
+    for (i, (dir, n)) in chat.tally().iter().enumerate() {
+        let color = if leader == Some(*dir) { WHITE } else { GRAY };
+        draw_text(&format!("{}: {}", tr(&format!("{:?}", dir)), n), x, line_h * (i as f32 + 2.0), size, color);
+    }
+    draw_text(&chat.status, x, line_h * 6.0, 16.0 * ui, palette.body);
+}
+
+impl GameScreen for PlayingState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        let now = frame.now;
+        let game = &mut self.game;
+        if app.settings.record_gif && self.recorder.is_none() { self.recorder = Some(GifRecorder::new()); }
+
+        // Alt-tabbing away shouldn't cost the run; bots and Twitch chat play on without the window
+        let unattended = app.bot.is_some() || game.replay.mode == GameMode::TwitchPlays;
+        let started = std::mem::replace(&mut self.started, true);
+        if app.settings.auto_pause && !unattended && started && Pacing::left_window(frame.dt) {
+            logging::info("Window left mid-run, pausing");
+            self.next = Transition::Push(Box::new(PausedState::new(app.settings.auto_pause_mute)));
+            return;
+        }
+
+        // The board shows but stays frozen until the countdown runs out; bots don't need one
+        if app.bot.is_none() && self.countdown > -GO_SECONDS {
+            self.countdown -= frame.dt.min(MAX_FRAME_DT);
+            let step = (self.countdown.ceil() as i32).max(0);
+            if step < self.countdown_beeped {
+                self.countdown_beeped = step;
+                let sound = if step == 0 { &app.sounds.go } else { &app.sounds.countdown };
+                play_sfx(sound, 0.5 * app.settings.sound_volume.clamp(0.0, 1.0));
+            }
+            if self.countdown > 0.0 { return; }
+        }
+
+        let ticks = game.replay.ticks;
+        if let Some(bot) = app.bot.as_mut() {
+            // Lockstep: every tick waits for the bot's answer to the state before it
+            if !self.bot_answered {
+                if !bot.is_waiting() { bot.send_state(game); }
+                match bot.poll() {
+                    Some(BotCommand::Turn(dir)) => {
+                        if let Some(dir) = dir { game.sim.turn(dir); }
+                        self.bot_answered = true;
+                    }
+                    Some(BotCommand::Restart) => logging::warn("Bot: 'restart' ignored while the run is going"),
+                    None => {}
+                }
+            }
+            if self.bot_answered && game.step_single(frame.dt) {
+                self.bot_answered = false;
+                // The final state tells the bot how the run ended
+                if !game.sim.alive { bot.send_state(game); }
+            }
+        } else if game.replay.mode == GameMode::TwitchPlays {
+            // Chat steers: the leading vote is queued every frame until the tick takes it
+            let chat = self.chat.get_or_insert_with(|| ChatVotes::connect(&app.settings.twitch_channel));
+            chat.poll();
+            if let Some(dir) = chat.leader() { game.sim.turn(dir); }
+        } else if app.settings.one_switch {
+            game.handle_switch();
+        } else {
+            game.handle_input(&app.settings.key_bindings);
+        }
+        // Turning the hint on for a moment is enough to keep the run off the table
+        if app.settings.path_hint && app.bot.is_none() { game.assisted = true; }
+        if app.bot.is_none() { game.step(frame.dt); }
+        if game.replay.ticks != ticks && let Some(chat) = self.chat.as_mut() { chat.next_window(); }
+        for event in game.drain_events() {
+            app.sounds.on_event(&event, app.settings.sound_volume);
+            game.popups.on_event(&event, game.sim.head(), now);
+            app.rain.on_event(&event, game.sim.map.grid, frame.look);
+            if game.replay.mode == GameMode::Adaptive {
+                app.difficulty.on_event(&event, app.settings.challenge);
+                if let GameEvent::LevelUp { level } = event { game.pace = app.difficulty.pace(level); }
+            }
+        }
+
+        if game.sim.alive && now - app.last_autosave >= AUTOSAVE_INTERVAL {
+            save_run(game);
+            app.last_autosave = now;
+        }
+
+        if !game.sim.alive {
+            clear_run();
+            if game.replay.modifier == Some(Modifier::Graveyard) && !game.sim.won {
+                app.graveyard.bury(&game.sim.map, game.sim.snake.iter().copied());
+            }
+            if game.replay.mode == GameMode::Adaptive {
+                let mut s = load_save();
+                s.history.difficulty = app.difficulty;
+                write_save(&s);
+                logging::info(format!("Adaptive difficulty now {:.2}", app.difficulty.rating));
+            }
+            // Move into GameOver keeping the final board for the overlay
+            self.next = Transition::Replace(Box::new(GameOverState::new(game.clone(), self.recorder.take())));
+        }
+    }
+
+    fn draw(&mut self, app: &App, frame: &Frame) {
+        self.game.draw(&frame.palette, frame.skin, frame.look);
+        if app.settings.path_hint && app.bot.is_none() { self.game.draw_path_hint(&frame.palette, frame.look); }
+        // Board only: the vote tally and anything drawn above stay out of the recording
+        if let Some(recorder) = self.recorder.as_mut() { recorder.capture(frame.now); }
+        if let Some(chat) = &self.chat { draw_vote_tally(chat, &frame.palette, frame.ui); }
+        if app.settings.one_switch && app.bot.is_none() && self.chat.is_none() { self.game.draw_next_direction(&frame.palette, frame.look); }
+        if app.settings.speedrun_timer && app.bot.is_none() {
+            draw_timer(&self.game, self.best_splits.as_deref(), &frame.palette, frame.ui);
+        }
+        if app.bot.is_none() && self.countdown > -GO_SECONDS { self.draw_countdown(frame); }
+        if let Some(status) = app.bot.as_ref().and_then(|b| b.status.as_deref()) {
+            draw_centered(status, 32.0 * frame.ui, 20.0 * frame.ui, frame.palette.head);
+        }
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn quit_gesture(&self) -> QuitGesture { QuitGesture::Hold }
+
+    // The pause screen on top holds the run until it pops
+    fn back(&mut self, app: &mut App) -> Transition {
+        Transition::Push(Box::new(PausedState::new(app.settings.auto_pause_mute)))
+    }
+
+    fn is_live(&self) -> bool { true }
+
+    fn spectated(&self) -> Option<&SnakeGame> { Some(&self.game) }
+
+    fn on_quit(&mut self) {
+        if self.game.sim.alive { save_run(&self.game); }
+    }
+}
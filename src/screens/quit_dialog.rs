@@ -0,0 +1,47 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, QuitGesture, Transition};
+use crate::i18n::tr;
+use crate::ui::draw_centered;
+
+// "Quit? Y/N" over a menu, so a stray Q doesn't close the game
+pub struct QuitDialogState {
+    next: Transition,
+}
+
+impl QuitDialogState {
+    pub fn new() -> Self {
+        Self { next: Transition::None }
+    }
+}
+
+impl GameScreen for QuitDialogState {
+    fn update(&mut self, _app: &mut App, _frame: &Frame) {
+        // A second Q confirms, like Y
+        if is_key_pressed(KeyCode::Y) || is_key_pressed(KeyCode::Q) || is_key_pressed(KeyCode::Enter) {
+            self.next = Transition::Quit;
+        } else if is_key_pressed(KeyCode::N) {
+            self.next = Transition::Pop;
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let ui = frame.ui;
+        let (sw, sh) = (screen_width(), screen_height());
+        draw_rectangle(0.0, 0.0, sw, sh, Color::new(0.0, 0.0, 0.0, 0.6));
+        let (w, h) = (320.0 * ui, 110.0 * ui);
+        draw_rectangle((sw - w) * 0.5, (sh - h) * 0.5, w, h, Color::new(0.0, 0.0, 0.0, 0.9));
+        draw_rectangle_lines((sw - w) * 0.5, (sh - h) * 0.5, w, h, 2.0, frame.palette.head);
+        draw_centered(tr("Quit?"), sh * 0.5 - 8.0 * ui, 36.0 * ui, frame.palette.head);
+        draw_centered(tr("Y: Quit   N: Stay"), sh * 0.5 + 32.0 * ui, 20.0 * ui, WHITE);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn is_overlay(&self) -> bool { true }
+
+    // Q is handled above
+    fn quit_gesture(&self) -> QuitGesture { QuitGesture::None }
+}
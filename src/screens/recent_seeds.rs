@@ -0,0 +1,120 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, LobbyState, Transition};
+use crate::i18n::tr;
+use crate::logging;
+use crate::runlog::{self, RecentSeed};
+use crate::screenshot::date_string;
+use crate::ui::{draw_centered, draw_text};
+
+const RECENT_SEEDS: usize = 10;
+const ROW_HEIGHT: f32 = 24.0;
+// Fits the default 800 px window at UI scale 1, like the High Scores table
+const TABLE_WIDTH: f32 = 780.0;
+// Left edge of each column, relative to the table
+const COLUMNS: [(&str, f32); 9] = [
+    ("Seed", 0.0),
+    ("Best", 176.0),
+    ("Runs", 232.0),
+    ("Map", 282.0),
+    ("Walls", 372.0),
+    ("Speed", 432.0),
+    ("Grid", 496.0),
+    ("Mode", 556.0),
+    ("Last played", 660.0),
+];
+
+// The last seeds played, from the run log, with the best score on each; Enter (or a click)
+// loads one into the lobby with the map style, density, speed, grid and mode it was played on
+pub struct RecentSeedsState {
+    seeds: Vec<RecentSeed>,
+    selected: usize,
+    next: Transition,
+}
+
+impl RecentSeedsState {
+    pub fn new() -> Self {
+        Self { seeds: runlog::recent_seeds(RECENT_SEEDS), selected: 0, next: Transition::None }
+    }
+
+    fn load(&mut self) {
+        let Some(seed) = self.seeds.get(self.selected) else { return; };
+        logging::info(format!("Lobby loaded recent seed {} style {}", seed.last.seed, seed.last.map_style));
+        let mut lobby = LobbyState::new();
+        lobby.load_setup(&seed.last);
+        self.next = Transition::Root(Box::new(lobby));
+    }
+}
+
+// Baseline of the first row and the table's left edge
+fn table_origin(ui: f32) -> (f32, f32) {
+    ((screen_width() - TABLE_WIDTH * ui) * 0.5, screen_height() * 0.15 + 84.0 * ui)
+}
+
+fn cells(s: &RecentSeed) -> [String; 9] {
+    let run = &s.last;
+    [
+        run.seed.to_string(),
+        s.best.to_string(),
+        s.runs.to_string(),
+        tr(&run.map_style).to_string(),
+        format!("{:.0}%", run.wall_density * 100.0),
+        format!("{:.0}ms", run.move_interval * 1000.0),
+        format!("{}x{}", run.grid.width, run.grid.height),
+        tr(run.mode.label()).to_string(),
+        date_string(run.date),
+    ]
+}
+
+impl GameScreen for RecentSeedsState {
+    fn update(&mut self, _app: &mut App, frame: &Frame) {
+        let len = self.seeds.len();
+        if len > 0 && is_key_pressed(KeyCode::Up) {
+            self.selected = if self.selected == 0 { len - 1 } else { self.selected - 1 };
+        }
+        if len > 0 && is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % len;
+        }
+
+        // Hovering a row selects it, clicking loads it
+        let (left, top) = table_origin(frame.ui);
+        let (mx, my) = mouse_position();
+        let row_h = ROW_HEIGHT * frame.ui;
+        let hovered = (mx >= left && mx <= left + TABLE_WIDTH * frame.ui && my > top - row_h)
+            .then(|| ((my - (top - row_h)) / row_h) as usize)
+            .filter(|i| *i < len);
+        if let Some(i) = hovered && mouse_delta_position() != Vec2::ZERO { self.selected = i; }
+        let clicked = hovered.is_some() && is_mouse_button_pressed(MouseButton::Left);
+
+        if is_key_pressed(KeyCode::Enter) || clicked { self.load(); }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let size = 16.0 * ui;
+
+        draw_centered(tr("RECENT SEEDS"), screen_height() * 0.15, 36.0 * ui, palette.head);
+        let (left, mut y) = table_origin(ui);
+        for (title, x) in COLUMNS {
+            draw_text(tr(title), left + x * ui, y - 30.0 * ui, 16.0 * ui, palette.body);
+        }
+        if self.seeds.is_empty() {
+            draw_centered(tr("No runs logged yet"), y, 20.0 * ui, GRAY);
+            y += ROW_HEIGHT * ui;
+        }
+        for (i, seed) in self.seeds.iter().enumerate() {
+            let color = if i == self.selected { WHITE } else { GRAY };
+            for (text, (_, x)) in cells(seed).iter().zip(COLUMNS) {
+                draw_text(text, left + x * ui, y, size, color);
+            }
+            y += ROW_HEIGHT * ui;
+        }
+        y += 16.0 * ui;
+        draw_centered(tr("Up/Down: Select  Enter/Click: Load into the lobby  Esc: Back"), y, 18.0 * ui, GRAY);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+}
@@ -0,0 +1,267 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, Transition};
+use crate::cast::export_cast;
+use crate::game::{SnakeGame, MAX_FRAME_DT};
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::toast;
+use crate::replay::{export_replay, import_replay, list_replays, list_shared_replays, load_replay, Replay};
+use crate::ui::draw_centered;
+
+const VISIBLE_ROWS: usize = 12;
+const SEEK_TICKS: u32 = 50;
+
+// List of saved replays, newest first
+pub struct ReplaysState {
+    names: Vec<String>,
+    selected: usize,
+    status: Option<String>,
+    next: Transition,
+}
+
+impl ReplaysState {
+    pub fn new() -> Self {
+        Self { names: list_replays(), selected: 0, status: None, next: Transition::None }
+    }
+}
+
+// "<unix time>_<seed>_<score>.json" -> "Seed 42  Score 17"
+fn describe(name: &str) -> String {
+    let parts: Vec<&str> = name.trim_end_matches(".json").split('_').collect();
+    match parts.as_slice() {
+        [_, seed, score] => trf!("Seed {}  Score {}", seed, score),
+        _ => name.to_string(),
+    }
+}
+
+impl GameScreen for ReplaysState {
+    fn update(&mut self, _app: &mut App, _frame: &Frame) {
+        let len = self.names.len();
+        if len > 0 && is_key_pressed(KeyCode::Up) {
+            self.selected = if self.selected == 0 { len - 1 } else { self.selected - 1 };
+        }
+        if len > 0 && is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % len;
+        }
+        if is_key_pressed(KeyCode::Enter) && let Some(name) = self.names.get(self.selected) {
+            match load_replay(name) {
+                Ok(replay) => {
+                    self.status = None;
+                    self.next = Transition::Push(Box::new(ReplayPlayerState::new(replay)));
+                }
+                Err(e) => self.status = Some(e),
+            }
+        }
+        if is_key_pressed(KeyCode::X) && let Some(name) = self.names.get(self.selected) {
+            match load_replay(name).and_then(|r| export_replay(&r)) {
+                Ok(path) => toast::info(trf!("Exported to {}", path)),
+                Err(e) => toast::error(e),
+            }
+        }
+        // A text animation for terminals and chat, playable with `asciinema play`
+        if is_key_pressed(KeyCode::C) && let Some(name) = self.names.get(self.selected) {
+            match load_replay(name).and_then(|r| export_cast(&r)) {
+                Ok(path) => toast::info(trf!("Exported to {}", path)),
+                Err(e) => toast::error(e),
+            }
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let sh = screen_height();
+
+        let mut y = sh * 0.15;
+        draw_centered(tr("REPLAYS"), y, 36.0 * ui, palette.head);
+        y += 48.0 * ui;
+
+        if self.names.is_empty() {
+            draw_centered(tr("No replays yet - finish a run to record one"), y, 20.0 * ui, GRAY);
+            y += 26.0 * ui;
+        }
+        // Scroll so the selection stays visible
+        let first = self.selected.saturating_sub(VISIBLE_ROWS - 1);
+        for (i, name) in self.names.iter().enumerate().skip(first).take(VISIBLE_ROWS) {
+            let color = if i == self.selected { WHITE } else { GRAY };
+            draw_centered(&describe(name), y, 20.0 * ui, color);
+            y += 24.0 * ui;
+        }
+        y += 12.0 * ui;
+        draw_centered(tr("Up/Down: Select  Enter: Play  X: Export  C: Export as cast  Esc: Back"), y, 18.0 * ui, GRAY);
+        if let Some(status) = &self.status {
+            draw_centered(status, y + 28.0 * ui, 18.0 * ui, ORANGE);
+        }
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+}
+
+// Loads a shared .snakereplay (someone else's run), verifies it and plays it back
+pub struct WatchReplayState {
+    files: Vec<String>,
+    selected: usize,
+    path: String,
+    editing: bool,
+    status: Option<String>,
+    next: Transition,
+}
+
+impl WatchReplayState {
+    pub fn new() -> Self {
+        let files = list_shared_replays();
+        let path = files.first().cloned().unwrap_or_default();
+        Self { files, selected: 0, path, editing: false, status: None, next: Transition::None }
+    }
+}
+
+impl GameScreen for WatchReplayState {
+    fn update(&mut self, _app: &mut App, _frame: &Frame) {
+        if self.editing {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() { self.path.push(c); }
+            }
+            if is_key_pressed(KeyCode::Backspace) { self.path.pop(); }
+            if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) { self.editing = false; }
+            return;
+        }
+
+        let len = self.files.len();
+        if len > 0 && is_key_pressed(KeyCode::Up) {
+            self.selected = if self.selected == 0 { len - 1 } else { self.selected - 1 };
+            self.path = self.files[self.selected].clone();
+        }
+        if len > 0 && is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % len;
+            self.path = self.files[self.selected].clone();
+        }
+        if is_key_pressed(KeyCode::F) {
+            clear_input_queue();
+            self.editing = true;
+        }
+        if is_key_pressed(KeyCode::Enter) && !self.path.is_empty() {
+            match import_replay(&self.path) {
+                Ok(replay) => {
+                    self.status = None;
+                    self.next = Transition::Push(Box::new(ReplayPlayerState::new(replay)));
+                }
+                Err(e) => {
+                    logging::warn(format!("Replay {} rejected: {}", self.path, e));
+                    self.status = Some(e);
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let sh = screen_height();
+
+        let mut y = sh * 0.15;
+        draw_centered(tr("WATCH REPLAY"), y, 36.0 * ui, palette.head);
+        y += 48.0 * ui;
+
+        if self.files.is_empty() {
+            draw_centered(tr("No .snakereplay files here - press F to type a path"), y, 20.0 * ui, GRAY);
+            y += 26.0 * ui;
+        }
+        let first = self.selected.saturating_sub(VISIBLE_ROWS - 1);
+        for (i, name) in self.files.iter().enumerate().skip(first).take(VISIBLE_ROWS) {
+            let color = if i == self.selected { WHITE } else { GRAY };
+            draw_centered(name, y, 20.0 * ui, color);
+            y += 24.0 * ui;
+        }
+        y += 12.0 * ui;
+        let cursor = if self.editing && (get_time() * 2.0) as i64 % 2 == 0 { "_" } else { "" };
+        draw_centered(&trf!("File: {}{}", self.path, cursor), y, 22.0 * ui, if self.editing { palette.head } else { WHITE });
+        y += 28.0 * ui;
+        draw_centered(tr("Up/Down: Select  F: Edit path  Enter: Verify and play  Esc: Back"), y, 18.0 * ui, GRAY);
+        if let Some(status) = &self.status {
+            draw_centered(status, y + 28.0 * ui, 18.0 * ui, ORANGE);
+        }
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn is_typing(&self) -> bool {
+        self.editing
+    }
+}
+
+// Deterministic playback of one replay with pause and seek
+pub struct ReplayPlayerState {
+    replay: Replay,
+    game: SnakeGame,
+    paused: bool,
+    next: Transition,
+}
+
+impl ReplayPlayerState {
+    pub fn new(replay: Replay) -> Self {
+        let game = SnakeGame::from_replay(&replay);
+        Self { replay, game, paused: false, next: Transition::None }
+    }
+
+    fn finished(&self) -> bool {
+        !self.game.sim.alive || self.game.replay.ticks >= self.replay.ticks
+    }
+
+    // Seeking backwards re-simulates from the start; the sim is cheap enough for that
+    fn seek(&mut self, target: u32) {
+        let target = target.min(self.replay.ticks);
+        if target < self.game.replay.ticks { self.game = SnakeGame::from_replay(&self.replay); }
+        while self.game.replay.ticks < target && !self.finished() {
+            self.game.playback_tick(&self.replay);
+        }
+        self.game.drain_events();
+        self.game.accumulator = 0.0;
+    }
+}
+
+impl GameScreen for ReplayPlayerState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        if is_key_pressed(KeyCode::Space) { self.paused = !self.paused; }
+        if is_key_pressed(KeyCode::Left) { self.seek(self.game.replay.ticks.saturating_sub(SEEK_TICKS)); }
+        if is_key_pressed(KeyCode::Right) { self.seek(self.game.replay.ticks + SEEK_TICKS); }
+        if is_key_pressed(KeyCode::Enter) && self.finished() { self.seek(0); }
+
+        if !self.paused {
+            self.game.accumulator += frame.dt.clamp(0.0, MAX_FRAME_DT);
+            while self.game.accumulator >= self.game.tick_interval() && !self.finished() {
+                self.game.accumulator -= self.game.tick_interval();
+                self.game.playback_tick(&self.replay);
+            }
+        }
+        for event in self.game.drain_events() {
+            app.sounds.on_event(&event, app.settings.sound_volume);
+            self.game.popups.on_event(&event, self.game.sim.head(), frame.now);
+            app.rain.on_event(&event, self.game.sim.map.grid, frame.look);
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let ui = frame.ui;
+        let sh = screen_height();
+        self.game.draw(&frame.palette, frame.skin, frame.look);
+
+        let state = if self.finished() { tr("  END") } else if self.paused { tr("  PAUSED") } else { "" };
+        let line = trf!("REPLAY  Tick {}/{}  Seed {}{}", self.game.replay.ticks, self.replay.ticks, self.replay.seed, state);
+        draw_centered(&line, sh - 40.0 * ui, 20.0 * ui, WHITE);
+        let hint = if self.finished() { tr("Enter: Watch again  Left: Seek back  Esc: Back") } else { tr("Space: Pause  Left/Right: Seek  Esc: Back") };
+        draw_centered(hint, sh - 16.0 * ui, 18.0 * ui, GRAY);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn is_live(&self) -> bool { !self.paused }
+
+    fn spectated(&self) -> Option<&SnakeGame> { Some(&self.game) }
+}
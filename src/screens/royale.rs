@@ -0,0 +1,192 @@
+use macroquad::color::hsl_to_rgb;
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, QuitGesture, Transition};
+use crate::arena::{Arena, ArenaEvent, ARENA_GRID, MAX_RIVALS, MIN_RIVALS, TIME_LIMIT};
+use crate::audio::play_sfx;
+use crate::game::MAX_FRAME_DT;
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::map::Direction;
+use crate::mapgen;
+use crate::render::{draw_glyph_at_cell_scaled, matrix_char_for_cell, Palette};
+use crate::ui::{draw_centered, draw_text, KeyBindings};
+
+const SPECTATE_SPEEDUP: f32 = 4.0; // once the player is out, the rest plays out this much faster
+
+// Battle royale against computer snakes on a large board, from the lobby's seed, map style,
+// density and speed. Left/Right picks the number of rivals before Enter starts the match.
+pub struct RoyaleState {
+    seed: u64,
+    map_style: String,
+    wall_density: f32,
+    move_interval: f32,
+    rivals: usize,
+    arena: Option<Arena>, // None until the match starts
+    accumulator: f32,
+    outcome: Option<String>,
+    next: Transition,
+}
+
+impl RoyaleState {
+    pub fn new(seed: u64, map_style: String, wall_density: f32, move_interval: f32) -> Self {
+        Self { seed, map_style, wall_density, move_interval, rivals: 4, arena: None, accumulator: 0.0, outcome: None, next: Transition::None }
+    }
+
+    fn start(&mut self) {
+        let map = mapgen::generate(&self.map_style, ARENA_GRID, self.seed, self.wall_density);
+        logging::info(format!("Battle royale started: seed {} with {} rivals", self.seed, self.rivals));
+        self.arena = Some(Arena::new(map, self.rivals + 1));
+    }
+
+    fn step(&mut self, app: &App) {
+        let Some(arena) = self.arena.as_mut() else { return; };
+        for i in 1..arena.snakes.len() {
+            if arena.snakes[i].alive {
+                let dir = arena.ai_direction(i);
+                arena.snakes[i].turn(dir);
+            }
+        }
+        for event in arena.tick() {
+            let volume = app.settings.sound_volume;
+            match event {
+                ArenaEvent::Ate { snake: 0 } => play_sfx(&app.sounds.eat, 0.35 * volume),
+                ArenaEvent::Died { snake: 0 } => {
+                    play_sfx(&app.sounds.die, 0.6 * volume);
+                    app.sounds.duck_music();
+                }
+                ArenaEvent::Died { .. } => play_sfx(&app.sounds.die, 0.2 * volume),
+                ArenaEvent::Ate { .. } => {}
+            }
+        }
+        if arena.finished() {
+            let winner = arena.winner().unwrap_or(0);
+            let outcome = if winner == 0 { tr("You win the battle royale!").to_string() } else { trf!("{} wins", rival_name(winner)) };
+            logging::info(format!("Battle royale ended at tick {}: {}", arena.tick, outcome));
+            self.outcome = Some(outcome);
+        }
+    }
+}
+
+fn rival_name(i: usize) -> String {
+    trf!("Rival {}", i)
+}
+
+// Player in the theme's colors, rivals in evenly spaced hues
+pub(super) fn snake_color(i: usize, head: bool, palette: &Palette) -> Color {
+    if i == 0 { return if head { palette.head } else { palette.body }; }
+    let hue = i as f32 / (MAX_RIVALS + 1) as f32;
+    hsl_to_rgb(hue, 0.8, if head { 0.75 } else { 0.5 })
+}
+
+// Walls, food and every snake, fitted and centred in `area`; shared with co-op
+pub(super) fn draw_board(arena: &Arena, palette: &Palette, area: Rect) {
+    let grid = arena.map.grid;
+    let tile = (area.w / grid.width as f32).min(area.h / grid.height as f32);
+    let off_x = area.x + (area.w - tile * grid.width as f32) * 0.5;
+    let off_y = area.y + (area.h - tile * grid.height as f32) * 0.5;
+    for c in &arena.map.walls {
+        draw_glyph_at_cell_scaled(matrix_char_for_cell(*c), *c, palette.wall, tile, tile, off_x, off_y);
+    }
+    for f in &arena.food {
+        draw_glyph_at_cell_scaled('*', *f, palette.food, tile, tile, off_x, off_y);
+    }
+    for (i, snake) in arena.snakes.iter().enumerate() {
+        for (k, c) in snake.body.iter().enumerate() {
+            draw_glyph_at_cell_scaled(matrix_char_for_cell(*c), *c, snake_color(i, k == 0, palette), tile, tile, off_x, off_y);
+        }
+    }
+}
+
+impl GameScreen for RoyaleState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        let Some(arena) = self.arena.as_mut() else {
+            if is_key_pressed(KeyCode::Left) { self.rivals = (self.rivals - 1).max(MIN_RIVALS); }
+            if is_key_pressed(KeyCode::Right) { self.rivals = (self.rivals + 1).min(MAX_RIVALS); }
+            if is_key_pressed(KeyCode::Enter) { self.start(); }
+            return;
+        };
+
+        let keys = &app.settings.key_bindings;
+        let player = &mut arena.snakes[0];
+        if KeyBindings::pressed(&keys.up) { player.turn(Direction::Up); }
+        if KeyBindings::pressed(&keys.down) { player.turn(Direction::Down); }
+        if KeyBindings::pressed(&keys.left) { player.turn(Direction::Left); }
+        if KeyBindings::pressed(&keys.right) { player.turn(Direction::Right); }
+
+        if self.outcome.is_none() {
+            let speed = if player.alive { 1.0 } else { SPECTATE_SPEEDUP };
+            self.accumulator += frame.dt.clamp(0.0, MAX_FRAME_DT) * speed;
+            while self.outcome.is_none() && self.accumulator >= self.move_interval {
+                self.accumulator -= self.move_interval;
+                self.step(app);
+            }
+        }
+
+        if self.outcome.is_some() && is_key_pressed(KeyCode::Enter) {
+            self.next = Transition::Pop;
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let sh = screen_height();
+        let Some(arena) = &self.arena else {
+            let mut y = sh * 0.3;
+            draw_centered(tr("BATTLE ROYALE"), y, 36.0 * ui, palette.head);
+            y += 48.0 * ui;
+            draw_centered(&trf!("Rivals: < {} >", self.rivals), y, 22.0 * ui, WHITE);
+            y += 28.0 * ui;
+            let info = trf!("Seed {}  Map {}  Density {}%  Speed {}ms", self.seed, tr(&self.map_style), format!("{:.0}", self.wall_density * 100.0), format!("{:.0}", self.move_interval * 1000.0));
+            draw_centered(&info, y, 18.0 * ui, LIGHTGRAY);
+            y += 28.0 * ui;
+            draw_centered(tr("Last snake alive wins; when time runs out, the top score among the survivors"), y, 18.0 * ui, palette.body);
+            y += 36.0 * ui;
+            draw_centered(tr("Left/Right: Rivals  Enter: Start  Esc: Back"), y, 18.0 * ui, GRAY);
+            return;
+        };
+
+        // Board below a one-line HUD, standings down the right
+        let top = 32.0 * ui;
+        let side = 170.0 * ui;
+        draw_board(arena, &palette, Rect::new(8.0, top, screen_width() - side - 16.0, sh - top - 40.0 * ui));
+
+        let left = (TIME_LIMIT.saturating_sub(arena.tick) as f32 * self.move_interval).ceil();
+        draw_text(&trf!("Alive: {}/{}  Time left: {}s", arena.alive(), arena.snakes.len(), left), 8.0, 22.0 * ui, 22.0 * ui, palette.body);
+
+        let mut order: Vec<usize> = (0..arena.snakes.len()).collect();
+        order.sort_by_key(|i| {
+            let s = &arena.snakes[*i];
+            std::cmp::Reverse((s.alive, s.score, s.out_at))
+        });
+        let x = screen_width() - side;
+        let mut y = top + 24.0 * ui;
+        draw_text(tr("STANDINGS"), x, y, 20.0 * ui, palette.head);
+        for i in order {
+            y += 24.0 * ui;
+            let s = &arena.snakes[i];
+            let name = if i == 0 { tr("You").to_string() } else { rival_name(i) };
+            let line = format!("{}  {}{}", name, s.score, if s.alive { "" } else { tr("  (out)") });
+            let color = if s.alive { snake_color(i, true, &palette) } else { GRAY };
+            draw_text(&line, x, y, 18.0 * ui, color);
+        }
+
+        let status = match &self.outcome {
+            Some(outcome) => trf!("{}  -  Enter: Back", outcome),
+            None if !arena.snakes[0].alive => tr("You're out - watching the rest play it out").to_string(),
+            None => tr("Esc: Leave").to_string(),
+        };
+        draw_centered(&status, sh - 16.0 * ui, 22.0 * ui, WHITE);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn quit_gesture(&self) -> QuitGesture {
+        if self.arena.is_some() && self.outcome.is_none() { QuitGesture::Hold } else { QuitGesture::Confirm }
+    }
+
+    fn is_live(&self) -> bool { self.arena.is_some() && self.outcome.is_none() }
+}
@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, QuitGesture, Transition};
+use crate::game::{SnakeGame, MAX_FRAME_DT};
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::map::SWITCH_GROUPS;
+use crate::render::{Look, SWITCH_COLORS};
+use crate::sim::SnakeSim;
+use crate::ui::{draw_centered, draw_text, KeyBindings};
+
+const REWIND_LIMIT: usize = 3000; // ticks kept to rewind through; the oldest go first
+const REWIND_RATE: f32 = 2.0; // holding rewind runs back this many times faster than play
+const SPEED_STEP: f32 = 0.01;
+const MIN_INTERVAL: f32 = 0.03;
+const MAX_INTERVAL: f32 = 0.5;
+
+// A tick to rewind to: the board as it was and the glyphs the body had
+struct Snapshot {
+    sim: SnakeSim,
+    body_chars: VecDeque<char>,
+}
+
+// Practice on the lobby's map with nothing at stake: no score, rewind as often as needed,
+// walls, switches and food placed or removed with the mouse, and the speed changed mid-run.
+// Rewinding or crashing pauses the run until a direction key picks it up again.
+pub struct SandboxState {
+    game: SnakeGame,
+    history: VecDeque<Snapshot>,
+    paused: bool,
+    rewind_time: f32, // time banked toward the next step back while rewind is held
+    group: usize, // switch group Shift+click edits
+    next: Transition,
+}
+
+impl SandboxState {
+    pub fn new(mut game: SnakeGame) -> Self {
+        game.practice = true;
+        Self { game, history: VecDeque::new(), paused: true, rewind_time: 0.0, group: 0, next: Transition::None }
+    }
+
+    fn rewind(&mut self) {
+        let Some(snapshot) = self.history.pop_back() else { return; };
+        self.game.sim = snapshot.sim;
+        self.game.body_chars = snapshot.body_chars;
+        self.game.accumulator = 0.0;
+        self.game.prev_snake.clear();
+        self.game.trail.clear();
+        self.paused = true;
+    }
+
+    // Left click toggles a wall, right click moves the food there or takes it away. With Shift
+    // held they toggle a wall and a plate of the current switch group instead.
+    fn edit(&mut self, look: Look) {
+        let (mx, my) = mouse_position();
+        let Some(cell) = self.game.cell_at(mx, my, look) else { return; };
+        let sim = &mut self.game.sim;
+        if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+            if is_mouse_button_pressed(MouseButton::Left) { sim.toggle_switch_wall(self.group, cell); }
+            if is_mouse_button_pressed(MouseButton::Right) { sim.toggle_plate(self.group, cell); }
+            return;
+        }
+        if is_mouse_button_pressed(MouseButton::Left) && !sim.remove_wall(cell) {
+            sim.add_wall(cell);
+        }
+        if is_mouse_button_pressed(MouseButton::Right) {
+            if sim.food == Some(cell) { sim.remove_food(); } else { sim.place_food(cell); }
+        }
+    }
+}
+
+impl GameScreen for SandboxState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        if is_key_pressed(KeyCode::R) {
+            self.game.restart();
+            self.history.clear();
+            self.paused = true;
+            logging::info("Sandbox restarted");
+        }
+        if is_key_pressed(KeyCode::Minus) { self.game.move_interval = (self.game.move_interval + SPEED_STEP).min(MAX_INTERVAL); }
+        if is_key_pressed(KeyCode::Equal) { self.game.move_interval = (self.game.move_interval - SPEED_STEP).max(MIN_INTERVAL); }
+        if is_key_pressed(KeyCode::Tab) { self.group = (self.group + 1) % SWITCH_GROUPS; }
+        self.edit(frame.look);
+
+        // A tap steps back one tick; holding keeps going
+        if is_key_pressed(KeyCode::Backspace) {
+            self.rewind();
+            self.rewind_time = 0.0;
+        } else if is_key_down(KeyCode::Backspace) {
+            self.rewind_time += frame.dt.clamp(0.0, MAX_FRAME_DT) * REWIND_RATE;
+            while self.rewind_time >= self.game.move_interval && !self.history.is_empty() {
+                self.rewind_time -= self.game.move_interval;
+                self.rewind();
+            }
+            return;
+        }
+
+        let keys = &app.settings.key_bindings;
+        let steering = [&keys.up, &keys.down, &keys.left, &keys.right].into_iter().any(|k| KeyBindings::pressed(k));
+        if is_key_pressed(KeyCode::Space) { self.paused = !self.paused; }
+        if steering && self.game.sim.alive { self.paused = false; }
+        self.game.handle_input(keys);
+        if self.paused || !self.game.sim.alive { return; }
+
+        // Only snapshot on frames that are about to tick
+        let dt = frame.dt.clamp(0.0, MAX_FRAME_DT);
+        let snapshot = (self.game.accumulator + dt >= self.game.tick_interval())
+            .then(|| Snapshot { sim: self.game.sim.clone(), body_chars: self.game.body_chars.clone() });
+        if self.game.step_single(dt) && let Some(snapshot) = snapshot {
+            self.history.push_back(snapshot);
+            if self.history.len() > REWIND_LIMIT { self.history.pop_front(); }
+        }
+        for event in self.game.drain_events() {
+            app.sounds.on_event(&event, app.settings.sound_volume);
+            app.rain.on_event(&event, self.game.sim.map.grid, frame.look);
+        }
+        if !self.game.sim.alive { self.paused = true; }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        self.game.draw(&palette, frame.skin, frame.look);
+
+        let speed = format!("{:.0}", self.game.move_interval * 1000.0);
+        draw_text(&trf!("PRACTICE  Speed: {}ms  Rewind: {} ticks", speed, self.history.len()), 8.0, 16.0, 20.0, palette.body);
+        draw_text(tr("Click: Wall  Right-click: Food  Space: Pause  Backspace: Rewind  -/=: Speed  R: Restart  Esc: Back"), 8.0, 36.0, 16.0, palette.wall);
+        let switches = trf!("Shift+Click: Switch wall  Shift+Right-click: Plate  Tab: Switch colour ({})", self.group + 1);
+        draw_text(&switches, 8.0, 54.0, 16.0, SWITCH_COLORS[self.group]);
+        let status = if !self.game.sim.alive {
+            tr("Crashed - hold Backspace to rewind or press R to restart")
+        } else if self.paused {
+            tr("Paused - press a direction or Space to go")
+        } else {
+            return;
+        };
+        draw_centered(status, screen_height() * 0.5, 24.0 * frame.ui, WHITE);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn quit_gesture(&self) -> QuitGesture { QuitGesture::Hold }
+
+    fn is_live(&self) -> bool { !self.paused }
+
+    fn spectated(&self) -> Option<&SnakeGame> { Some(&self.game) }
+}
@@ -0,0 +1,93 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, QuitGesture, Transition};
+use crate::ai;
+use crate::game::{SnakeGame, DEFAULT_WALL_DENSITY};
+use crate::i18n::tr;
+use crate::map::GridConfig;
+use crate::mapgen;
+use crate::pacing::Pacing;
+use crate::rng::Rng;
+use crate::ui::draw_centered;
+
+const MOVE_INTERVAL: f32 = 0.07;
+const NEXT_MAP_SECONDS: f32 = 2.0; // a finished run stays up this long before the next map
+
+// Comes up over any screen after a while without input: the computer plays one random map
+// after another, in every style, behind stronger rain. Any input pops it, back to the screen
+// it covered.
+pub struct ScreensaverState {
+    game: SnakeGame,
+    rng: Rng,
+    over_for: f32, // seconds since the run ended
+    next: Transition,
+}
+
+impl ScreensaverState {
+    pub fn new() -> Self {
+        let mut rng = Rng::new(miniquad::date::now().to_bits());
+        let game = Self::start(&mut rng);
+        Self { game, rng, over_for: 0.0, next: Transition::None }
+    }
+
+    // A fresh run on a random seed in a random style
+    fn start(rng: &mut Rng) -> SnakeGame {
+        let names = mapgen::names();
+        let style = &names[rng.range_usize(0, names.len())];
+        let seed = (rng.next_u32() as u64) << 32 | rng.next_u32() as u64;
+        let mut game = SnakeGame::new(mapgen::generate(style, GridConfig::DEFAULT, seed, DEFAULT_WALL_DENSITY), MOVE_INTERVAL);
+        game.practice = true; // no HUD
+        game
+    }
+
+    // The autopilot's next turn: after the food, around walls, its own body and glitch food
+    fn steer(&mut self) {
+        let sim = &self.game.sim;
+        let glitch = sim.glitch.as_ref().and_then(|g| g.at);
+        let food: Vec<_> = sim.food.into_iter().chain(sim.premium.as_ref().and_then(|p| p.at)).collect();
+        let dir = ai::steer(sim.map.grid, sim.head(), sim.direction, &food, |c| {
+            sim.map.is_wall(c) || sim.occupied.contains(&c) || glitch == Some(c)
+        });
+        self.game.sim.turn(dir);
+    }
+}
+
+impl GameScreen for ScreensaverState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        if Pacing::any_input() {
+            self.next = Transition::Pop;
+            return;
+        }
+        if self.game.sim.alive {
+            self.steer();
+            self.game.step_single(frame.dt);
+        } else {
+            self.over_for += frame.dt;
+            if self.over_for >= NEXT_MAP_SECONDS {
+                self.game = Self::start(&mut self.rng);
+                self.over_for = 0.0;
+            }
+        }
+        // Silent, but the rain still pulses with the meals
+        for event in self.game.drain_events() {
+            self.game.popups.on_event(&event, self.game.sim.head(), frame.now);
+            app.rain.on_event(&event, self.game.sim.map.grid, frame.look);
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        self.game.draw(&frame.palette, frame.skin, frame.look);
+        let pulse = 0.3 + 0.2 * (frame.now * 1.5).sin();
+        draw_centered(tr("Press any key"), screen_height() - 24.0 * frame.ui, 18.0 * frame.ui, Color { a: pulse, ..frame.palette.wall });
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn quit_gesture(&self) -> QuitGesture { QuitGesture::None }
+
+    fn is_live(&self) -> bool { true }
+
+    fn storms_rain(&self) -> bool { true }
+}
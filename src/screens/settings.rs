@@ -0,0 +1,752 @@
+use macroquad::prelude::*;
+use std::fs;
+
+use super::{App, Frame, GameScreen, HelpState, Transition};
+use crate::cloud::CloudSync;
+use crate::i18n::{tr, trf, Language};
+use crate::logging;
+use crate::render::{Backdrop, BoardFilter, Theme, WindowFit};
+use crate::save::{export_save_text, import_save_text, load_save, valid_channel, valid_sync_url, write_save, ImportMode, SaveData, Settings, EXPORT_PATH};
+use crate::skins::{self, Skin};
+use crate::ui::{draw_centered, draw_text, drag_slider, draw_slider, key_name, measure_text, DisplayMode, HudCorner, MinWindow, SLIDER_WIDTH, UI_SCALE_MAX, UI_SCALE_MIN};
+use crate::weekly::Modifier;
+
+const ROW_HEIGHT: f32 = 28.0;
+
+// Settings are grouped into pages; Tab or Left/Right on the page bar moves between them
+#[derive(Clone, Copy, PartialEq)]
+enum Page {
+    Audio,
+    Video,
+    Controls,
+    Gameplay,
+    Accessibility,
+    Data,
+}
+
+impl Page {
+    const ALL: [Page; 6] = [Page::Audio, Page::Video, Page::Controls, Page::Gameplay, Page::Accessibility, Page::Data];
+
+    fn label(self) -> &'static str {
+        match self {
+            Page::Audio => "Audio",
+            Page::Video => "Video",
+            Page::Controls => "Controls",
+            Page::Gameplay => "Gameplay",
+            Page::Accessibility => "Accessibility",
+            Page::Data => "Data",
+        }
+    }
+
+    fn items(self) -> &'static [Item] {
+        match self {
+            Page::Audio => &[Item::Volume, Item::Music],
+            Page::Video => &[Item::Theme, Item::Skin, Item::BodyGradient, Item::Lighting, Item::Filter, Item::Display, Item::MinWindow, Item::WindowFit, Item::Backdrop, Item::Rain, Item::RainDensity, Item::HudCorner, Item::PowerSaving],
+            Page::Controls => &[Item::Binding(0), Item::Binding(1), Item::Binding(2), Item::Binding(3)],
+            Page::Gameplay => &[Item::AutoPause, Item::AutoPauseMute, Item::RecordGif, Item::SpeedrunTimer, Item::PathHint, Item::Missions, Item::Modifier, Item::Premium, Item::PowerUps, Item::Phase, Item::Glitch, Item::Challenge, Item::TwitchChannel],
+            Page::Accessibility => &[Item::UiScale, Item::Language, Item::OneSwitch],
+            Page::Data => &[Item::TransferPath, Item::ImportMode, Item::Export, Item::Import, Item::Copy, Item::Paste, Item::SyncUrl, Item::SyncToken, Item::SyncNow],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Item {
+    Volume,
+    Music,
+    Theme,
+    Skin,
+    BodyGradient,
+    Lighting,
+    Filter,
+    Display,
+    MinWindow,
+    WindowFit,
+    Backdrop,
+    Rain,
+    RainDensity,
+    HudCorner,
+    PowerSaving,
+    Binding(usize), // index into `KeyBindings::actions`
+    AutoPause,
+    AutoPauseMute,
+    RecordGif,
+    SpeedrunTimer,
+    PathHint,
+    Missions,
+    Modifier,
+    Premium,
+    PowerUps,
+    Phase,
+    Glitch,
+    Challenge,
+    TwitchChannel,
+    UiScale,
+    Language,
+    OneSwitch,
+    TransferPath,
+    ImportMode,
+    Export,
+    Import,
+    Copy,
+    Paste,
+    SyncUrl,
+    SyncToken,
+    SyncNow,
+}
+
+// How an item is drawn, and what Left/Right and Enter do to it
+enum Widget {
+    Slider { fraction: f32, text: String },
+    Toggle(bool),
+    Choice(String),
+    Text { value: String, editing: bool },
+    Key { keys: String, waiting: bool },
+    Button,
+}
+
+// Edits apply (and preview) immediately; Esc keeps them and R puts back what was there
+// when the screen opened
+pub struct SettingsState {
+    pub settings: Settings,
+    original: Settings,
+    page: usize,
+    selected: Option<usize>, // None while the page bar is focused
+    pub rebinding: bool,
+    pub transfer_path: String,
+    pub editing_path: bool,
+    pub editing_channel: bool,
+    pub editing_sync_url: bool,
+    pub editing_sync_token: bool,
+    cloud: CloudSync, // Sync now, separate from the startup sync
+    pub import_mode: ImportMode,
+    pub status: Option<String>,
+    skins: Vec<&'static Skin>, // unlocked ones, the only ones that can be picked
+    sliders: Vec<(usize, Rect)>, // item index and track of each slider drawn last frame
+    dragging: Option<(usize, Rect)>, // the slider the mouse holds, and its track at the start
+    next: Transition,
+}
+
+impl SettingsState {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            original: settings.clone(),
+            settings,
+            page: 0,
+            selected: Some(0),
+            rebinding: false,
+            transfer_path: EXPORT_PATH.to_string(),
+            editing_path: false,
+            editing_channel: false,
+            editing_sync_url: false,
+            editing_sync_token: false,
+            cloud: CloudSync::default(),
+            import_mode: ImportMode::Merge,
+            status: None,
+            skins: skins::unlocked(&load_save().progress),
+            sliders: Vec::new(),
+            dragging: None,
+            next: Transition::None,
+        }
+    }
+
+    fn items(&self) -> &'static [Item] {
+        Page::ALL[self.page].items()
+    }
+
+    fn current(&self) -> Option<Item> {
+        self.selected.map(|i| self.items()[i])
+    }
+
+    fn turn_page(&mut self, dir: i32) {
+        let len = Page::ALL.len() as i32;
+        self.page = ((self.page as i32 + dir + len) % len) as usize;
+        self.selected = self.selected.map(|_| 0);
+        self.sliders.clear();
+        self.dragging = None;
+    }
+
+    // A slider follows the mouse from a press on its track until the button is let go
+    fn drag_sliders(&mut self) {
+        for &(i, track) in &self.sliders.clone() {
+            if self.dragging.is_some_and(|(held, _)| held != i) { continue; }
+            let mut grabbed = self.dragging.map(|(_, r)| r);
+            let fraction = drag_slider(track, &mut grabbed);
+            self.dragging = grabbed.map(|r| (i, r));
+            if let Some(f) = fraction {
+                self.selected = Some(i);
+                self.set_fraction(self.items()[i], f);
+            }
+        }
+    }
+
+    fn apply_import(&mut self, result: Result<SaveData, String>) {
+        match result {
+            Ok(s) => {
+                logging::info(format!("Imported save (best {})", s.progress.best_score));
+                self.status = Some(trf!("Imported save (best {})", s.progress.best_score));
+                // The import is already written, so it becomes what R reverts to
+                self.settings = s.settings;
+                self.original = self.settings.clone();
+                self.skins = skins::unlocked(&s.progress);
+                self.settings.display_mode.apply();
+            }
+            Err(e) => {
+                logging::warn(format!("Import rejected: {}", e));
+                self.status = Some(e);
+            }
+        }
+    }
+
+    // Persists the edited settings first so the exported save is up to date
+    fn write_edited(&self) {
+        let mut s = load_save();
+        s.settings = self.settings.clone();
+        write_save(&s);
+    }
+
+    fn export(&mut self) {
+        self.write_edited();
+        let status = match fs::write(&self.transfer_path, export_save_text()) {
+            Ok(()) => trf!("Exported to {}", self.transfer_path),
+            Err(e) => trf!("Export failed: {}", e),
+        };
+        logging::info(&status);
+        self.status = Some(status);
+    }
+
+    fn import(&mut self) {
+        let result = fs::read_to_string(&self.transfer_path)
+            .map_err(|e| trf!("Import failed: {}", e))
+            .and_then(|text| import_save_text(&text, self.import_mode));
+        self.apply_import(result);
+    }
+
+    fn copy(&mut self) {
+        self.write_edited();
+        miniquad::window::clipboard_set(&export_save_text());
+        self.status = Some(tr("Save copied to clipboard").to_string());
+    }
+
+    fn paste(&mut self) {
+        let result = miniquad::window::clipboard_get()
+            .ok_or_else(|| tr("Clipboard is empty").to_string())
+            .and_then(|text| import_save_text(&text, self.import_mode));
+        self.apply_import(result);
+    }
+
+    fn sync_now(&mut self) {
+        if self.settings.sync_url.is_empty() {
+            self.status = Some(tr("Set a cloud sync URL first").to_string());
+            return;
+        }
+        // The sync reads the address and token from the save
+        self.write_edited();
+        self.cloud.start(&self.settings);
+        self.status = Some(tr("Syncing...").to_string());
+    }
+
+    fn revert(&mut self) {
+        let fullscreen = self.settings.display_mode;
+        self.settings = self.original.clone();
+        if fullscreen != self.settings.display_mode {
+            self.settings.display_mode.apply();
+        }
+        self.status = Some(tr("Changes reverted").to_string());
+    }
+
+    fn label(item: Item) -> &'static str {
+        match item {
+            Item::Volume => "Volume",
+            Item::Music => "Music volume",
+            Item::Theme => "Theme",
+            Item::Skin => "Snake skin",
+            Item::BodyGradient => "Body gradient",
+            Item::Lighting => "Lighting",
+            Item::Filter => "Board scaling",
+            Item::Display => "Display",
+            Item::MinWindow => "Minimum window",
+            Item::WindowFit => "Window shape",
+            Item::Backdrop => "Background",
+            Item::HudCorner => "HUD corner",
+            Item::Rain => "Matrix rain",
+            Item::RainDensity => "Rain density",
+            Item::PowerSaving => "Power saving",
+            Item::Binding(i) => ["Up", "Down", "Left", "Right"][i],
+            Item::AutoPause => "Pause on focus loss",
+            Item::AutoPauseMute => "Mute while paused",
+            Item::RecordGif => "Record GIF",
+            Item::SpeedrunTimer => "Speedrun timer",
+            Item::PathHint => "Path hint",
+            Item::Missions => "Missions",
+            Item::Modifier => "Rule modifier",
+            Item::Premium => "Premium food",
+            Item::PowerUps => "Power-ups",
+            Item::Phase => "Phase charges",
+            Item::Glitch => "Glitch food",
+            Item::Challenge => "Adaptive challenge",
+            Item::TwitchChannel => "Twitch channel",
+            Item::UiScale => "UI scale",
+            Item::Language => "Language",
+            Item::OneSwitch => "One-switch controls",
+            Item::TransferPath => "File",
+            Item::ImportMode => "Import mode",
+            Item::Export => "Export save to file",
+            Item::Import => "Import save from file",
+            Item::Copy => "Copy save to clipboard",
+            Item::Paste => "Paste save from clipboard",
+            Item::SyncUrl => "Cloud sync URL",
+            Item::SyncToken => "Cloud sync token",
+            Item::SyncNow => "Sync now",
+        }
+    }
+
+    fn widget(&self, item: Item) -> Widget {
+        let st = &self.settings;
+        match item {
+            Item::Volume => Widget::Slider { fraction: st.sound_volume, text: format!("{:.0}%", st.sound_volume * 100.0) },
+            Item::Music => Widget::Slider { fraction: st.music_volume, text: format!("{:.0}%", st.music_volume * 100.0) },
+            Item::UiScale => Widget::Slider {
+                fraction: (st.ui_scale - UI_SCALE_MIN) / (UI_SCALE_MAX - UI_SCALE_MIN),
+                text: format!("{:.0}%", st.ui_scale * 100.0),
+            },
+            Item::Challenge => Widget::Slider { fraction: st.challenge, text: format!("{:.0}%", st.challenge * 100.0) },
+            Item::RainDensity => Widget::Slider { fraction: st.rain_density, text: format!("{:.0}%", st.rain_density * 200.0) },
+            Item::Rain => Widget::Toggle(st.rain),
+            Item::PowerSaving => Widget::Toggle(st.power_saving),
+            Item::BodyGradient => Widget::Toggle(st.body_gradient),
+            Item::Lighting => Widget::Toggle(st.lighting),
+            Item::AutoPause => Widget::Toggle(st.auto_pause),
+            Item::AutoPauseMute => Widget::Toggle(st.auto_pause_mute),
+            Item::RecordGif => Widget::Toggle(st.record_gif),
+            Item::SpeedrunTimer => Widget::Toggle(st.speedrun_timer),
+            Item::PathHint => Widget::Toggle(st.path_hint),
+            Item::Missions => Widget::Toggle(st.missions),
+            Item::Modifier => Widget::Choice(st.modifier.map_or(tr("Off"), |m| tr(m.label())).to_string()),
+            Item::Premium => Widget::Toggle(st.extras.premium),
+            Item::PowerUps => Widget::Toggle(st.extras.powerups),
+            Item::Phase => Widget::Toggle(st.extras.phase),
+            Item::Glitch => Widget::Toggle(st.extras.glitch),
+            Item::Theme => Widget::Choice(tr(st.theme.label()).to_string()),
+            Item::Filter => Widget::Choice(tr(st.board_filter.label()).to_string()),
+            Item::Skin => Widget::Choice(format!("{}  ({}/{})", tr(skins::by_id(&st.skin).name), self.skins.len(), skins::ALL.len())),
+            Item::Display => Widget::Choice(tr(st.display_mode.label()).to_string()),
+            Item::MinWindow => Widget::Choice(tr(st.min_window.label()).to_string()),
+            Item::WindowFit => Widget::Choice(tr(st.window_fit.label()).to_string()),
+            Item::Backdrop => Widget::Choice(tr(st.backdrop.label()).to_string()),
+            Item::HudCorner => Widget::Choice(tr(st.hud_corner.label()).to_string()),
+            Item::Language => Widget::Choice(st.language.label().to_string()), // always in its own language
+            Item::OneSwitch => Widget::Toggle(st.one_switch),
+            Item::ImportMode => Widget::Choice(tr(self.import_mode.label()).to_string()),
+            Item::TwitchChannel => Widget::Text { value: st.twitch_channel.clone(), editing: self.editing_channel },
+            Item::TransferPath => Widget::Text { value: self.transfer_path.clone(), editing: self.editing_path },
+            Item::Binding(i) => Widget::Key {
+                keys: st.key_bindings.actions()[i].1.join(" / "),
+                waiting: self.rebinding && self.current() == Some(item),
+            },
+            Item::SyncUrl => Widget::Text { value: st.sync_url.clone(), editing: self.editing_sync_url },
+            // Masked even while typing, in case the screen is being streamed
+            Item::SyncToken => Widget::Text { value: "*".repeat(st.sync_token.chars().count()), editing: self.editing_sync_token },
+            Item::Export | Item::Import | Item::Copy | Item::Paste | Item::SyncNow => Widget::Button,
+        }
+    }
+
+    // Whether the item differs from when the screen opened
+    fn changed(&self, item: Item) -> bool {
+        let (now, was) = (&self.settings, &self.original);
+        match item {
+            Item::Volume => now.sound_volume != was.sound_volume,
+            Item::Music => now.music_volume != was.music_volume,
+            Item::Theme => now.theme != was.theme,
+            Item::Skin => now.skin != was.skin,
+            Item::Display => now.display_mode != was.display_mode,
+            Item::MinWindow => now.min_window != was.min_window,
+            Item::WindowFit => now.window_fit != was.window_fit,
+            Item::Backdrop => now.backdrop != was.backdrop,
+            Item::HudCorner => now.hud_corner != was.hud_corner,
+            Item::Rain => now.rain != was.rain,
+            Item::RainDensity => now.rain_density != was.rain_density,
+            Item::PowerSaving => now.power_saving != was.power_saving,
+            Item::BodyGradient => now.body_gradient != was.body_gradient,
+            Item::Lighting => now.lighting != was.lighting,
+            Item::Filter => now.board_filter != was.board_filter,
+            Item::Binding(i) => now.key_bindings.actions()[i].1 != was.key_bindings.actions()[i].1,
+            Item::AutoPause => now.auto_pause != was.auto_pause,
+            Item::AutoPauseMute => now.auto_pause_mute != was.auto_pause_mute,
+            Item::RecordGif => now.record_gif != was.record_gif,
+            Item::SpeedrunTimer => now.speedrun_timer != was.speedrun_timer,
+            Item::PathHint => now.path_hint != was.path_hint,
+            Item::Missions => now.missions != was.missions,
+            Item::Modifier => now.modifier != was.modifier,
+            Item::Premium => now.extras.premium != was.extras.premium,
+            Item::PowerUps => now.extras.powerups != was.extras.powerups,
+            Item::Phase => now.extras.phase != was.extras.phase,
+            Item::Glitch => now.extras.glitch != was.extras.glitch,
+            Item::Challenge => now.challenge != was.challenge,
+            Item::TwitchChannel => now.twitch_channel != was.twitch_channel,
+            Item::UiScale => now.ui_scale != was.ui_scale,
+            Item::Language => now.language != was.language,
+            Item::OneSwitch => now.one_switch != was.one_switch,
+            Item::SyncUrl => now.sync_url != was.sync_url,
+            Item::SyncToken => now.sync_token != was.sync_token,
+            _ => false,
+        }
+    }
+
+    // Left/Right on the selected item; `dir` is -1 or +1
+    fn adjust(&mut self, item: Item, dir: i32) {
+        let st = &mut self.settings;
+        match item {
+            Item::Volume => st.sound_volume = ((st.sound_volume + 0.05 * dir as f32).clamp(0.0, 1.0) * 20.0).round() / 20.0,
+            Item::Music => st.music_volume = ((st.music_volume + 0.05 * dir as f32).clamp(0.0, 1.0) * 20.0).round() / 20.0,
+            Item::UiScale => st.ui_scale = ((st.ui_scale + 0.1 * dir as f32).clamp(UI_SCALE_MIN, UI_SCALE_MAX) * 10.0).round() / 10.0,
+            Item::Challenge => st.challenge = ((st.challenge + 0.1 * dir as f32).clamp(0.0, 1.0) * 10.0).round() / 10.0,
+            Item::RainDensity => st.rain_density = ((st.rain_density + 0.05 * dir as f32).clamp(0.0, 1.0) * 20.0).round() / 20.0,
+            Item::Theme => st.theme = cycle(&Theme::ALL, st.theme, dir),
+            Item::Filter => st.board_filter = cycle(&BoardFilter::ALL, st.board_filter, dir),
+            Item::Skin => {
+                let ids: Vec<&str> = self.skins.iter().map(|s| s.id).collect();
+                st.skin = cycle(&ids, st.skin.as_str(), dir).to_string();
+            }
+            Item::Language => st.language = cycle(&Language::ALL, st.language, dir),
+            Item::OneSwitch => st.one_switch = !st.one_switch,
+            Item::ImportMode => self.import_mode = cycle(&[ImportMode::Merge, ImportMode::Replace], self.import_mode, dir),
+            Item::Rain => st.rain = !st.rain,
+            Item::PowerSaving => st.power_saving = !st.power_saving,
+            Item::BodyGradient => st.body_gradient = !st.body_gradient,
+            Item::Lighting => st.lighting = !st.lighting,
+            Item::AutoPause => st.auto_pause = !st.auto_pause,
+            Item::AutoPauseMute => st.auto_pause_mute = !st.auto_pause_mute,
+            Item::RecordGif => st.record_gif = !st.record_gif,
+            Item::SpeedrunTimer => st.speedrun_timer = !st.speedrun_timer,
+            Item::PathHint => st.path_hint = !st.path_hint,
+            Item::Missions => st.missions = !st.missions,
+            Item::Modifier => {
+                let options: Vec<Option<Modifier>> = std::iter::once(None).chain(Modifier::ALL.map(Some)).collect();
+                st.modifier = cycle(&options, st.modifier, dir);
+            }
+            Item::Premium => st.extras.premium = !st.extras.premium,
+            Item::PowerUps => st.extras.powerups = !st.extras.powerups,
+            Item::Phase => st.extras.phase = !st.extras.phase,
+            Item::Glitch => st.extras.glitch = !st.extras.glitch,
+            Item::MinWindow => st.min_window = cycle(&MinWindow::ALL, st.min_window, dir),
+            Item::WindowFit => st.window_fit = cycle(&WindowFit::ALL, st.window_fit, dir),
+            Item::Display => {
+                st.display_mode = cycle(&DisplayMode::ALL, st.display_mode, dir);
+                st.display_mode.apply();
+            }
+            Item::Backdrop => st.backdrop = cycle(&Backdrop::ALL, st.backdrop, dir),
+            Item::HudCorner => st.hud_corner = cycle(&HudCorner::ALL, st.hud_corner, dir),
+            _ => {}
+        }
+    }
+
+    // A slider dragged to `fraction` (0-1), snapped to the steps Left/Right take
+    fn set_fraction(&mut self, item: Item, fraction: f32) {
+        let st = &mut self.settings;
+        let snap = |f: f32, steps: f32| (f * steps).round() / steps;
+        match item {
+            Item::Volume => st.sound_volume = snap(fraction, 20.0),
+            Item::Music => st.music_volume = snap(fraction, 20.0),
+            Item::UiScale => st.ui_scale = snap(UI_SCALE_MIN + fraction * (UI_SCALE_MAX - UI_SCALE_MIN), 10.0),
+            Item::Challenge => st.challenge = snap(fraction, 10.0),
+            Item::RainDensity => st.rain_density = snap(fraction, 20.0),
+            _ => {}
+        }
+    }
+
+    // Enter on the selected item
+    fn activate(&mut self, item: Item) {
+        match self.widget(item) {
+            Widget::Toggle(_) | Widget::Choice(_) => self.adjust(item, 1),
+            Widget::Slider { .. } => {}
+            Widget::Text { .. } => {
+                clear_input_queue();
+                match item {
+                    Item::TwitchChannel => self.editing_channel = true,
+                    Item::SyncUrl => self.editing_sync_url = true,
+                    Item::SyncToken => self.editing_sync_token = true,
+                    _ => self.editing_path = true,
+                }
+            }
+            Widget::Key { .. } => {
+                get_last_key_pressed(); // drop the Enter press itself
+                self.rebinding = true;
+            }
+            Widget::Button => match item {
+                Item::Export => self.export(),
+                Item::Import => self.import(),
+                Item::Copy => self.copy(),
+                Item::SyncNow => self.sync_now(),
+                _ => self.paste(),
+            },
+        }
+    }
+
+    fn draw_widget(&self, widget: &Widget, x: f32, y: f32, ui: f32, color: Color, accent: Color) {
+        let size = 20.0 * ui;
+        match widget {
+            Widget::Slider { fraction, text } => {
+                let track = slider_track(x, y, ui);
+                draw_slider(track, *fraction, color, accent);
+                draw_text(text, track.x + track.w + 10.0 * ui, y, size, color);
+            }
+            Widget::Toggle(on) => {
+                let text = format!("[{}] {}", if *on { "x" } else { " " }, if *on { tr("On") } else { tr("Off") });
+                draw_text(&text, x, y, size, color);
+            }
+            Widget::Choice(value) => { draw_text(&format!("< {} >", value), x, y, size, color); }
+            Widget::Text { value, editing } => {
+                let cursor = if *editing && (get_time() * 2.0) as i64 % 2 == 0 { "_" } else { "" };
+                let value = if value.is_empty() && !editing { tr("(none)") } else { value };
+                draw_text(&format!("{}{}", value, cursor), x, y, size, if *editing { accent } else { color });
+            }
+            Widget::Key { keys, waiting } => {
+                let text = if *waiting { tr("press a key...") } else { keys };
+                draw_text(text, x, y, size, if *waiting { accent } else { color });
+            }
+            Widget::Button => { draw_text(tr("[Enter]"), x, y, size, color); }
+        }
+    }
+}
+
+// Where a slider widget's track goes for a row whose text sits on `y`
+fn slider_track(x: f32, y: f32, ui: f32) -> Rect {
+    Rect::new(x, y - 14.0 * ui, SLIDER_WIDTH * ui, 14.0 * ui)
+}
+
+// The entry `dir` steps away from `current`, wrapping around
+fn cycle<T: Copy + PartialEq>(all: &[T], current: T, dir: i32) -> T {
+    let len = all.len() as i32;
+    let idx = all.iter().position(|v| *v == current).unwrap_or(0) as i32;
+    all[((idx + dir + len) % len) as usize]
+}
+
+fn widget_hint(widget: &Widget) -> &'static str {
+    match widget {
+        Widget::Slider { .. } => "Left/Right or drag: Change",
+        Widget::Choice(_) => "Left/Right: Change",
+        Widget::Toggle(_) => "Left/Right/Enter: Toggle",
+        Widget::Text { editing: true, .. } => "Type, then Enter: Done",
+        Widget::Text { .. } => "Enter: Edit",
+        Widget::Key { waiting: true, .. } => "Press the new alternate key  Esc: Cancel",
+        Widget::Key { .. } => "Enter: Rebind the alternate key",
+        Widget::Button => "Enter: Run",
+    }
+}
+
+impl GameScreen for SettingsState {
+    fn update(&mut self, _app: &mut App, _frame: &Frame) {
+        if let Some(result) = self.cloud.poll() {
+            let synced = result.is_ok();
+            self.apply_import(result);
+            if synced { self.status = Some(tr("Save synced with the cloud").to_string()); }
+        }
+        if self.editing_path {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() { self.transfer_path.push(c); }
+            }
+            if is_key_pressed(KeyCode::Backspace) { self.transfer_path.pop(); }
+            if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) {
+                self.editing_path = false;
+            }
+        } else if self.editing_channel {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() { self.settings.twitch_channel.push(c); }
+            }
+            if is_key_pressed(KeyCode::Backspace) { self.settings.twitch_channel.pop(); }
+            if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) {
+                self.editing_channel = false;
+                if !valid_channel(&self.settings.twitch_channel) {
+                    self.status = Some(tr("Channel names use only letters, digits and _ (max 25)").to_string());
+                    self.settings.twitch_channel.clear();
+                }
+            }
+        } else if self.editing_sync_url {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() && !c.is_whitespace() { self.settings.sync_url.push(c); }
+            }
+            if is_key_pressed(KeyCode::Backspace) { self.settings.sync_url.pop(); }
+            if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) {
+                self.editing_sync_url = false;
+                if !valid_sync_url(&self.settings.sync_url) {
+                    self.status = Some(tr("Sync URLs start with http:// or https://").to_string());
+                    self.settings.sync_url.clear();
+                }
+            }
+        } else if self.editing_sync_token {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() { self.settings.sync_token.push(c); }
+            }
+            if is_key_pressed(KeyCode::Backspace) { self.settings.sync_token.pop(); }
+            if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) {
+                self.editing_sync_token = false;
+            }
+        } else if self.rebinding {
+            if is_key_pressed(KeyCode::Escape) {
+                self.rebinding = false;
+            } else if let Some(key) = get_last_key_pressed() {
+                match (key_name(key), self.current()) {
+                    (Some("Q") | None, _) => {
+                        self.status = Some(tr("That key can't be bound").to_string());
+                    }
+                    (Some(name), Some(Item::Binding(i))) => {
+                        // Replace the alternate key, keep the primary (arrow) binding
+                        let keys = self.settings.key_bindings.action_mut(i);
+                        keys.truncate(1);
+                        keys.push(name.to_string());
+                        self.status = None;
+                    }
+                    _ => {}
+                }
+                self.rebinding = false;
+            }
+        } else {
+            self.drag_sliders();
+            let len = self.items().len();
+            if is_key_pressed(KeyCode::Tab) {
+                let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+                self.turn_page(if shift { -1 } else { 1 });
+            }
+            // Up from the first item reaches the page bar, Down from it comes back
+            if is_key_pressed(KeyCode::Up) {
+                self.selected = match self.selected {
+                    Some(0) => None,
+                    Some(i) => Some(i - 1),
+                    None => Some(len - 1),
+                };
+            }
+            if is_key_pressed(KeyCode::Down) {
+                self.selected = match self.selected {
+                    Some(i) if i + 1 < len => Some(i + 1),
+                    Some(_) => None,
+                    None => Some(0),
+                };
+            }
+            for (keys, dir) in [([KeyCode::Left, KeyCode::Minus], -1), ([KeyCode::Right, KeyCode::Equal], 1)] {
+                if keys.iter().any(|k| is_key_pressed(*k)) {
+                    match self.current() {
+                        Some(item) => self.adjust(item, dir),
+                        None => self.turn_page(dir),
+                    }
+                }
+            }
+            if is_key_pressed(KeyCode::Enter) {
+                match self.current() {
+                    Some(item) => self.activate(item),
+                    None => self.selected = Some(0),
+                }
+            }
+            if is_key_pressed(KeyCode::R) { self.revert(); }
+            if is_key_pressed(KeyCode::H) || is_key_pressed(KeyCode::F1) {
+                // Shows the bindings as edited here, even before they're saved
+                self.next = Transition::Push(Box::new(HelpState::new(&self.settings.key_bindings)));
+            }
+            if is_key_pressed(KeyCode::M) {
+                self.settings.sound_volume = if self.settings.sound_volume > 0.0 { 0.0 } else { 1.0 };
+            }
+            // Shortcuts for the Data page that work from any page
+            if is_key_pressed(KeyCode::F) {
+                clear_input_queue();
+                self.editing_path = true;
+            }
+            if is_key_pressed(KeyCode::E) { self.export(); }
+            if is_key_pressed(KeyCode::C) { self.copy(); }
+            if is_key_pressed(KeyCode::I) { self.import(); }
+            if is_key_pressed(KeyCode::V) { self.paste(); }
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let (sw, sh) = (screen_width(), screen_height());
+
+        let mut y = sh * 0.15;
+        draw_centered(tr("SETTINGS"), y, 36.0 * ui, palette.head);
+        y += 44.0 * ui;
+
+        // Page bar: the current page is highlighted, and bracketed while the bar has focus
+        let size = 20.0 * ui;
+        let gap = 18.0 * ui;
+        let labels: Vec<String> = Page::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, p)| if i == self.page && self.selected.is_none() { format!("< {} >", tr(p.label())) } else { tr(p.label()).to_string() })
+            .collect();
+        let total: f32 = labels.iter().map(|l| measure_text(l, size).width).sum::<f32>() + gap * (labels.len() - 1) as f32;
+        let mut x = (sw - total) * 0.5;
+        for (i, label) in labels.iter().enumerate() {
+            let color = match (i == self.page, self.selected.is_none()) {
+                (true, true) => WHITE,
+                (true, false) => palette.head,
+                _ => GRAY,
+            };
+            x += draw_text(label, x, y, size, color).width + gap;
+        }
+        y += 12.0 * ui;
+        draw_line(sw * 0.5 - total * 0.5, y, sw * 0.5 + total * 0.5, y, 1.0, palette.wall);
+        y += 36.0 * ui;
+
+        // Items: labels end just left of the centre, widgets start just right of it
+        let mid = sw * 0.5;
+        self.sliders.clear();
+        for (i, item) in self.items().iter().enumerate() {
+            let selected = self.selected == Some(i);
+            let color = if selected { WHITE } else { GRAY };
+            let widget = self.widget(*item);
+            let label = if self.changed(*item) { format!("* {}", tr(Self::label(*item))) } else { tr(Self::label(*item)).to_string() };
+            if matches!(widget, Widget::Button) {
+                draw_centered(&label, y, size, color);
+            } else {
+                let w = measure_text(&label, size).width;
+                draw_text(&label, mid - 16.0 * ui - w, y, size, color);
+                self.draw_widget(&widget, mid + 16.0 * ui, y, ui, color, palette.head);
+                if matches!(widget, Widget::Slider { .. }) { self.sliders.push((i, slider_track(mid + 16.0 * ui, y, ui))); }
+            }
+            y += ROW_HEIGHT * ui;
+        }
+
+        // What the skins still locked take
+        if self.current() == Some(Item::Skin) {
+            y += 8.0 * ui;
+            for skin in skins::ALL.iter().filter(|s| !self.skins.iter().any(|u| u.id == s.id)) {
+                draw_centered(&format!("{}: {}", tr(skin.name), skin.requirement()), y, 16.0 * ui, GRAY);
+                y += 20.0 * ui;
+            }
+        }
+
+        let hints_y = sh - 90.0 * ui;
+        if let Some(status) = &self.status {
+            draw_centered(status, hints_y - 36.0 * ui, 18.0 * ui, palette.body);
+        }
+        let context = match self.current() {
+            Some(item) => tr(widget_hint(&self.widget(item))),
+            None => tr("Left/Right: Page  Down: Items"),
+        };
+        draw_centered(context, hints_y, 18.0 * ui, palette.body);
+        draw_centered(tr("Tab: Next page  Up/Down: Select  R: Revert changes  M: Mute"), hints_y + 24.0 * ui, 18.0 * ui, GRAY);
+        draw_centered(tr("H/F1: Help  Esc: Keep changes and go back"), hints_y + 48.0 * ui, 18.0 * ui, GRAY);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn is_typing(&self) -> bool {
+        self.editing_path || self.editing_channel || self.editing_sync_url || self.editing_sync_token || self.rebinding
+    }
+
+    fn preview_settings(&self) -> Option<&Settings> {
+        Some(&self.settings)
+    }
+
+    // Leaving keeps the changes
+    fn back(&mut self, app: &mut App) -> Transition {
+        app.settings = self.settings.clone();
+        let mut s = load_save();
+        s.settings = app.settings.clone();
+        write_save(&s);
+        Transition::Pop
+    }
+}
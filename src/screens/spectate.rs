@@ -0,0 +1,89 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, LobbyState, Transition};
+use crate::broadcast::{Spectator, TickState, Update, Xy};
+use crate::i18n::{tr, trf};
+use crate::map::{Cell, GridConfig};
+use crate::render::{board_layout, draw_board_canvas, draw_glyph_at_cell_scaled, matrix_char_for_cell};
+use crate::ui::{draw_centered, draw_text};
+
+fn cell((x, y): Xy) -> Cell { Cell { x, y } }
+
+// Watching another game's --broadcast (--spectate HOST:PORT). Only draws what arrives, so it
+// needs no rules of its own; R reconnects after the connection drops, Esc goes to the lobby.
+pub struct SpectateState {
+    spectator: Spectator,
+    board: Option<(GridConfig, Vec<Cell>)>,
+    tick: Option<TickState>,
+    next: Transition,
+}
+
+impl SpectateState {
+    pub fn new(address: &str) -> Self {
+        Self { spectator: Spectator::connect(address), board: None, tick: None, next: Transition::None }
+    }
+}
+
+impl GameScreen for SpectateState {
+    fn update(&mut self, _app: &mut App, _frame: &Frame) {
+        for update in self.spectator.poll() {
+            match update {
+                Update::Board { width, height, walls, .. } => {
+                    self.board = Some((GridConfig { width, height }, walls.into_iter().map(cell).collect()));
+                }
+                Update::Tick(tick) => self.tick = Some(tick),
+            }
+        }
+        if is_key_pressed(KeyCode::R) && !self.spectator.connected {
+            self.spectator = Spectator::connect(&self.spectator.address);
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let (palette, ui) = (frame.palette, frame.ui);
+        let (Some((grid, walls)), Some(tick)) = (&self.board, &self.tick) else {
+            draw_centered(&self.spectator.status, screen_height() * 0.45, 24.0 * ui, palette.head);
+            draw_centered(tr("R: Reconnect  Esc: Lobby"), screen_height() * 0.45 + 36.0 * ui, 18.0 * ui, palette.wall);
+            return;
+        };
+        let layout = board_layout(*grid, frame.look);
+        draw_board_canvas(&layout, frame.look.filter, || {
+            let t = layout.tile;
+            for c in walls {
+                draw_glyph_at_cell_scaled(matrix_char_for_cell(*c), *c, palette.wall, t, t, 0.0, 0.0);
+            }
+            let len = tick.snake.len();
+            for (i, c) in tick.snake.iter().map(|c| cell(*c)).enumerate() {
+                let ch = frame.skin.glyph(i, matrix_char_for_cell(c));
+                draw_glyph_at_cell_scaled(ch, c, frame.skin.color(i, len, &palette), t, t, 0.0, 0.0);
+            }
+            for p in &tick.patrollers {
+                draw_glyph_at_cell_scaled('X', cell(*p), palette.food, t, t, 0.0, 0.0);
+            }
+            if let Some(at) = tick.premium { draw_glyph_at_cell_scaled('$', cell(at), palette.head, t, t, 0.0, 0.0); }
+            if let Some((kind, at)) = tick.powerup { draw_glyph_at_cell_scaled(kind.glyph(), cell(at), palette.head, t, t, 0.0, 0.0); }
+            if let Some(food) = tick.food { draw_glyph_at_cell_scaled(matrix_char_for_cell(cell(food)), cell(food), palette.food, t, t, 0.0, 0.0); }
+        });
+
+        draw_text(&trf!("SPECTATING  Score: {}", tick.score), 8.0, 16.0, 20.0, palette.body);
+        draw_text(&self.spectator.status, 8.0, 36.0, 16.0, palette.wall);
+        let status = if tick.won {
+            tr("Board cleared")
+        } else if !tick.alive {
+            tr("Game over - waiting for the next run")
+        } else if !self.spectator.connected {
+            tr("R: Reconnect  Esc: Lobby")
+        } else {
+            return;
+        };
+        draw_centered(status, screen_height() * 0.5, 24.0 * ui, WHITE);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn back(&mut self, _app: &mut App) -> Transition { Transition::Root(Box::new(LobbyState::new())) }
+
+    fn is_live(&self) -> bool { self.spectator.connected }
+}
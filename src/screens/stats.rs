@@ -0,0 +1,192 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, PlayingState, Transition};
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::map::Cell;
+use crate::render::matrix_char_for_cell;
+use crate::runlog::{self, RunRecord};
+use crate::save::{clear_run, load_save};
+use crate::screenshot::date_string;
+use crate::stats::{format_duration, Stats};
+use crate::ui::{draw_centered, draw_text};
+
+const BAR_GLYPHS: usize = 30; // length of the longest bar
+const LINE_HEIGHT: f32 = 24.0;
+const RECENT_RUNS: usize = 15;
+const ROW_HEIGHT: f32 = 24.0;
+// Fits the default 800 px window at UI scale 1, like the High Scores table
+const TABLE_WIDTH: f32 = 780.0;
+// Left edge of each Recent runs column, relative to the table
+const COLUMNS: [(&str, f32); 7] = [
+    ("Date", 0.0),
+    ("Score", 96.0),
+    ("Mode", 150.0),
+    ("Map", 310.0),
+    ("Seed", 400.0),
+    ("Ended by", 580.0),
+    ("Time", 690.0),
+];
+
+// Lifetime totals from the save, with deaths by cause as glyph bars; Tab switches to the
+// run log, where any recent run's setup can be played again
+pub struct StatsState {
+    stats: Stats,
+    runs: Vec<RunRecord>,
+    recent: bool, // showing the run log instead of the totals
+    selected: usize,
+    next: Transition,
+}
+
+impl StatsState {
+    pub fn new() -> Self {
+        Self { stats: load_save().stats, runs: runlog::recent(RECENT_RUNS), recent: false, selected: 0, next: Transition::None }
+    }
+
+    fn play_again(&mut self, app: &mut App) {
+        let Some(run) = self.runs.get(self.selected) else { return; };
+        logging::info(format!("Run started from the run log: seed {} style {}", run.seed, run.map_style));
+        let game = run.start(&app.graveyard);
+        clear_run();
+        app.last_autosave = get_time() as f32;
+        self.next = Transition::Root(Box::new(PlayingState::new(game)));
+    }
+
+    fn update_recent(&mut self, app: &mut App, frame: &Frame) {
+        let len = self.runs.len();
+        if len > 0 && is_key_pressed(KeyCode::Up) {
+            self.selected = if self.selected == 0 { len - 1 } else { self.selected - 1 };
+        }
+        if len > 0 && is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % len;
+        }
+        // Hovering a row selects it, clicking plays it
+        let (left, top) = table_origin(frame.ui);
+        let (mx, my) = mouse_position();
+        let row_h = ROW_HEIGHT * frame.ui;
+        let hovered = (mx >= left && mx <= left + TABLE_WIDTH * frame.ui && my > top - row_h)
+            .then(|| ((my - (top - row_h)) / row_h) as usize)
+            .filter(|i| *i < len);
+        if let Some(i) = hovered && mouse_delta_position() != Vec2::ZERO { self.selected = i; }
+        let clicked = hovered.is_some() && is_mouse_button_pressed(MouseButton::Left);
+        if is_key_pressed(KeyCode::Enter) || clicked { self.play_again(app); }
+    }
+
+    fn draw_totals(&self, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let st = &self.stats;
+        let size = 20.0 * ui;
+        let line = LINE_HEIGHT * ui;
+
+        let mut y = screen_height() * 0.15;
+        draw_centered(tr("STATS"), y, 36.0 * ui, palette.head);
+        y += 48.0 * ui;
+
+        let totals = [
+            trf!("Games played: {}", st.games),
+            trf!("Food eaten: {}", st.food),
+            trf!("Play time: {}", format_duration(st.play_seconds)),
+            trf!("Longest snake: {}  Longest run: {} ticks", st.longest_snake, st.longest_run_ticks),
+            trf!("Day streak: {} (best {})  Boards cleared: {}", st.current_day_streak(), st.best_day_streak, st.boards_cleared),
+        ];
+        for text in &totals {
+            draw_centered(text, y, size, WHITE);
+            y += line;
+        }
+
+        y += 16.0 * ui;
+        draw_centered(tr("Deaths by cause"), y, size, palette.body);
+        y += line + 4.0 * ui;
+        let rows = st.deaths.rows();
+        let most = rows.iter().map(|(_, n)| *n).max().unwrap_or(0).max(1);
+        let left = screen_width() * 0.5 - 230.0 * ui;
+        for (i, (label, count)) in rows.iter().enumerate() {
+            draw_text(tr(label), left, y, size, LIGHTGRAY);
+            draw_text(&count.to_string(), left + 100.0 * ui, y, size, WHITE);
+            // Any death at all gets at least one glyph
+            let len = (*count as usize * BAR_GLYPHS).div_ceil(most as usize);
+            draw_bar(left + 160.0 * ui, y, len, i as i32, size, palette.body);
+            y += line;
+        }
+
+        y += 24.0 * ui;
+        if st.games == 0 { draw_centered(tr("Finish a run to start the counters"), y, 18.0 * ui, GRAY); }
+        draw_centered(tr("Tab: Recent runs  Esc: Back"), y + line, 18.0 * ui, GRAY);
+    }
+
+    fn draw_recent(&self, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let size = 16.0 * ui;
+        draw_centered(tr("RECENT RUNS"), screen_height() * 0.15, 36.0 * ui, palette.head);
+        let (left, mut y) = table_origin(ui);
+        for (title, x) in COLUMNS {
+            draw_text(tr(title), left + x * ui, y - 30.0 * ui, 16.0 * ui, palette.body);
+        }
+        if self.runs.is_empty() {
+            draw_centered(tr("No runs logged yet"), y, 20.0 * ui, GRAY);
+            y += ROW_HEIGHT * ui;
+        }
+        for (i, run) in self.runs.iter().enumerate() {
+            let color = if i == self.selected { WHITE } else { GRAY };
+            for (text, (_, x)) in cells(run).iter().zip(COLUMNS) {
+                draw_text(text, left + x * ui, y, size, color);
+            }
+            y += ROW_HEIGHT * ui;
+        }
+        y += 16.0 * ui;
+        draw_centered(tr("Up/Down: Select  Enter/Click: Play this setup again  Tab: Totals  Esc: Back"), y, 18.0 * ui, GRAY);
+    }
+}
+
+// Baseline of the first Recent runs row and the table's left edge
+fn table_origin(ui: f32) -> (f32, f32) {
+    ((screen_width() - TABLE_WIDTH * ui) * 0.5, screen_height() * 0.15 + 84.0 * ui)
+}
+
+fn cells(run: &RunRecord) -> [String; 7] {
+    let ended = match run.death {
+        Some(cause) => tr(cause.label()),
+        None => tr("Board cleared"),
+    };
+    let mode = match run.modifier {
+        Some(m) => format!("{}, {}", tr(run.mode.label()), tr(m.label())),
+        None => tr(run.mode.label()).to_string(),
+    };
+    [
+        date_string(run.date),
+        run.score.to_string(),
+        mode,
+        tr(&run.map_style).to_string(),
+        run.seed.to_string(),
+        ended.to_string(),
+        format_duration(run.seconds as f64),
+    ]
+}
+
+// A row of matrix glyphs `len` long; the glyphs depend on the row so bars don't look alike
+fn draw_bar(x: f32, y: f32, len: usize, row: i32, size: f32, color: Color) {
+    let step = size * 0.6;
+    for i in 0..len {
+        let ch = matrix_char_for_cell(Cell { x: i as i32, y: row });
+        // Fades toward the tip like a falling trail
+        let fade = 1.0 - 0.5 * i as f32 / BAR_GLYPHS as f32;
+        draw_text(&ch.to_string(), x + i as f32 * step, y, size, Color::new(color.r, color.g, color.b, fade));
+    }
+}
+
+impl GameScreen for StatsState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        if is_key_pressed(KeyCode::Tab) { self.recent = !self.recent; }
+        if self.recent { self.update_recent(app, frame); }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        if self.recent { self.draw_recent(frame); } else { self.draw_totals(frame); }
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+}
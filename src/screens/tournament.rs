@@ -0,0 +1,251 @@
+use macroquad::prelude::*;
+
+use super::{App, Frame, GameScreen, QuitGesture, Transition};
+use crate::game::SnakeGame;
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::map::GridConfig;
+use crate::mapgen;
+use crate::replay::save_replay;
+use crate::tournament::{Tournament, MAX_PLAYERS, MIN_PLAYERS};
+use crate::ui::{draw_centered, draw_text, measure_text, KeyBindings};
+
+// Lobby settings every turn is played with
+pub struct TournamentParams {
+    pub seed: u64,
+    pub map_style: String,
+    pub wall_density: f32,
+    pub move_interval: f32,
+    pub grid: GridConfig,
+}
+
+// Hot-seat tournament: names are typed in first, then each player in turn gets one run on
+// the same map, with the standings shown between turns and a podium at the end
+pub struct TournamentState {
+    params: TournamentParams,
+    names: Vec<String>,
+    input: String,
+    error: Option<String>,
+    tournament: Option<Tournament>, // None while names are being entered
+    game: Option<SnakeGame>, // the turn being played
+    waiting: bool, // the turn's snake holds still until its player presses a direction
+    next: Transition,
+}
+
+impl TournamentState {
+    pub fn new(params: TournamentParams) -> Self {
+        // The key that opened the screen shouldn't become the first name
+        clear_input_queue();
+        Self { params, names: Vec::new(), input: String::new(), error: None, tournament: None, game: None, waiting: false, next: Transition::None }
+    }
+
+    fn update_names(&mut self) {
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() { self.input.push(c); }
+        }
+        if is_key_pressed(KeyCode::Backspace) && self.input.pop().is_none() { self.names.pop(); }
+        if is_key_pressed(KeyCode::Escape) { self.next = Transition::Pop; }
+        if !is_key_pressed(KeyCode::Enter) { return; }
+
+        // Enter adds the typed name, or on an empty line starts with the names so far
+        let name = self.input.trim().to_string();
+        if name.is_empty() && self.names.len() >= MIN_PLAYERS {
+            logging::info(format!("Tournament started on seed {}: {}", self.params.seed, self.names.join(", ")));
+            self.tournament = Some(Tournament::new(std::mem::take(&mut self.names)));
+            return;
+        }
+        match Tournament::check_name(&self.names, &name) {
+            Ok(()) => {
+                self.names.push(name);
+                self.input.clear();
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn start_turn(&mut self) {
+        let p = &self.params;
+        let map = mapgen::generate(&p.map_style, p.grid, p.seed, p.wall_density);
+        self.game = Some(SnakeGame::new(map, p.move_interval));
+        self.waiting = true;
+    }
+
+    fn update_turn(&mut self, app: &mut App, frame: &Frame) {
+        let (Some(game), Some(tournament)) = (self.game.as_mut(), self.tournament.as_mut()) else { return; };
+        let keys = &app.settings.key_bindings;
+        if self.waiting {
+            let [up, down, left, right] = [&keys.up, &keys.down, &keys.left, &keys.right].map(|k| KeyBindings::pressed(k));
+            let switch = app.settings.one_switch && KeyBindings::switch_pressed();
+            if !(up || down || left || right || switch) { return; }
+            self.waiting = false;
+        }
+        if app.settings.one_switch { game.handle_switch(); } else { game.handle_input(keys); }
+        game.step(frame.dt);
+        for event in game.drain_events() {
+            app.sounds.on_event(&event, app.settings.sound_volume);
+            game.popups.on_event(&event, game.sim.head(), frame.now);
+            app.rain.on_event(&event, game.sim.map.grid, frame.look);
+        }
+        if game.sim.alive { return; }
+
+        let name = tournament.current().map(|e| e.name.clone()).unwrap_or_default();
+        logging::info(format!("Tournament: {} scored {} in {} ticks", name, game.sim.score, game.replay.ticks));
+        tournament.record(game.sim.score, game.replay.ticks);
+        save_replay(&game.replay);
+        if tournament.finished() && let Some(winner) = tournament.standings().first() {
+            logging::info(format!("Tournament won by {}", tournament.entrants[*winner].name));
+        }
+        self.game = None;
+    }
+
+    fn draw_names(&self, frame: &Frame) {
+        let ui = frame.ui;
+        let palette = frame.palette;
+        let mut y = screen_height() * 0.2;
+        draw_centered(tr("TOURNAMENT"), y, 36.0 * ui, palette.head);
+        y += 36.0 * ui;
+        draw_centered(&trf!("Everyone plays seed {} once; the best score wins", self.params.seed), y, 18.0 * ui, LIGHTGRAY);
+        y += 40.0 * ui;
+        for (i, name) in self.names.iter().enumerate() {
+            draw_centered(&format!("{}. {}", i + 1, name), y, 22.0 * ui, palette.body);
+            y += 28.0 * ui;
+        }
+        if self.names.len() < MAX_PLAYERS {
+            let cursor = if (frame.now * 2.0) as i32 % 2 == 0 { "_" } else { " " };
+            draw_centered(&format!("{}. {}{}", self.names.len() + 1, self.input, cursor), y, 22.0 * ui, WHITE);
+        }
+        y += 40.0 * ui;
+        if let Some(e) = &self.error {
+            draw_centered(tr(e), y, 18.0 * ui, RED);
+        }
+        y += 28.0 * ui;
+        let hint = if self.names.len() >= MIN_PLAYERS {
+            tr("Enter: Add player, or start on an empty line  Backspace: Remove  Esc: Back")
+        } else {
+            tr("Type a name and press Enter (2-8 players)  Esc: Back")
+        };
+        draw_centered(hint, y, 18.0 * ui, GRAY);
+    }
+
+    // Places, names and scores; turns still to come show a dash
+    fn draw_table(&self, tournament: &Tournament, mut y: f32, frame: &Frame) {
+        let ui = frame.ui;
+        let size = 20.0 * ui;
+        let x = screen_width() * 0.5 - 160.0 * ui;
+        let next = tournament.current().map(|e| e.name.as_str());
+        for (i, place) in tournament.standings().into_iter().zip(tournament.places()) {
+            let e = &tournament.entrants[i];
+            let (score, color) = match e.result {
+                Some((score, _)) => (score.to_string(), WHITE),
+                None if Some(e.name.as_str()) == next => ("-".to_string(), frame.palette.head),
+                None => ("-".to_string(), GRAY),
+            };
+            let place = if e.result.is_some() { format!("{}.", place) } else { String::new() };
+            draw_text(&place, x, y, size, color);
+            draw_text(&e.name, x + 40.0 * ui, y, size, color);
+            draw_text(&score, x + 320.0 * ui - measure_text(&score, size).width, y, size, color);
+            y += 26.0 * ui;
+        }
+    }
+
+    fn draw_standings(&self, tournament: &Tournament, frame: &Frame) {
+        let ui = frame.ui;
+        let mut y = screen_height() * 0.15;
+        draw_centered(tr("STANDINGS"), y, 32.0 * ui, frame.palette.head);
+        y += 48.0 * ui;
+        self.draw_table(tournament, y, frame);
+        y += 26.0 * ui * tournament.entrants.len() as f32 + 24.0 * ui;
+        if let Some(e) = tournament.current() {
+            draw_centered(&trf!("Next up: {} - pass the keyboard", e.name), y, 24.0 * ui, WHITE);
+            y += 32.0 * ui;
+        }
+        draw_centered(tr("Enter: Play  Esc: Abandon tournament"), y, 18.0 * ui, GRAY);
+    }
+
+    // The top three on steps, tallest in the middle, with the full table below
+    fn draw_podium(&self, tournament: &Tournament, frame: &Frame) {
+        let ui = frame.ui;
+        let palette = frame.palette;
+        let sw = screen_width();
+        let base = screen_height() * 0.45;
+        draw_centered(tr("PODIUM"), screen_height() * 0.1, 36.0 * ui, palette.head);
+        let order = tournament.standings();
+        let places = tournament.places();
+        let step_w = 150.0 * ui;
+        // Second, first and third from left to right
+        for (slot, k) in [1, 0, 2].into_iter().enumerate() {
+            let Some(i) = order.get(k) else { continue; };
+            let e = &tournament.entrants[*i];
+            let height = [120.0, 90.0, 60.0][places[k].min(3) - 1] * ui;
+            let x = sw * 0.5 + (slot as f32 - 1.0) * (step_w + 8.0 * ui) - step_w * 0.5;
+            let color = [palette.food, palette.head, palette.body][places[k].min(3) - 1];
+            draw_rectangle(x, base - height, step_w, height, Color::new(color.r, color.g, color.b, 0.35));
+            draw_rectangle_lines(x, base - height, step_w, height, 2.0, color);
+            let place = places[k].to_string();
+            draw_text(&place, x + (step_w - measure_text(&place, 32.0 * ui).width) * 0.5, base - height * 0.5 + 12.0 * ui, 32.0 * ui, WHITE);
+            let score = e.result.map_or(0, |(score, _)| score).to_string();
+            draw_text(&e.name, x + (step_w - measure_text(&e.name, 20.0 * ui).width) * 0.5, base - height - 28.0 * ui, 20.0 * ui, color);
+            draw_text(&score, x + (step_w - measure_text(&score, 18.0 * ui).width) * 0.5, base - height - 8.0 * ui, 18.0 * ui, LIGHTGRAY);
+        }
+        self.draw_table(tournament, base + 40.0 * ui, frame);
+        draw_centered(tr("Enter: Back to lobby"), screen_height() - 24.0 * ui, 18.0 * ui, GRAY);
+    }
+}
+
+impl GameScreen for TournamentState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        let Some(tournament) = &self.tournament else {
+            self.update_names();
+            return;
+        };
+        if self.game.is_some() {
+            self.update_turn(app, frame);
+        } else if tournament.finished() {
+            if is_key_pressed(KeyCode::Enter) { self.next = Transition::Pop; }
+        } else if is_key_pressed(KeyCode::Enter) {
+            self.start_turn();
+        }
+    }
+
+    fn draw(&mut self, app: &App, frame: &Frame) {
+        let Some(tournament) = &self.tournament else {
+            self.draw_names(frame);
+            return;
+        };
+        if let Some(game) = self.game.as_mut() {
+            game.draw(&frame.palette, frame.skin, frame.look);
+            if app.settings.one_switch { game.draw_next_direction(&frame.palette, frame.look); }
+            let name = tournament.current().map_or("", |e| e.name.as_str());
+            draw_centered(&trf!("{}'s turn", name), 24.0 * frame.ui, 22.0 * frame.ui, frame.palette.head);
+            if self.waiting {
+                draw_centered(tr("Press a direction to start"), screen_height() * 0.5, 28.0 * frame.ui, WHITE);
+            }
+        } else if tournament.finished() {
+            self.draw_podium(tournament, frame);
+        } else {
+            self.draw_standings(tournament, frame);
+        }
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn is_typing(&self) -> bool { self.tournament.is_none() }
+
+    // A turn is played out; between turns Esc abandons the tournament
+    fn back(&mut self, _app: &mut App) -> Transition {
+        if self.game.is_some() { return Transition::None; }
+        if self.tournament.as_ref().is_some_and(|t| !t.finished()) { logging::info("Tournament abandoned"); }
+        Transition::Pop
+    }
+
+    fn quit_gesture(&self) -> QuitGesture {
+        if self.game.is_some() { QuitGesture::Hold } else { QuitGesture::Confirm }
+    }
+
+    fn is_live(&self) -> bool { self.game.is_some() }
+
+    fn spectated(&self) -> Option<&SnakeGame> { self.game.as_ref() }
+}
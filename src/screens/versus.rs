@@ -0,0 +1,391 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::net::TcpStream;
+
+use super::{App, Frame, GameScreen, QuitGesture, Transition};
+use crate::game::MAX_FRAME_DT;
+use crate::i18n::{tr, trf};
+use crate::logging;
+use crate::map::Direction;
+use crate::mapgen;
+use crate::net::Pending;
+use crate::netplay::{join, Host, MatchParams, Msg, Peer, DEFAULT_PORT, INPUT_DELAY, NETPLAY_VERSION, PEER_TIMEOUT};
+use crate::render::{draw_conveyors, draw_gates, draw_stairs, draw_switches, draw_glyph_at_cell_scaled, matrix_char_for_cell, Palette};
+use crate::sim::SnakeSim;
+use crate::ui::{draw_centered, draw_text, measure_text, KeyBindings};
+
+// Host or join a two-player race on the same map
+pub struct VersusSetupState {
+    params: MatchParams,
+    hosting: bool,
+    address: String,
+    editing: bool,
+    host: Option<Host>,
+    joining: Option<Pending<TcpStream>>,
+    peer: Option<Peer>, // guest side, waiting for the host's Hello
+    status: Option<String>,
+    next: Transition,
+}
+
+impl VersusSetupState {
+    pub fn new(params: MatchParams) -> Self {
+        Self {
+            params,
+            hosting: true,
+            address: format!("127.0.0.1:{}", DEFAULT_PORT),
+            editing: false,
+            host: None,
+            joining: None,
+            peer: None,
+            status: None,
+            next: Transition::None,
+        }
+    }
+
+    fn busy(&self) -> bool {
+        self.host.is_some() || self.joining.is_some() || self.peer.is_some()
+    }
+
+    fn cancel(&mut self, status: Option<String>) {
+        self.host = None;
+        self.joining = None;
+        self.peer = None;
+        self.status = status;
+    }
+}
+
+impl GameScreen for VersusSetupState {
+    fn update(&mut self, _app: &mut App, _frame: &Frame) {
+        if self.editing {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() { self.address.push(c); }
+            }
+            if is_key_pressed(KeyCode::Backspace) { self.address.pop(); }
+            if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) { self.editing = false; }
+            return;
+        }
+
+        if let Some(result) = self.host.as_ref().and_then(|h| h.poll()) {
+            match result.and_then(Peer::new) {
+                Ok(mut peer) => match peer.send(&Msg::Hello { version: NETPLAY_VERSION, params: self.params.clone() }) {
+                    Ok(()) => {
+                        logging::info("Versus: guest connected");
+                        self.next = Transition::Replace(Box::new(VersusState::new(peer, self.params.clone(), true)));
+                    }
+                    Err(e) => self.cancel(Some(e)),
+                },
+                Err(e) => self.cancel(Some(e)),
+            }
+        }
+        if let Some(result) = self.joining.as_ref().and_then(|p| p.poll()) {
+            self.joining = None;
+            match result.and_then(Peer::new) {
+                Ok(peer) => {
+                    self.peer = Some(peer);
+                    self.status = Some(tr("Connected - waiting for the host...").to_string());
+                }
+                Err(e) => self.cancel(Some(e)),
+            }
+        }
+        if let Some(peer) = self.peer.as_mut() {
+            match peer.recv() {
+                Ok(msgs) => {
+                    for msg in msgs {
+                        if let Msg::Hello { version, params } = msg {
+                            if version != NETPLAY_VERSION {
+                                self.cancel(Some(trf!("Host runs netplay version {}, this game uses {}", version, NETPLAY_VERSION)));
+                                return;
+                            }
+                            if !mapgen::is_registered(&params.map_style) {
+                                self.cancel(Some(trf!("Host uses map style '{}', which isn't installed here", params.map_style)));
+                                return;
+                            }
+                            if let Some(peer) = self.peer.take() {
+                                logging::info(format!("Versus: joined match on seed {}", params.seed));
+                                self.next = Transition::Replace(Box::new(VersusState::new(peer, params, false)));
+                            }
+                            return;
+                        }
+                    }
+                }
+                Err(e) => self.cancel(Some(e)),
+            }
+        }
+
+        if self.busy() { return; }
+        if is_key_pressed(KeyCode::Tab) || is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::Right) {
+            self.hosting = !self.hosting;
+        }
+        if is_key_pressed(KeyCode::F) && !self.hosting {
+            clear_input_queue();
+            self.editing = true;
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            if self.hosting {
+                match Host::listen(DEFAULT_PORT) {
+                    Ok(host) => {
+                        self.host = Some(host);
+                        self.status = Some(trf!("Waiting for an opponent on port {}...", DEFAULT_PORT));
+                    }
+                    Err(e) => self.status = Some(e),
+                }
+            } else {
+                self.joining = Some(join(&self.address));
+                self.status = Some(trf!("Connecting to {}...", self.address));
+            }
+        }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let sh = screen_height();
+
+        let mut y = sh * 0.25;
+        draw_centered(tr("VERSUS"), y, 36.0 * ui, palette.head);
+        y += 48.0 * ui;
+        draw_centered(&trf!("Mode: {}", if self.hosting { tr("Host") } else { tr("Join") }), y, 22.0 * ui, WHITE);
+        y += 28.0 * ui;
+        if self.hosting {
+            let p = &self.params;
+            let info = trf!("Seed {}  Map {}  Density {}%  Speed {}ms  Port {}", p.seed, tr(&p.map_style), format!("{:.0}", p.wall_density * 100.0), format!("{:.0}", p.move_interval * 1000.0), DEFAULT_PORT);
+            draw_centered(&info, y, 18.0 * ui, LIGHTGRAY);
+        } else {
+            let cursor = if self.editing && (get_time() * 2.0) as i64 % 2 == 0 { "_" } else { "" };
+            draw_centered(&trf!("Address: {}{}", self.address, cursor), y, 22.0 * ui, if self.editing { palette.head } else { WHITE });
+        }
+        y += 36.0 * ui;
+        draw_centered(tr("Tab: Host/Join  F: Edit address  Enter: Start  Esc: Back"), y, 18.0 * ui, GRAY);
+        if let Some(status) = &self.status {
+            draw_centered(status, y + 32.0 * ui, 18.0 * ui, palette.body);
+        }
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn is_typing(&self) -> bool {
+        self.editing
+    }
+
+    // Hosting or joining is called off first; a second Esc leaves
+    fn back(&mut self, _app: &mut App) -> Transition {
+        if !self.busy() { return Transition::Pop; }
+        self.cancel(None);
+        Transition::None
+    }
+}
+
+// Lockstep race: both ends run the host's and the guest's sims from the same inputs and
+// compare state hashes every tick. The host is player 0.
+pub struct VersusState {
+    peer: Peer,
+    me: usize,
+    params: MatchParams,
+    sims: [SnakeSim; 2],
+    tick: u32,
+    accumulator: f32,
+    local_inputs: HashMap<u32, Option<Direction>>,
+    remote_inputs: HashMap<u32, Option<Direction>>,
+    my_hashes: HashMap<u32, u64>,
+    their_hashes: HashMap<u32, u64>,
+    pending_dir: Option<Direction>,
+    waiting: bool,
+    silent: f32, // seconds since the peer last sent anything, counted while waiting on it
+    outcome: Option<String>,
+    next: Transition,
+}
+
+impl VersusState {
+    pub fn new(peer: Peer, params: MatchParams, is_host: bool) -> Self {
+        let map = mapgen::generate(&params.map_style, params.grid, params.seed, params.wall_density);
+        let mut local_inputs = HashMap::new();
+        let mut remote_inputs = HashMap::new();
+        for t in 0..INPUT_DELAY {
+            local_inputs.insert(t, None);
+            remote_inputs.insert(t, None);
+        }
+        Self {
+            peer,
+            me: if is_host { 0 } else { 1 },
+            params,
+            sims: [SnakeSim::new(map.clone()), SnakeSim::new(map)],
+            tick: 0,
+            accumulator: 0.0,
+            local_inputs,
+            remote_inputs,
+            my_hashes: HashMap::new(),
+            their_hashes: HashMap::new(),
+            pending_dir: None,
+            waiting: false,
+            silent: 0.0,
+            outcome: None,
+            next: Transition::None,
+        }
+    }
+
+    fn send(&mut self, msg: &Msg) {
+        if let Err(e) = self.peer.send(msg) { self.finish(trf!("Connection lost: {}", e)); }
+    }
+
+    fn finish(&mut self, outcome: String) {
+        if self.outcome.is_none() {
+            logging::info(format!("Versus ended at tick {}: {}", self.tick, outcome));
+            self.outcome = Some(outcome);
+        }
+    }
+
+    fn step(&mut self) {
+        // Schedule this frame's input INPUT_DELAY ticks ahead and tell the peer
+        let ahead = self.tick + INPUT_DELAY;
+        let dir = self.pending_dir.take();
+        self.local_inputs.insert(ahead, dir);
+        self.send(&Msg::Input { tick: ahead, dir });
+
+        let local = self.local_inputs.remove(&self.tick).flatten();
+        let remote = self.remote_inputs.remove(&self.tick).flatten();
+        let them = 1 - self.me;
+        if let Some(d) = local { self.sims[self.me].turn(d); }
+        if let Some(d) = remote { self.sims[them].turn(d); }
+        for sim in &mut self.sims { sim.tick(); }
+
+        let hash = self.sims[0].state_hash() ^ self.sims[1].state_hash().rotate_left(1);
+        self.my_hashes.insert(self.tick, hash);
+        self.send(&Msg::Hash { tick: self.tick, hash });
+        self.tick += 1;
+
+        let (mine, theirs) = (&self.sims[self.me], &self.sims[them]);
+        if !mine.alive || !theirs.alive {
+            let result = match (mine.alive, theirs.alive) {
+                (true, false) => tr("You win!"),
+                (false, true) => tr("You lose"),
+                _ if mine.score > theirs.score => tr("Both crashed - you win on score!"),
+                _ if mine.score < theirs.score => tr("Both crashed - you lose on score"),
+                _ => tr("Draw"),
+            };
+            self.finish(result.to_string());
+        }
+    }
+
+    fn check_hashes(&mut self) {
+        let ticks: Vec<u32> = self.their_hashes.keys().copied().filter(|t| self.my_hashes.contains_key(t)).collect();
+        for t in ticks {
+            let theirs = self.their_hashes.remove(&t);
+            let mine = self.my_hashes.remove(&t);
+            if theirs != mine {
+                logging::error(format!("Versus desync at tick {}", t));
+                self.finish(trf!("Desync detected at tick {} - match stopped", t));
+            }
+        }
+    }
+}
+
+fn draw_board(sim: &SnakeSim, palette: &Palette, x: f32, y: f32, w: f32, h: f32) {
+    let grid = sim.map.grid;
+    let tile = (w / grid.width as f32).min(h / grid.height as f32);
+    let off_x = x + (w - tile * grid.width as f32) * 0.5;
+    let off_y = y + (h - tile * grid.height as f32) * 0.5;
+    for c in &sim.map.walls {
+        draw_glyph_at_cell_scaled(matrix_char_for_cell(*c), *c, palette.wall, tile, tile, off_x, off_y);
+    }
+    draw_gates(&sim.map, palette, |_| 1.0, tile, tile, off_x, off_y);
+    draw_conveyors(&sim.map, palette, |_| 1.0, tile, tile, off_x, off_y);
+    draw_switches(&sim.map, |_| 1.0, tile, tile, off_x, off_y);
+    draw_stairs(&sim.map, palette, |_| 1.0, tile, tile, off_x, off_y);
+    for (i, c) in sim.snake.iter().enumerate() {
+        let color = if i == 0 { palette.head } else { palette.body };
+        draw_glyph_at_cell_scaled(matrix_char_for_cell(*c), *c, color, tile, tile, off_x, off_y);
+    }
+    if let Some(food) = sim.food {
+        draw_glyph_at_cell_scaled('*', food, palette.food, tile, tile, off_x, off_y);
+    }
+}
+
+impl GameScreen for VersusState {
+    fn update(&mut self, app: &mut App, frame: &Frame) {
+        let keys = &app.settings.key_bindings;
+        if KeyBindings::pressed(&keys.up) { self.pending_dir = Some(Direction::Up); }
+        if KeyBindings::pressed(&keys.down) { self.pending_dir = Some(Direction::Down); }
+        if KeyBindings::pressed(&keys.left) { self.pending_dir = Some(Direction::Left); }
+        if KeyBindings::pressed(&keys.right) { self.pending_dir = Some(Direction::Right); }
+
+        match self.peer.recv() {
+            Ok(msgs) => {
+                if !msgs.is_empty() { self.silent = 0.0; }
+                for msg in msgs {
+                    match msg {
+                        Msg::Input { tick, dir } => { self.remote_inputs.insert(tick, dir); }
+                        Msg::Hash { tick, hash } => { self.their_hashes.insert(tick, hash); }
+                        Msg::Bye => self.finish(tr("Opponent left").to_string()),
+                        Msg::Hello { .. } => {}
+                    }
+                }
+            }
+            Err(e) => self.finish(trf!("Connection lost: {}", e)),
+        }
+        self.check_hashes();
+
+        if self.outcome.is_none() {
+            self.accumulator += frame.dt.clamp(0.0, MAX_FRAME_DT);
+            while self.outcome.is_none() && self.accumulator >= self.params.move_interval {
+                // Lockstep: never run a tick before the peer's input for it has arrived
+                self.waiting = !self.remote_inputs.contains_key(&self.tick);
+                if self.waiting {
+                    self.accumulator = self.params.move_interval;
+                    self.silent += frame.dt;
+                    if self.silent >= PEER_TIMEOUT {
+                        self.finish(trf!("Connection lost: {}", trf!("no word from the opponent for {} seconds", PEER_TIMEOUT)));
+                    }
+                    break;
+                }
+                self.accumulator -= self.params.move_interval;
+                self.step();
+            }
+        }
+
+        if self.outcome.is_some() && is_key_pressed(KeyCode::Enter) { self.next = self.back(app); }
+    }
+
+    fn draw(&mut self, _app: &App, frame: &Frame) {
+        let palette = frame.palette;
+        let ui = frame.ui;
+        let sw = screen_width();
+        let sh = screen_height();
+        let top = 40.0 * ui;
+        let board_h = sh - top - 60.0 * ui;
+
+        for (i, sim) in self.sims.iter().enumerate() {
+            let x = sw * 0.5 * i as f32;
+            draw_board(sim, &palette, x + 8.0, top, sw * 0.5 - 16.0, board_h);
+            let who = if i == self.me { tr("You") } else { tr("Opponent") };
+            let label = format!("{}: {}{}", who, sim.score, if sim.alive { "" } else { tr("  (crashed)") });
+            let m = measure_text(&label, 22.0 * ui);
+            draw_text(&label, x + (sw * 0.5 - m.width) * 0.5, top - 10.0 * ui, 22.0 * ui, if i == self.me { palette.head } else { palette.body });
+        }
+
+        let status = match &self.outcome {
+            Some(outcome) => trf!("{}  -  Enter: Back", outcome),
+            None if self.waiting => tr("Waiting for opponent...").to_string(),
+            None => trf!("Tick {}  Esc: Leave", self.tick),
+        };
+        draw_centered(&status, sh - 24.0 * ui, 22.0 * ui, WHITE);
+    }
+
+    fn transition(&mut self) -> Transition {
+        std::mem::take(&mut self.next)
+    }
+
+    fn quit_gesture(&self) -> QuitGesture { QuitGesture::Hold }
+
+    fn back(&mut self, _app: &mut App) -> Transition {
+        let _ = self.peer.send(&Msg::Bye);
+        Transition::Pop
+    }
+
+    fn is_live(&self) -> bool { true }
+
+    fn on_quit(&mut self) {
+        let _ = self.peer.send(&Msg::Bye);
+    }
+}
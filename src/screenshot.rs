@@ -0,0 +1,86 @@
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use macroquad::prelude::*;
+
+use crate::save::unix_now;
+
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+// (year, month, day, seconds into the day) in UTC, without pulling in a date crate
+pub fn civil(secs: u64) -> (i64, i64, i64, u64) {
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, rem)
+}
+
+// "20261016_142233" in UTC
+pub fn timestamp() -> String {
+    let (year, month, day, rem) = civil(unix_now());
+    format!("{:04}{:02}{:02}_{:02}{:02}{:02}", year, month, day, rem / 3_600, rem % 3_600 / 60, rem % 60)
+}
+
+// "2026-10-16" in UTC
+pub fn date_string(secs: u64) -> String {
+    let (year, month, day, _) = civil(secs);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn encode_png(rgba: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png).write_image(rgba, width, height, ColorType::Rgba8).map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+// PNG of everything drawn so far this frame
+fn encode_frame() -> Result<Vec<u8>, String> {
+    let frame = get_screen_data();
+    let (w, h) = (frame.width as usize, frame.height as usize);
+    // The framebuffer is read bottom-up
+    let mut rgba = Vec::with_capacity(frame.bytes.len());
+    for row in frame.bytes.chunks_exact(w * 4).rev() {
+        rgba.extend_from_slice(row);
+    }
+    // Opaque, so the translucent rain doesn't turn into holes in image viewers
+    for px in rgba.chunks_exact_mut(4) { px[3] = 255; }
+    encode_png(&rgba, w as u32, h as u32)
+}
+
+// Writes `screenshots/<stem>.png` (numbered if taken) and returns its path
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_png(stem: &str, png: &[u8]) -> Result<String, String> {
+    std::fs::create_dir_all(SCREENSHOT_DIR).map_err(|e| format!("Could not create {}: {}", SCREENSHOT_DIR, e))?;
+    let mut path = format!("{}/{}.png", SCREENSHOT_DIR, stem);
+    let mut n = 2;
+    while std::path::Path::new(&path).exists() {
+        path = format!("{}/{}_{}.png", SCREENSHOT_DIR, stem, n);
+        n += 1;
+    }
+    std::fs::write(&path, png).map_err(|e| format!("Writing {} failed: {}", path, e))?;
+    Ok(path)
+}
+
+// The browser has no file system: hand the PNG to the page, which offers it as a download
+// (see web/screenshot.js)
+#[cfg(target_arch = "wasm32")]
+pub fn save_png(stem: &str, png: &[u8]) -> Result<String, String> {
+    unsafe extern "C" {
+        fn snake_download(name: *const u8, name_len: usize, data: *const u8, data_len: usize);
+    }
+    let name = format!("{}.png", stem);
+    unsafe { snake_download(name.as_ptr(), name.len(), png.as_ptr(), png.len()) };
+    Ok(name)
+}
+
+// Saves the current frame as a timestamped PNG and returns where it went; call after drawing
+pub fn capture() -> Result<String, String> {
+    save_png(&format!("snake_{}", timestamp()), &encode_frame()?)
+}
@@ -0,0 +1,163 @@
+// Cosmetic snake skins: which glyph each segment shows, how segments are colored and what
+// the tail leaves behind. The first one is always available; the rest unlock through
+// achievements or a best score, and the equipped one is kept in the save's settings.
+use macroquad::color::hsl_to_rgb;
+use macroquad::prelude::*;
+
+use crate::achievements;
+use crate::i18n::{tr, trf};
+use crate::render::Palette;
+use crate::save::Progress;
+
+pub const TRAIL_TICKS: u32 = 6; // how long a trail glyph lingers where the tail was
+const GLOW_TICKS: f32 = 2.0; // body gradient: segments this new glow toward white
+const TAIL_SHADE: f32 = 0.3; // body gradient: brightness left at the tip of the tail
+
+pub enum Glyphs {
+    Matrix, // a random glyph per segment, as the snake has always been drawn
+    Word(&'static str), // spelled out from the head and repeated down the body
+}
+
+pub enum Colors {
+    Theme, // the theme's head and body colors
+    Gradient(Color, Color), // from the head to the tip of the tail
+    Rainbow, // hues cycling along the body
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Trail {
+    None,
+    Fade, // cells the tail leaves fade out over TRAIL_TICKS
+}
+
+pub enum Unlock {
+    Free,
+    Achievement(&'static str), // achievement id
+    BestScore(u32),
+}
+
+pub struct Skin {
+    pub id: &'static str, // stored in saves, so keep it stable
+    pub name: &'static str,
+    pub glyphs: Glyphs,
+    pub colors: Colors,
+    pub trail: Trail,
+    pub unlock: Unlock,
+}
+
+pub const ALL: &[Skin] = &[
+    Skin { id: "classic", name: "Classic", glyphs: Glyphs::Matrix, colors: Colors::Theme, trail: Trail::None, unlock: Unlock::Free },
+    Skin {
+        id: "binary",
+        name: "Binary",
+        glyphs: Glyphs::Word("10"),
+        colors: Colors::Theme,
+        trail: Trail::None,
+        unlock: Unlock::Achievement("first_bite"),
+    },
+    Skin {
+        id: "ember",
+        name: "Ember",
+        glyphs: Glyphs::Matrix,
+        colors: Colors::Gradient(Color::new(1.0, 0.9, 0.3, 1.0), Color::new(0.7, 0.1, 0.0, 1.0)),
+        trail: Trail::None,
+        unlock: Unlock::BestScore(25),
+    },
+    Skin {
+        id: "ghost",
+        name: "Ghost",
+        glyphs: Glyphs::Matrix,
+        colors: Colors::Gradient(Color::new(0.95, 0.95, 1.0, 1.0), Color::new(0.5, 0.5, 0.6, 0.4)),
+        trail: Trail::Fade,
+        unlock: Unlock::Achievement("survivor"),
+    },
+    Skin {
+        id: "serpent",
+        name: "Serpent",
+        glyphs: Glyphs::Word("SNAKE"),
+        colors: Colors::Rainbow,
+        trail: Trail::None,
+        unlock: Unlock::Achievement("score_50"),
+    },
+    Skin {
+        id: "comet",
+        name: "Comet",
+        glyphs: Glyphs::Word("@*+."),
+        colors: Colors::Gradient(Color::new(1.0, 1.0, 1.0, 1.0), Color::new(0.2, 0.4, 1.0, 1.0)),
+        trail: Trail::Fade,
+        unlock: Unlock::BestScore(100),
+    },
+];
+
+// The skin saved under `id`, or the first one if it's gone
+pub fn by_id(id: &str) -> &'static Skin {
+    ALL.iter().find(|s| s.id == id).unwrap_or(&ALL[0])
+}
+
+pub fn unlocked(progress: &Progress) -> Vec<&'static Skin> {
+    ALL.iter().filter(|s| s.is_unlocked(progress)).collect()
+}
+
+impl Skin {
+    pub fn is_unlocked(&self, progress: &Progress) -> bool {
+        match self.unlock {
+            Unlock::Free => true,
+            Unlock::Achievement(id) => progress.achievements.contains_key(id),
+            Unlock::BestScore(score) => progress.best_score >= score,
+        }
+    }
+
+    // What it takes to unlock, for the settings screen
+    pub fn requirement(&self) -> String {
+        match self.unlock {
+            Unlock::Free => String::new(),
+            Unlock::Achievement(id) => {
+                let name = achievements::ALL.iter().find(|a| a.id == id).map_or(id, |a| a.name);
+                trf!("Unlock: achievement {}", tr(name))
+            }
+            Unlock::BestScore(score) => trf!("Unlock: score {}", score),
+        }
+    }
+
+    // Glyph of segment `i` (0 is the head); `random` is the segment's own Matrix glyph
+    pub fn glyph(&self, i: usize, random: char) -> char {
+        match self.glyphs {
+            Glyphs::Matrix => random,
+            Glyphs::Word(word) => word.chars().nth(i % word.chars().count()).unwrap_or(random),
+        }
+    }
+
+    // Color of segment `i` of `len`
+    pub fn color(&self, i: usize, len: usize, palette: &Palette) -> Color {
+        let t = if len > 1 { i as f32 / (len - 1) as f32 } else { 0.0 };
+        match self.colors {
+            Colors::Theme if i == 0 => palette.head,
+            Colors::Theme => palette.body,
+            Colors::Gradient(head, tail) => Color::new(
+                head.r + (tail.r - head.r) * t,
+                head.g + (tail.g - head.g) * t,
+                head.b + (tail.b - head.b) * t,
+                head.a + (tail.a - head.a) * t,
+            ),
+            Colors::Rainbow => {
+                let hue = (i as f32 * 0.08) % 1.0;
+                hsl_to_rgb(hue, 0.9, if i == 0 { 0.75 } else { 0.55 })
+            }
+        }
+    }
+
+    // Color of segment `i` of `len`, `age` ticks after it left the head, with the body gradient
+    // on: Theme skins shade from the head color through the body color to a dark tail, and
+    // fresh segments glow briefly. Skins with colors of their own keep them.
+    pub fn aged_color(&self, i: usize, age: f32, len: usize, palette: &Palette) -> Color {
+        if !matches!(self.colors, Colors::Theme) || i == 0 { return self.color(i, len, palette); }
+        let t = i as f32 / (len - 1).max(1) as f32;
+        let mix = |a: f32, b: f32, k: f32| a + (b - a) * k;
+        let near = (t * 3.0).min(1.0); // the head color gives way to the body within the first third
+        let shade = mix(1.0, TAIL_SHADE, t);
+        let glow = 0.6 * (1.0 - age / GLOW_TICKS).max(0.0);
+        let (head, body) = (palette.head, palette.body);
+        let channel = |h: f32, b: f32| mix(mix(h, b, near) * shade, 1.0, glow);
+        Color::new(channel(head.r, body.r), channel(head.g, body.g), channel(head.b, body.b), body.a)
+    }
+}
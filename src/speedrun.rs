@@ -0,0 +1,52 @@
+// Speedrun timer: board time with a split every SPLIT_EVERY points, compared against the
+// personal best pace on the same seed and settings. Time is counted in ticks, so it stands
+// still while the game is paused or counting down and carries over an autosave.
+use macroquad::prelude::*;
+
+use crate::game::SnakeGame;
+use crate::render::Palette;
+use crate::replay::Replay;
+use crate::ui::draw_centered;
+
+pub const SPLIT_EVERY: u32 = 10;
+
+// Splits are only compared between runs with this same key
+pub fn run_key(replay: &Replay) -> String {
+    format!("{}|{}", replay.seed, replay.settings_key())
+}
+
+// "1:23.45"
+pub fn format_time(seconds: f32) -> String {
+    let s = seconds.max(0.0);
+    format!("{}:{:05.2}", (s / 60.0) as u32, s % 60.0)
+}
+
+// "+1.20" behind the best pace, "-0.80" ahead of it
+pub fn format_delta(seconds: f32) -> String {
+    format!("{}{:.2}", if seconds < 0.0 { "-" } else { "+" }, seconds.abs())
+}
+
+pub fn delta_color(seconds: f32, palette: &Palette) -> Color {
+    if seconds <= 0.0 { palette.body } else { palette.food }
+}
+
+// Seconds behind (positive) or ahead of `best` at split `i`, if the best run got that far
+pub fn split_delta(splits: &[u32], best: &[u32], i: usize, interval: f32) -> Option<f32> {
+    let (now, then) = (*splits.get(i)?, *best.get(i)?);
+    Some((now as i64 - then as i64) as f32 * interval)
+}
+
+// Top centre of the board: the running time, then the latest split against the best pace
+pub fn draw_timer(game: &SnakeGame, best: Option<&[u32]>, palette: &Palette, ui: f32) {
+    let interval = game.replay.move_interval;
+    let ticks = game.replay.ticks as f32 + if game.sim.alive { game.alpha() } else { 0.0 };
+    draw_centered(&format_time(ticks * interval), 28.0 * ui, 28.0 * ui, WHITE);
+
+    let Some(last) = game.splits.len().checked_sub(1) else { return; };
+    let points = (last as u32 + 1) * SPLIT_EVERY;
+    let time = format_time(game.splits[last] as f32 * interval);
+    match best.and_then(|b| split_delta(&game.splits, b, last, interval)) {
+        Some(delta) => draw_centered(&format!("{}: {}  {}", points, time, format_delta(delta)), 50.0 * ui, 18.0 * ui, delta_color(delta, palette)),
+        None => draw_centered(&format!("{}: {}", points, time), 50.0 * ui, 18.0 * ui, LIGHTGRAY),
+    }
+}
@@ -0,0 +1,139 @@
+// Lifetime totals over finished runs, kept in the save's `stats` section and shown on the
+// Stats screen
+use serde::{Deserialize, Serialize};
+
+use crate::game::SnakeGame;
+use crate::i18n::trf;
+use crate::save::unix_now;
+use crate::sim::{DeathCause, PREMIUM_POINTS};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct Deaths {
+    pub border: u32,
+    pub wall: u32,
+    pub self_collision: u32,
+    pub starved: u32,
+    pub patroller: u32,
+}
+
+impl Deaths {
+    fn add(&mut self, cause: DeathCause) {
+        match cause {
+            DeathCause::Border => self.border += 1,
+            DeathCause::Wall => self.wall += 1,
+            DeathCause::SelfCollision => self.self_collision += 1,
+            DeathCause::Starved => self.starved += 1,
+            DeathCause::Patroller => self.patroller += 1,
+        }
+    }
+
+    pub fn rows(&self) -> [(&'static str, u32); 5] {
+        [
+            ("Border", self.border),
+            ("Wall", self.wall),
+            ("Own tail", self.self_collision),
+            ("Starved", self.starved),
+            ("Patroller", self.patroller),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Stats {
+    pub games: u32,
+    pub food: u64,
+    pub play_seconds: f64, // board time, not counting pauses or countdowns
+    pub deaths: Deaths,
+    pub boards_cleared: u32,
+    pub longest_snake: u32,
+    pub longest_run_ticks: u32,
+    pub day_streak: u32, // days in a row with a finished run, ending on `last_day`
+    pub best_day_streak: u32,
+    pub last_day: u64, // days since the unix epoch (UTC)
+}
+
+impl Stats {
+    // Adds a run that just ended
+    pub fn record_run(&mut self, game: &SnakeGame) {
+        let sim = &game.sim;
+        self.games += 1;
+        self.food += food_eaten(game);
+        self.play_seconds += play_seconds(game);
+        match sim.death {
+            Some(cause) => self.deaths.add(cause),
+            None if sim.won => self.boards_cleared += 1,
+            None => {}
+        }
+        self.longest_snake = self.longest_snake.max(sim.snake.len() as u32);
+        self.longest_run_ticks = self.longest_run_ticks.max(game.replay.ticks);
+
+        let today = unix_now() / SECONDS_PER_DAY;
+        if today != self.last_day || self.day_streak == 0 {
+            self.day_streak = if self.last_day + 1 == today { self.day_streak + 1 } else { 1 };
+            self.last_day = today;
+        }
+        self.best_day_streak = self.best_day_streak.max(self.day_streak);
+    }
+
+    // The streak as of today: it lapses once a whole day passes without a run
+    pub fn current_day_streak(&self) -> u32 {
+        let today = unix_now() / SECONDS_PER_DAY;
+        if today <= self.last_day + 1 { self.day_streak } else { 0 }
+    }
+}
+
+// Mission bonuses, premium food's and night meals' extra points and food chain bonuses aren't
+// food, and points lost to score decay and glitch food were
+fn food_eaten(game: &SnakeGame) -> u64 {
+    let sim = &game.sim;
+    let bonus = sim.missions.as_ref().map_or(0, |m| m.bonus) + sim.premium.as_ref().map_or(0, |p| p.eaten * (PREMIUM_POINTS - 1))
+        + sim.daylight.map_or(0, |d| d.bonus) + sim.chain.as_ref().map_or(0, |c| c.bonus);
+    let lost = sim.decay.map_or(0, |d| d.lost) + sim.glitch.as_ref().map_or(0, |g| g.lost);
+    (sim.score + lost).saturating_sub(bonus) as u64
+}
+
+// Board time, not counting pauses or countdowns
+fn play_seconds(game: &SnakeGame) -> f64 {
+    game.replay.ticks as f64 * game.replay.move_interval as f64
+}
+
+// One finished run in the same terms as the totals, for the game over screen
+pub struct RunSummary {
+    pub cause: Option<DeathCause>, // None for a cleared board
+    pub length: u32,
+    pub seconds: f64,
+    pub food: u64,
+    pub longest_combo: u32,
+    pub distance: u32, // cells the head moved
+}
+
+impl RunSummary {
+    pub fn of(game: &SnakeGame) -> Self {
+        Self {
+            cause: game.sim.death,
+            length: game.sim.snake.len() as u32,
+            seconds: play_seconds(game),
+            food: food_eaten(game),
+            longest_combo: game.best_combo,
+            distance: game.distance,
+        }
+    }
+
+    pub fn food_per_minute(&self) -> f64 {
+        if self.seconds > 0.0 { self.food as f64 * 60.0 / self.seconds } else { 0.0 }
+    }
+}
+
+// "3h 05m" / "12m 40s"
+pub fn format_duration(seconds: f64) -> String {
+    let s = seconds.max(0.0) as u64;
+    if s >= 3600 {
+        trf!("{}h {}m", s / 3600, format!("{:02}", s / 60 % 60))
+    } else {
+        trf!("{}m {}s", s / 60, format!("{:02}", s % 60))
+    }
+}
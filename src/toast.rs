@@ -0,0 +1,71 @@
+// Short notices at the top of the window: achievements, a new best, save problems, screenshot
+// and export confirmations. Anything can post one, from any thread, the way it would log a
+// line; the main loop draws them over whatever screen is up, a few at a time, each sliding
+// in, holding and fading out. Text is translated by whoever posts it.
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::render::Palette;
+use crate::ui::{draw_text, measure_text};
+
+const SLIDE_SECONDS: f32 = 0.25;
+const HOLD_SECONDS: f32 = 2.5;
+const FADE_SECONDS: f32 = 0.5;
+const LIFETIME: f32 = SLIDE_SECONDS + HOLD_SECONDS + FADE_SECONDS;
+const MAX_SHOWN: usize = 3; // the rest wait their turn
+const MAX_QUEUED: usize = 20; // past this the oldest waiting one is dropped
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ToastKind {
+    Info, // confirmations: screenshot saved, replay exported
+    Reward, // achievements, unlocks, new bests
+    Error, // something failed and the player should know
+}
+
+struct Toast {
+    text: String,
+    kind: ToastKind,
+    shown_at: Option<f32>, // None until it reaches the screen
+}
+
+static QUEUE: Mutex<VecDeque<Toast>> = Mutex::new(VecDeque::new());
+
+pub fn post(kind: ToastKind, text: impl Into<String>) {
+    let Ok(mut queue) = QUEUE.lock() else { return; };
+    if queue.len() >= MAX_QUEUED && let Some(i) = queue.iter().position(|t| t.shown_at.is_none()) {
+        queue.remove(i);
+    }
+    queue.push_back(Toast { text: text.into(), kind, shown_at: None });
+}
+
+pub fn info(text: impl Into<String>) { post(ToastKind::Info, text); }
+pub fn reward(text: impl Into<String>) { post(ToastKind::Reward, text); }
+pub fn error(text: impl Into<String>) { post(ToastKind::Error, text); }
+
+// Stacked down from the top centre, the oldest first
+pub fn draw(now: f32, palette: &Palette, ui: f32) {
+    let Ok(mut queue) = QUEUE.lock() else { return; };
+    queue.retain(|t| t.shown_at.is_none_or(|at| now - at < LIFETIME));
+    let size = 20.0 * ui;
+    let (pad, gap) = (10.0 * ui, 6.0 * ui);
+    let mut y = 8.0;
+    for toast in queue.iter_mut().take(MAX_SHOWN) {
+        let age = now - *toast.shown_at.get_or_insert(now);
+        // Eased in from above the window, then faded over the last half second
+        let slide = 1.0 - (1.0 - (age / SLIDE_SECONDS).min(1.0)).powi(3);
+        let alpha = ((LIFETIME - age) / FADE_SECONDS).clamp(0.0, 1.0);
+        let accent = match toast.kind {
+            ToastKind::Info => palette.body,
+            ToastKind::Reward => palette.food,
+            ToastKind::Error => ORANGE,
+        };
+        let (w, h) = (measure_text(&toast.text, size).width + pad * 2.0 + 4.0 * ui, size + pad);
+        let x = (screen_width() - w) * 0.5;
+        let top = y - (1.0 - slide) * (y + h);
+        draw_rectangle(x, top, w, h, Color::new(0.0, 0.0, 0.0, 0.75 * alpha));
+        draw_rectangle(x, top, 4.0 * ui, h, Color { a: alpha, ..accent });
+        draw_text(&toast.text, x + 4.0 * ui + pad, top + size, size, Color::new(1.0, 1.0, 1.0, alpha));
+        y += (h + gap) * slide;
+    }
+}
@@ -0,0 +1,70 @@
+// Hot-seat tournament: 2-8 players take one turn each, in entry order, on the same map.
+// Only the bookkeeping lives here; the tournament screen runs the turns.
+use crate::save::NAME_MAX_LEN;
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+
+pub struct Entrant {
+    pub name: String,
+    pub result: Option<(u32, u32)>, // score and ticks survived, once the turn is played
+}
+
+pub struct Tournament {
+    pub entrants: Vec<Entrant>,
+    turn: usize, // index of the next entrant to play
+}
+
+impl Tournament {
+    pub fn new(names: Vec<String>) -> Self {
+        let entrants = names.into_iter().map(|name| Entrant { name, result: None }).collect();
+        Self { entrants, turn: 0 }
+    }
+
+    // Why `name` can't join, if it can't
+    pub fn check_name(names: &[String], name: &str) -> Result<(), String> {
+        if name.is_empty() { return Err("Name can't be empty".to_string()); }
+        if name.chars().count() > NAME_MAX_LEN { return Err("That name is too long".to_string()); }
+        if names.len() >= MAX_PLAYERS { return Err("The tournament is full".to_string()); }
+        if names.iter().any(|n| n.eq_ignore_ascii_case(name)) { return Err("That name is already taken".to_string()); }
+        Ok(())
+    }
+
+    pub fn current(&self) -> Option<&Entrant> {
+        self.entrants.get(self.turn)
+    }
+
+    // Ends the current turn
+    pub fn record(&mut self, score: u32, ticks: u32) {
+        if let Some(e) = self.entrants.get_mut(self.turn) {
+            e.result = Some((score, ticks));
+            self.turn += 1;
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.turn >= self.entrants.len()
+    }
+
+    // Entrant indices best first: played turns by score, then by the fewest ticks taken to
+    // get it, then entry order; turns still to come go last
+    pub fn standings(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.entrants.len()).collect();
+        order.sort_by_key(|i| match self.entrants[*i].result {
+            Some((score, ticks)) => (false, std::cmp::Reverse(score), ticks),
+            None => (true, std::cmp::Reverse(0), 0),
+        });
+        order
+    }
+
+    // Places for `standings`, with equal results sharing a place (1, 2, 2, 4)
+    pub fn places(&self) -> Vec<usize> {
+        let order = self.standings();
+        let mut places = Vec::with_capacity(order.len());
+        for (k, i) in order.iter().enumerate() {
+            let same = k > 0 && self.entrants[order[k - 1]].result == self.entrants[*i].result;
+            places.push(if same { places[k - 1] } else { k + 1 });
+        }
+        places
+    }
+}
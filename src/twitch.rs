@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+
+use crate::logging;
+use crate::map::Direction;
+
+// Chat needs time to react, so Twitch runs never tick faster than this (seconds)
+pub const VOTE_WINDOW: f32 = 1.0;
+
+// What the reader thread reports back to the game; the stub build only ever fails
+#[cfg_attr(not(all(feature = "twitch", not(target_arch = "wasm32"))), allow(dead_code))]
+pub enum ChatEvent {
+    Joined,
+    Vote { user: String, dir: Direction },
+    Failed(String),
+}
+
+// Channel name as typed in settings ("#Foo " -> "foo")
+pub fn normalize_channel(name: &str) -> String {
+    name.trim().trim_start_matches('#').to_ascii_lowercase()
+}
+
+#[cfg(all(feature = "twitch", not(target_arch = "wasm32")))]
+mod irc {
+    use std::io::{BufRead, BufReader, ErrorKind, Write};
+    use std::net::TcpStream;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::Sender;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::ChatEvent;
+    use crate::map::Direction;
+
+    const SERVER: &str = "irc.chat.twitch.tv:6667";
+    // Short reads so the thread notices when the game stops listening
+    const POLL: Duration = Duration::from_millis(500);
+
+    // "up", "w", "Left!" ... the first word of a chat message, if it names a direction
+    fn parse_vote(text: &str) -> Option<Direction> {
+        let word = text.split_whitespace().next()?.trim_matches(|c: char| !c.is_alphanumeric()).to_ascii_lowercase();
+        match word.as_str() {
+            "up" | "w" => Some(Direction::Up),
+            "down" | "s" => Some(Direction::Down),
+            "left" | "a" => Some(Direction::Left),
+            "right" | "d" => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    // Anonymous read-only login: Twitch accepts any "justinfan" nick without a token
+    fn run(channel: &str, tx: &Sender<ChatEvent>, stop: &AtomicBool) -> Result<(), String> {
+        let mut stream = TcpStream::connect(SERVER).map_err(|e| format!("Can't reach Twitch chat: {}", e))?;
+        stream.set_read_timeout(Some(POLL)).map_err(|e| e.to_string())?;
+        let login = format!("PASS SCHMOOPIIE\r\nNICK justinfan{}\r\nJOIN #{}\r\n", std::process::id() % 100_000, channel);
+        stream.write_all(login.as_bytes()).map_err(|e| e.to_string())?;
+
+        let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        let mut line = String::new();
+        while !stop.load(Ordering::Relaxed) {
+            match reader.read_line(&mut line) {
+                Ok(0) => return Err("Twitch closed the connection".to_string()),
+                Ok(_) => {}
+                // A partial line stays in `line` and is completed by the next read
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+                Err(e) => return Err(e.to_string()),
+            }
+            let msg = line.trim_end();
+            if let Some(payload) = msg.strip_prefix("PING") {
+                stream.write_all(format!("PONG{}\r\n", payload).as_bytes()).map_err(|e| e.to_string())?;
+            } else if let Some((prefix, rest)) = msg.split_once(" PRIVMSG ") {
+                // ":nick!nick@nick.tmi.twitch.tv PRIVMSG #channel :text"
+                let user = prefix.trim_start_matches(':').split('!').next().unwrap_or_default().to_string();
+                if let Some((_, text)) = rest.split_once(" :") && let Some(dir) = parse_vote(text) {
+                    let _ = tx.send(ChatEvent::Vote { user, dir });
+                }
+            } else if msg.contains(" 366 ") {
+                // End of the NAMES list: the JOIN went through
+                let _ = tx.send(ChatEvent::Joined);
+            }
+            line.clear();
+        }
+        Ok(())
+    }
+
+    pub fn connect(channel: String, tx: Sender<ChatEvent>, stop: Arc<AtomicBool>) {
+        std::thread::spawn(move || {
+            if let Err(e) = run(&channel, &tx, &stop) { let _ = tx.send(ChatEvent::Failed(e)); }
+        });
+    }
+}
+
+// Without the `twitch` feature (and on the web build, which has no raw sockets) the mode
+// still exists but reports why no votes will arrive
+#[cfg(not(all(feature = "twitch", not(target_arch = "wasm32"))))]
+mod irc {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc::Sender;
+    use std::sync::Arc;
+
+    use super::ChatEvent;
+
+    pub fn connect(_channel: String, tx: Sender<ChatEvent>, _stop: Arc<AtomicBool>) {
+        let _ = tx.send(ChatEvent::Failed("Twitch chat is not available in this build".to_string()));
+    }
+}
+
+// Votes from one channel, counted per tick: each chatter's latest vote in the window counts once
+pub struct ChatVotes {
+    pub channel: String,
+    pub status: String,
+    rx: Receiver<ChatEvent>,
+    stop: Arc<AtomicBool>,
+    votes: HashMap<String, Direction>,
+}
+
+impl ChatVotes {
+    pub fn connect(channel: &str) -> Self {
+        let channel = normalize_channel(channel);
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let status = if channel.is_empty() {
+            "Set a Twitch channel in Settings".to_string()
+        } else {
+            logging::info(format!("Twitch: joining #{}", channel));
+            irc::connect(channel.clone(), tx, stop.clone());
+            format!("Connecting to #{}...", channel)
+        };
+        Self { channel, status, rx, stop, votes: HashMap::new() }
+    }
+
+    // Collect everything the reader thread sent since the last frame
+    pub fn poll(&mut self) {
+        loop {
+            match self.rx.try_recv() {
+                Ok(ChatEvent::Joined) => self.status = format!("Reading chat in #{}", self.channel),
+                Ok(ChatEvent::Vote { user, dir }) => { self.votes.insert(user, dir); }
+                Ok(ChatEvent::Failed(e)) => {
+                    logging::warn(format!("Twitch: {}", e));
+                    self.status = e;
+                }
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => break,
+            }
+        }
+    }
+
+    // Votes per direction in the current window, in Up, Down, Left, Right order
+    pub fn tally(&self) -> [(Direction, u32); 4] {
+        let mut counts = [(Direction::Up, 0), (Direction::Down, 0), (Direction::Left, 0), (Direction::Right, 0)];
+        for dir in self.votes.values() {
+            if let Some(entry) = counts.iter_mut().find(|(d, _)| d == dir) { entry.1 += 1; }
+        }
+        counts
+    }
+
+    // Majority direction so far; ties go to the direction listed first
+    pub fn leader(&self) -> Option<Direction> {
+        let mut best: Option<(Direction, u32)> = None;
+        for (dir, n) in self.tally() {
+            if n > 0 && best.is_none_or(|(_, m)| n > m) { best = Some((dir, n)); }
+        }
+        best.map(|(dir, _)| dir)
+    }
+
+    // Start counting afresh for the next tick
+    pub fn next_window(&mut self) {
+        self.votes.clear();
+    }
+}
+
+impl Drop for ChatVotes {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
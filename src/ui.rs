@@ -0,0 +1,238 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::i18n::language;
+
+pub const UI_SCALE_MIN: f32 = 0.5;
+pub const UI_SCALE_MAX: f32 = 2.0;
+
+// Covers Cyrillic and the rest of what translations need; loaded on first use
+static UNICODE_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono.ttf");
+
+// The font for UI text in the current language; None is macroquad's built-in one
+fn ui_font() -> Option<&'static Font> {
+    static FONT: OnceLock<Option<Font>> = OnceLock::new();
+    if !language().needs_unicode_font() { return None; }
+    FONT.get_or_init(|| load_ttf_font_from_bytes(UNICODE_FONT).ok()).as_ref()
+}
+
+// UI text goes through these instead of macroquad's so translated text gets a font that has
+// its glyphs and is measured with that same font
+pub fn draw_text(text: &str, x: f32, y: f32, size: f32, color: Color) -> TextDimensions {
+    let params = TextParams { font: ui_font(), font_size: size as u16, color, ..Default::default() };
+    draw_text_ex(text, x, y, params)
+}
+
+pub fn measure_text(text: &str, size: f32) -> TextDimensions {
+    macroquad::text::measure_text(text, ui_font(), size as u16, 1.0)
+}
+
+// Draws a line of text horizontally centered on screen; `size` already includes UI scale
+pub fn draw_centered(text: &str, y: f32, size: f32, color: Color) {
+    let m = measure_text(text, size);
+    draw_text(text, (screen_width() - m.width) * 0.5, y, size, color);
+}
+
+pub const SLIDER_WIDTH: f32 = 160.0;
+
+// A slider: the track, filled up to `fraction`, with a handle on the end of the fill
+pub fn draw_slider(track: Rect, fraction: f32, color: Color, accent: Color) {
+    let fill = track.w * fraction.clamp(0.0, 1.0);
+    let (line_h, handle_w) = (track.h * 0.4, (track.h * 0.5).max(4.0));
+    let y = track.y + (track.h - line_h) * 0.5;
+    draw_rectangle(track.x, y, track.w, line_h, Color { a: 0.25, ..color });
+    draw_rectangle(track.x, y, fill, line_h, accent);
+    draw_rectangle(track.x + fill - handle_w * 0.5, track.y, handle_w, track.h, color);
+}
+
+// Mouse input for one slider. A press on the track starts a drag, which then follows the
+// mouse anywhere until the button is let go; `grabbed` holds the track as it was when the
+// drag started, so a slider that changes the layout (UI scale) doesn't move under the mouse.
+// Returns the fraction to set while dragging.
+pub fn drag_slider(track: Rect, grabbed: &mut Option<Rect>) -> Option<f32> {
+    let mouse = Vec2::from(mouse_position());
+    if grabbed.is_none() && is_mouse_button_pressed(MouseButton::Left) {
+        // A little slack above and below, since the track is thin
+        let target = Rect::new(track.x - 4.0, track.y - 6.0, track.w + 8.0, track.h + 12.0);
+        if target.contains(mouse) { *grabbed = Some(track); }
+    }
+    let track = (*grabbed)?;
+    if !is_mouse_button_down(MouseButton::Left) {
+        *grabbed = None;
+        return None;
+    }
+    Some(((mouse.x - track.x) / track.w).clamp(0.0, 1.0))
+}
+
+// Client area of the Stream window, a common capture size
+pub const STREAM_WINDOW: (i32, i32) = (1280, 720);
+
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    Fullscreen,
+    Windowed,
+    Stream, // fixed-size window for capture; no title bar drawn by the game on Wayland
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [DisplayMode::Fullscreen, DisplayMode::Windowed, DisplayMode::Stream];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DisplayMode::Fullscreen => "Fullscreen",
+            DisplayMode::Windowed => "Windowed",
+            DisplayMode::Stream => "Stream window",
+        }
+    }
+
+    // Switches the running window over; miniquad can't change decorations after startup
+    pub fn apply(self) {
+        set_fullscreen(self == DisplayMode::Fullscreen);
+        if self == DisplayMode::Stream {
+            request_new_screen_size(STREAM_WINDOW.0 as f32, STREAM_WINDOW.1 as f32);
+        }
+    }
+
+    // F11: fullscreen goes to a window, any window to fullscreen
+    pub fn toggled(self) -> Self {
+        if self == DisplayMode::Fullscreen { DisplayMode::Windowed } else { DisplayMode::Fullscreen }
+    }
+}
+
+// The smallest a resizable window may be dragged to; miniquad has no such limit, so a window
+// dragged smaller is sized back up
+#[derive(Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MinWindow {
+    Off,
+    #[default]
+    Small, // 640x360
+    Medium, // 960x540
+    Large, // 1280x720
+}
+
+impl MinWindow {
+    pub const ALL: [MinWindow; 4] = [MinWindow::Off, MinWindow::Small, MinWindow::Medium, MinWindow::Large];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MinWindow::Off => "Off",
+            MinWindow::Small => "640x360",
+            MinWindow::Medium => "960x540",
+            MinWindow::Large => "1280x720",
+        }
+    }
+
+    pub fn size(self) -> Option<(f32, f32)> {
+        match self {
+            MinWindow::Off => None,
+            MinWindow::Small => Some((640.0, 360.0)),
+            MinWindow::Medium => Some((960.0, 540.0)),
+            MinWindow::Large => Some((1280.0, 720.0)),
+        }
+    }
+}
+
+// Where the in-game HUD sits, so it can be kept clear of a webcam or stream overlay
+#[derive(Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HudCorner {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl HudCorner {
+    pub const ALL: [HudCorner; 4] = [HudCorner::TopLeft, HudCorner::TopRight, HudCorner::BottomLeft, HudCorner::BottomRight];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HudCorner::TopLeft => "Top left",
+            HudCorner::TopRight => "Top right",
+            HudCorner::BottomLeft => "Bottom left",
+            HudCorner::BottomRight => "Bottom right",
+        }
+    }
+
+    // Top-left point of a `size` block pushed into this corner of the screen
+    pub fn origin(self, size: Vec2) -> Vec2 {
+        let right = matches!(self, HudCorner::TopRight | HudCorner::BottomRight);
+        let bottom = matches!(self, HudCorner::BottomLeft | HudCorner::BottomRight);
+        vec2(if right { screen_width() - size.x } else { 0.0 }, if bottom { screen_height() - size.y } else { 0.0 })
+    }
+
+    // The other corner on the same edge, for a second panel
+    pub fn across(self) -> Self {
+        match self {
+            HudCorner::TopLeft => HudCorner::TopRight,
+            HudCorner::TopRight => HudCorner::TopLeft,
+            HudCorner::BottomLeft => HudCorner::BottomRight,
+            HudCorner::BottomRight => HudCorner::BottomLeft,
+        }
+    }
+}
+
+// Key bindings are stored by name so they survive in the JSON save
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("Up", KeyCode::Up), ("Down", KeyCode::Down), ("Left", KeyCode::Left), ("Right", KeyCode::Right),
+    ("A", KeyCode::A), ("B", KeyCode::B), ("C", KeyCode::C), ("D", KeyCode::D), ("E", KeyCode::E),
+    ("F", KeyCode::F), ("G", KeyCode::G), ("H", KeyCode::H), ("I", KeyCode::I), ("J", KeyCode::J),
+    ("K", KeyCode::K), ("L", KeyCode::L), ("M", KeyCode::M), ("N", KeyCode::N), ("O", KeyCode::O),
+    ("P", KeyCode::P), ("R", KeyCode::R), ("S", KeyCode::S), ("T", KeyCode::T), ("U", KeyCode::U),
+    ("V", KeyCode::V), ("W", KeyCode::W), ("X", KeyCode::X), ("Y", KeyCode::Y), ("Z", KeyCode::Z),
+    ("Kp8", KeyCode::Kp8), ("Kp2", KeyCode::Kp2), ("Kp4", KeyCode::Kp4), ("Kp6", KeyCode::Kp6),
+    ("Space", KeyCode::Space),
+];
+
+pub fn key_code(name: &str) -> Option<KeyCode> {
+    KEY_NAMES.iter().find(|(n, _)| *n == name).map(|(_, k)| *k)
+}
+
+pub fn key_name(code: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(_, k)| *k == code).map(|(n, _)| *n)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub up: Vec<String>,
+    pub down: Vec<String>,
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            up: vec!["Up".into(), "W".into()],
+            down: vec!["Down".into(), "S".into()],
+            left: vec!["Left".into(), "A".into()],
+            right: vec!["Right".into(), "D".into()],
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn actions(&self) -> [(&'static str, &Vec<String>); 4] {
+        [("Up", &self.up), ("Down", &self.down), ("Left", &self.left), ("Right", &self.right)]
+    }
+
+    pub fn action_mut(&mut self, idx: usize) -> &mut Vec<String> {
+        match idx {
+            0 => &mut self.up,
+            1 => &mut self.down,
+            2 => &mut self.left,
+            _ => &mut self.right,
+        }
+    }
+
+    pub fn pressed(keys: &[String]) -> bool {
+        keys.iter().filter_map(|k| key_code(k)).any(is_key_pressed)
+    }
+
+    // The one button of one-switch controls: Space, a click or a tap
+    pub fn switch_pressed() -> bool {
+        is_key_pressed(KeyCode::Space) || is_mouse_button_pressed(MouseButton::Left)
+    }
+}
@@ -0,0 +1,75 @@
+// Weekly challenge: one seed and one rule modifier for everybody in a given ISO week, played
+// on the default map settings so results are comparable. Best scores are kept per week in
+// the save's `progress` section.
+use crate::game::{GameMode, SnakeGame, DEFAULT_MOVE_INTERVAL, DEFAULT_WALL_DENSITY};
+use crate::map::{GridConfig, DEFAULT_STYLE};
+use crate::mapgen;
+use crate::replay::Replay;
+use crate::rng::fnv1a;
+use crate::save::unix_now;
+use crate::screenshot::civil;
+
+pub use snake_core::rules::Modifier;
+
+// The weekly rotation; new modifiers go at the end so past weeks keep theirs
+const WEEKLY: [Modifier; 3] = [Modifier::Mirror, Modifier::Fog, Modifier::Hunger];
+
+pub const FOG_RADIUS: f32 = 5.0; // cells around the head that stay visible in fog
+
+// (ISO year, ISO week) of a day counted from 1970-01-01; weeks start on Monday and week 1 is
+// the one holding the year's first Thursday
+pub fn iso_week(days: i64) -> (i64, u32) {
+    let weekday = (days + 3).rem_euclid(7); // 1970-01-01 was a Thursday; Monday = 0
+    let thursday = days - weekday + 3;
+    let (year, _, _, _) = civil(thursday as u64 * 86_400);
+    (year, ((thursday - jan_first(year)) / 7 + 1) as u32)
+}
+
+// Days from 1970-01-01 to January 1st of `year` (Howard Hinnant's days_from_civil)
+fn jan_first(year: i64) -> i64 {
+    let y = year - 1; // January counts as a month of the year before
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    era * 146_097 + yoe * 365 + yoe / 4 - yoe / 100 + 306 - 719_468
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    pub year: i64,
+    pub week: u32,
+    pub seed: u64,
+    pub modifier: Modifier,
+}
+
+impl Challenge {
+    pub fn current() -> Self {
+        let (year, week) = iso_week((unix_now() / 86_400) as i64);
+        let seed = fnv1a(format!("weekly {}-{}", year, week).as_bytes()) % 1_000_000_000;
+        let modifier = WEEKLY[week as usize % WEEKLY.len()];
+        Self { year, week, seed, modifier }
+    }
+
+    // "2026-W42", also the key of the week's best score in the save
+    pub fn key(&self) -> String {
+        format!("{}-W{:02}", self.year, self.week)
+    }
+
+    pub fn start(&self) -> SnakeGame {
+        let map = mapgen::generate(DEFAULT_STYLE, GridConfig::DEFAULT, self.seed, DEFAULT_WALL_DENSITY);
+        let mut game = SnakeGame::new(map, DEFAULT_MOVE_INTERVAL);
+        game.set_modifier(Some(self.modifier));
+        game
+    }
+
+    // True if `replay` was played on this challenge and nothing else
+    pub fn matches(&self, replay: &Replay) -> bool {
+        replay.seed == self.seed
+            && replay.modifier == Some(self.modifier)
+            && replay.map_style == DEFAULT_STYLE
+            && replay.wall_density == DEFAULT_WALL_DENSITY
+            && replay.move_interval == DEFAULT_MOVE_INTERVAL
+            && replay.grid == GridConfig::DEFAULT
+            && replay.mode == GameMode::Classic
+            && !replay.missions
+    }
+}
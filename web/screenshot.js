@@ -0,0 +1,23 @@
+// miniquad plugin used by the web build: receives a PNG from the game (F12) and
+// offers it as a browser download. Load it after gl.js and before the .wasm:
+//   <script src="gl.js"></script>
+//   <script src="screenshot.js"></script>
+miniquad_add_plugin({
+    name: "snake_download",
+    version: 1,
+    register_plugin: function (importObject) {
+        importObject.env.snake_download = function (name_ptr, name_len, data_ptr, data_len) {
+            var name = new TextDecoder().decode(new Uint8Array(wasm_memory.buffer, name_ptr, name_len));
+            // Copy out of wasm memory, which may move once the game allocates again
+            var data = new Uint8Array(wasm_memory.buffer, data_ptr, data_len).slice();
+            var url = URL.createObjectURL(new Blob([data], { type: "image/png" }));
+            var link = document.createElement("a");
+            link.href = url;
+            link.download = name;
+            document.body.appendChild(link);
+            link.click();
+            document.body.removeChild(link);
+            setTimeout(function () { URL.revokeObjectURL(url); }, 1000);
+        };
+    },
+});